@@ -0,0 +1,1068 @@
+// Generates the `Client` trait in `src/client.rs` and its implementation for `DefaultClient` in
+// `src/default_client/client_gen.rs`.
+//
+// Every operation gets a method calling its request builder. The parameters of a method are derived from
+// the arguments of the request builder and the fields of its body. `METHODS` lists the methods whose name,
+// documentation or parameters differ from the derived ones.
+
+use super::{
+    argument_type, camel_to_snake, field_type, function_name, is_basic_auth, response_type, sorted,
+    strip_newlines, type_name, Definition, Operation, Spec,
+};
+use std::fmt::Write;
+
+const CLIENT_HEADER: &str = r#"/* Code generated by codegen/src/main.rs. DO NOT EDIT. */
+
+use crate::api;
+use crate::enums::{FriendState, GroupRole};
+use crate::session::Session;
+use async_trait::async_trait;
+use nanoserde::SerJson;
+use std::collections::HashMap;
+use std::error::Error;
+
+#[async_trait]
+pub trait Client {
+    type Error: Error;
+"#;
+
+const DEFAULT_CLIENT_HEADER: &str = r#"/* Code generated by codegen/src/main.rs. DO NOT EDIT. */
+
+use super::{str_slice_to_owned, DefaultClient, DefaultClientError};
+use crate::api;
+use crate::client::Client;
+use crate::enums::{FriendState, GroupRole};
+use crate::http_adapter::ClientAdapter;
+use crate::session::Session;
+use async_trait::async_trait;
+use nanoserde::SerJson;
+use std::collections::HashMap;
+
+#[async_trait]
+impl<A: ClientAdapter + Sync + Send> Client for DefaultClient<A> {
+    type Error = DefaultClientError<A>;
+"#;
+
+// A JSON value serialized to a string field, like the metadata of a record
+const METADATA: &str = "Option<&(dyn SerJson + Sync)>";
+
+struct Method {
+    /// The operation ID without the `Nakama_` prefix
+    operation: &'static str,
+    /// The name of the method, if not the name of the request builder
+    name: &'static str,
+    /// The documentation, if not the summary of the operation
+    doc: &'static str,
+    /// The parameters as `name: Type`. A parameter is passed as the arguments and body fields of the same
+    /// name, or as the one after `=>`, e.g. `timestamp: &str => body.timestamp_seconds`. Arguments and
+    /// fields without parameter are `None` or their default. If empty, every argument and field that is not
+    /// in `fixed` is a parameter.
+    params: &'static [&'static str],
+    /// Arguments and body fields passed as an expression instead of a parameter
+    fixed: &'static [(&'static str, &'static str)],
+    /// Statements before the request is built
+    prelude: &'static str,
+    /// The authentication of the request, if not the one of the operation
+    authentication: &'static str,
+}
+
+const DERIVED: Method = Method {
+    operation: "",
+    name: "",
+    doc: "",
+    params: &[],
+    fixed: &[],
+    prelude: "",
+    authentication: "",
+};
+
+// The linked accounts are not updated
+const NO_VARS: &[(&str, &str)] = &[("body.vars", "HashMap::new()")];
+
+const AUTHENTICATE_DOC: &str = "If the user does not exist and `create` is passed, the user is created with the optional `username`.
+`vars` can contain extra information that will be bundled in the session token.";
+
+// Operations without `Client` method, with the reason
+const EXCLUDED: &[(&str, &str)] = &[(
+    "WriteTournamentRecord2",
+    "The same endpoint as `WriteTournamentRecord`, using POST instead of PUT",
+)];
+
+const METHODS: &[Method] = &[
+    Method {
+        operation: "AuthenticateApple",
+        doc: "Authenticate a user with an Apple ID against the server.
+
+Authenticate user with the ID `token` received from Apple.
+If the user does not exist and `create` is passed, the user is created with the optional `username`.
+`vars` can contain extra information that will be bundled in the session token.",
+        params: &[
+            "token: &str",
+            "username: Option<&str>",
+            "create: bool",
+            "vars: HashMap<String, String>",
+        ],
+        ..DERIVED
+    },
+    Method {
+        operation: "AuthenticateCustom",
+        doc: "Authenticate a user with a custom id.
+
+Authenticate user with a custom identifier usually obtained from an external authentication service.
+If the user does not exist and `create` is passed, the user is created with the optional `username`.
+`vars` can contain extra information that will be bundled in the session token.",
+        params: &[
+            "id: &str",
+            "username: Option<&str>",
+            "create: bool",
+            "vars: HashMap<String, String>",
+        ],
+        ..DERIVED
+    },
+    Method {
+        operation: "AuthenticateDevice",
+        doc: "Authenticate a user with a device id.
+
+TODO: Mention minimum length requirements;
+Authenticate user with a device identifier usually obtained from a platform API.
+If the user does not exist and `create` is passed, the user is created with the optional `username`.
+`vars` can contain extra information that will be bundled in the session token.",
+        params: &[
+            "id: &str",
+            "username: Option<&str>",
+            "create: bool",
+            "vars: HashMap<String, String>",
+        ],
+        ..DERIVED
+    },
+    Method {
+        operation: "AuthenticateEmail",
+        doc: AUTHENTICATE_DOC,
+        params: &[
+            "email: &str",
+            "password: &str",
+            "username: Option<&str>",
+            "create: bool",
+            "vars: HashMap<String, String>",
+        ],
+        ..DERIVED
+    },
+    Method {
+        operation: "AuthenticateFacebook",
+        doc: AUTHENTICATE_DOC,
+        params: &[
+            "token: &str",
+            "username: Option<&str>",
+            "create: bool",
+            "vars: HashMap<String, String>",
+            "import: bool => sync",
+        ],
+        ..DERIVED
+    },
+    Method {
+        operation: "AuthenticateFacebookInstantGame",
+        doc: AUTHENTICATE_DOC,
+        params: &[
+            "signed_player_info: &str",
+            "username: Option<&str>",
+            "create: bool",
+            "vars: HashMap<String, String>",
+        ],
+        ..DERIVED
+    },
+    Method {
+        operation: "AuthenticateGameCenter",
+        doc: AUTHENTICATE_DOC,
+        params: &[
+            "bundle_id: &str",
+            "player_id: &str",
+            "public_key_url: &str",
+            "salt: &str",
+            "signature: &str",
+            "timestamp: &str => body.timestamp_seconds",
+            "username: Option<&str>",
+            "create: bool",
+            "vars: HashMap<String, String>",
+        ],
+        ..DERIVED
+    },
+    Method {
+        operation: "AuthenticateGoogle",
+        doc: AUTHENTICATE_DOC,
+        params: &[
+            "token: &str",
+            "username: Option<&str>",
+            "create: bool",
+            "vars: HashMap<String, String>",
+        ],
+        ..DERIVED
+    },
+    Method {
+        operation: "AuthenticateSteam",
+        doc: AUTHENTICATE_DOC,
+        params: &[
+            "token: &str",
+            "username: Option<&str>",
+            "create: bool",
+            "vars: HashMap<String, String>",
+        ],
+        ..DERIVED
+    },
+    Method {
+        operation: "CreateGroup",
+        params: &[
+            "name: &str",
+            "description: Option<&str>",
+            "avatar_url: Option<&str>",
+            "lang_tag: Option<&str>",
+            "open: Option<bool>",
+            "max_count: Option<i32>",
+        ],
+        fixed: &[
+            ("body.open", "open.unwrap_or(true)"),
+            ("body.max_count", "max_count.unwrap_or(100)"),
+        ],
+        ..DERIVED
+    },
+    Method {
+        operation: "DeleteAccount",
+        doc: "Delete the account of the authenticated user with all its data.",
+        ..DERIVED
+    },
+    Method {
+        operation: "Event",
+        params: &["name: &str", "properties: HashMap<String, String>"],
+        // The server uses the time it received the event, as `timestamp` is not set
+        fixed: &[("body.external", "true")],
+        ..DERIVED
+    },
+    Method {
+        operation: "GetSubscription",
+        doc: "Get the subscription `product_id` of the authenticated user.",
+        ..DERIVED
+    },
+    Method {
+        operation: "ImportFacebookFriends",
+        params: &["token: &str", "reset: Option<bool>"],
+        ..DERIVED
+    },
+    Method {
+        operation: "ImportSteamFriends",
+        params: &["token: &str", "reset: Option<bool>"],
+        ..DERIVED
+    },
+    Method {
+        operation: "LinkApple",
+        fixed: NO_VARS,
+        ..DERIVED
+    },
+    Method {
+        operation: "LinkCustom",
+        fixed: NO_VARS,
+        ..DERIVED
+    },
+    Method {
+        operation: "LinkDevice",
+        fixed: NO_VARS,
+        ..DERIVED
+    },
+    Method {
+        operation: "LinkEmail",
+        fixed: NO_VARS,
+        ..DERIVED
+    },
+    Method {
+        operation: "LinkFacebook",
+        fixed: NO_VARS,
+        ..DERIVED
+    },
+    Method {
+        operation: "LinkFacebookInstantGame",
+        fixed: NO_VARS,
+        ..DERIVED
+    },
+    Method {
+        operation: "LinkGameCenter",
+        fixed: NO_VARS,
+        ..DERIVED
+    },
+    Method {
+        operation: "LinkGoogle",
+        fixed: NO_VARS,
+        ..DERIVED
+    },
+    Method {
+        operation: "LinkSteam",
+        fixed: &[("body.account.vars", "HashMap::new()")],
+        ..DERIVED
+    },
+    Method {
+        operation: "ListFriends",
+        params: &[
+            "state: Option<FriendState>",
+            "limit: Option<i32>",
+            "cursor: Option<&str>",
+        ],
+        ..DERIVED
+    },
+    Method {
+        operation: "ListGroupUsers",
+        params: &[
+            "group_id: &str",
+            "state: Option<GroupRole>",
+            "limit: Option<i32>",
+            "cursor: Option<&str>",
+        ],
+        ..DERIVED
+    },
+    Method {
+        operation: "ListGroups",
+        params: &[
+            "name: Option<&str>",
+            "limit: Option<i32>",
+            "cursor: Option<&str>",
+        ],
+        ..DERIVED
+    },
+    Method {
+        operation: "ListLeaderboardRecords",
+        params: &[
+            "leaderboard_id: &str",
+            "owner_ids: &[&str]",
+            "expiry: Option<&str>",
+            "limit: Option<i32>",
+            "cursor: Option<&str>",
+        ],
+        ..DERIVED
+    },
+    Method {
+        operation: "ListLeaderboardRecordsAroundOwner",
+        params: &[
+            "leaderboard_id: &str",
+            "owner_id: &str",
+            "expiry: Option<&str>",
+            "limit: Option<i32>",
+        ],
+        ..DERIVED
+    },
+    Method {
+        operation: "ListMatches",
+        params: &[
+            "min: Option<i32> => min_size",
+            "max: Option<i32> => max_size",
+            "limit: Option<i32>",
+            "authoritative: Option<bool>",
+            "label: &str",
+            "query: &str",
+        ],
+        ..DERIVED
+    },
+    Method {
+        operation: "ListStorageObjects",
+        doc: "List the storage objects of the user `user_id` in `collection`, or the public objects of all
+users if `user_id` is `None`.",
+        ..DERIVED
+    },
+    Method {
+        operation: "ListStorageObjects2",
+        name: "list_users_storage_objects",
+        ..DERIVED
+    },
+    Method {
+        operation: "ListSubscriptions",
+        doc: "List the validated subscriptions of the authenticated user.",
+        params: &["limit: Option<i32>", "cursor: Option<&str>"],
+        ..DERIVED
+    },
+    Method {
+        operation: "ListTournamentRecords",
+        params: &[
+            "tournament_id: &str",
+            "owner_ids: &[&str]",
+            "expiry: Option<&str>",
+            "limit: Option<i32>",
+            "cursor: Option<&str>",
+        ],
+        ..DERIVED
+    },
+    Method {
+        operation: "ListTournamentRecordsAroundOwner",
+        params: &[
+            "tournament_id: &str",
+            "owner_id: &str",
+            "expiry: Option<&str>",
+            "limit: Option<i32>",
+        ],
+        ..DERIVED
+    },
+    Method {
+        operation: "ListUserGroups",
+        params: &[
+            "user_id: &str",
+            "state: Option<GroupRole>",
+            "limit: Option<i32>",
+            "cursor: Option<&str>",
+        ],
+        ..DERIVED
+    },
+    Method {
+        operation: "ListUserGroups",
+        name: "list_current_user_groups",
+        doc: "List the groups of the authenticated user.",
+        params: &[
+            "state: Option<GroupRole>",
+            "limit: Option<i32>",
+            "cursor: Option<&str>",
+        ],
+        fixed: &[("user_id", "&user_id")],
+        prelude: "let user_id = session.user_id().ok_or_else(|| {
+            DefaultClientError::ClientError(\"The session token has no user id\".to_owned())
+        })?;",
+        ..DERIVED
+    },
+    Method {
+        operation: "PromoteGroupUsers",
+        name: "promote_group_user",
+        ..DERIVED
+    },
+    Method {
+        operation: "RpcFunc",
+        name: "rpc_http_key",
+        doc: "Call the RPC function `id` authenticated with the runtime's `http_key` instead of a session,
+e.g. for server-to-server calls.",
+        params: &["http_key: &str", "id: &str", "payload: Option<&str>"],
+        fixed: &[("body", "&payload")],
+        prelude: "// The body of a POST request is the payload encoded as JSON string
+        let payload = payload.unwrap_or(\"\").serialize_json();",
+        // Nakama checks the HTTP key only if the request has no `Authorization` header
+        authentication: "api::Authentication::None",
+    },
+    Method {
+        operation: "RpcFunc2",
+        name: "rpc",
+        params: &["id: &str", "payload: Option<&str>"],
+        ..DERIVED
+    },
+    Method {
+        operation: "SessionLogout",
+        fixed: &[
+            ("body.token", "session.auth_token.clone()"),
+            (
+                "body.refresh_token",
+                "session.refresh_token.clone().unwrap_or_default()",
+            ),
+        ],
+        ..DERIVED
+    },
+    Method {
+        operation: "SessionLogout",
+        name: "session_logout_all",
+        doc: "Invalidate all sessions of the authenticated user, not only `session`.",
+        fixed: &[
+            ("body.token", "String::new()"),
+            ("body.refresh_token", "String::new()"),
+        ],
+        prelude: "// Nakama invalidates all sessions of the user if neither token is given",
+        ..DERIVED
+    },
+    Method {
+        operation: "SessionRefresh",
+        params: &["session: &mut Session", "vars: HashMap<String, String>"],
+        fixed: &[("body.token", "session.auth_token.clone()")],
+        ..DERIVED
+    },
+    Method {
+        operation: "UnlinkApple",
+        fixed: NO_VARS,
+        ..DERIVED
+    },
+    Method {
+        operation: "UnlinkCustom",
+        fixed: NO_VARS,
+        ..DERIVED
+    },
+    Method {
+        operation: "UnlinkDevice",
+        fixed: NO_VARS,
+        ..DERIVED
+    },
+    Method {
+        operation: "UnlinkEmail",
+        fixed: NO_VARS,
+        ..DERIVED
+    },
+    Method {
+        operation: "UnlinkFacebook",
+        fixed: NO_VARS,
+        ..DERIVED
+    },
+    Method {
+        operation: "UnlinkFacebookInstantGame",
+        fixed: NO_VARS,
+        ..DERIVED
+    },
+    Method {
+        operation: "UnlinkGameCenter",
+        fixed: NO_VARS,
+        ..DERIVED
+    },
+    Method {
+        operation: "UnlinkGoogle",
+        fixed: NO_VARS,
+        ..DERIVED
+    },
+    Method {
+        operation: "UnlinkSteam",
+        fixed: NO_VARS,
+        ..DERIVED
+    },
+    Method {
+        operation: "UpdateAccount",
+        params: &[
+            "username: &str",
+            "display_name: Option<&str>",
+            "avatar_url: Option<&str>",
+            "lang_tag: Option<&str>",
+            "location: Option<&str>",
+            "timezone: Option<&str>",
+        ],
+        ..DERIVED
+    },
+    Method {
+        operation: "UpdateGroup",
+        params: &[
+            "group_id: &str",
+            "name: &str",
+            "open: bool",
+            "description: Option<&str>",
+            "avatar_url: Option<&str>",
+            "lang_tag: Option<&str>",
+        ],
+        ..DERIVED
+    },
+    Method {
+        operation: "ValidatePurchaseApple",
+        params: &["receipt: &str", "persist: bool"],
+        ..DERIVED
+    },
+    Method {
+        operation: "ValidatePurchaseGoogle",
+        params: &["receipt: &str => body.purchase", "persist: bool"],
+        ..DERIVED
+    },
+    Method {
+        operation: "ValidatePurchaseHuawei",
+        params: &[
+            "receipt: &str => body.purchase",
+            "signature: &str",
+            "persist: bool",
+        ],
+        ..DERIVED
+    },
+    Method {
+        operation: "ValidateSubscriptionApple",
+        params: &["receipt: &str", "persist: bool"],
+        ..DERIVED
+    },
+    Method {
+        operation: "ValidateSubscriptionGoogle",
+        params: &["receipt: &str", "persist: bool"],
+        ..DERIVED
+    },
+    Method {
+        operation: "WriteLeaderboardRecord",
+        doc: "Write a record with the metadata `metadata`, which must serialize to a JSON object.",
+        params: &[
+            "leaderboard_id: &str",
+            "score: i64",
+            "sub_score: Option<i64> => body.subscore",
+            "override_operator: api::ApiOverrideOperator => body.operator",
+            "metadata: Option<&(dyn SerJson + Sync)>",
+        ],
+        ..DERIVED
+    },
+    Method {
+        operation: "WriteTournamentRecord",
+        params: &[
+            "tournament_id: &str",
+            "score: i64",
+            "sub_score: Option<i64> => body.subscore",
+            "override_operator: api::ApiOverrideOperator => body.operator",
+            "metadata: Option<&(dyn SerJson + Sync)>",
+        ],
+        ..DERIVED
+    },
+];
+
+/// An argument of a request builder or a field of its body
+struct Target {
+    /// e.g. `user_id` or `body.account.token`
+    path: String,
+    kind: String,
+}
+
+impl Target {
+    fn name(&self) -> &str {
+        self.path.rsplit('.').next().unwrap()
+    }
+}
+
+struct Param {
+    name: String,
+    kind: String,
+    targets: Vec<String>,
+}
+
+fn struct_definition<'a>(spec: &'a Spec, reference: &str) -> Option<&'a Definition> {
+    spec.definitions
+        .get(reference.trim_start_matches("#/definitions/"))
+        .filter(|definition| !definition.properties.is_empty())
+}
+
+// Prefixes the generated types with the module `api`
+fn qualify(spec: &Spec, kind: &str) -> String {
+    let mut output = String::new();
+    let mut ident = String::new();
+    for c in kind.chars().chain(std::iter::once(' ')) {
+        if c.is_alphanumeric() || c == '_' {
+            ident.push(c);
+            continue;
+        }
+        let generated = spec.definitions.keys().any(|name| type_name(name) == ident);
+        if generated {
+            output.push_str("api::");
+        } else if ident == "Timestamp" {
+            output.push_str("crate::timestamp::");
+        }
+        output.push_str(&ident);
+        output.push(c);
+        ident.clear();
+    }
+    output.pop();
+    output
+}
+
+fn field_targets(spec: &Spec, definition: &Definition, prefix: &str, targets: &mut Vec<Target>) {
+    for (name, property) in sorted(&definition.properties) {
+        let path = format!("{}.{}", prefix, camel_to_snake(name));
+        match struct_definition(spec, &property.reference) {
+            Some(definition) => field_targets(spec, definition, &path, targets),
+            None => targets.push(Target {
+                path,
+                kind: qualify(spec, &field_type(property)),
+            }),
+        }
+    }
+}
+
+fn targets(spec: &Spec, operation: &Operation) -> Vec<Target> {
+    let mut targets = Vec::new();
+    for parameter in &operation.parameters {
+        let path = camel_to_snake(&parameter.name);
+        match struct_definition(spec, &parameter.schema.reference) {
+            Some(definition) if parameter.location == "body" => {
+                field_targets(spec, definition, &path, &mut targets)
+            }
+            _ => targets.push(Target {
+                path,
+                kind: qualify(spec, &argument_type(parameter)),
+            }),
+        }
+    }
+    targets
+}
+
+// The type of the parameter passed as a target of type `kind`
+fn param_kind(kind: &str) -> String {
+    match kind {
+        "String" => "&str".to_owned(),
+        "Option<String>" => "Option<&str>".to_owned(),
+        "&[String]" | "Vec<String>" => "&[&str]".to_owned(),
+        kind => match kind
+            .strip_prefix("Vec<")
+            .and_then(|kind| kind.strip_suffix('>'))
+        {
+            Some(item) => format!("&[{}]", item),
+            None => kind.to_owned(),
+        },
+    }
+}
+
+// Converts the parameter `name` of type `from` to `to`, returns a statement binding the converted value
+// if it needs to be borrowed.
+fn convert(name: &str, from: &str, to: &str) -> (Option<String>, String) {
+    let expression = match (from, to) {
+        _ if from == to => name.to_owned(),
+        ("&str", "String") => format!("{}.to_owned()", name),
+        ("Option<&str>", "String") => format!("{}.unwrap_or_default().to_owned()", name),
+        ("Option<&str>", "Option<String>") => format!("{}.map(str::to_owned)", name),
+        ("&[&str]", "&[String]") => {
+            let binding = format!("let {0} = str_slice_to_owned({0});", name);
+            return (Some(binding), format!("&{}", name));
+        }
+        ("&[&str]", "Vec<String>") => format!("str_slice_to_owned({})", name),
+        (METADATA, "String") => format!(
+            "{}.map_or(String::new(), |value| value.serialize_json())",
+            name
+        ),
+        (METADATA, "Option<String>") => format!("{}.map(|value| value.serialize_json())", name),
+        _ if to == format!("Option<{}>", from) => format!("Some({})", name),
+        _ if from == format!("Option<{}>", to) => format!("{}.unwrap_or_default()", name),
+        _ if param_kind(to) == from => format!("{}.to_vec()", name),
+        // Enums like `FriendState` passed as their number
+        _ if from.starts_with("Option<") && to == "Option<i32>" => {
+            format!("{}.map(i32::from)", name)
+        }
+        _ => panic!("Unable to pass {} of type {} as {}", name, from, to),
+    };
+    (None, expression)
+}
+
+fn is_fixed(method: &Method, path: &str) -> bool {
+    method.fixed.iter().any(|(fixed, _)| {
+        path == *fixed
+            || path
+                .strip_prefix(fixed)
+                .is_some_and(|rest| rest.starts_with('.'))
+    })
+}
+
+fn params(targets: &[Target], operation: &Operation, method: &Method) -> Vec<Param> {
+    let mut params: Vec<Param> = Vec::new();
+    if method.params.is_empty() {
+        for target in targets
+            .iter()
+            .filter(|target| !is_fixed(method, &target.path))
+        {
+            // An argument and a body field of the same name, like the ID of an updated group
+            match params.iter_mut().find(|param| param.name == target.name()) {
+                Some(param) => param.targets.push(target.path.clone()),
+                None => params.push(Param {
+                    name: target.name().to_owned(),
+                    kind: param_kind(&target.kind),
+                    targets: vec![target.path.clone()],
+                }),
+            }
+        }
+        return params;
+    }
+
+    for param in method.params {
+        let (param, target) = match param.split_once(" => ") {
+            Some((param, target)) => (param, Some(target)),
+            None => (*param, None),
+        };
+        let (name, kind) = param
+            .split_once(": ")
+            .unwrap_or_else(|| panic!("Parameter {} has no type", param));
+        let targets = targets
+            .iter()
+            .filter(|candidate| match target {
+                Some(target) => candidate.path == target,
+                None => candidate.name() == name && !is_fixed(method, &candidate.path),
+            })
+            .map(|target| target.path.clone())
+            .collect::<Vec<_>>();
+        if target.is_some() && targets.is_empty() {
+            panic!("{} has no target {:?}", operation.operation_id, target);
+        }
+        params.push(Param {
+            name: name.to_owned(),
+            kind: kind.to_owned(),
+            targets,
+        });
+    }
+    params
+}
+
+// The body `kind` with the fields assigned in `values`, or `None` if no field is assigned
+fn struct_literal(
+    spec: &Spec,
+    kind: &str,
+    definition: &Definition,
+    prefix: &str,
+    values: &[(String, String)],
+) -> Option<String> {
+    let mut fields = Vec::new();
+    let mut complete = true;
+    for (name, property) in sorted(&definition.properties) {
+        let field = camel_to_snake(name);
+        let path = format!("{}.{}", prefix, field);
+        let value = match struct_definition(spec, &property.reference) {
+            Some(nested) => {
+                let kind = qualify(spec, &type_name(&property.reference));
+                struct_literal(spec, &kind, nested, &path, values)
+            }
+            None => values
+                .iter()
+                .find(|(target, _)| *target == path)
+                .map(|(_, value)| value.clone()),
+        };
+        match value {
+            Some(value) if value == field => fields.push(format!("{},", field)),
+            Some(value) => fields.push(format!("{}: {},", field, value)),
+            None => complete = false,
+        }
+    }
+    if fields.is_empty() {
+        return None;
+    }
+    if !complete {
+        fields.push("..Default::default()".to_owned());
+    }
+    Some(format!("{} {{\n{}\n}}", kind, fields.join("\n")))
+}
+
+fn write_doc(out: &mut String, doc: &str) {
+    for line in doc.lines() {
+        match line.is_empty() {
+            true => writeln!(out, "    ///").unwrap(),
+            false => writeln!(out, "    /// {}", line).unwrap(),
+        }
+    }
+}
+
+fn write_method(
+    spec: &Spec,
+    operation: &Operation,
+    method: &Method,
+    client: &mut String,
+    default_client: &mut String,
+) {
+    let function = function_name(operation);
+    let name = match method.name {
+        "" => function.clone(),
+        name => name.to_owned(),
+    };
+    let basic_auth = is_basic_auth(operation);
+    let targets = targets(spec, operation);
+    let params = params(&targets, operation, method);
+
+    let mut signature = format!("async fn {}(&self", name);
+    let bearer_token = !basic_auth && method.authentication.is_empty();
+    if bearer_token {
+        signature.push_str(", session: &mut Session");
+    }
+    for param in &params {
+        write!(signature, ", {}: {}", param.name, param.kind).unwrap();
+    }
+    let (response, map_session) = match response_type(operation) {
+        Some(response) if response == "ApiSession" => ("Session".to_owned(), true),
+        Some(response) => (qualify(spec, &response), false),
+        None => ("()".to_owned(), false),
+    };
+    write!(signature, ") -> Result<{}, Self::Error>", response).unwrap();
+
+    let doc = match method.doc {
+        "" => strip_newlines(&operation.summary),
+        doc => doc.to_owned(),
+    };
+    writeln!(client).unwrap();
+    write_doc(client, &doc);
+    writeln!(client, "    {};", signature).unwrap();
+
+    let mut bindings = Vec::new();
+    let mut values: Vec<(String, String)> = method
+        .fixed
+        .iter()
+        .map(|(target, value)| ((*target).to_owned(), (*value).to_owned()))
+        .collect();
+    for param in &params {
+        for target in &param.targets {
+            let kind = &targets
+                .iter()
+                .find(|candidate| candidate.path == *target)
+                .unwrap()
+                .kind;
+            let (binding, value) = convert(&param.name, &param.kind, kind);
+            bindings.extend(binding);
+            values.push((target.clone(), value));
+        }
+    }
+
+    let mut arguments = Vec::new();
+    let value = |target: &str| {
+        values
+            .iter()
+            .find(|(candidate, _)| candidate == target)
+            .map(|(_, value)| value.clone())
+    };
+    if basic_auth {
+        arguments.push("&self.server_key".to_owned());
+        arguments.push("\"\"".to_owned());
+    } else if bearer_token {
+        arguments.push("&session.auth_token".to_owned());
+    } else {
+        // Replaced by `method.authentication`
+        arguments.push("\"\"".to_owned());
+    }
+    for parameter in &operation.parameters {
+        let path = camel_to_snake(&parameter.name);
+        let kind = argument_type(parameter);
+        let argument = match struct_definition(spec, &parameter.schema.reference) {
+            Some(definition) if parameter.location == "body" => {
+                let kind = qualify(spec, &kind);
+                struct_literal(spec, &kind, definition, &path, &values)
+                    .unwrap_or_else(|| format!("{}::default()", kind))
+            }
+            _ => match value(&path) {
+                Some(value) => value,
+                None if parameter.location == "path" => panic!(
+                    "{} has no value for {}",
+                    operation.operation_id, parameter.name
+                ),
+                None if parameter.kind == "array" => "&[]".to_owned(),
+                None => "None".to_owned(),
+            },
+        };
+        arguments.push(argument);
+    }
+
+    writeln!(default_client, "\n    {} {{", signature).unwrap();
+    if !method.prelude.is_empty() {
+        writeln!(default_client, "        {}", method.prelude).unwrap();
+    }
+    for binding in bindings {
+        writeln!(default_client, "        {}", binding).unwrap();
+    }
+    match method.authentication {
+        "" => writeln!(
+            default_client,
+            "        let request = api::{}({});",
+            function,
+            arguments.join(", ")
+        ),
+        authentication => writeln!(
+            default_client,
+            "        let mut request = api::{}({});\n        request.authentication = {};",
+            function,
+            arguments.join(", "),
+            authentication
+        ),
+    }
+    .unwrap();
+    match map_session {
+        true => writeln!(
+            default_client,
+            "        self.send(request).await.map(DefaultClient::<A>::map_session)"
+        ),
+        false => writeln!(default_client, "        self.send(request).await"),
+    }
+    .unwrap();
+    writeln!(default_client, "    }}").unwrap();
+}
+
+/// Generates `src/client.rs` and `src/default_client/client_gen.rs`
+pub fn generate(spec: &Spec) -> (String, String) {
+    let mut operations = Vec::new();
+    for (_, path) in sorted(&spec.paths) {
+        for (_, operation) in sorted(path) {
+            operations.push(operation);
+        }
+    }
+    let operation_ids = METHODS
+        .iter()
+        .map(|method| method.operation)
+        .chain(EXCLUDED.iter().map(|(operation, _)| *operation));
+    for id in operation_ids {
+        let exists = operations
+            .iter()
+            .any(|operation| operation.operation_id.trim_start_matches("Nakama_") == id);
+        assert!(exists, "No operation {}", id);
+    }
+
+    let mut methods = Vec::new();
+    for operation in operations {
+        let id = operation.operation_id.trim_start_matches("Nakama_");
+        if EXCLUDED.iter().any(|(excluded, _)| *excluded == id) {
+            continue;
+        }
+        let mut overrides = METHODS
+            .iter()
+            .filter(|method| method.operation == id)
+            .peekable();
+        if overrides.peek().is_none() {
+            methods.push((function_name(operation), operation, &DERIVED));
+        }
+        for method in overrides {
+            let name = match method.name {
+                "" => function_name(operation),
+                name => name.to_owned(),
+            };
+            methods.push((name, operation, method));
+        }
+    }
+    methods.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut client = CLIENT_HEADER.to_owned();
+    let mut default_client = DEFAULT_CLIENT_HEADER.to_owned();
+    for (_, operation, method) in methods {
+        write_method(spec, operation, method, &mut client, &mut default_client);
+    }
+    client.push_str("}\n");
+    default_client.push_str("}\n");
+    (client, default_client)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use nanoserde::DeJson;
+
+    #[test]
+    fn test_every_operation_has_client_method() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/apigrpc.swagger.json");
+        let spec = Spec::deserialize_json(&std::fs::read_to_string(path).unwrap()).unwrap();
+        let (_, default_client) = generate(&spec);
+
+        let mut missing = Vec::new();
+        for (_, path) in sorted(&spec.paths) {
+            for (_, operation) in sorted(path) {
+                let id = operation.operation_id.trim_start_matches("Nakama_");
+                let called = format!("api::{}(", function_name(operation));
+                if !default_client.contains(&called)
+                    && !EXCLUDED.iter().any(|(excluded, _)| *excluded == id)
+                {
+                    missing.push(id);
+                }
+            }
+        }
+        assert!(
+            missing.is_empty(),
+            "Operations without Client method: {:?}",
+            missing
+        );
+    }
+
+    #[test]
+    fn test_convert() {
+        assert_eq!(convert("id", "&str", "&str"), (None, "id".to_owned()));
+        assert_eq!(
+            convert("id", "&str", "String"),
+            (None, "id.to_owned()".to_owned())
+        );
+        assert_eq!(
+            convert("create", "bool", "Option<bool>"),
+            (None, "Some(create)".to_owned())
+        );
+        assert_eq!(
+            convert("ids", "&[&str]", "&[String]"),
+            (
+                Some("let ids = str_slice_to_owned(ids);".to_owned()),
+                "&ids".to_owned()
+            )
+        );
+        assert_eq!(
+            convert(
+                "ids",
+                "&[api::ApiReadStorageObjectId]",
+                "Vec<api::ApiReadStorageObjectId>"
+            ),
+            (None, "ids.to_vec()".to_owned())
+        );
+        assert_eq!(
+            convert("state", "Option<FriendState>", "Option<i32>"),
+            (None, "state.map(i32::from)".to_owned())
+        );
+    }
+}
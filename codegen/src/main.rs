@@ -0,0 +1,659 @@
+// Generates `src/api_gen.rs`, the `Client` trait in `src/client.rs` and its implementation for
+// `DefaultClient` in `src/default_client/client_gen.rs` from the swagger specification of the
+// Nakama REST API.
+//
+// Usage: cargo run -p nakama-codegen -- [--output src/api_gen.rs] [--client src/client.rs] [--default-client src/default_client/client_gen.rs] codegen/apigrpc.swagger.json
+//
+// The output is formatted with `rustfmt`, which needs to be installed.
+
+use nanoserde::DeJson;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::io::Write as _;
+use std::process::{Command, Stdio};
+
+mod client;
+
+const HEADER: &str = r#"/* Code generated by codegen/src/main.rs. DO NOT EDIT. */
+
+use std::collections::HashMap;
+
+use core::str::Chars;
+use nanoserde::{DeJson, DeJsonErr, DeJsonState, DeJsonTok, SerJson, SerJsonState};
+use urlencoding::encode;
+
+use crate::api_gen_int64::Int64String;
+use crate::timestamp::Timestamp;
+
+#[derive(Debug, Clone)]
+pub enum Authentication {
+    Basic { username: String, password: String },
+    Bearer { token: String },
+    /// No `Authorization` header, e.g. for requests authenticated with the runtime's `http_key`.
+    None,
+}
+
+#[derive(Debug, Clone, PartialEq, Copy)]
+pub enum Method {
+    Post,
+    Get,
+    Put,
+    Delete,
+}
+
+#[derive(Debug, Clone)]
+pub struct RestRequest<Response> {
+    pub authentication: Authentication,
+    pub urlpath: String,
+    pub query_params: String,
+    pub body: String,
+    pub method: Method,
+    _marker: std::marker::PhantomData<Response>,
+}
+
+impl<Response> RestRequest<Response> {
+    /// The same request, deserializing the response as `R`.
+    pub fn with_response<R>(self) -> RestRequest<R> {
+        RestRequest {
+            authentication: self.authentication,
+            urlpath: self.urlpath,
+            query_params: self.query_params,
+            body: self.body,
+            method: self.method,
+            _marker: std::marker::PhantomData,
+        }
+    }
+}
+"#;
+
+#[derive(DeJson, Default)]
+#[nserde(default)]
+struct Spec {
+    paths: HashMap<String, HashMap<String, Operation>>,
+    definitions: HashMap<String, Definition>,
+}
+
+#[derive(DeJson, Default)]
+#[nserde(default)]
+struct Operation {
+    summary: String,
+    #[nserde(rename = "operationId")]
+    operation_id: String,
+    responses: HashMap<String, Response>,
+    parameters: Vec<Parameter>,
+    security: Vec<HashMap<String, Vec<String>>>,
+}
+
+#[derive(DeJson, Default)]
+#[nserde(default)]
+struct Response {
+    schema: Schema,
+}
+
+#[derive(DeJson, Default)]
+#[nserde(default)]
+struct Parameter {
+    name: String,
+    #[nserde(rename = "in")]
+    location: String,
+    required: bool,
+    #[nserde(rename = "type")]
+    kind: String,
+    items: Schema,
+    schema: Schema,
+}
+
+#[derive(DeJson, Default)]
+#[nserde(default)]
+struct Schema {
+    #[nserde(rename = "type")]
+    kind: String,
+    #[nserde(rename = "$ref")]
+    reference: String,
+}
+
+#[derive(DeJson, Default)]
+#[nserde(default)]
+struct Definition {
+    properties: HashMap<String, Property>,
+    #[nserde(rename = "enum")]
+    variants: Vec<String>,
+    description: String,
+}
+
+#[derive(DeJson, Default)]
+#[nserde(default)]
+struct Property {
+    #[nserde(rename = "type")]
+    kind: String,
+    #[nserde(rename = "$ref")]
+    reference: String,
+    items: Schema,
+    #[nserde(rename = "additionalProperties")]
+    additional_properties: Schema,
+    description: String,
+    format: String,
+}
+
+fn sorted<V>(map: &HashMap<String, V>) -> Vec<(&String, &V)> {
+    let mut entries: Vec<_> = map.iter().collect();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+    entries
+}
+
+fn upper_first(input: &str) -> String {
+    let mut chars = input.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+fn lower_first(input: &str) -> String {
+    let mut chars = input.chars();
+    match chars.next() {
+        Some(first) => first.to_lowercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+fn camel_to_snake(input: &str) -> String {
+    let mut output = String::new();
+    for (i, c) in input.chars().enumerate() {
+        if c.is_uppercase() {
+            if i != 0 {
+                output.push('_');
+            }
+            output.extend(c.to_lowercase());
+        } else {
+            output.push(c);
+        }
+    }
+    output
+}
+
+// "#/definitions/apiAccount" -> "ApiAccount"
+fn type_name(reference: &str) -> String {
+    upper_first(reference.trim_start_matches("#/definitions/"))
+}
+
+fn strip_newlines(input: &str) -> String {
+    input.replace('\n', " ")
+}
+
+fn primitive(kind: &str) -> Option<&'static str> {
+    match kind {
+        "integer" => Some("i32"),
+        "number" => Some("f32"),
+        "boolean" => Some("bool"),
+        "string" => Some("String"),
+        _ => None,
+    }
+}
+
+// The server encodes 64 bit integers as strings
+fn is_int64(property: &Property) -> bool {
+    property.kind == "string" && property.format == "int64"
+}
+
+fn field_type(property: &Property) -> String {
+    match property.kind.as_str() {
+        // The server omits unset google.protobuf.Timestamp fields
+        "string" if property.format == "date-time" => "Option<Timestamp>".to_owned(),
+        "string" if is_int64(property) => match property.description.contains("optional") {
+            true => "Option<i64>".to_owned(),
+            false => "i64".to_owned(),
+        },
+        "array" => match primitive(&property.items.kind) {
+            Some(kind) => format!("Vec<{}>", kind),
+            None => format!("Vec<{}>", type_name(&property.items.reference)),
+        },
+        "object" => match primitive(&property.additional_properties.kind) {
+            Some(kind) => format!("HashMap<String, {}>", kind),
+            None => format!(
+                "HashMap<String, {}>",
+                type_name(&property.additional_properties.reference)
+            ),
+        },
+        kind => match primitive(kind) {
+            // Wrapper types like google.protobuf.BoolValue, where the server distinguishes
+            // unset from the default value
+            Some(kind) if property.description.contains("optional") => format!("Option<{}>", kind),
+            Some(kind) => kind.to_owned(),
+            None => type_name(&property.reference),
+        },
+    }
+}
+
+// Splits a description like "Header.\n - NAME: Doc." into the header and the docs of the variants
+fn enum_docs(definition: &Definition) -> (String, HashMap<&str, &str>) {
+    let mut header = Vec::new();
+    let mut docs = HashMap::new();
+    for line in definition.description.split('\n') {
+        let line = line.trim();
+        let variant_doc = line
+            .strip_prefix("- ")
+            .and_then(|line| line.split_once(": "));
+        match variant_doc {
+            Some((variant, doc)) if definition.variants.iter().any(|name| name == variant) => {
+                docs.insert(variant, doc);
+            }
+            _ if !line.is_empty() => header.push(line),
+            _ => {}
+        }
+    }
+    (header.join(" "), docs)
+}
+
+fn write_enum(out: &mut String, name: &str, definition: &Definition) {
+    let (header, docs) = enum_docs(definition);
+    if !header.is_empty() {
+        writeln!(out, "\n/// {}", header).unwrap();
+    } else {
+        writeln!(out).unwrap();
+    }
+    writeln!(
+        out,
+        "#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]\n#[repr(i32)]"
+    )
+    .unwrap();
+    writeln!(out, "pub enum {} {{", name).unwrap();
+    for (i, variant) in definition.variants.iter().enumerate() {
+        if let Some(doc) = docs.get(variant.as_str()) {
+            writeln!(out, "    /// {}", doc).unwrap();
+        }
+        if i == 0 {
+            writeln!(out, "    #[default]").unwrap();
+        }
+        writeln!(out, "    {} = {},", variant, i).unwrap();
+    }
+    writeln!(out, "}}").unwrap();
+
+    writeln!(
+        out,
+        "\nimpl {} {{\n    /// The name of the variant in the API.\n    pub fn as_str_name(&self) -> &'static str {{\n        match self {{",
+        name
+    )
+    .unwrap();
+    for variant in &definition.variants {
+        writeln!(out, "            {}::{} => \"{}\",", name, variant, variant).unwrap();
+    }
+    writeln!(
+        out,
+        "        }}\n    }}\n\n    pub fn from_str_name(name: &str) -> Option<Self> {{\n        match name {{"
+    )
+    .unwrap();
+    for variant in &definition.variants {
+        writeln!(
+            out,
+            "            \"{}\" => Some({}::{}),",
+            variant, name, variant
+        )
+        .unwrap();
+    }
+    writeln!(
+        out,
+        "            _ => None,\n        }}\n    }}\n\n    pub fn from_i32(value: i32) -> Option<Self> {{\n        match value {{"
+    )
+    .unwrap();
+    for (i, variant) in definition.variants.iter().enumerate() {
+        writeln!(out, "            {} => Some({}::{}),", i, name, variant).unwrap();
+    }
+    writeln!(out, "            _ => None,\n        }}\n    }}\n}}").unwrap();
+
+    // Serialized by the name of the variant, deserialized from its name or number
+    writeln!(
+        out,
+        "\nimpl SerJson for {} {{\n    fn ser_json(&self, _d: usize, s: &mut SerJsonState) {{\n        s.label(self.as_str_name());\n    }}\n}}",
+        name
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "\nimpl DeJson for {name} {{\n    fn de_json(state: &mut DeJsonState, input: &mut Chars) -> Result<Self, DeJsonErr> {{\n        let value = match state.tok {{\n            DeJsonTok::Str => {name}::from_str_name(&state.as_string()?),\n            _ => {name}::from_i32(state.i64_range(i32::MIN as i64, i32::MAX as i64)? as i32),\n        }};\n        let value = value.ok_or_else(|| state.err_parse(\"{name}\"))?;\n        state.next_tok(input)?;\n        Ok(value)\n    }}\n}}",
+        name = name
+    )
+    .unwrap();
+
+    writeln!(
+        out,
+        "\n#[cfg(feature = \"serde\")]\nimpl serde::Serialize for {} {{\n    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {{\n        serializer.serialize_str(self.as_str_name())\n    }}\n}}",
+        name
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "\n#[cfg(feature = \"serde\")]\nimpl<'de> serde::Deserialize<'de> for {name} {{\n    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {{\n        crate::api_gen_serde::deserialize_enum(deserializer, \"{name}\", {name}::from_str_name, {name}::from_i32)\n    }}\n}}",
+        name = name
+    )
+    .unwrap();
+}
+
+fn write_struct(out: &mut String, name: &str, definition: &Definition) {
+    writeln!(out, "\n/// {}", strip_newlines(&definition.description)).unwrap();
+    writeln!(out, "#[derive(Debug, DeJson, SerJson, Default, Clone)]").unwrap();
+    writeln!(out, "#[nserde(default)]").unwrap();
+    writeln!(
+        out,
+        "#[cfg_attr(feature = \"serde\", derive(serde::Serialize, serde::Deserialize), serde(default))]"
+    )
+    .unwrap();
+    writeln!(out, "pub struct {} {{", name).unwrap();
+    for (property_name, property) in sorted(&definition.properties) {
+        let field = camel_to_snake(property_name);
+        let kind = field_type(property);
+        if is_int64(property) {
+            writeln!(out, "    #[nserde(proxy = \"Int64String\")]").unwrap();
+        }
+        // nanoserde omits `None`, do the same with serde
+        let serde_attribute = match (is_int64(property), kind.starts_with("Option<")) {
+            (true, true) => Some("with = \"crate::api_gen_serde::option_int64_string\", skip_serializing_if = \"Option::is_none\""),
+            (true, false) => Some("with = \"crate::api_gen_serde::int64_string\""),
+            (false, true) => Some("skip_serializing_if = \"Option::is_none\""),
+            (false, false) => None,
+        };
+        if let Some(attribute) = serde_attribute {
+            writeln!(
+                out,
+                "    #[cfg_attr(feature = \"serde\", serde({}))]",
+                attribute
+            )
+            .unwrap();
+        }
+        writeln!(out, "    pub {}: {},", field, kind).unwrap();
+    }
+    writeln!(out, "}}").unwrap();
+}
+
+fn argument_type(parameter: &Parameter) -> String {
+    let optional = |kind: &str| match parameter.required {
+        true => kind.to_owned(),
+        false => format!("Option<{}>", kind),
+    };
+    match parameter.location.as_str() {
+        "path" if parameter.kind == "string" => optional("&str"),
+        "path" => optional(&parameter.kind),
+        "body" if parameter.schema.kind == "string" => optional("&str"),
+        "body" => optional(&type_name(&parameter.schema.reference)),
+        _ => match parameter.kind.as_str() {
+            "array" if parameter.items.kind == "string" => "&[String]".to_owned(),
+            "array" => format!("&[{}]", parameter.items.kind),
+            "integer" => "Option<i32>".to_owned(),
+            "boolean" => "Option<bool>".to_owned(),
+            "string" => "Option<&str>".to_owned(),
+            kind => panic!("unsupported parameter type {} of {}", kind, parameter.name),
+        },
+    }
+}
+
+fn write_query_param(out: &mut String, argument: &str, kind: &str) {
+    let (binding, value) = match kind {
+        "integer" => ("param", "param"),
+        "string" => ("param", "encode(param)"),
+        "boolean" => ("param", "param"),
+        "array" => ("elem", "encode(elem)"),
+        kind => panic!("unsupported query parameter type {} of {}", kind, argument),
+    };
+    let format = if kind == "boolean" { "{:?}" } else { "{}" };
+    if kind == "array" {
+        writeln!(out, "    for {} in {} {{", binding, argument).unwrap();
+    } else {
+        writeln!(out, "    if let Some({}) = {} {{", binding, argument).unwrap();
+    }
+    writeln!(
+        out,
+        "        query_params.push_str(&format!(\"{}={}&\", {}));\n    }}",
+        argument, format, value
+    )
+    .unwrap();
+}
+
+// "Nakama_ListStorageObjects2" -> "list_storage_objects2"
+fn function_name(operation: &Operation) -> String {
+    camel_to_snake(&lower_first(
+        &operation.operation_id.replacen("Nakama_", "", 1),
+    ))
+}
+
+fn response_type(operation: &Operation) -> Option<String> {
+    operation
+        .responses
+        .get("200")
+        .map(|response| response.schema.reference.as_str())
+        .filter(|reference| !reference.is_empty())
+        .map(type_name)
+}
+
+fn is_basic_auth(operation: &Operation) -> bool {
+    operation
+        .security
+        .first()
+        .is_some_and(|security| security.contains_key("BasicAuth"))
+}
+
+fn write_function(out: &mut String, url: &str, method: &str, operation: &Operation) {
+    let name = function_name(operation);
+    let basic_auth = is_basic_auth(operation);
+
+    writeln!(out, "/// {}", strip_newlines(&operation.summary)).unwrap();
+    writeln!(out, "pub fn {}(", name).unwrap();
+    if basic_auth {
+        writeln!(
+            out,
+            "    basic_auth_username: &str,\n    basic_auth_password: &str,"
+        )
+        .unwrap();
+    } else {
+        writeln!(out, "    bearer_token: &str,").unwrap();
+    }
+    for parameter in &operation.parameters {
+        let argument = camel_to_snake(&parameter.name);
+        writeln!(out, "    {}: {},", argument, argument_type(parameter)).unwrap();
+    }
+    let response = response_type(operation).unwrap_or_else(|| "()".to_owned());
+    writeln!(out, ") -> RestRequest<{}> {{", response).unwrap();
+
+    writeln!(out, "    #[allow(unused_mut)]").unwrap();
+    writeln!(out, "    let mut urlpath = \"{}\".to_string();", url).unwrap();
+    for parameter in &operation.parameters {
+        if parameter.location == "path" {
+            writeln!(
+                out,
+                "    urlpath = urlpath.replace(\"{{{}}}\", {});",
+                parameter.name,
+                camel_to_snake(&parameter.name)
+            )
+            .unwrap();
+        }
+    }
+
+    writeln!(out, "\n    #[allow(unused_mut)]").unwrap();
+    writeln!(out, "    let mut query_params = String::new();").unwrap();
+    for parameter in &operation.parameters {
+        if parameter.location == "query" {
+            write_query_param(out, &camel_to_snake(&parameter.name), &parameter.kind);
+        }
+    }
+
+    if basic_auth {
+        writeln!(
+            out,
+            "\n    let authentication = Authentication::Basic {{\n        username: basic_auth_username.to_owned(),\n        password: basic_auth_password.to_owned(),\n    }};"
+        )
+        .unwrap();
+    } else {
+        writeln!(
+            out,
+            "\n    let authentication = Authentication::Bearer {{\n        token: bearer_token.to_owned(),\n    }};"
+        )
+        .unwrap();
+    }
+    match operation
+        .parameters
+        .iter()
+        .find(|parameter| parameter.location == "body")
+    {
+        Some(body) if body.schema.kind == "string" => {
+            let argument = camel_to_snake(&body.name);
+            writeln!(out, "    let body_json = {}.to_string();", argument).unwrap();
+        }
+        Some(body) => {
+            let argument = camel_to_snake(&body.name);
+            writeln!(out, "    let body_json = {}.serialize_json();", argument).unwrap();
+        }
+        None => writeln!(out, "\n    let body_json = String::new();").unwrap(),
+    }
+
+    writeln!(out, "\n    let method = Method::{};", upper_first(method)).unwrap();
+    writeln!(
+        out,
+        "
+    RestRequest {{
+        authentication,
+        urlpath,
+        query_params,
+        body: body_json,
+        method,
+        _marker: std::marker::PhantomData,
+    }}
+}}"
+    )
+    .unwrap();
+}
+
+fn generate(spec: &Spec) -> String {
+    let mut out = HEADER.to_owned();
+    for (name, definition) in sorted(&spec.definitions) {
+        if definition.variants.is_empty() {
+            write_struct(&mut out, &type_name(name), definition);
+        } else {
+            write_enum(&mut out, &type_name(name), definition);
+        }
+    }
+    for (url, path) in sorted(&spec.paths) {
+        for (method, operation) in sorted(path) {
+            write_function(&mut out, url, method, operation);
+        }
+    }
+    out
+}
+
+fn rustfmt(code: &str) -> String {
+    let mut child = Command::new("rustfmt")
+        .args(["--edition", "2018", "--emit", "stdout"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Unable to run rustfmt");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(code.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success(), "rustfmt failed");
+    String::from_utf8(output.stdout).unwrap()
+}
+
+struct Files {
+    api_gen: String,
+    client: String,
+    default_client: String,
+}
+
+fn generate_files(input: &str) -> Files {
+    let content = std::fs::read_to_string(input)
+        .unwrap_or_else(|err| panic!("Unable to read file {}: {}", input, err));
+    let spec = Spec::deserialize_json(&content)
+        .unwrap_or_else(|err| panic!("Unable to decode input file {}: {:?}", input, err));
+    let (client, default_client) = client::generate(&spec);
+    Files {
+        api_gen: rustfmt(&generate(&spec)),
+        client: rustfmt(&client),
+        default_client: rustfmt(&default_client),
+    }
+}
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let mut output = None;
+    let mut client_output = None;
+    let mut default_client_output = None;
+    let mut input = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--output" => output = args.next(),
+            "--client" => client_output = args.next(),
+            "--default-client" => default_client_output = args.next(),
+            _ => input = Some(arg),
+        }
+    }
+    let input = match input {
+        Some(input) => input,
+        None => {
+            eprintln!("No input file found\n\ncodegen [--output FILE] [--client FILE] [--default-client FILE] INPUT");
+            std::process::exit(1);
+        }
+    };
+
+    let files = generate_files(&input);
+    match output {
+        Some(output) => std::fs::write(&output, files.api_gen).expect("Unable to write output"),
+        None => print!("{}", files.api_gen),
+    }
+    if let Some(output) = client_output {
+        std::fs::write(&output, files.client).expect("Unable to write output");
+    }
+    if let Some(output) = default_client_output {
+        std::fs::write(&output, files.default_client).expect("Unable to write output");
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_api_gen_is_up_to_date() {
+        let dir = env!("CARGO_MANIFEST_DIR");
+        let files = generate_files(&format!("{}/apigrpc.swagger.json", dir));
+        let outputs = [
+            ("src/api_gen.rs", files.api_gen),
+            ("src/client.rs", files.client),
+            ("src/default_client/client_gen.rs", files.default_client),
+        ];
+        for (path, code) in outputs {
+            let file = std::fs::read_to_string(format!("{}/../{}", dir, path)).unwrap();
+            assert!(
+                code == file,
+                "{} is out of date, regenerate it with codegen",
+                path
+            );
+        }
+    }
+
+    #[test]
+    fn test_names() {
+        assert_eq!(
+            camel_to_snake("facebookInstantGame"),
+            "facebook_instant_game"
+        );
+        assert_eq!(type_name("#/definitions/apiAccount"), "ApiAccount");
+        assert_eq!(lower_first("ListStorageObjects2"), "listStorageObjects2");
+    }
+
+    #[test]
+    fn test_enum_docs() {
+        let definition = Definition {
+            variants: vec!["NO_OVERRIDE".to_owned(), "BEST".to_owned()],
+            description: "Operator.\n\n - NO_OVERRIDE: Do not override.\n - BEST: Keep the best."
+                .to_owned(),
+            ..Default::default()
+        };
+        let (header, docs) = enum_docs(&definition);
+        assert_eq!(header, "Operator.");
+        assert_eq!(docs["NO_OVERRIDE"], "Do not override.");
+        assert_eq!(docs["BEST"], "Keep the best.");
+    }
+}
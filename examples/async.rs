@@ -13,7 +13,7 @@ use log::{trace, LevelFilter};
 use nakama_rs::client::Client;
 use nakama_rs::default_client::DefaultClient;
 use nakama_rs::http_adapter::RestHttpAdapter;
-use nakama_rs::socket::Socket;
+use nakama_rs::socket::{ChannelJoinType, Socket};
 use nakama_rs::web_socket::WebSocket;
 use nakama_rs::web_socket_adapter::WebSocketAdapter;
 
@@ -131,9 +131,9 @@ fn main() {
         let web_socket = web_socket.clone();
         let web_socket2 = web_socket2.clone();
         async move {
-            web_socket.join_chat("MyRoom", 1, false, false).await.expect("Failed to join chat");
+            web_socket.join_chat("MyRoom", ChannelJoinType::Room, false, false).await.expect("Failed to join chat");
             let channel = web_socket2
-                .join_chat("MyRoom", 1, false, false)
+                .join_chat("MyRoom", ChannelJoinType::Room, false, false)
                 .await
                 .unwrap();
             web_socket2
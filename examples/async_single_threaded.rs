@@ -1,4 +1,5 @@
 use std::collections::HashMap;
+use std::rc::Rc;
 use std::thread::sleep;
 use std::time::Duration;
 
@@ -11,8 +12,8 @@ use crate::State::SentMessage;
 use nakama_rs::client::Client;
 use nakama_rs::default_client::DefaultClient;
 use nakama_rs::http_adapter::RestHttpAdapter;
-use nakama_rs::socket::Socket;
-use nakama_rs::web_socket::WebSocket;
+use nakama_rs::socket::ChannelJoinType;
+use nakama_rs::web_socket::LocalWebSocket;
 use nakama_rs::web_socket_adapter::WebSocketAdapter;
 
 #[derive(Eq, PartialEq, Clone, Debug)]
@@ -40,10 +41,19 @@ fn main() {
     let client = DefaultClient::new(http_adapter);
     let adapter = WebSocketAdapter::new();
     let adapter2 = WebSocketAdapter::new();
-    let web_socket = WebSocket::new(adapter);
-    let web_socket2 = WebSocket::new(adapter2);
+    // `LocalWebSocket` accepts callbacks that are not `Send`, e.g. closures capturing `Rc`.
+    let web_socket = LocalWebSocket::new(adapter);
+    let web_socket2 = LocalWebSocket::new(adapter2);
 
     let state = RefCell::new(Connecting);
+    let received_messages = Rc::new(RefCell::new(Vec::new()));
+
+    web_socket.on_received_channel_message({
+        let received_messages = received_messages.clone();
+        move |message| {
+            received_messages.borrow_mut().push(message.content);
+        }
+    });
 
     let network_future = {
         async {
@@ -65,10 +75,10 @@ fn main() {
                         state.replace(Connected);
                     }
                     JoiningChat => {
-                        web_socket.join_chat("MyRoom", 1, false, false).await.expect("Failed to join chat");
+                        web_socket.join_chat("MyRoom", ChannelJoinType::Room, false, false).await.expect("Failed to join chat");
                         channel = Some(
                             web_socket2
-                                .join_chat("MyRoom", 1, false, false)
+                                .join_chat("MyRoom", ChannelJoinType::Room, false, false)
                                 .await
                                 .unwrap(),
                         );
@@ -114,7 +124,10 @@ fn main() {
             }
             SendingMessage => {}
             SentMessage => {
-                state.replace(Exiting);
+                if !received_messages.borrow().is_empty() {
+                    println!("Received messages: {:?}", received_messages.borrow());
+                    state.replace(Exiting);
+                }
             }
             Exiting => {
                 return;
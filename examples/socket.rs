@@ -1,7 +1,7 @@
 use nakama_macro::nakama_main;
 use nakama_rs::client::Client;
 use nakama_rs::default_client::{DefaultClient};
-use nakama_rs::helper::tick_socket;
+use nakama_rs::helper::SocketDriver;
 use nakama_rs::socket::{Socket, StatusPresenceEvent};
 use nakama_rs::web_socket::WebSocket;
 use std::collections::HashMap;
@@ -11,8 +11,8 @@ use std::sync::mpsc;
 #[nakama_main]
 async fn main() {
     let client = DefaultClient::new_with_adapter();
-    let mut socket = WebSocket::new_with_adapter();
-    tick_socket(&socket);
+    let socket = WebSocket::new_with_adapter();
+    let driver = SocketDriver::new(&socket);
 
     let (tx_presence, rx_presence) = mpsc::channel::<StatusPresenceEvent>();
 
@@ -28,4 +28,6 @@ async fn main() {
 
     let status_presence = rx_presence.recv().expect("Failed to receive status presence");
     println!("Status presence: {:?}", status_presence);
+
+    driver.stop();
 }
@@ -1,14 +1,25 @@
-/* Code generated by codegen/main.go. DO NOT EDIT. */
+/* Code generated by codegen/src/main.rs. DO NOT EDIT. */
 
 use std::collections::HashMap;
 
-use nanoserde::{DeJson, SerJson};
+use core::str::Chars;
+use nanoserde::{DeJson, DeJsonErr, DeJsonState, DeJsonTok, SerJson, SerJsonState};
 use urlencoding::encode;
 
+use crate::api_gen_int64::Int64String;
+use crate::timestamp::Timestamp;
+
 #[derive(Debug, Clone)]
 pub enum Authentication {
-    Basic { username: String, password: String },
-    Bearer { token: String },
+    Basic {
+        username: String,
+        password: String,
+    },
+    Bearer {
+        token: String,
+    },
+    /// No `Authorization` header, e.g. for requests authenticated with the runtime's `http_key`.
+    None,
 }
 
 #[derive(Debug, Clone, PartialEq, Copy)]
@@ -29,9 +40,28 @@ pub struct RestRequest<Response> {
     _marker: std::marker::PhantomData<Response>,
 }
 
+impl<Response> RestRequest<Response> {
+    /// The same request, deserializing the response as `R`.
+    pub fn with_response<R>(self) -> RestRequest<R> {
+        RestRequest {
+            authentication: self.authentication,
+            urlpath: self.urlpath,
+            query_params: self.query_params,
+            body: self.body,
+            method: self.method,
+            _marker: std::marker::PhantomData,
+        }
+    }
+}
+
 /// A single user-role pair.
 #[derive(Debug, DeJson, SerJson, Default, Clone)]
 #[nserde(default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct GroupUserListGroupUser {
     pub state: i32,
     pub user: ApiUser,
@@ -40,71 +70,260 @@ pub struct GroupUserListGroupUser {
 /// A single group-role pair.
 #[derive(Debug, DeJson, SerJson, Default, Clone)]
 #[nserde(default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct UserGroupListUserGroup {
     pub group: ApiGroup,
     pub state: i32,
 }
 
-/// - UNKNOWN: Unknown environment.  - SANDBOX: Sandbox/test environment.  - PRODUCTION: Production environment.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 #[repr(i32)]
 pub enum ValidatedPurchaseEnvironment {
-    /// - UNKNOWN: Unknown environment.
+    /// Unknown environment.
+    #[default]
     UNKNOWN = 0,
-    ///  - SANDBOX: Sandbox/test environment.
+    /// Sandbox/test environment.
     SANDBOX = 1,
-    ///  - PRODUCTION: Production environment.
+    /// Production environment.
     PRODUCTION = 2,
 }
 
-/// - APPLE_APP_STORE: Apple App Store  - GOOGLE_PLAY_STORE: Google Play Store  - HUAWEI_APP_GALLERY: Huawei App Gallery
-#[derive(Debug, Clone, Copy)]
+impl ValidatedPurchaseEnvironment {
+    /// The name of the variant in the API.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            ValidatedPurchaseEnvironment::UNKNOWN => "UNKNOWN",
+            ValidatedPurchaseEnvironment::SANDBOX => "SANDBOX",
+            ValidatedPurchaseEnvironment::PRODUCTION => "PRODUCTION",
+        }
+    }
+
+    pub fn from_str_name(name: &str) -> Option<Self> {
+        match name {
+            "UNKNOWN" => Some(ValidatedPurchaseEnvironment::UNKNOWN),
+            "SANDBOX" => Some(ValidatedPurchaseEnvironment::SANDBOX),
+            "PRODUCTION" => Some(ValidatedPurchaseEnvironment::PRODUCTION),
+            _ => None,
+        }
+    }
+
+    pub fn from_i32(value: i32) -> Option<Self> {
+        match value {
+            0 => Some(ValidatedPurchaseEnvironment::UNKNOWN),
+            1 => Some(ValidatedPurchaseEnvironment::SANDBOX),
+            2 => Some(ValidatedPurchaseEnvironment::PRODUCTION),
+            _ => None,
+        }
+    }
+}
+
+impl SerJson for ValidatedPurchaseEnvironment {
+    fn ser_json(&self, _d: usize, s: &mut SerJsonState) {
+        s.label(self.as_str_name());
+    }
+}
+
+impl DeJson for ValidatedPurchaseEnvironment {
+    fn de_json(state: &mut DeJsonState, input: &mut Chars) -> Result<Self, DeJsonErr> {
+        let value = match state.tok {
+            DeJsonTok::Str => ValidatedPurchaseEnvironment::from_str_name(&state.as_string()?),
+            _ => ValidatedPurchaseEnvironment::from_i32(
+                state.i64_range(i32::MIN as i64, i32::MAX as i64)? as i32,
+            ),
+        };
+        let value = value.ok_or_else(|| state.err_parse("ValidatedPurchaseEnvironment"))?;
+        state.next_tok(input)?;
+        Ok(value)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for ValidatedPurchaseEnvironment {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str_name())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for ValidatedPurchaseEnvironment {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        crate::api_gen_serde::deserialize_enum(
+            deserializer,
+            "ValidatedPurchaseEnvironment",
+            ValidatedPurchaseEnvironment::from_str_name,
+            ValidatedPurchaseEnvironment::from_i32,
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 #[repr(i32)]
 pub enum ValidatedPurchaseStore {
-    /// - APPLE_APP_STORE: Apple App Store
+    /// Apple App Store
+    #[default]
     APPLE_APP_STORE = 0,
-    ///  - GOOGLE_PLAY_STORE: Google Play Store
+    /// Google Play Store
     GOOGLE_PLAY_STORE = 1,
-    ///  - HUAWEI_APP_GALLERY: Huawei App Gallery
+    /// Huawei App Gallery
     HUAWEI_APP_GALLERY = 2,
+    /// Facebook Instant Store
+    FACEBOOK_INSTANT_STORE = 3,
+}
+
+impl ValidatedPurchaseStore {
+    /// The name of the variant in the API.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            ValidatedPurchaseStore::APPLE_APP_STORE => "APPLE_APP_STORE",
+            ValidatedPurchaseStore::GOOGLE_PLAY_STORE => "GOOGLE_PLAY_STORE",
+            ValidatedPurchaseStore::HUAWEI_APP_GALLERY => "HUAWEI_APP_GALLERY",
+            ValidatedPurchaseStore::FACEBOOK_INSTANT_STORE => "FACEBOOK_INSTANT_STORE",
+        }
+    }
+
+    pub fn from_str_name(name: &str) -> Option<Self> {
+        match name {
+            "APPLE_APP_STORE" => Some(ValidatedPurchaseStore::APPLE_APP_STORE),
+            "GOOGLE_PLAY_STORE" => Some(ValidatedPurchaseStore::GOOGLE_PLAY_STORE),
+            "HUAWEI_APP_GALLERY" => Some(ValidatedPurchaseStore::HUAWEI_APP_GALLERY),
+            "FACEBOOK_INSTANT_STORE" => Some(ValidatedPurchaseStore::FACEBOOK_INSTANT_STORE),
+            _ => None,
+        }
+    }
+
+    pub fn from_i32(value: i32) -> Option<Self> {
+        match value {
+            0 => Some(ValidatedPurchaseStore::APPLE_APP_STORE),
+            1 => Some(ValidatedPurchaseStore::GOOGLE_PLAY_STORE),
+            2 => Some(ValidatedPurchaseStore::HUAWEI_APP_GALLERY),
+            3 => Some(ValidatedPurchaseStore::FACEBOOK_INSTANT_STORE),
+            _ => None,
+        }
+    }
+}
+
+impl SerJson for ValidatedPurchaseStore {
+    fn ser_json(&self, _d: usize, s: &mut SerJsonState) {
+        s.label(self.as_str_name());
+    }
+}
+
+impl DeJson for ValidatedPurchaseStore {
+    fn de_json(state: &mut DeJsonState, input: &mut Chars) -> Result<Self, DeJsonErr> {
+        let value = match state.tok {
+            DeJsonTok::Str => ValidatedPurchaseStore::from_str_name(&state.as_string()?),
+            _ => ValidatedPurchaseStore::from_i32(
+                state.i64_range(i32::MIN as i64, i32::MAX as i64)? as i32,
+            ),
+        };
+        let value = value.ok_or_else(|| state.err_parse("ValidatedPurchaseStore"))?;
+        state.next_tok(input)?;
+        Ok(value)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for ValidatedPurchaseStore {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str_name())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for ValidatedPurchaseStore {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        crate::api_gen_serde::deserialize_enum(
+            deserializer,
+            "ValidatedPurchaseStore",
+            ValidatedPurchaseStore::from_str_name,
+            ValidatedPurchaseStore::from_i32,
+        )
+    }
 }
 
 /// Record values to write.
 #[derive(Debug, DeJson, SerJson, Default, Clone)]
 #[nserde(default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct WriteLeaderboardRecordRequestLeaderboardRecordWrite {
     pub metadata: String,
     pub operator: ApiOverrideOperator,
-    pub score: String,
-    pub subscore: Option<String>,
+    #[nserde(proxy = "Int64String")]
+    #[cfg_attr(feature = "serde", serde(with = "crate::api_gen_serde::int64_string"))]
+    pub score: i64,
+    #[nserde(proxy = "Int64String")]
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            with = "crate::api_gen_serde::option_int64_string",
+            skip_serializing_if = "Option::is_none"
+        )
+    )]
+    pub subscore: Option<i64>,
 }
 
 /// Record values to write.
 #[derive(Debug, DeJson, SerJson, Default, Clone)]
 #[nserde(default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct WriteTournamentRecordRequestTournamentRecordWrite {
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub metadata: Option<String>,
     pub operator: ApiOverrideOperator,
-    pub score: String,
-    pub subscore: Option<String>,
+    #[nserde(proxy = "Int64String")]
+    #[cfg_attr(feature = "serde", serde(with = "crate::api_gen_serde::int64_string"))]
+    pub score: i64,
+    #[nserde(proxy = "Int64String")]
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            with = "crate::api_gen_serde::option_int64_string",
+            skip_serializing_if = "Option::is_none"
+        )
+    )]
+    pub subscore: Option<i64>,
 }
 
 /// A user with additional account details. Always the current user.
 #[derive(Debug, DeJson, SerJson, Default, Clone)]
 #[nserde(default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct ApiAccount {
     pub custom_id: String,
     pub devices: Vec<ApiAccountDevice>,
-    pub disable_time: String,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub disable_time: Option<Timestamp>,
     pub email: String,
     pub user: ApiUser,
-    pub verify_time: String,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub verify_time: Option<Timestamp>,
     pub wallet: String,
 }
 
 /// Send a Apple Sign In token to the server. Used with authenticate/link/unlink.
 #[derive(Debug, DeJson, SerJson, Default, Clone)]
 #[nserde(default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct ApiAccountApple {
     pub token: String,
     pub vars: HashMap<String, String>,
@@ -113,6 +332,11 @@ pub struct ApiAccountApple {
 /// Send a custom ID to the server. Used with authenticate/link/unlink.
 #[derive(Debug, DeJson, SerJson, Default, Clone)]
 #[nserde(default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct ApiAccountCustom {
     pub id: String,
     pub vars: HashMap<String, String>,
@@ -121,6 +345,11 @@ pub struct ApiAccountCustom {
 /// Send a device to the server. Used with authenticate/link/unlink and user.
 #[derive(Debug, DeJson, SerJson, Default, Clone)]
 #[nserde(default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct ApiAccountDevice {
     pub id: String,
     pub vars: HashMap<String, String>,
@@ -129,6 +358,11 @@ pub struct ApiAccountDevice {
 /// Send an email with password to the server. Used with authenticate/link/unlink.
 #[derive(Debug, DeJson, SerJson, Default, Clone)]
 #[nserde(default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct ApiAccountEmail {
     pub email: String,
     pub password: String,
@@ -138,6 +372,11 @@ pub struct ApiAccountEmail {
 /// Send a Facebook token to the server. Used with authenticate/link/unlink.
 #[derive(Debug, DeJson, SerJson, Default, Clone)]
 #[nserde(default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct ApiAccountFacebook {
     pub token: String,
     pub vars: HashMap<String, String>,
@@ -146,6 +385,11 @@ pub struct ApiAccountFacebook {
 /// Send a Facebook Instant Game token to the server. Used with authenticate/link/unlink.
 #[derive(Debug, DeJson, SerJson, Default, Clone)]
 #[nserde(default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct ApiAccountFacebookInstantGame {
     pub signed_player_info: String,
     pub vars: HashMap<String, String>,
@@ -154,6 +398,11 @@ pub struct ApiAccountFacebookInstantGame {
 /// Send Apple's Game Center account credentials to the server. Used with authenticate/link/unlink.
 #[derive(Debug, DeJson, SerJson, Default, Clone)]
 #[nserde(default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct ApiAccountGameCenter {
     pub bundle_id: String,
     pub player_id: String,
@@ -167,6 +416,11 @@ pub struct ApiAccountGameCenter {
 /// Send a Google token to the server. Used with authenticate/link/unlink.
 #[derive(Debug, DeJson, SerJson, Default, Clone)]
 #[nserde(default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct ApiAccountGoogle {
     pub token: String,
     pub vars: HashMap<String, String>,
@@ -175,6 +429,11 @@ pub struct ApiAccountGoogle {
 /// Send a Steam token to the server. Used with authenticate/link/unlink.
 #[derive(Debug, DeJson, SerJson, Default, Clone)]
 #[nserde(default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct ApiAccountSteam {
     pub token: String,
     pub vars: HashMap<String, String>,
@@ -183,17 +442,24 @@ pub struct ApiAccountSteam {
 /// A message sent on a channel.
 #[derive(Debug, DeJson, SerJson, Default, Clone)]
 #[nserde(default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct ApiChannelMessage {
     pub channel_id: String,
     pub code: i32,
     pub content: String,
-    pub create_time: String,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub create_time: Option<Timestamp>,
     pub group_id: String,
     pub message_id: String,
     pub persistent: bool,
     pub room_name: String,
     pub sender_id: String,
-    pub update_time: String,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub update_time: Option<Timestamp>,
     pub user_id_one: String,
     pub user_id_two: String,
     pub username: String,
@@ -202,6 +468,11 @@ pub struct ApiChannelMessage {
 /// A list of channel messages, usually a result of a list operation.
 #[derive(Debug, DeJson, SerJson, Default, Clone)]
 #[nserde(default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct ApiChannelMessageList {
     pub cacheable_cursor: String,
     pub messages: Vec<ApiChannelMessage>,
@@ -212,6 +483,11 @@ pub struct ApiChannelMessageList {
 /// Create a group with the current user as owner.
 #[derive(Debug, DeJson, SerJson, Default, Clone)]
 #[nserde(default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct ApiCreateGroupRequest {
     pub avatar_url: String,
     pub description: String,
@@ -224,6 +500,11 @@ pub struct ApiCreateGroupRequest {
 /// Storage objects to delete.
 #[derive(Debug, DeJson, SerJson, Default, Clone)]
 #[nserde(default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct ApiDeleteStorageObjectId {
     pub collection: String,
     pub key: String,
@@ -233,6 +514,11 @@ pub struct ApiDeleteStorageObjectId {
 /// Batch delete storage objects.
 #[derive(Debug, DeJson, SerJson, Default, Clone)]
 #[nserde(default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct ApiDeleteStorageObjectsRequest {
     pub object_ids: Vec<ApiDeleteStorageObjectId>,
 }
@@ -240,25 +526,42 @@ pub struct ApiDeleteStorageObjectsRequest {
 /// Represents an event to be passed through the server to registered event handlers.
 #[derive(Debug, DeJson, SerJson, Default, Clone)]
 #[nserde(default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct ApiEvent {
     pub external: bool,
     pub name: String,
     pub properties: HashMap<String, String>,
-    pub timestamp: String,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub timestamp: Option<Timestamp>,
 }
 
 /// A friend of a user.
 #[derive(Debug, DeJson, SerJson, Default, Clone)]
 #[nserde(default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct ApiFriend {
     pub state: i32,
-    pub update_time: String,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub update_time: Option<Timestamp>,
     pub user: ApiUser,
 }
 
 /// A collection of zero or more friends of the user.
 #[derive(Debug, DeJson, SerJson, Default, Clone)]
 #[nserde(default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct ApiFriendList {
     pub cursor: String,
     pub friends: Vec<ApiFriend>,
@@ -267,9 +570,15 @@ pub struct ApiFriendList {
 /// A group in the server.
 #[derive(Debug, DeJson, SerJson, Default, Clone)]
 #[nserde(default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct ApiGroup {
     pub avatar_url: String,
-    pub create_time: String,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub create_time: Option<Timestamp>,
     pub creator_id: String,
     pub description: String,
     pub edge_count: i32,
@@ -279,12 +588,18 @@ pub struct ApiGroup {
     pub metadata: String,
     pub name: String,
     pub open: bool,
-    pub update_time: String,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub update_time: Option<Timestamp>,
 }
 
 /// One or more groups returned from a listing operation.
 #[derive(Debug, DeJson, SerJson, Default, Clone)]
 #[nserde(default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct ApiGroupList {
     pub cursor: String,
     pub groups: Vec<ApiGroup>,
@@ -293,6 +608,11 @@ pub struct ApiGroupList {
 /// A list of users belonging to a group, along with their role.
 #[derive(Debug, DeJson, SerJson, Default, Clone)]
 #[nserde(default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct ApiGroupUserList {
     pub cursor: String,
     pub group_users: Vec<GroupUserListGroupUser>,
@@ -301,24 +621,49 @@ pub struct ApiGroupUserList {
 /// Represents a complete leaderboard record with all scores and associated metadata.
 #[derive(Debug, DeJson, SerJson, Default, Clone)]
 #[nserde(default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct ApiLeaderboardRecord {
-    pub create_time: String,
-    pub expiry_time: String,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub create_time: Option<Timestamp>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub expiry_time: Option<Timestamp>,
     pub leaderboard_id: String,
     pub max_num_score: i32,
     pub metadata: String,
     pub num_score: i32,
     pub owner_id: String,
-    pub rank: String,
-    pub score: String,
-    pub subscore: Option<String>,
-    pub update_time: String,
+    #[nserde(proxy = "Int64String")]
+    #[cfg_attr(feature = "serde", serde(with = "crate::api_gen_serde::int64_string"))]
+    pub rank: i64,
+    #[nserde(proxy = "Int64String")]
+    #[cfg_attr(feature = "serde", serde(with = "crate::api_gen_serde::int64_string"))]
+    pub score: i64,
+    #[nserde(proxy = "Int64String")]
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            with = "crate::api_gen_serde::option_int64_string",
+            skip_serializing_if = "Option::is_none"
+        )
+    )]
+    pub subscore: Option<i64>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub update_time: Option<Timestamp>,
     pub username: String,
 }
 
 /// A set of leaderboard records, may be part of a leaderboard records page or a batch of individual records.
 #[derive(Debug, DeJson, SerJson, Default, Clone)]
 #[nserde(default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct ApiLeaderboardRecordList {
     pub next_cursor: String,
     pub owner_records: Vec<ApiLeaderboardRecord>,
@@ -329,14 +674,38 @@ pub struct ApiLeaderboardRecordList {
 /// Link Steam to the current user's account.
 #[derive(Debug, DeJson, SerJson, Default, Clone)]
 #[nserde(default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct ApiLinkSteamRequest {
     pub account: ApiAccountSteam,
     pub sync: bool,
 }
 
+/// List user subscriptions.
+#[derive(Debug, DeJson, SerJson, Default, Clone)]
+#[nserde(default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct ApiListSubscriptionsRequest {
+    pub cursor: String,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub limit: Option<i32>,
+}
+
 /// Represents a realtime match.
 #[derive(Debug, DeJson, SerJson, Default, Clone)]
 #[nserde(default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct ApiMatch {
     pub authoritative: bool,
     pub handler_name: String,
@@ -349,6 +718,11 @@ pub struct ApiMatch {
 /// A list of realtime matches.
 #[derive(Debug, DeJson, SerJson, Default, Clone)]
 #[nserde(default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct ApiMatchList {
     pub matches: Vec<ApiMatch>,
 }
@@ -356,10 +730,16 @@ pub struct ApiMatchList {
 /// A notification in the server.
 #[derive(Debug, DeJson, SerJson, Default, Clone)]
 #[nserde(default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct ApiNotification {
     pub code: i32,
     pub content: String,
-    pub create_time: String,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub create_time: Option<Timestamp>,
     pub id: String,
     pub persistent: bool,
     pub sender_id: String,
@@ -369,30 +749,115 @@ pub struct ApiNotification {
 /// A collection of zero or more notifications.
 #[derive(Debug, DeJson, SerJson, Default, Clone)]
 #[nserde(default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct ApiNotificationList {
     pub cacheable_cursor: String,
     pub notifications: Vec<ApiNotification>,
 }
 
-/// Operator that can be used to override the one set in the leaderboard.   - NO_OVERRIDE: Do not override the leaderboard operator.  - BEST: Override the leaderboard operator with BEST.  - SET: Override the leaderboard operator with SET.  - INCREMENT: Override the leaderboard operator with INCREMENT.  - DECREMENT: Override the leaderboard operator with DECREMENT.
-#[derive(Debug, Clone, Copy)]
+/// Operator that can be used to override the one set in the leaderboard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 #[repr(i32)]
 pub enum ApiOverrideOperator {
-    /// Operator that can be used to override the one set in the leaderboard.
+    /// Do not override the leaderboard operator.
+    #[default]
     NO_OVERRIDE = 0,
-    ///
+    /// Override the leaderboard operator with BEST.
     BEST = 1,
-    ///  - NO_OVERRIDE: Do not override the leaderboard operator.
+    /// Override the leaderboard operator with SET.
     SET = 2,
-    ///  - BEST: Override the leaderboard operator with BEST.
+    /// Override the leaderboard operator with INCREMENT.
     INCREMENT = 3,
-    ///  - SET: Override the leaderboard operator with SET.
+    /// Override the leaderboard operator with DECREMENT.
     DECREMENT = 4,
 }
 
+impl ApiOverrideOperator {
+    /// The name of the variant in the API.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            ApiOverrideOperator::NO_OVERRIDE => "NO_OVERRIDE",
+            ApiOverrideOperator::BEST => "BEST",
+            ApiOverrideOperator::SET => "SET",
+            ApiOverrideOperator::INCREMENT => "INCREMENT",
+            ApiOverrideOperator::DECREMENT => "DECREMENT",
+        }
+    }
+
+    pub fn from_str_name(name: &str) -> Option<Self> {
+        match name {
+            "NO_OVERRIDE" => Some(ApiOverrideOperator::NO_OVERRIDE),
+            "BEST" => Some(ApiOverrideOperator::BEST),
+            "SET" => Some(ApiOverrideOperator::SET),
+            "INCREMENT" => Some(ApiOverrideOperator::INCREMENT),
+            "DECREMENT" => Some(ApiOverrideOperator::DECREMENT),
+            _ => None,
+        }
+    }
+
+    pub fn from_i32(value: i32) -> Option<Self> {
+        match value {
+            0 => Some(ApiOverrideOperator::NO_OVERRIDE),
+            1 => Some(ApiOverrideOperator::BEST),
+            2 => Some(ApiOverrideOperator::SET),
+            3 => Some(ApiOverrideOperator::INCREMENT),
+            4 => Some(ApiOverrideOperator::DECREMENT),
+            _ => None,
+        }
+    }
+}
+
+impl SerJson for ApiOverrideOperator {
+    fn ser_json(&self, _d: usize, s: &mut SerJsonState) {
+        s.label(self.as_str_name());
+    }
+}
+
+impl DeJson for ApiOverrideOperator {
+    fn de_json(state: &mut DeJsonState, input: &mut Chars) -> Result<Self, DeJsonErr> {
+        let value = match state.tok {
+            DeJsonTok::Str => ApiOverrideOperator::from_str_name(&state.as_string()?),
+            _ => ApiOverrideOperator::from_i32(
+                state.i64_range(i32::MIN as i64, i32::MAX as i64)? as i32
+            ),
+        };
+        let value = value.ok_or_else(|| state.err_parse("ApiOverrideOperator"))?;
+        state.next_tok(input)?;
+        Ok(value)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for ApiOverrideOperator {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str_name())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for ApiOverrideOperator {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        crate::api_gen_serde::deserialize_enum(
+            deserializer,
+            "ApiOverrideOperator",
+            ApiOverrideOperator::from_str_name,
+            ApiOverrideOperator::from_i32,
+        )
+    }
+}
+
 /// Storage objects to get.
 #[derive(Debug, DeJson, SerJson, Default, Clone)]
 #[nserde(default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct ApiReadStorageObjectId {
     pub collection: String,
     pub key: String,
@@ -402,6 +867,11 @@ pub struct ApiReadStorageObjectId {
 /// Batch get storage objects.
 #[derive(Debug, DeJson, SerJson, Default, Clone)]
 #[nserde(default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct ApiReadStorageObjectsRequest {
     pub object_ids: Vec<ApiReadStorageObjectId>,
 }
@@ -409,6 +879,11 @@ pub struct ApiReadStorageObjectsRequest {
 /// Execute an Lua function on the server.
 #[derive(Debug, DeJson, SerJson, Default, Clone)]
 #[nserde(default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct ApiRpc {
     pub http_key: String,
     pub id: String,
@@ -418,6 +893,11 @@ pub struct ApiRpc {
 /// A user's session used to authenticate messages.
 #[derive(Debug, DeJson, SerJson, Default, Clone)]
 #[nserde(default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct ApiSession {
     pub created: bool,
     pub refresh_token: String,
@@ -427,6 +907,11 @@ pub struct ApiSession {
 /// Log out a session, invalidate a refresh token, or log out all sessions/refresh tokens for a user.
 #[derive(Debug, DeJson, SerJson, Default, Clone)]
 #[nserde(default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct ApiSessionLogoutRequest {
     pub refresh_token: String,
     pub token: String,
@@ -435,6 +920,11 @@ pub struct ApiSessionLogoutRequest {
 /// Authenticate against the server with a refresh token.
 #[derive(Debug, DeJson, SerJson, Default, Clone)]
 #[nserde(default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct ApiSessionRefreshRequest {
     pub token: String,
     pub vars: HashMap<String, String>,
@@ -443,13 +933,20 @@ pub struct ApiSessionRefreshRequest {
 /// An object within the storage engine.
 #[derive(Debug, DeJson, SerJson, Default, Clone)]
 #[nserde(default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct ApiStorageObject {
     pub collection: String,
-    pub create_time: String,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub create_time: Option<Timestamp>,
     pub key: String,
     pub permission_read: i32,
     pub permission_write: i32,
-    pub update_time: String,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub update_time: Option<Timestamp>,
     pub user_id: String,
     pub value: String,
     pub version: String,
@@ -458,6 +955,11 @@ pub struct ApiStorageObject {
 /// A storage acknowledgement.
 #[derive(Debug, DeJson, SerJson, Default, Clone)]
 #[nserde(default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct ApiStorageObjectAck {
     pub collection: String,
     pub key: String,
@@ -468,6 +970,11 @@ pub struct ApiStorageObjectAck {
 /// Batch of acknowledgements for the storage object write.
 #[derive(Debug, DeJson, SerJson, Default, Clone)]
 #[nserde(default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct ApiStorageObjectAcks {
     pub acks: Vec<ApiStorageObjectAck>,
 }
@@ -475,6 +982,11 @@ pub struct ApiStorageObjectAcks {
 /// List of storage objects.
 #[derive(Debug, DeJson, SerJson, Default, Clone)]
 #[nserde(default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct ApiStorageObjectList {
     pub cursor: String,
     pub objects: Vec<ApiStorageObject>,
@@ -483,21 +995,47 @@ pub struct ApiStorageObjectList {
 /// Batch of storage objects.
 #[derive(Debug, DeJson, SerJson, Default, Clone)]
 #[nserde(default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct ApiStorageObjects {
     pub objects: Vec<ApiStorageObject>,
 }
 
+/// A list of validated subscriptions stored by Nakama.
+#[derive(Debug, DeJson, SerJson, Default, Clone)]
+#[nserde(default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct ApiSubscriptionList {
+    pub cursor: String,
+    pub prev_cursor: String,
+    pub validated_subscriptions: Vec<ApiValidatedSubscription>,
+}
+
 /// A tournament on the server.
 #[derive(Debug, DeJson, SerJson, Default, Clone)]
 #[nserde(default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct ApiTournament {
     pub can_enter: bool,
     pub category: i32,
-    pub create_time: String,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub create_time: Option<Timestamp>,
     pub description: String,
     pub duration: i32,
     pub end_active: i32,
-    pub end_time: String,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub end_time: Option<Timestamp>,
     pub id: String,
     pub max_num_score: i32,
     pub max_size: i32,
@@ -506,14 +1044,21 @@ pub struct ApiTournament {
     pub size: i32,
     pub sort_order: i32,
     pub start_active: i32,
-    pub start_time: String,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub start_time: Option<Timestamp>,
     pub title: String,
 }
 
 /// A list of tournaments.
 #[derive(Debug, DeJson, SerJson, Default, Clone)]
 #[nserde(default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct ApiTournamentList {
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub cursor: Option<String>,
     pub tournaments: Vec<ApiTournament>,
 }
@@ -521,9 +1066,16 @@ pub struct ApiTournamentList {
 /// A set of tournament records which may be part of a tournament records page or a batch of individual records.
 #[derive(Debug, DeJson, SerJson, Default, Clone)]
 #[nserde(default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct ApiTournamentRecordList {
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub next_cursor: Option<String>,
     pub owner_records: Vec<ApiLeaderboardRecord>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub prev_cursor: Option<String>,
     pub records: Vec<ApiLeaderboardRecord>,
 }
@@ -531,6 +1083,11 @@ pub struct ApiTournamentRecordList {
 /// Update a user's account details.
 #[derive(Debug, DeJson, SerJson, Default, Clone)]
 #[nserde(default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct ApiUpdateAccountRequest {
     pub avatar_url: String,
     pub display_name: String,
@@ -543,6 +1100,11 @@ pub struct ApiUpdateAccountRequest {
 /// Update fields in a given group.
 #[derive(Debug, DeJson, SerJson, Default, Clone)]
 #[nserde(default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct ApiUpdateGroupRequest {
     pub avatar_url: String,
     pub description: String,
@@ -555,10 +1117,16 @@ pub struct ApiUpdateGroupRequest {
 /// A user in the server.
 #[derive(Debug, DeJson, SerJson, Default, Clone)]
 #[nserde(default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct ApiUser {
     pub apple_id: String,
     pub avatar_url: String,
-    pub create_time: String,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub create_time: Option<Timestamp>,
     pub display_name: String,
     pub edge_count: i32,
     pub facebook_id: String,
@@ -572,13 +1140,19 @@ pub struct ApiUser {
     pub online: bool,
     pub steam_id: String,
     pub timezone: String,
-    pub update_time: String,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub update_time: Option<Timestamp>,
     pub username: String,
 }
 
 /// A list of groups belonging to a user, along with the user's role in each group.
 #[derive(Debug, DeJson, SerJson, Default, Clone)]
 #[nserde(default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct ApiUserGroupList {
     pub cursor: String,
     pub user_groups: Vec<UserGroupListUserGroup>,
@@ -587,6 +1161,11 @@ pub struct ApiUserGroupList {
 /// A collection of zero or more users.
 #[derive(Debug, DeJson, SerJson, Default, Clone)]
 #[nserde(default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct ApiUsers {
     pub users: Vec<ApiUser>,
 }
@@ -594,21 +1173,42 @@ pub struct ApiUsers {
 ///
 #[derive(Debug, DeJson, SerJson, Default, Clone)]
 #[nserde(default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct ApiValidatePurchaseAppleRequest {
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub persist: Option<bool>,
     pub receipt: String,
 }
 
 ///
 #[derive(Debug, DeJson, SerJson, Default, Clone)]
 #[nserde(default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct ApiValidatePurchaseGoogleRequest {
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub persist: Option<bool>,
     pub purchase: String,
 }
 
 ///
 #[derive(Debug, DeJson, SerJson, Default, Clone)]
 #[nserde(default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct ApiValidatePurchaseHuaweiRequest {
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub persist: Option<bool>,
     pub purchase: String,
     pub signature: String,
 }
@@ -616,27 +1216,118 @@ pub struct ApiValidatePurchaseHuaweiRequest {
 ///
 #[derive(Debug, DeJson, SerJson, Default, Clone)]
 #[nserde(default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct ApiValidatePurchaseResponse {
     pub validated_purchases: Vec<ApiValidatedPurchase>,
 }
 
+/// Apple Subscription validation request
+#[derive(Debug, DeJson, SerJson, Default, Clone)]
+#[nserde(default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct ApiValidateSubscriptionAppleRequest {
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub persist: Option<bool>,
+    pub receipt: String,
+}
+
+/// Google Subscription validation request
+#[derive(Debug, DeJson, SerJson, Default, Clone)]
+#[nserde(default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct ApiValidateSubscriptionGoogleRequest {
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub persist: Option<bool>,
+    pub receipt: String,
+}
+
+/// Validate Subscription response.
+#[derive(Debug, DeJson, SerJson, Default, Clone)]
+#[nserde(default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct ApiValidateSubscriptionResponse {
+    pub validated_subscription: ApiValidatedSubscription,
+}
+
 /// Validated Purchase stored by Nakama.
 #[derive(Debug, DeJson, SerJson, Default, Clone)]
 #[nserde(default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct ApiValidatedPurchase {
-    pub create_time: String,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub create_time: Option<Timestamp>,
     pub environment: ValidatedPurchaseEnvironment,
     pub product_id: String,
     pub provider_response: String,
-    pub purchase_time: String,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub purchase_time: Option<Timestamp>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub refund_time: Option<Timestamp>,
+    pub seen_before: bool,
     pub store: ValidatedPurchaseStore,
     pub transaction_id: String,
-    pub update_time: String,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub update_time: Option<Timestamp>,
+    pub user_id: String,
+}
+
+/// A validated subscription stored by Nakama.
+#[derive(Debug, DeJson, SerJson, Default, Clone)]
+#[nserde(default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct ApiValidatedSubscription {
+    pub active: bool,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub create_time: Option<Timestamp>,
+    pub environment: ValidatedPurchaseEnvironment,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub expiry_time: Option<Timestamp>,
+    pub original_transaction_id: String,
+    pub product_id: String,
+    pub provider_notification: String,
+    pub provider_response: String,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub purchase_time: Option<Timestamp>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub refund_time: Option<Timestamp>,
+    pub store: ValidatedPurchaseStore,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub update_time: Option<Timestamp>,
+    pub user_id: String,
 }
 
 /// The object to store.
 #[derive(Debug, DeJson, SerJson, Default, Clone)]
 #[nserde(default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct ApiWriteStorageObject {
     pub collection: String,
     pub key: String,
@@ -649,6 +1340,11 @@ pub struct ApiWriteStorageObject {
 /// Write objects to the storage engine.
 #[derive(Debug, DeJson, SerJson, Default, Clone)]
 #[nserde(default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct ApiWriteStorageObjectsRequest {
     pub objects: Vec<ApiWriteStorageObject>,
 }
@@ -656,6 +1352,11 @@ pub struct ApiWriteStorageObjectsRequest {
 ///
 #[derive(Debug, DeJson, SerJson, Default, Clone)]
 #[nserde(default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct ProtobufAny {
     pub type_url: String,
     pub value: String,
@@ -664,6 +1365,11 @@ pub struct ProtobufAny {
 ///
 #[derive(Debug, DeJson, SerJson, Default, Clone)]
 #[nserde(default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct RpcStatus {
     pub code: i32,
     pub details: Vec<ProtobufAny>,
@@ -694,6 +1400,31 @@ pub fn healthcheck(bearer_token: &str) -> RestRequest<()> {
         _marker: std::marker::PhantomData,
     }
 }
+/// Delete the current user's account.
+pub fn delete_account(bearer_token: &str) -> RestRequest<()> {
+    #[allow(unused_mut)]
+    let mut urlpath = "/v2/account".to_string();
+
+    #[allow(unused_mut)]
+    let mut query_params = String::new();
+
+    let authentication = Authentication::Bearer {
+        token: bearer_token.to_owned(),
+    };
+
+    let body_json = String::new();
+
+    let method = Method::Delete;
+
+    RestRequest {
+        authentication,
+        urlpath,
+        query_params,
+        body: body_json,
+        method,
+        _marker: std::marker::PhantomData,
+    }
+}
 /// Fetch the current user's account.
 pub fn get_account(bearer_token: &str) -> RestRequest<ApiAccount> {
     #[allow(unused_mut)]
@@ -2266,6 +2997,116 @@ pub fn validate_purchase_huawei(
         _marker: std::marker::PhantomData,
     }
 }
+/// List user's subscriptions.
+pub fn list_subscriptions(
+    bearer_token: &str,
+    body: ApiListSubscriptionsRequest,
+) -> RestRequest<ApiSubscriptionList> {
+    #[allow(unused_mut)]
+    let mut urlpath = "/v2/iap/subscription".to_string();
+
+    #[allow(unused_mut)]
+    let mut query_params = String::new();
+
+    let authentication = Authentication::Bearer {
+        token: bearer_token.to_owned(),
+    };
+    let body_json = body.serialize_json();
+
+    let method = Method::Post;
+
+    RestRequest {
+        authentication,
+        urlpath,
+        query_params,
+        body: body_json,
+        method,
+        _marker: std::marker::PhantomData,
+    }
+}
+/// Validate Apple Subscription Receipt
+pub fn validate_subscription_apple(
+    bearer_token: &str,
+    body: ApiValidateSubscriptionAppleRequest,
+) -> RestRequest<ApiValidateSubscriptionResponse> {
+    #[allow(unused_mut)]
+    let mut urlpath = "/v2/iap/subscription/apple".to_string();
+
+    #[allow(unused_mut)]
+    let mut query_params = String::new();
+
+    let authentication = Authentication::Bearer {
+        token: bearer_token.to_owned(),
+    };
+    let body_json = body.serialize_json();
+
+    let method = Method::Post;
+
+    RestRequest {
+        authentication,
+        urlpath,
+        query_params,
+        body: body_json,
+        method,
+        _marker: std::marker::PhantomData,
+    }
+}
+/// Validate Google Subscription Receipt
+pub fn validate_subscription_google(
+    bearer_token: &str,
+    body: ApiValidateSubscriptionGoogleRequest,
+) -> RestRequest<ApiValidateSubscriptionResponse> {
+    #[allow(unused_mut)]
+    let mut urlpath = "/v2/iap/subscription/google".to_string();
+
+    #[allow(unused_mut)]
+    let mut query_params = String::new();
+
+    let authentication = Authentication::Bearer {
+        token: bearer_token.to_owned(),
+    };
+    let body_json = body.serialize_json();
+
+    let method = Method::Post;
+
+    RestRequest {
+        authentication,
+        urlpath,
+        query_params,
+        body: body_json,
+        method,
+        _marker: std::marker::PhantomData,
+    }
+}
+/// Get subscription by product id.
+pub fn get_subscription(
+    bearer_token: &str,
+    product_id: &str,
+) -> RestRequest<ApiValidatedSubscription> {
+    #[allow(unused_mut)]
+    let mut urlpath = "/v2/iap/subscription/{productId}".to_string();
+    urlpath = urlpath.replace("{productId}", product_id);
+
+    #[allow(unused_mut)]
+    let mut query_params = String::new();
+
+    let authentication = Authentication::Bearer {
+        token: bearer_token.to_owned(),
+    };
+
+    let body_json = String::new();
+
+    let method = Method::Get;
+
+    RestRequest {
+        authentication,
+        urlpath,
+        query_params,
+        body: body_json,
+        method,
+        _marker: std::marker::PhantomData,
+    }
+}
 /// Delete a leaderboard record.
 pub fn delete_leaderboard_record(bearer_token: &str, leaderboard_id: &str) -> RestRequest<()> {
     #[allow(unused_mut)]
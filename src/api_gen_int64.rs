@@ -0,0 +1,63 @@
+use core::str::Chars;
+use nanoserde::{DeJson, DeJsonErr, DeJsonState, DeJsonTok, SerJson, SerJsonState};
+
+/// The `nserde(proxy)` of the `i64` fields of `api_gen`. The server encodes 64 bit integers as
+/// JSON strings, because JavaScript numbers can't represent them exactly.
+pub struct Int64String(i64);
+
+impl From<&i64> for Int64String {
+    fn from(value: &i64) -> Self {
+        Int64String(*value)
+    }
+}
+
+impl From<&Int64String> for i64 {
+    fn from(value: &Int64String) -> Self {
+        value.0
+    }
+}
+
+impl SerJson for Int64String {
+    fn ser_json(&self, d: usize, s: &mut SerJsonState) {
+        self.0.to_string().ser_json(d, s);
+    }
+}
+
+impl DeJson for Int64String {
+    fn de_json(state: &mut DeJsonState, input: &mut Chars) -> Result<Self, DeJsonErr> {
+        let value = match state.tok {
+            DeJsonTok::Str => {
+                let value = state
+                    .as_string()?
+                    .parse()
+                    .map_err(|_| state.err_parse("i64"))?;
+                state.next_tok(input)?;
+                value
+            }
+            // Plain numbers are accepted as well
+            _ => i64::de_json(state, input)?,
+        };
+        Ok(Int64String(value))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Int64String;
+    use nanoserde::{DeJson, SerJson};
+
+    #[test]
+    fn test_int64_string() {
+        assert_eq!(Int64String(-42).serialize_json(), "\"-42\"");
+        assert_eq!(
+            i64::from(&Int64String::deserialize_json("\"-42\"").unwrap()),
+            -42
+        );
+        assert_eq!(
+            i64::from(&Int64String::deserialize_json("\"9223372036854775807\"").unwrap()),
+            i64::MAX
+        );
+        assert_eq!(i64::from(&Int64String::deserialize_json("42").unwrap()), 42);
+        assert_eq!(Int64String::deserialize_json("\"4.2\"").is_err(), true);
+    }
+}
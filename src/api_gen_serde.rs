@@ -0,0 +1,123 @@
+//! The serde counterparts of the nanoserde encodings used by `api_gen`, so both produce the same JSON.
+use crate::api_gen_int64::Int64String;
+use serde::de::{Error, Unexpected, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::convert::TryFrom;
+use std::fmt::Formatter;
+
+struct EnumVisitor<T> {
+    name: &'static str,
+    from_str_name: fn(&str) -> Option<T>,
+    from_i32: fn(i32) -> Option<T>,
+}
+
+impl<'de, T> Visitor<'de> for EnumVisitor<T> {
+    type Value = T;
+
+    fn expecting(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "a variant name or number of {}", self.name)
+    }
+
+    fn visit_str<E: Error>(self, value: &str) -> Result<T, E> {
+        (self.from_str_name)(value).ok_or_else(|| E::invalid_value(Unexpected::Str(value), &self))
+    }
+
+    fn visit_i64<E: Error>(self, value: i64) -> Result<T, E> {
+        i32::try_from(value)
+            .ok()
+            .and_then(self.from_i32)
+            .ok_or_else(|| E::invalid_value(Unexpected::Signed(value), &self))
+    }
+
+    fn visit_u64<E: Error>(self, value: u64) -> Result<T, E> {
+        i32::try_from(value)
+            .ok()
+            .and_then(self.from_i32)
+            .ok_or_else(|| E::invalid_value(Unexpected::Unsigned(value), &self))
+    }
+}
+
+/// Deserializes a generated enum from the name or the number of the variant.
+pub(crate) fn deserialize_enum<'de, D: Deserializer<'de>, T>(
+    deserializer: D,
+    name: &'static str,
+    from_str_name: fn(&str) -> Option<T>,
+    from_i32: fn(i32) -> Option<T>,
+) -> Result<T, D::Error> {
+    deserializer.deserialize_any(EnumVisitor {
+        name,
+        from_str_name,
+        from_i32,
+    })
+}
+
+struct Int64Visitor;
+
+impl<'de> Visitor<'de> for Int64Visitor {
+    type Value = i64;
+
+    fn expecting(&self, f: &mut Formatter) -> std::fmt::Result {
+        f.write_str("a 64 bit integer or a string containing one")
+    }
+
+    fn visit_str<E: Error>(self, value: &str) -> Result<i64, E> {
+        value
+            .parse()
+            .map_err(|_| E::invalid_value(Unexpected::Str(value), &self))
+    }
+
+    fn visit_i64<E: Error>(self, value: i64) -> Result<i64, E> {
+        Ok(value)
+    }
+
+    fn visit_u64<E: Error>(self, value: u64) -> Result<i64, E> {
+        i64::try_from(value).map_err(|_| E::invalid_value(Unexpected::Unsigned(value), &self))
+    }
+}
+
+impl Serialize for Int64String {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&i64::from(self))
+    }
+}
+
+impl<'de> Deserialize<'de> for Int64String {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer
+            .deserialize_any(Int64Visitor)
+            .map(|value| Int64String::from(&value))
+    }
+}
+
+/// `serde(with)` for the `i64` fields, which the server encodes as JSON strings.
+pub(crate) mod int64_string {
+    use crate::api_gen_int64::Int64String;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub(crate) fn serialize<S: Serializer>(value: &i64, serializer: S) -> Result<S::Ok, S::Error> {
+        Int64String::from(value).serialize(serializer)
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<i64, D::Error> {
+        Int64String::deserialize(deserializer).map(|value| i64::from(&value))
+    }
+}
+
+/// `serde(with)` for the `Option<i64>` fields.
+pub(crate) mod option_int64_string {
+    use crate::api_gen_int64::Int64String;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub(crate) fn serialize<S: Serializer>(
+        value: &Option<i64>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        value.as_ref().map(Int64String::from).serialize(serializer)
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<i64>, D::Error> {
+        Option::<Int64String>::deserialize(deserializer).map(|value| value.as_ref().map(i64::from))
+    }
+}
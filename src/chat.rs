@@ -0,0 +1,367 @@
+//! A handle to a joined chat channel.
+use crate::api::ApiChannelMessageList;
+use crate::client::Client;
+use crate::paginator::Paginator;
+use crate::socket::{
+    self, ChannelJoinType, ChannelMessageAck, ChannelPresenceEvent, HandlerId, Socket, UserPresence,
+};
+use crate::socket_adapter::SocketAdapter;
+use crate::web_socket::{LocalWebSocket, WebSocketError};
+use std::sync::{Arc, Mutex};
+
+#[derive(Default)]
+struct Presences {
+    // `None` until the join response arrived
+    channel_id: Option<String>,
+    presences: Vec<UserPresence>,
+    // Events received before the join response, when the ID of the channel was not known yet
+    pending: Vec<ChannelPresenceEvent>,
+}
+
+impl Presences {
+    fn apply(&mut self, event: ChannelPresenceEvent) {
+        for leave in &event.leaves {
+            self.presences
+                .retain(|presence| presence.session_id != leave.session_id);
+        }
+        for join in event.joins {
+            if !self
+                .presences
+                .iter()
+                .any(|presence| presence.session_id == join.session_id)
+            {
+                self.presences.push(join);
+            }
+        }
+    }
+
+    // The presence callback, registered before joining because the server can send presence
+    // events of the channel before the join response
+    fn handler(presences: &Arc<Mutex<Presences>>) -> impl Fn(ChannelPresenceEvent) + Send + Sync {
+        let presences = presences.clone();
+        move |event| {
+            let mut presences = presences.lock().expect("panic inside other mutex!");
+            if presences.channel_id.is_none() {
+                presences.pending.push(event);
+            } else if presences.channel_id.as_deref() == Some(event.channel_id.as_str()) {
+                presences.apply(event);
+            }
+        }
+    }
+
+    fn joined(&mut self, channel: &socket::Channel) {
+        self.channel_id = Some(channel.id.clone());
+        self.presences = channel.presences.clone();
+        for event in std::mem::take(&mut self.pending) {
+            if event.channel_id == channel.id {
+                self.apply(event);
+            }
+        }
+    }
+}
+
+/// A chat channel joined with `Socket::join_room`, `Socket::join_direct` or
+/// `Socket::join_group_chat`, or the functions of the same name of `LocalWebSocket`.
+///
+/// The channel keeps track of the users in it. It stops tracking them when it is dropped, but
+/// stays joined until `leave` is called or the socket is closed.
+///
+/// ```
+/// # use nakama_rs::client::Client;
+/// # use nakama_rs::session::Session;
+/// # use nakama_rs::socket::Socket;
+/// async fn greet<C: Client + Sync + 'static, S: Socket + Clone + Send + Sync>(
+///     client: &C,
+///     session: &mut Session,
+///     socket: &S,
+/// ) {
+///     let channel = socket.join_room("Lobby", true, false).await.unwrap();
+///     channel.send(r#"{"text":"Hello!"}"#).await.unwrap();
+///     println!("{} users are in the lobby", channel.presences().len());
+///
+///     let mut history = channel.history(Some(false));
+///     while let Some(message) = history.next(client, session).await.unwrap() {
+///         println!("{}", message.content);
+///     }
+///     channel.leave().await.unwrap();
+/// }
+/// ```
+pub struct Channel<S> {
+    socket: S,
+    channel: socket::Channel,
+    presences: Arc<Mutex<Presences>>,
+    presence_handler: HandlerId,
+    remove_handler: fn(&S, HandlerId) -> bool,
+}
+
+impl<S: ChatSocket> Channel<S> {
+    /// Join the channel `target` of type `channel_type`, see `Socket::join_chat`.
+    pub async fn join(
+        socket: S,
+        target: &str,
+        channel_type: ChannelJoinType,
+        persistence: bool,
+        hidden: bool,
+    ) -> Result<Channel<S>, S::Error> {
+        let presences = Arc::new(Mutex::new(Presences::default()));
+        let presence_handler = socket.on_received_channel_presence(Presences::handler(&presences));
+        let channel = match socket
+            .join_chat(target, channel_type, persistence, hidden)
+            .await
+        {
+            Ok(channel) => channel,
+            Err(err) => {
+                socket.remove_handler(presence_handler);
+                return Err(err);
+            }
+        };
+        presences
+            .lock()
+            .expect("panic inside other mutex!")
+            .joined(&channel);
+
+        Ok(Channel {
+            socket,
+            channel,
+            presences,
+            presence_handler,
+            remove_handler: S::remove_handler,
+        })
+    }
+
+    /// Send the message `content`, which must be a JSON object.
+    pub async fn send(&self, content: &str) -> Result<ChannelMessageAck, S::Error> {
+        self.socket
+            .write_chat_message(&self.channel.id, content)
+            .await
+    }
+
+    /// Replace the content of the message `message_id`.
+    pub async fn update(
+        &self,
+        message_id: &str,
+        content: &str,
+    ) -> Result<ChannelMessageAck, S::Error> {
+        self.socket
+            .update_chat_message(&self.channel.id, message_id, content)
+            .await
+    }
+
+    pub async fn remove(&self, message_id: &str) -> Result<ChannelMessageAck, S::Error> {
+        self.socket
+            .remove_chat_message(&self.channel.id, message_id)
+            .await
+    }
+
+    pub async fn leave(self) -> Result<(), S::Error> {
+        self.socket.leave_chat(&self.channel.id).await
+    }
+}
+
+impl<S> Channel<S> {
+    pub fn id(&self) -> &str {
+        &self.channel.id
+    }
+
+    /// The name of the room, empty for other channels.
+    pub fn room_name(&self) -> &str {
+        &self.channel.room_name
+    }
+
+    /// The ID of the group, empty for other channels.
+    pub fn group_id(&self) -> &str {
+        &self.channel.group_id
+    }
+
+    /// The IDs of the two users of a direct message, empty for other channels.
+    pub fn user_ids(&self) -> (&str, &str) {
+        (&self.channel.user_id_one, &self.channel.user_id_two)
+    }
+
+    /// The presence of the current user in the channel.
+    pub fn self_presence(&self) -> &UserPresence {
+        &self.channel._self
+    }
+
+    /// The users currently in the channel, except hidden ones.
+    pub fn presences(&self) -> Vec<UserPresence> {
+        self.presences
+            .lock()
+            .expect("panic inside other mutex!")
+            .presences
+            .clone()
+    }
+
+    /// The persisted messages of the channel, oldest first if `forward` is `true`.
+    pub fn history<C: Client + Sync + 'static>(
+        &self,
+        forward: Option<bool>,
+    ) -> Paginator<C, ApiChannelMessageList> {
+        Paginator::channel_messages(&self.channel.id, forward)
+    }
+}
+
+/// Stops tracking the presences. The channel stays joined.
+impl<S> Drop for Channel<S> {
+    fn drop(&mut self) {
+        (self.remove_handler)(&self.socket, self.presence_handler);
+    }
+}
+
+mod private {
+    use crate::socket::{
+        self, ChannelJoinType, ChannelMessageAck, ChannelPresenceEvent, HandlerId,
+    };
+    use std::future::Future;
+
+    /// The functions of the socket used by `Channel`. Implemented by every `Socket` and by
+    /// `LocalWebSocket`, whose futures are not `Send`.
+    pub trait ChatSocket {
+        type Error;
+
+        fn on_received_channel_presence<T>(&self, callback: T) -> HandlerId
+        where
+            T: Fn(ChannelPresenceEvent) + Send + 'static;
+
+        fn remove_handler(&self, id: HandlerId) -> bool;
+
+        fn join_chat(
+            &self,
+            target: &str,
+            channel_type: ChannelJoinType,
+            persistence: bool,
+            hidden: bool,
+        ) -> impl Future<Output = Result<socket::Channel, Self::Error>>;
+
+        fn leave_chat(&self, channel_id: &str) -> impl Future<Output = Result<(), Self::Error>>;
+
+        fn remove_chat_message(
+            &self,
+            channel_id: &str,
+            message_id: &str,
+        ) -> impl Future<Output = Result<ChannelMessageAck, Self::Error>>;
+
+        fn update_chat_message(
+            &self,
+            channel_id: &str,
+            message_id: &str,
+            content: &str,
+        ) -> impl Future<Output = Result<ChannelMessageAck, Self::Error>>;
+
+        fn write_chat_message(
+            &self,
+            channel_id: &str,
+            content: &str,
+        ) -> impl Future<Output = Result<ChannelMessageAck, Self::Error>>;
+    }
+}
+
+use private::ChatSocket;
+
+impl<S: Socket> ChatSocket for S {
+    type Error = S::Error;
+
+    fn on_received_channel_presence<T>(&self, callback: T) -> HandlerId
+    where
+        T: Fn(ChannelPresenceEvent) + Send + 'static,
+    {
+        Socket::on_received_channel_presence(self, callback)
+    }
+
+    fn remove_handler(&self, id: HandlerId) -> bool {
+        Socket::remove_handler(self, id)
+    }
+
+    async fn join_chat(
+        &self,
+        target: &str,
+        channel_type: ChannelJoinType,
+        persistence: bool,
+        hidden: bool,
+    ) -> Result<socket::Channel, S::Error> {
+        Socket::join_chat(self, target, channel_type, persistence, hidden).await
+    }
+
+    async fn leave_chat(&self, channel_id: &str) -> Result<(), S::Error> {
+        Socket::leave_chat(self, channel_id).await
+    }
+
+    async fn remove_chat_message(
+        &self,
+        channel_id: &str,
+        message_id: &str,
+    ) -> Result<ChannelMessageAck, S::Error> {
+        Socket::remove_chat_message(self, channel_id, message_id).await
+    }
+
+    async fn update_chat_message(
+        &self,
+        channel_id: &str,
+        message_id: &str,
+        content: &str,
+    ) -> Result<ChannelMessageAck, S::Error> {
+        Socket::update_chat_message(self, channel_id, message_id, content).await
+    }
+
+    async fn write_chat_message(
+        &self,
+        channel_id: &str,
+        content: &str,
+    ) -> Result<ChannelMessageAck, S::Error> {
+        Socket::write_chat_message(self, channel_id, content).await
+    }
+}
+
+impl<A: SocketAdapter> ChatSocket for LocalWebSocket<A> {
+    type Error = WebSocketError<A>;
+
+    fn on_received_channel_presence<T>(&self, callback: T) -> HandlerId
+    where
+        T: Fn(ChannelPresenceEvent) + Send + 'static,
+    {
+        LocalWebSocket::on_received_channel_presence(self, callback)
+    }
+
+    fn remove_handler(&self, id: HandlerId) -> bool {
+        LocalWebSocket::remove_handler(self, id)
+    }
+
+    async fn join_chat(
+        &self,
+        target: &str,
+        channel_type: ChannelJoinType,
+        persistence: bool,
+        hidden: bool,
+    ) -> Result<socket::Channel, WebSocketError<A>> {
+        LocalWebSocket::join_chat(self, target, channel_type, persistence, hidden).await
+    }
+
+    async fn leave_chat(&self, channel_id: &str) -> Result<(), WebSocketError<A>> {
+        LocalWebSocket::leave_chat(self, channel_id).await
+    }
+
+    async fn remove_chat_message(
+        &self,
+        channel_id: &str,
+        message_id: &str,
+    ) -> Result<ChannelMessageAck, WebSocketError<A>> {
+        LocalWebSocket::remove_chat_message(self, channel_id, message_id).await
+    }
+
+    async fn update_chat_message(
+        &self,
+        channel_id: &str,
+        message_id: &str,
+        content: &str,
+    ) -> Result<ChannelMessageAck, WebSocketError<A>> {
+        LocalWebSocket::update_chat_message(self, channel_id, message_id, content).await
+    }
+
+    async fn write_chat_message(
+        &self,
+        channel_id: &str,
+        content: &str,
+    ) -> Result<ChannelMessageAck, WebSocketError<A>> {
+        LocalWebSocket::write_chat_message(self, channel_id, content).await
+    }
+}
@@ -1,7 +1,10 @@
-use crate::api::{ApiChannelMessageList, ApiDeleteStorageObjectId, ApiFriendList, ApiGroup, ApiGroupList, ApiGroupUserList, ApiLeaderboardRecord, ApiLeaderboardRecordList, ApiMatchList, ApiNotificationList, ApiReadStorageObjectId, ApiRpc, ApiStorageObjectAcks, ApiStorageObjectList, ApiStorageObjects, ApiTournamentList, ApiTournamentRecordList, ApiUserGroupList, ApiUsers, ApiValidatePurchaseResponse, ApiWriteStorageObject, ApiOverrideOperator};
-use crate::api_gen::ApiAccount;
+/* Code generated by codegen/src/main.rs. DO NOT EDIT. */
+
+use crate::api;
+use crate::enums::{FriendState, GroupRole};
 use crate::session::Session;
 use async_trait::async_trait;
+use nanoserde::SerJson;
 use std::collections::HashMap;
 use std::error::Error;
 
@@ -9,6 +12,7 @@ use std::error::Error;
 pub trait Client {
     type Error: Error;
 
+    /// Add friends by ID or username to a user's account.
     async fn add_friends(
         &self,
         session: &mut Session,
@@ -16,13 +20,19 @@ pub trait Client {
         usernames: &[&str],
     ) -> Result<(), Self::Error>;
 
+    /// Add users to a group.
     async fn add_group_users(
         &self,
         session: &mut Session,
         group_id: &str,
-        ids: &[&str],
+        user_ids: &[&str],
     ) -> Result<(), Self::Error>;
 
+    /// Authenticate a user with an Apple ID against the server.
+    ///
+    /// Authenticate user with the ID `token` received from Apple.
+    /// If the user does not exist and `create` is passed, the user is created with the optional `username`.
+    /// `vars` can contain extra information that will be bundled in the session token.
     async fn authenticate_apple(
         &self,
         token: &str,
@@ -31,6 +41,11 @@ pub trait Client {
         vars: HashMap<String, String>,
     ) -> Result<Session, Self::Error>;
 
+    /// Authenticate a user with a custom id.
+    ///
+    /// Authenticate user with a custom identifier usually obtained from an external authentication service.
+    /// If the user does not exist and `create` is passed, the user is created with the optional `username`.
+    /// `vars` can contain extra information that will be bundled in the session token.
     async fn authenticate_custom(
         &self,
         id: &str,
@@ -39,6 +54,12 @@ pub trait Client {
         vars: HashMap<String, String>,
     ) -> Result<Session, Self::Error>;
 
+    /// Authenticate a user with a device id.
+    ///
+    /// TODO: Mention minimum length requirements;
+    /// Authenticate user with a device identifier usually obtained from a platform API.
+    /// If the user does not exist and `create` is passed, the user is created with the optional `username`.
+    /// `vars` can contain extra information that will be bundled in the session token.
     async fn authenticate_device(
         &self,
         id: &str,
@@ -47,6 +68,8 @@ pub trait Client {
         vars: HashMap<String, String>,
     ) -> Result<Session, Self::Error>;
 
+    /// If the user does not exist and `create` is passed, the user is created with the optional `username`.
+    /// `vars` can contain extra information that will be bundled in the session token.
     async fn authenticate_email(
         &self,
         email: &str,
@@ -56,6 +79,8 @@ pub trait Client {
         vars: HashMap<String, String>,
     ) -> Result<Session, Self::Error>;
 
+    /// If the user does not exist and `create` is passed, the user is created with the optional `username`.
+    /// `vars` can contain extra information that will be bundled in the session token.
     async fn authenticate_facebook(
         &self,
         token: &str,
@@ -65,6 +90,18 @@ pub trait Client {
         import: bool,
     ) -> Result<Session, Self::Error>;
 
+    /// If the user does not exist and `create` is passed, the user is created with the optional `username`.
+    /// `vars` can contain extra information that will be bundled in the session token.
+    async fn authenticate_facebook_instant_game(
+        &self,
+        signed_player_info: &str,
+        username: Option<&str>,
+        create: bool,
+        vars: HashMap<String, String>,
+    ) -> Result<Session, Self::Error>;
+
+    /// If the user does not exist and `create` is passed, the user is created with the optional `username`.
+    /// `vars` can contain extra information that will be bundled in the session token.
     async fn authenticate_game_center(
         &self,
         bundle_id: &str,
@@ -78,6 +115,8 @@ pub trait Client {
         vars: HashMap<String, String>,
     ) -> Result<Session, Self::Error>;
 
+    /// If the user does not exist and `create` is passed, the user is created with the optional `username`.
+    /// `vars` can contain extra information that will be bundled in the session token.
     async fn authenticate_google(
         &self,
         token: &str,
@@ -86,6 +125,8 @@ pub trait Client {
         vars: HashMap<String, String>,
     ) -> Result<Session, Self::Error>;
 
+    /// If the user does not exist and `create` is passed, the user is created with the optional `username`.
+    /// `vars` can contain extra information that will be bundled in the session token.
     async fn authenticate_steam(
         &self,
         token: &str,
@@ -94,6 +135,7 @@ pub trait Client {
         vars: HashMap<String, String>,
     ) -> Result<Session, Self::Error>;
 
+    /// Ban a set of users from a group.
     async fn ban_group_users(
         &self,
         session: &mut Session,
@@ -101,6 +143,7 @@ pub trait Client {
         user_ids: &[&str],
     ) -> Result<(), Self::Error>;
 
+    /// Block one or more users by ID or username.
     async fn block_friends(
         &self,
         session: &mut Session,
@@ -108,6 +151,7 @@ pub trait Client {
         usernames: &[&str],
     ) -> Result<(), Self::Error>;
 
+    /// Create a new group with the current user as the owner.
     async fn create_group(
         &self,
         session: &mut Session,
@@ -117,8 +161,12 @@ pub trait Client {
         lang_tag: Option<&str>,
         open: Option<bool>,
         max_count: Option<i32>,
-    ) -> Result<ApiGroup, Self::Error>;
+    ) -> Result<api::ApiGroup, Self::Error>;
 
+    /// Delete the account of the authenticated user with all its data.
+    async fn delete_account(&self, session: &mut Session) -> Result<(), Self::Error>;
+
+    /// Delete one or more users by ID or username.
     async fn delete_friends(
         &self,
         session: &mut Session,
@@ -126,26 +174,31 @@ pub trait Client {
         usernames: &[&str],
     ) -> Result<(), Self::Error>;
 
+    /// Delete a group by ID.
     async fn delete_group(&self, session: &mut Session, group_id: &str) -> Result<(), Self::Error>;
 
+    /// Delete a leaderboard record.
     async fn delete_leaderboard_record(
         &self,
         session: &mut Session,
         leaderboard_id: &str,
     ) -> Result<(), Self::Error>;
 
+    /// Delete one or more notifications for the current user.
     async fn delete_notifications(
         &self,
         session: &mut Session,
         ids: &[&str],
     ) -> Result<(), Self::Error>;
 
+    /// Delete one or more objects by ID or username.
     async fn delete_storage_objects(
         &self,
         session: &mut Session,
-        ids: &[ApiDeleteStorageObjectId],
+        object_ids: &[api::ApiDeleteStorageObjectId],
     ) -> Result<(), Self::Error>;
 
+    /// Demote a set of users in a group to the next role down.
     async fn demote_group_users(
         &self,
         session: &mut Session,
@@ -153,6 +206,7 @@ pub trait Client {
         user_ids: &[&str],
     ) -> Result<(), Self::Error>;
 
+    /// Submit an event for processing in the server's registered runtime custom events handler.
     async fn event(
         &self,
         session: &mut Session,
@@ -160,16 +214,29 @@ pub trait Client {
         properties: HashMap<String, String>,
     ) -> Result<(), Self::Error>;
 
-    async fn get_account(&self, session: &mut Session) -> Result<ApiAccount, Self::Error>;
+    /// Fetch the current user's account.
+    async fn get_account(&self, session: &mut Session) -> Result<api::ApiAccount, Self::Error>;
+
+    /// Get the subscription `product_id` of the authenticated user.
+    async fn get_subscription(
+        &self,
+        session: &mut Session,
+        product_id: &str,
+    ) -> Result<api::ApiValidatedSubscription, Self::Error>;
 
+    /// Fetch zero or more users by ID and/or username.
     async fn get_users(
         &self,
         session: &mut Session,
         ids: &[&str],
         usernames: &[&str],
         facebook_ids: &[&str],
-    ) -> Result<ApiUsers, Self::Error>;
+    ) -> Result<api::ApiUsers, Self::Error>;
 
+    /// A healthcheck which load balancers can use to check the service.
+    async fn healthcheck(&self, session: &mut Session) -> Result<(), Self::Error>;
+
+    /// Import Facebook friends and add them to a user's account.
     async fn import_facebook_friends(
         &self,
         session: &mut Session,
@@ -177,6 +244,7 @@ pub trait Client {
         reset: Option<bool>,
     ) -> Result<(), Self::Error>;
 
+    /// Import Steam friends and add them to a user's account.
     async fn import_steam_friends(
         &self,
         session: &mut Session,
@@ -184,29 +252,37 @@ pub trait Client {
         reset: Option<bool>,
     ) -> Result<(), Self::Error>;
 
+    /// Immediately join an open group, or request to join a closed one.
     async fn join_group(&self, session: &mut Session, group_id: &str) -> Result<(), Self::Error>;
 
+    /// Attempt to join an open and running tournament.
     async fn join_tournament(
         &self,
         session: &mut Session,
         tournament_id: &str,
     ) -> Result<(), Self::Error>;
 
+    /// Kick a set of users from a group.
     async fn kick_group_users(
         &self,
         session: &mut Session,
         group_id: &str,
-        ids: &[&str],
+        user_ids: &[&str],
     ) -> Result<(), Self::Error>;
 
+    /// Leave a group the user is a member of.
     async fn leave_group(&self, session: &mut Session, group_id: &str) -> Result<(), Self::Error>;
 
+    /// Add an Apple ID to the social profiles on the current user's account.
     async fn link_apple(&self, session: &mut Session, token: &str) -> Result<(), Self::Error>;
 
+    /// Add a custom ID to the social profiles on the current user's account.
     async fn link_custom(&self, session: &mut Session, id: &str) -> Result<(), Self::Error>;
 
+    /// Add a device ID to the social profiles on the current user's account.
     async fn link_device(&self, session: &mut Session, id: &str) -> Result<(), Self::Error>;
 
+    /// Add an email+password to the social profiles on the current user's account.
     async fn link_email(
         &self,
         session: &mut Session,
@@ -214,13 +290,22 @@ pub trait Client {
         password: &str,
     ) -> Result<(), Self::Error>;
 
+    /// Add Facebook to the social profiles on the current user's account.
     async fn link_facebook(
         &self,
         session: &mut Session,
         token: &str,
-        import: Option<bool>,
+        sync: Option<bool>,
+    ) -> Result<(), Self::Error>;
+
+    /// Add Facebook Instant Game to the social profiles on the current user's account.
+    async fn link_facebook_instant_game(
+        &self,
+        session: &mut Session,
+        signed_player_info: &str,
     ) -> Result<(), Self::Error>;
 
+    /// Add Apple's GameCenter to the social profiles on the current user's account.
     async fn link_game_center(
         &self,
         session: &mut Session,
@@ -229,18 +314,21 @@ pub trait Client {
         public_key_url: &str,
         salt: &str,
         signature: &str,
-        timestamp: &str,
+        timestamp_seconds: &str,
     ) -> Result<(), Self::Error>;
 
+    /// Add Google to the social profiles on the current user's account.
     async fn link_google(&self, session: &mut Session, token: &str) -> Result<(), Self::Error>;
 
+    /// Add Steam to the social profiles on the current user's account.
     async fn link_steam(
         &self,
         session: &mut Session,
         token: &str,
-        import: bool,
+        sync: bool,
     ) -> Result<(), Self::Error>;
 
+    /// List a channel's message history.
     async fn list_channel_messages(
         &self,
         session: &mut Session,
@@ -248,33 +336,46 @@ pub trait Client {
         limit: Option<i32>,
         forward: Option<bool>,
         cursor: Option<&str>,
-    ) -> Result<ApiChannelMessageList, Self::Error>;
+    ) -> Result<api::ApiChannelMessageList, Self::Error>;
 
+    /// List the groups of the authenticated user.
+    async fn list_current_user_groups(
+        &self,
+        session: &mut Session,
+        state: Option<GroupRole>,
+        limit: Option<i32>,
+        cursor: Option<&str>,
+    ) -> Result<api::ApiUserGroupList, Self::Error>;
+
+    /// List all friends for the current user.
     async fn list_friends(
         &self,
         session: &mut Session,
-        state: Option<i32>,
+        state: Option<FriendState>,
         limit: Option<i32>,
         cursor: Option<&str>,
-    ) -> Result<ApiFriendList, Self::Error>;
+    ) -> Result<api::ApiFriendList, Self::Error>;
 
+    /// List all users that are part of a group.
     async fn list_group_users(
         &self,
         session: &mut Session,
         group_id: &str,
-        state: Option<i32>,
+        state: Option<GroupRole>,
         limit: Option<i32>,
         cursor: Option<&str>,
-    ) -> Result<ApiGroupUserList, Self::Error>;
+    ) -> Result<api::ApiGroupUserList, Self::Error>;
 
+    /// List groups based on given filters.
     async fn list_groups(
         &self,
         session: &mut Session,
         name: Option<&str>,
         limit: Option<i32>,
         cursor: Option<&str>,
-    ) -> Result<ApiGroupList, Self::Error>;
+    ) -> Result<api::ApiGroupList, Self::Error>;
 
+    /// List leaderboard records.
     async fn list_leaderboard_records(
         &self,
         session: &mut Session,
@@ -283,8 +384,9 @@ pub trait Client {
         expiry: Option<&str>,
         limit: Option<i32>,
         cursor: Option<&str>,
-    ) -> Result<ApiLeaderboardRecordList, Self::Error>;
+    ) -> Result<api::ApiLeaderboardRecordList, Self::Error>;
 
+    /// List leaderboard records that belong to a user.
     async fn list_leaderboard_records_around_owner(
         &self,
         session: &mut Session,
@@ -292,8 +394,9 @@ pub trait Client {
         owner_id: &str,
         expiry: Option<&str>,
         limit: Option<i32>,
-    ) -> Result<ApiLeaderboardRecordList, Self::Error>;
+    ) -> Result<api::ApiLeaderboardRecordList, Self::Error>;
 
+    /// Fetch list of running matches.
     async fn list_matches(
         &self,
         session: &mut Session,
@@ -303,32 +406,36 @@ pub trait Client {
         authoritative: Option<bool>,
         label: &str,
         query: &str,
-    ) -> Result<ApiMatchList, Self::Error>;
+    ) -> Result<api::ApiMatchList, Self::Error>;
 
+    /// Fetch list of notifications.
     async fn list_notifications(
         &self,
         session: &mut Session,
         limit: Option<i32>,
         cacheable_cursor: Option<&str>,
-    ) -> Result<ApiNotificationList, Self::Error>;
+    ) -> Result<api::ApiNotificationList, Self::Error>;
 
+    /// List the storage objects of the user `user_id` in `collection`, or the public objects of all
+    /// users if `user_id` is `None`.
     async fn list_storage_objects(
         &self,
         session: &mut Session,
         collection: &str,
+        user_id: Option<&str>,
         limit: Option<i32>,
         cursor: Option<&str>,
-    ) -> Result<ApiStorageObjectList, Self::Error>;
+    ) -> Result<api::ApiStorageObjectList, Self::Error>;
 
-    async fn list_tournament_records_around_owner(
+    /// List the validated subscriptions of the authenticated user.
+    async fn list_subscriptions(
         &self,
         session: &mut Session,
-        tournament_id: &str,
-        owner_id: &str,
-        expiry: Option<&str>,
         limit: Option<i32>,
-    ) -> Result<ApiTournamentRecordList, Self::Error>;
+        cursor: Option<&str>,
+    ) -> Result<api::ApiSubscriptionList, Self::Error>;
 
+    /// List tournament records.
     async fn list_tournament_records(
         &self,
         session: &mut Session,
@@ -337,8 +444,19 @@ pub trait Client {
         expiry: Option<&str>,
         limit: Option<i32>,
         cursor: Option<&str>,
-    ) -> Result<ApiTournamentRecordList, Self::Error>;
+    ) -> Result<api::ApiTournamentRecordList, Self::Error>;
 
+    /// List tournament records for a given owner.
+    async fn list_tournament_records_around_owner(
+        &self,
+        session: &mut Session,
+        tournament_id: &str,
+        owner_id: &str,
+        expiry: Option<&str>,
+        limit: Option<i32>,
+    ) -> Result<api::ApiTournamentRecordList, Self::Error>;
+
+    /// List current or upcoming tournaments.
     async fn list_tournaments(
         &self,
         session: &mut Session,
@@ -348,25 +466,19 @@ pub trait Client {
         end_time: Option<i32>,
         limit: Option<i32>,
         cursor: Option<&str>,
-    ) -> Result<ApiTournamentList, Self::Error>;
-
-    async fn list_current_user_groups(
-        &self,
-        session: &mut Session,
-        state: Option<i32>,
-        limit: Option<i32>,
-        cursor: Option<&str>,
-    ) -> Result<ApiUserGroupList, Self::Error>;
+    ) -> Result<api::ApiTournamentList, Self::Error>;
 
+    /// List groups the current user belongs to.
     async fn list_user_groups(
         &self,
         session: &mut Session,
         user_id: &str,
-        state: Option<i32>,
+        state: Option<GroupRole>,
         limit: Option<i32>,
         cursor: Option<&str>,
-    ) -> Result<ApiUserGroupList, Self::Error>;
+    ) -> Result<api::ApiUserGroupList, Self::Error>;
 
+    /// List publicly readable storage objects in a given collection.
     async fn list_users_storage_objects(
         &self,
         session: &mut Session,
@@ -374,42 +486,63 @@ pub trait Client {
         user_id: &str,
         limit: Option<i32>,
         cursor: Option<&str>,
-    ) -> Result<ApiStorageObjectList, Self::Error>;
+    ) -> Result<api::ApiStorageObjectList, Self::Error>;
 
+    /// Promote a set of users in a group to the next role up.
     async fn promote_group_user(
         &self,
         session: &mut Session,
         group_id: &str,
-        ids: &[&str],
+        user_ids: &[&str],
     ) -> Result<(), Self::Error>;
 
+    /// Get storage objects.
     async fn read_storage_objects(
         &self,
         session: &mut Session,
-        ids: &[ApiReadStorageObjectId],
-    ) -> Result<ApiStorageObjects, Self::Error>;
+        object_ids: &[api::ApiReadStorageObjectId],
+    ) -> Result<api::ApiStorageObjects, Self::Error>;
 
+    /// Execute a Lua function on the server.
     async fn rpc(
         &self,
         session: &mut Session,
         id: &str,
         payload: Option<&str>,
-    ) -> Result<ApiRpc, Self::Error>;
+    ) -> Result<api::ApiRpc, Self::Error>;
 
+    /// Call the RPC function `id` authenticated with the runtime's `http_key` instead of a session,
+    /// e.g. for server-to-server calls.
+    async fn rpc_http_key(
+        &self,
+        http_key: &str,
+        id: &str,
+        payload: Option<&str>,
+    ) -> Result<api::ApiRpc, Self::Error>;
+
+    /// Log out a session, invalidate a refresh token, or log out all sessions/refresh tokens for a user.
     async fn session_logout(&self, session: &mut Session) -> Result<(), Self::Error>;
 
+    /// Invalidate all sessions of the authenticated user, not only `session`.
+    async fn session_logout_all(&self, session: &mut Session) -> Result<(), Self::Error>;
+
+    /// Refresh a user's session using a refresh token retrieved from a previous authentication request.
     async fn session_refresh(
         &self,
         session: &mut Session,
         vars: HashMap<String, String>,
     ) -> Result<Session, Self::Error>;
 
+    /// Remove the Apple ID from the social profiles on the current user's account.
     async fn unlink_apple(&self, session: &mut Session, token: &str) -> Result<(), Self::Error>;
 
+    /// Remove the custom ID from the social profiles on the current user's account.
     async fn unlink_custom(&self, session: &mut Session, id: &str) -> Result<(), Self::Error>;
 
+    /// Remove the device ID from the social profiles on the current user's account.
     async fn unlink_device(&self, session: &mut Session, id: &str) -> Result<(), Self::Error>;
 
+    /// Remove the email+password from the social profiles on the current user's account.
     async fn unlink_email(
         &self,
         session: &mut Session,
@@ -417,8 +550,17 @@ pub trait Client {
         password: &str,
     ) -> Result<(), Self::Error>;
 
+    /// Remove Facebook from the social profiles on the current user's account.
     async fn unlink_facebook(&self, session: &mut Session, token: &str) -> Result<(), Self::Error>;
 
+    /// Remove Facebook Instant Game profile from the social profiles on the current user's account.
+    async fn unlink_facebook_instant_game(
+        &self,
+        session: &mut Session,
+        signed_player_info: &str,
+    ) -> Result<(), Self::Error>;
+
+    /// Remove Apple's GameCenter from the social profiles on the current user's account.
     async fn unlink_game_center(
         &self,
         session: &mut Session,
@@ -427,13 +569,16 @@ pub trait Client {
         public_key_url: &str,
         salt: &str,
         signature: &str,
-        timestamp: &str,
+        timestamp_seconds: &str,
     ) -> Result<(), Self::Error>;
 
+    /// Remove Google from the social profiles on the current user's account.
     async fn unlink_google(&self, session: &mut Session, token: &str) -> Result<(), Self::Error>;
 
+    /// Remove Steam from the social profiles on the current user's account.
     async fn unlink_steam(&self, session: &mut Session, token: &str) -> Result<(), Self::Error>;
 
+    /// Update fields in the current user's account.
     async fn update_account(
         &self,
         session: &mut Session,
@@ -445,6 +590,7 @@ pub trait Client {
         timezone: Option<&str>,
     ) -> Result<(), Self::Error>;
 
+    /// Update fields in a given group.
     async fn update_group(
         &self,
         session: &mut Session,
@@ -456,48 +602,73 @@ pub trait Client {
         lang_tag: Option<&str>,
     ) -> Result<(), Self::Error>;
 
+    /// Validate Apple IAP Receipt
     async fn validate_purchase_apple(
         &self,
         session: &mut Session,
         receipt: &str,
-    ) -> Result<ApiValidatePurchaseResponse, Self::Error>;
+        persist: bool,
+    ) -> Result<api::ApiValidatePurchaseResponse, Self::Error>;
 
+    /// Validate Google IAP Receipt
     async fn validate_purchase_google(
         &self,
         session: &mut Session,
         receipt: &str,
-    ) -> Result<ApiValidatePurchaseResponse, Self::Error>;
+        persist: bool,
+    ) -> Result<api::ApiValidatePurchaseResponse, Self::Error>;
 
+    /// Validate Huawei IAP Receipt
     async fn validate_purchase_huawei(
         &self,
         session: &mut Session,
         receipt: &str,
         signature: &str,
-    ) -> Result<ApiValidatePurchaseResponse, Self::Error>;
+        persist: bool,
+    ) -> Result<api::ApiValidatePurchaseResponse, Self::Error>;
+
+    /// Validate Apple Subscription Receipt
+    async fn validate_subscription_apple(
+        &self,
+        session: &mut Session,
+        receipt: &str,
+        persist: bool,
+    ) -> Result<api::ApiValidateSubscriptionResponse, Self::Error>;
+
+    /// Validate Google Subscription Receipt
+    async fn validate_subscription_google(
+        &self,
+        session: &mut Session,
+        receipt: &str,
+        persist: bool,
+    ) -> Result<api::ApiValidateSubscriptionResponse, Self::Error>;
 
+    /// Write a record with the metadata `metadata`, which must serialize to a JSON object.
     async fn write_leaderboard_record(
         &self,
         session: &mut Session,
         leaderboard_id: &str,
         score: i64,
         sub_score: Option<i64>,
-        override_operator: Option<ApiOverrideOperator>,
-        metadata: Option<&str>,
-    ) -> Result<ApiLeaderboardRecord, Self::Error>;
+        override_operator: api::ApiOverrideOperator,
+        metadata: Option<&(dyn SerJson + Sync)>,
+    ) -> Result<api::ApiLeaderboardRecord, Self::Error>;
 
+    /// Write objects into the storage engine.
     async fn write_storage_objects(
         &self,
         session: &mut Session,
-        objects: &[ApiWriteStorageObject],
-    ) -> Result<ApiStorageObjectAcks, Self::Error>;
+        objects: &[api::ApiWriteStorageObject],
+    ) -> Result<api::ApiStorageObjectAcks, Self::Error>;
 
+    /// Write a record to a tournament.
     async fn write_tournament_record(
         &self,
         session: &mut Session,
         tournament_id: &str,
         score: i64,
         sub_score: Option<i64>,
-        override_operator: Option<ApiOverrideOperator>,
-        metadata: Option<&str>,
-    ) -> Result<ApiLeaderboardRecord, Self::Error>;
+        override_operator: api::ApiOverrideOperator,
+        metadata: Option<&(dyn SerJson + Sync)>,
+    ) -> Result<api::ApiLeaderboardRecord, Self::Error>;
 }
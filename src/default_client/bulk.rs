@@ -0,0 +1,442 @@
+use super::{str_slice_to_owned, DefaultClient, DefaultClientError};
+use crate::api;
+use crate::api::{
+    ApiDeleteStorageObjectId, ApiDeleteStorageObjectsRequest, ApiReadStorageObjectId,
+    ApiReadStorageObjectsRequest, ApiStorageObjectAcks, ApiStorageObjects, ApiUsers,
+    ApiWriteStorageObject, ApiWriteStorageObjectsRequest, RestRequest,
+};
+use crate::http_adapter::ClientAdapter;
+use crate::session::Session;
+use nanoserde::DeJson;
+use std::future::{poll_fn, Future};
+use std::ops::Range;
+use std::pin::Pin;
+use std::task::Poll;
+
+pub(super) const DEFAULT_CHUNK_SIZE: usize = 100;
+pub(super) const DEFAULT_CONCURRENCY: usize = 4;
+
+/// A chunk of a bulk operation that failed.
+#[derive(Debug)]
+pub struct ChunkFailure<E> {
+    /// The indices of the items of the chunk in the input.
+    pub range: Range<usize>,
+    pub error: E,
+}
+
+/// The merged result of all successful chunks of a bulk operation and the failed chunks.
+#[derive(Debug)]
+pub struct BulkResult<T, E> {
+    pub value: T,
+    pub failures: Vec<ChunkFailure<E>>,
+}
+
+impl<T, E> BulkResult<T, E> {
+    /// Whether all chunks succeeded.
+    pub fn is_ok(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    /// The merged value if all chunks succeeded, otherwise the first failure.
+    pub fn into_result(self) -> Result<T, ChunkFailure<E>> {
+        match self.failures.into_iter().next() {
+            Some(failure) => Err(failure),
+            None => Ok(self.value),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum UserRef {
+    Id,
+    Username,
+    FacebookId,
+}
+
+// Polls `futures`, at most `concurrency` at a time, and returns their outputs in order.
+async fn join_bounded<F: Future + Unpin>(futures: Vec<F>, concurrency: usize) -> Vec<F::Output> {
+    let mut futures: Vec<Option<F>> = futures.into_iter().map(Some).collect();
+    let mut outputs: Vec<Option<F::Output>> = futures.iter().map(|_| None).collect();
+    let mut started = 0;
+    poll_fn(|cx| {
+        let mut running = 0;
+        let mut index = 0;
+        while index < started || (index < futures.len() && running < concurrency.max(1)) {
+            started = started.max(index + 1);
+            if let Some(future) = &mut futures[index] {
+                match Pin::new(future).poll(cx) {
+                    Poll::Ready(output) => {
+                        outputs[index] = Some(output);
+                        futures[index] = None;
+                    }
+                    Poll::Pending => running += 1,
+                }
+            }
+            index += 1;
+        }
+        if futures.iter().all(Option::is_none) {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    })
+    .await;
+    outputs.into_iter().flatten().collect()
+}
+
+impl<A: ClientAdapter + Send + Sync> DefaultClient<A> {
+    /// The maximum number of items sent in one request of a bulk operation. Defaults to 100.
+    pub fn set_bulk_chunk_size(&mut self, chunk_size: usize) {
+        self.bulk_chunk_size = chunk_size.max(1);
+    }
+
+    /// The maximum number of requests of a bulk operation that are sent at the same time.
+    /// Defaults to 4.
+    pub fn set_bulk_concurrency(&mut self, concurrency: usize) {
+        self.bulk_concurrency = concurrency.max(1);
+    }
+
+    async fn send_chunks<I, R, T>(
+        &self,
+        items: &[I],
+        request: impl Fn(&[I]) -> RestRequest<R>,
+        mut merge: impl FnMut(&mut T, R),
+    ) -> BulkResult<T, DefaultClientError<A>>
+    where
+        R: DeJson + Send,
+        T: Default,
+    {
+        let ranges: Vec<Range<usize>> = (0..items.len())
+            .step_by(self.bulk_chunk_size)
+            .map(|start| start..(start + self.bulk_chunk_size).min(items.len()))
+            .collect();
+        let requests = ranges
+            .iter()
+            .map(|range| Box::pin(self.send(request(&items[range.clone()]))))
+            .collect();
+        let responses = join_bounded(requests, self.bulk_concurrency).await;
+
+        let mut result = BulkResult {
+            value: T::default(),
+            failures: vec![],
+        };
+        for (range, response) in ranges.into_iter().zip(responses) {
+            match response {
+                Ok(response) => merge(&mut result.value, response),
+                Err(error) => result.failures.push(ChunkFailure { range, error }),
+            }
+        }
+        result
+    }
+
+    async fn send_group_chunks(
+        &self,
+        session: &Session,
+        group_id: &str,
+        ids: &[&str],
+        request: fn(&str, &str, &[String]) -> RestRequest<()>,
+    ) -> BulkResult<(), DefaultClientError<A>> {
+        let ids = str_slice_to_owned(ids);
+        self.send_chunks(
+            &ids,
+            |ids| request(&session.auth_token, group_id, ids),
+            |_, _| {},
+        )
+        .await
+    }
+
+    /// Fetch users in chunks. The ranges of failed chunks refer to the concatenation of
+    /// `ids`, `usernames` and `facebook_ids`.
+    pub async fn bulk_get_users(
+        &self,
+        session: &Session,
+        ids: &[&str],
+        usernames: &[&str],
+        facebook_ids: &[&str],
+    ) -> BulkResult<ApiUsers, DefaultClientError<A>> {
+        let users: Vec<(UserRef, String)> = ids
+            .iter()
+            .map(|id| (UserRef::Id, id))
+            .chain(usernames.iter().map(|id| (UserRef::Username, id)))
+            .chain(facebook_ids.iter().map(|id| (UserRef::FacebookId, id)))
+            .map(|(user_ref, id)| (user_ref, (*id).to_owned()))
+            .collect();
+        let of_kind = |users: &[(UserRef, String)], kind: fn(UserRef) -> bool| -> Vec<String> {
+            users
+                .iter()
+                .filter(|(user_ref, _)| kind(*user_ref))
+                .map(|(_, id)| id.clone())
+                .collect()
+        };
+        self.send_chunks(
+            &users,
+            |users| {
+                api::get_users(
+                    &session.auth_token,
+                    &of_kind(users, |user_ref| matches!(user_ref, UserRef::Id)),
+                    &of_kind(users, |user_ref| matches!(user_ref, UserRef::Username)),
+                    &of_kind(users, |user_ref| matches!(user_ref, UserRef::FacebookId)),
+                )
+            },
+            |merged: &mut ApiUsers, response| merged.users.extend(response.users),
+        )
+        .await
+    }
+
+    /// Read storage objects in chunks.
+    pub async fn bulk_read_storage_objects(
+        &self,
+        session: &Session,
+        ids: &[ApiReadStorageObjectId],
+    ) -> BulkResult<ApiStorageObjects, DefaultClientError<A>> {
+        self.send_chunks(
+            ids,
+            |ids| {
+                api::read_storage_objects(
+                    &session.auth_token,
+                    ApiReadStorageObjectsRequest {
+                        object_ids: ids.to_vec(),
+                    },
+                )
+            },
+            |merged: &mut ApiStorageObjects, response| merged.objects.extend(response.objects),
+        )
+        .await
+    }
+
+    /// Write storage objects in chunks. Each chunk is written atomically, but the chunks are not.
+    pub async fn bulk_write_storage_objects(
+        &self,
+        session: &Session,
+        objects: &[ApiWriteStorageObject],
+    ) -> BulkResult<ApiStorageObjectAcks, DefaultClientError<A>> {
+        self.send_chunks(
+            objects,
+            |objects| {
+                api::write_storage_objects(
+                    &session.auth_token,
+                    ApiWriteStorageObjectsRequest {
+                        objects: objects.to_vec(),
+                    },
+                )
+            },
+            |merged: &mut ApiStorageObjectAcks, response| merged.acks.extend(response.acks),
+        )
+        .await
+    }
+
+    /// Delete storage objects in chunks.
+    pub async fn bulk_delete_storage_objects(
+        &self,
+        session: &Session,
+        ids: &[ApiDeleteStorageObjectId],
+    ) -> BulkResult<(), DefaultClientError<A>> {
+        self.send_chunks(
+            ids,
+            |ids| {
+                api::delete_storage_objects(
+                    &session.auth_token,
+                    ApiDeleteStorageObjectsRequest {
+                        object_ids: ids.to_vec(),
+                    },
+                )
+            },
+            |_, _| {},
+        )
+        .await
+    }
+
+    /// Add friends by id in chunks.
+    pub async fn bulk_add_friends(
+        &self,
+        session: &Session,
+        ids: &[&str],
+    ) -> BulkResult<(), DefaultClientError<A>> {
+        let ids = str_slice_to_owned(ids);
+        self.send_chunks(
+            &ids,
+            |ids| api::add_friends(&session.auth_token, ids, &[]),
+            |_, _| {},
+        )
+        .await
+    }
+
+    /// Add friends by username in chunks.
+    pub async fn bulk_add_friends_by_username(
+        &self,
+        session: &Session,
+        usernames: &[&str],
+    ) -> BulkResult<(), DefaultClientError<A>> {
+        let usernames = str_slice_to_owned(usernames);
+        self.send_chunks(
+            &usernames,
+            |usernames| api::add_friends(&session.auth_token, &[], usernames),
+            |_, _| {},
+        )
+        .await
+    }
+
+    /// Add users to a group in chunks.
+    pub async fn bulk_add_group_users(
+        &self,
+        session: &Session,
+        group_id: &str,
+        ids: &[&str],
+    ) -> BulkResult<(), DefaultClientError<A>> {
+        self.send_group_chunks(session, group_id, ids, api::add_group_users)
+            .await
+    }
+
+    /// Kick users from a group in chunks.
+    pub async fn bulk_kick_group_users(
+        &self,
+        session: &Session,
+        group_id: &str,
+        ids: &[&str],
+    ) -> BulkResult<(), DefaultClientError<A>> {
+        self.send_group_chunks(session, group_id, ids, api::kick_group_users)
+            .await
+    }
+
+    /// Ban users from a group in chunks.
+    pub async fn bulk_ban_group_users(
+        &self,
+        session: &Session,
+        group_id: &str,
+        ids: &[&str],
+    ) -> BulkResult<(), DefaultClientError<A>> {
+        self.send_group_chunks(session, group_id, ids, api::ban_group_users)
+            .await
+    }
+
+    /// Promote users of a group in chunks.
+    pub async fn bulk_promote_group_users(
+        &self,
+        session: &Session,
+        group_id: &str,
+        ids: &[&str],
+    ) -> BulkResult<(), DefaultClientError<A>> {
+        self.send_group_chunks(session, group_id, ids, api::promote_group_users)
+            .await
+    }
+
+    /// Demote users of a group in chunks.
+    pub async fn bulk_demote_group_users(
+        &self,
+        session: &Session,
+        group_id: &str,
+        ids: &[&str],
+    ) -> BulkResult<(), DefaultClientError<A>> {
+        self.send_group_chunks(session, group_id, ids, api::demote_group_users)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::http_adapter::RestHttpError;
+    use crate::mock_client_adapter::MockClientAdapter;
+    use futures::executor::block_on;
+    use std::cell::Cell;
+    use std::task::Context;
+
+    fn session() -> Session {
+        Session {
+            auth_token: "token".to_owned(),
+            refresh_token: None,
+        }
+    }
+
+    // Pending for `polls` polls, counting how many instances are pending at the same time
+    struct CountingFuture<'a> {
+        polls: u32,
+        pending: &'a Cell<usize>,
+        max_pending: &'a Cell<usize>,
+        started: bool,
+    }
+
+    impl Future for CountingFuture<'_> {
+        type Output = u32;
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<u32> {
+            if !self.started {
+                self.started = true;
+                self.pending.set(self.pending.get() + 1);
+                self.max_pending
+                    .set(self.max_pending.get().max(self.pending.get()));
+            }
+            if self.polls == 0 {
+                self.pending.set(self.pending.get() - 1);
+                return Poll::Ready(self.max_pending.get() as u32);
+            }
+            self.polls -= 1;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+
+    #[test]
+    fn test_join_bounded() {
+        let pending = Cell::new(0);
+        let max_pending = Cell::new(0);
+        let futures = (0..10)
+            .map(|i| CountingFuture {
+                polls: i % 3,
+                pending: &pending,
+                max_pending: &max_pending,
+                started: false,
+            })
+            .collect();
+
+        let outputs = block_on(join_bounded(futures, 3));
+        assert_eq!(outputs.len(), 10);
+        assert_eq!(max_pending.get(), 3);
+        assert_eq!(pending.get(), 0);
+    }
+
+    #[test]
+    fn test_chunks_and_failures() {
+        let adapter = MockClientAdapter::new();
+        adapter
+            .stub(crate::api::Method::Post, "/v2/storage")
+            .respond_with(r#"{"objects":[{"key":"a"},{"key":"b"}]}"#);
+        let mut client = DefaultClient::new(adapter.clone());
+        client.set_bulk_chunk_size(2);
+        let ids: Vec<ApiReadStorageObjectId> = (0..5)
+            .map(|i| ApiReadStorageObjectId {
+                collection: "c".to_owned(),
+                key: i.to_string(),
+                user_id: "".to_owned(),
+            })
+            .collect();
+
+        let result = block_on(client.bulk_read_storage_objects(&session(), &ids));
+        assert_eq!(result.is_ok(), true);
+        assert_eq!(result.value.objects.len(), 6);
+        let requests = adapter.requests();
+        assert_eq!(requests.len(), 3);
+        assert_eq!(
+            requests[2].body,
+            r#"{"object_ids":[{"collection":"c","key":"4","user_id":""}]}"#
+        );
+
+        let adapter = MockClientAdapter::new();
+        adapter
+            .stub(crate::api::Method::Post, "/v2/friend")
+            .with_query_params("ids=0&ids=1&");
+        adapter
+            .stub(crate::api::Method::Post, "/v2/friend")
+            .with_query_params("ids=4&");
+        let mut client = DefaultClient::new(adapter.clone());
+        client.set_bulk_chunk_size(2);
+
+        let result = block_on(client.bulk_add_friends(&session(), &["0", "1", "2", "3", "4"]));
+        assert_eq!(result.failures.len(), 1);
+        assert_eq!(result.failures[0].range, 2..4);
+        assert!(matches!(
+            result.failures[0].error,
+            DefaultClientError::HttpAdapterError(RestHttpError::OtherError(_))
+        ));
+    }
+}
@@ -0,0 +1,1283 @@
+/* Code generated by codegen/src/main.rs. DO NOT EDIT. */
+
+use super::{str_slice_to_owned, DefaultClient, DefaultClientError};
+use crate::api;
+use crate::client::Client;
+use crate::enums::{FriendState, GroupRole};
+use crate::http_adapter::ClientAdapter;
+use crate::session::Session;
+use async_trait::async_trait;
+use nanoserde::SerJson;
+use std::collections::HashMap;
+
+#[async_trait]
+impl<A: ClientAdapter + Sync + Send> Client for DefaultClient<A> {
+    type Error = DefaultClientError<A>;
+
+    async fn add_friends(
+        &self,
+        session: &mut Session,
+        ids: &[&str],
+        usernames: &[&str],
+    ) -> Result<(), Self::Error> {
+        let ids = str_slice_to_owned(ids);
+        let usernames = str_slice_to_owned(usernames);
+        let request = api::add_friends(&session.auth_token, &ids, &usernames);
+        self.send(request).await
+    }
+
+    async fn add_group_users(
+        &self,
+        session: &mut Session,
+        group_id: &str,
+        user_ids: &[&str],
+    ) -> Result<(), Self::Error> {
+        let user_ids = str_slice_to_owned(user_ids);
+        let request = api::add_group_users(&session.auth_token, group_id, &user_ids);
+        self.send(request).await
+    }
+
+    async fn authenticate_apple(
+        &self,
+        token: &str,
+        username: Option<&str>,
+        create: bool,
+        vars: HashMap<String, String>,
+    ) -> Result<Session, Self::Error> {
+        let request = api::authenticate_apple(
+            &self.server_key,
+            "",
+            api::ApiAccountApple {
+                token: token.to_owned(),
+                vars,
+            },
+            Some(create),
+            username,
+        );
+        self.send(request)
+            .await
+            .map(DefaultClient::<A>::map_session)
+    }
+
+    async fn authenticate_custom(
+        &self,
+        id: &str,
+        username: Option<&str>,
+        create: bool,
+        vars: HashMap<String, String>,
+    ) -> Result<Session, Self::Error> {
+        let request = api::authenticate_custom(
+            &self.server_key,
+            "",
+            api::ApiAccountCustom {
+                id: id.to_owned(),
+                vars,
+            },
+            Some(create),
+            username,
+        );
+        self.send(request)
+            .await
+            .map(DefaultClient::<A>::map_session)
+    }
+
+    async fn authenticate_device(
+        &self,
+        id: &str,
+        username: Option<&str>,
+        create: bool,
+        vars: HashMap<String, String>,
+    ) -> Result<Session, Self::Error> {
+        let request = api::authenticate_device(
+            &self.server_key,
+            "",
+            api::ApiAccountDevice {
+                id: id.to_owned(),
+                vars,
+            },
+            Some(create),
+            username,
+        );
+        self.send(request)
+            .await
+            .map(DefaultClient::<A>::map_session)
+    }
+
+    async fn authenticate_email(
+        &self,
+        email: &str,
+        password: &str,
+        username: Option<&str>,
+        create: bool,
+        vars: HashMap<String, String>,
+    ) -> Result<Session, Self::Error> {
+        let request = api::authenticate_email(
+            &self.server_key,
+            "",
+            api::ApiAccountEmail {
+                email: email.to_owned(),
+                password: password.to_owned(),
+                vars,
+            },
+            Some(create),
+            username,
+        );
+        self.send(request)
+            .await
+            .map(DefaultClient::<A>::map_session)
+    }
+
+    async fn authenticate_facebook(
+        &self,
+        token: &str,
+        username: Option<&str>,
+        create: bool,
+        vars: HashMap<String, String>,
+        import: bool,
+    ) -> Result<Session, Self::Error> {
+        let request = api::authenticate_facebook(
+            &self.server_key,
+            "",
+            api::ApiAccountFacebook {
+                token: token.to_owned(),
+                vars,
+            },
+            Some(create),
+            username,
+            Some(import),
+        );
+        self.send(request)
+            .await
+            .map(DefaultClient::<A>::map_session)
+    }
+
+    async fn authenticate_facebook_instant_game(
+        &self,
+        signed_player_info: &str,
+        username: Option<&str>,
+        create: bool,
+        vars: HashMap<String, String>,
+    ) -> Result<Session, Self::Error> {
+        let request = api::authenticate_facebook_instant_game(
+            &self.server_key,
+            "",
+            api::ApiAccountFacebookInstantGame {
+                signed_player_info: signed_player_info.to_owned(),
+                vars,
+            },
+            Some(create),
+            username,
+        );
+        self.send(request)
+            .await
+            .map(DefaultClient::<A>::map_session)
+    }
+
+    async fn authenticate_game_center(
+        &self,
+        bundle_id: &str,
+        player_id: &str,
+        public_key_url: &str,
+        salt: &str,
+        signature: &str,
+        timestamp: &str,
+        username: Option<&str>,
+        create: bool,
+        vars: HashMap<String, String>,
+    ) -> Result<Session, Self::Error> {
+        let request = api::authenticate_game_center(
+            &self.server_key,
+            "",
+            api::ApiAccountGameCenter {
+                bundle_id: bundle_id.to_owned(),
+                player_id: player_id.to_owned(),
+                public_key_url: public_key_url.to_owned(),
+                salt: salt.to_owned(),
+                signature: signature.to_owned(),
+                timestamp_seconds: timestamp.to_owned(),
+                vars,
+            },
+            Some(create),
+            username,
+        );
+        self.send(request)
+            .await
+            .map(DefaultClient::<A>::map_session)
+    }
+
+    async fn authenticate_google(
+        &self,
+        token: &str,
+        username: Option<&str>,
+        create: bool,
+        vars: HashMap<String, String>,
+    ) -> Result<Session, Self::Error> {
+        let request = api::authenticate_google(
+            &self.server_key,
+            "",
+            api::ApiAccountGoogle {
+                token: token.to_owned(),
+                vars,
+            },
+            Some(create),
+            username,
+        );
+        self.send(request)
+            .await
+            .map(DefaultClient::<A>::map_session)
+    }
+
+    async fn authenticate_steam(
+        &self,
+        token: &str,
+        username: Option<&str>,
+        create: bool,
+        vars: HashMap<String, String>,
+    ) -> Result<Session, Self::Error> {
+        let request = api::authenticate_steam(
+            &self.server_key,
+            "",
+            api::ApiAccountSteam {
+                token: token.to_owned(),
+                vars,
+            },
+            Some(create),
+            username,
+            None,
+        );
+        self.send(request)
+            .await
+            .map(DefaultClient::<A>::map_session)
+    }
+
+    async fn ban_group_users(
+        &self,
+        session: &mut Session,
+        group_id: &str,
+        user_ids: &[&str],
+    ) -> Result<(), Self::Error> {
+        let user_ids = str_slice_to_owned(user_ids);
+        let request = api::ban_group_users(&session.auth_token, group_id, &user_ids);
+        self.send(request).await
+    }
+
+    async fn block_friends(
+        &self,
+        session: &mut Session,
+        ids: &[&str],
+        usernames: &[&str],
+    ) -> Result<(), Self::Error> {
+        let ids = str_slice_to_owned(ids);
+        let usernames = str_slice_to_owned(usernames);
+        let request = api::block_friends(&session.auth_token, &ids, &usernames);
+        self.send(request).await
+    }
+
+    async fn create_group(
+        &self,
+        session: &mut Session,
+        name: &str,
+        description: Option<&str>,
+        avatar_url: Option<&str>,
+        lang_tag: Option<&str>,
+        open: Option<bool>,
+        max_count: Option<i32>,
+    ) -> Result<api::ApiGroup, Self::Error> {
+        let request = api::create_group(
+            &session.auth_token,
+            api::ApiCreateGroupRequest {
+                avatar_url: avatar_url.unwrap_or_default().to_owned(),
+                description: description.unwrap_or_default().to_owned(),
+                lang_tag: lang_tag.unwrap_or_default().to_owned(),
+                max_count: max_count.unwrap_or(100),
+                name: name.to_owned(),
+                open: open.unwrap_or(true),
+            },
+        );
+        self.send(request).await
+    }
+
+    async fn delete_account(&self, session: &mut Session) -> Result<(), Self::Error> {
+        let request = api::delete_account(&session.auth_token);
+        self.send(request).await
+    }
+
+    async fn delete_friends(
+        &self,
+        session: &mut Session,
+        ids: &[&str],
+        usernames: &[&str],
+    ) -> Result<(), Self::Error> {
+        let ids = str_slice_to_owned(ids);
+        let usernames = str_slice_to_owned(usernames);
+        let request = api::delete_friends(&session.auth_token, &ids, &usernames);
+        self.send(request).await
+    }
+
+    async fn delete_group(&self, session: &mut Session, group_id: &str) -> Result<(), Self::Error> {
+        let request = api::delete_group(&session.auth_token, group_id);
+        self.send(request).await
+    }
+
+    async fn delete_leaderboard_record(
+        &self,
+        session: &mut Session,
+        leaderboard_id: &str,
+    ) -> Result<(), Self::Error> {
+        let request = api::delete_leaderboard_record(&session.auth_token, leaderboard_id);
+        self.send(request).await
+    }
+
+    async fn delete_notifications(
+        &self,
+        session: &mut Session,
+        ids: &[&str],
+    ) -> Result<(), Self::Error> {
+        let ids = str_slice_to_owned(ids);
+        let request = api::delete_notifications(&session.auth_token, &ids);
+        self.send(request).await
+    }
+
+    async fn delete_storage_objects(
+        &self,
+        session: &mut Session,
+        object_ids: &[api::ApiDeleteStorageObjectId],
+    ) -> Result<(), Self::Error> {
+        let request = api::delete_storage_objects(
+            &session.auth_token,
+            api::ApiDeleteStorageObjectsRequest {
+                object_ids: object_ids.to_vec(),
+            },
+        );
+        self.send(request).await
+    }
+
+    async fn demote_group_users(
+        &self,
+        session: &mut Session,
+        group_id: &str,
+        user_ids: &[&str],
+    ) -> Result<(), Self::Error> {
+        let user_ids = str_slice_to_owned(user_ids);
+        let request = api::demote_group_users(&session.auth_token, group_id, &user_ids);
+        self.send(request).await
+    }
+
+    async fn event(
+        &self,
+        session: &mut Session,
+        name: &str,
+        properties: HashMap<String, String>,
+    ) -> Result<(), Self::Error> {
+        let request = api::event(
+            &session.auth_token,
+            api::ApiEvent {
+                external: true,
+                name: name.to_owned(),
+                properties,
+                ..Default::default()
+            },
+        );
+        self.send(request).await
+    }
+
+    async fn get_account(&self, session: &mut Session) -> Result<api::ApiAccount, Self::Error> {
+        let request = api::get_account(&session.auth_token);
+        self.send(request).await
+    }
+
+    async fn get_subscription(
+        &self,
+        session: &mut Session,
+        product_id: &str,
+    ) -> Result<api::ApiValidatedSubscription, Self::Error> {
+        let request = api::get_subscription(&session.auth_token, product_id);
+        self.send(request).await
+    }
+
+    async fn get_users(
+        &self,
+        session: &mut Session,
+        ids: &[&str],
+        usernames: &[&str],
+        facebook_ids: &[&str],
+    ) -> Result<api::ApiUsers, Self::Error> {
+        let ids = str_slice_to_owned(ids);
+        let usernames = str_slice_to_owned(usernames);
+        let facebook_ids = str_slice_to_owned(facebook_ids);
+        let request = api::get_users(&session.auth_token, &ids, &usernames, &facebook_ids);
+        self.send(request).await
+    }
+
+    async fn healthcheck(&self, session: &mut Session) -> Result<(), Self::Error> {
+        let request = api::healthcheck(&session.auth_token);
+        self.send(request).await
+    }
+
+    async fn import_facebook_friends(
+        &self,
+        session: &mut Session,
+        token: &str,
+        reset: Option<bool>,
+    ) -> Result<(), Self::Error> {
+        let request = api::import_facebook_friends(
+            &session.auth_token,
+            api::ApiAccountFacebook {
+                token: token.to_owned(),
+                ..Default::default()
+            },
+            reset,
+        );
+        self.send(request).await
+    }
+
+    async fn import_steam_friends(
+        &self,
+        session: &mut Session,
+        token: &str,
+        reset: Option<bool>,
+    ) -> Result<(), Self::Error> {
+        let request = api::import_steam_friends(
+            &session.auth_token,
+            api::ApiAccountSteam {
+                token: token.to_owned(),
+                ..Default::default()
+            },
+            reset,
+        );
+        self.send(request).await
+    }
+
+    async fn join_group(&self, session: &mut Session, group_id: &str) -> Result<(), Self::Error> {
+        let request = api::join_group(&session.auth_token, group_id);
+        self.send(request).await
+    }
+
+    async fn join_tournament(
+        &self,
+        session: &mut Session,
+        tournament_id: &str,
+    ) -> Result<(), Self::Error> {
+        let request = api::join_tournament(&session.auth_token, tournament_id);
+        self.send(request).await
+    }
+
+    async fn kick_group_users(
+        &self,
+        session: &mut Session,
+        group_id: &str,
+        user_ids: &[&str],
+    ) -> Result<(), Self::Error> {
+        let user_ids = str_slice_to_owned(user_ids);
+        let request = api::kick_group_users(&session.auth_token, group_id, &user_ids);
+        self.send(request).await
+    }
+
+    async fn leave_group(&self, session: &mut Session, group_id: &str) -> Result<(), Self::Error> {
+        let request = api::leave_group(&session.auth_token, group_id);
+        self.send(request).await
+    }
+
+    async fn link_apple(&self, session: &mut Session, token: &str) -> Result<(), Self::Error> {
+        let request = api::link_apple(
+            &session.auth_token,
+            api::ApiAccountApple {
+                token: token.to_owned(),
+                vars: HashMap::new(),
+            },
+        );
+        self.send(request).await
+    }
+
+    async fn link_custom(&self, session: &mut Session, id: &str) -> Result<(), Self::Error> {
+        let request = api::link_custom(
+            &session.auth_token,
+            api::ApiAccountCustom {
+                id: id.to_owned(),
+                vars: HashMap::new(),
+            },
+        );
+        self.send(request).await
+    }
+
+    async fn link_device(&self, session: &mut Session, id: &str) -> Result<(), Self::Error> {
+        let request = api::link_device(
+            &session.auth_token,
+            api::ApiAccountDevice {
+                id: id.to_owned(),
+                vars: HashMap::new(),
+            },
+        );
+        self.send(request).await
+    }
+
+    async fn link_email(
+        &self,
+        session: &mut Session,
+        email: &str,
+        password: &str,
+    ) -> Result<(), Self::Error> {
+        let request = api::link_email(
+            &session.auth_token,
+            api::ApiAccountEmail {
+                email: email.to_owned(),
+                password: password.to_owned(),
+                vars: HashMap::new(),
+            },
+        );
+        self.send(request).await
+    }
+
+    async fn link_facebook(
+        &self,
+        session: &mut Session,
+        token: &str,
+        sync: Option<bool>,
+    ) -> Result<(), Self::Error> {
+        let request = api::link_facebook(
+            &session.auth_token,
+            api::ApiAccountFacebook {
+                token: token.to_owned(),
+                vars: HashMap::new(),
+            },
+            sync,
+        );
+        self.send(request).await
+    }
+
+    async fn link_facebook_instant_game(
+        &self,
+        session: &mut Session,
+        signed_player_info: &str,
+    ) -> Result<(), Self::Error> {
+        let request = api::link_facebook_instant_game(
+            &session.auth_token,
+            api::ApiAccountFacebookInstantGame {
+                signed_player_info: signed_player_info.to_owned(),
+                vars: HashMap::new(),
+            },
+        );
+        self.send(request).await
+    }
+
+    async fn link_game_center(
+        &self,
+        session: &mut Session,
+        bundle_id: &str,
+        player_id: &str,
+        public_key_url: &str,
+        salt: &str,
+        signature: &str,
+        timestamp_seconds: &str,
+    ) -> Result<(), Self::Error> {
+        let request = api::link_game_center(
+            &session.auth_token,
+            api::ApiAccountGameCenter {
+                bundle_id: bundle_id.to_owned(),
+                player_id: player_id.to_owned(),
+                public_key_url: public_key_url.to_owned(),
+                salt: salt.to_owned(),
+                signature: signature.to_owned(),
+                timestamp_seconds: timestamp_seconds.to_owned(),
+                vars: HashMap::new(),
+            },
+        );
+        self.send(request).await
+    }
+
+    async fn link_google(&self, session: &mut Session, token: &str) -> Result<(), Self::Error> {
+        let request = api::link_google(
+            &session.auth_token,
+            api::ApiAccountGoogle {
+                token: token.to_owned(),
+                vars: HashMap::new(),
+            },
+        );
+        self.send(request).await
+    }
+
+    async fn link_steam(
+        &self,
+        session: &mut Session,
+        token: &str,
+        sync: bool,
+    ) -> Result<(), Self::Error> {
+        let request = api::link_steam(
+            &session.auth_token,
+            api::ApiLinkSteamRequest {
+                account: api::ApiAccountSteam {
+                    token: token.to_owned(),
+                    vars: HashMap::new(),
+                },
+                sync,
+            },
+        );
+        self.send(request).await
+    }
+
+    async fn list_channel_messages(
+        &self,
+        session: &mut Session,
+        channel_id: &str,
+        limit: Option<i32>,
+        forward: Option<bool>,
+        cursor: Option<&str>,
+    ) -> Result<api::ApiChannelMessageList, Self::Error> {
+        let request =
+            api::list_channel_messages(&session.auth_token, channel_id, limit, forward, cursor);
+        self.send(request).await
+    }
+
+    async fn list_current_user_groups(
+        &self,
+        session: &mut Session,
+        state: Option<GroupRole>,
+        limit: Option<i32>,
+        cursor: Option<&str>,
+    ) -> Result<api::ApiUserGroupList, Self::Error> {
+        let user_id = session.user_id().ok_or_else(|| {
+            DefaultClientError::ClientError("The session token has no user id".to_owned())
+        })?;
+        let request = api::list_user_groups(
+            &session.auth_token,
+            &user_id,
+            limit,
+            state.map(i32::from),
+            cursor,
+        );
+        self.send(request).await
+    }
+
+    async fn list_friends(
+        &self,
+        session: &mut Session,
+        state: Option<FriendState>,
+        limit: Option<i32>,
+        cursor: Option<&str>,
+    ) -> Result<api::ApiFriendList, Self::Error> {
+        let request = api::list_friends(&session.auth_token, limit, state.map(i32::from), cursor);
+        self.send(request).await
+    }
+
+    async fn list_group_users(
+        &self,
+        session: &mut Session,
+        group_id: &str,
+        state: Option<GroupRole>,
+        limit: Option<i32>,
+        cursor: Option<&str>,
+    ) -> Result<api::ApiGroupUserList, Self::Error> {
+        let request = api::list_group_users(
+            &session.auth_token,
+            group_id,
+            limit,
+            state.map(i32::from),
+            cursor,
+        );
+        self.send(request).await
+    }
+
+    async fn list_groups(
+        &self,
+        session: &mut Session,
+        name: Option<&str>,
+        limit: Option<i32>,
+        cursor: Option<&str>,
+    ) -> Result<api::ApiGroupList, Self::Error> {
+        let request = api::list_groups(&session.auth_token, name, cursor, limit);
+        self.send(request).await
+    }
+
+    async fn list_leaderboard_records(
+        &self,
+        session: &mut Session,
+        leaderboard_id: &str,
+        owner_ids: &[&str],
+        expiry: Option<&str>,
+        limit: Option<i32>,
+        cursor: Option<&str>,
+    ) -> Result<api::ApiLeaderboardRecordList, Self::Error> {
+        let owner_ids = str_slice_to_owned(owner_ids);
+        let request = api::list_leaderboard_records(
+            &session.auth_token,
+            leaderboard_id,
+            &owner_ids,
+            limit,
+            cursor,
+            expiry,
+        );
+        self.send(request).await
+    }
+
+    async fn list_leaderboard_records_around_owner(
+        &self,
+        session: &mut Session,
+        leaderboard_id: &str,
+        owner_id: &str,
+        expiry: Option<&str>,
+        limit: Option<i32>,
+    ) -> Result<api::ApiLeaderboardRecordList, Self::Error> {
+        let request = api::list_leaderboard_records_around_owner(
+            &session.auth_token,
+            leaderboard_id,
+            owner_id,
+            limit,
+            expiry,
+        );
+        self.send(request).await
+    }
+
+    async fn list_matches(
+        &self,
+        session: &mut Session,
+        min: Option<i32>,
+        max: Option<i32>,
+        limit: Option<i32>,
+        authoritative: Option<bool>,
+        label: &str,
+        query: &str,
+    ) -> Result<api::ApiMatchList, Self::Error> {
+        let request = api::list_matches(
+            &session.auth_token,
+            limit,
+            authoritative,
+            Some(label),
+            min,
+            max,
+            Some(query),
+        );
+        self.send(request).await
+    }
+
+    async fn list_notifications(
+        &self,
+        session: &mut Session,
+        limit: Option<i32>,
+        cacheable_cursor: Option<&str>,
+    ) -> Result<api::ApiNotificationList, Self::Error> {
+        let request = api::list_notifications(&session.auth_token, limit, cacheable_cursor);
+        self.send(request).await
+    }
+
+    async fn list_storage_objects(
+        &self,
+        session: &mut Session,
+        collection: &str,
+        user_id: Option<&str>,
+        limit: Option<i32>,
+        cursor: Option<&str>,
+    ) -> Result<api::ApiStorageObjectList, Self::Error> {
+        let request =
+            api::list_storage_objects(&session.auth_token, collection, user_id, limit, cursor);
+        self.send(request).await
+    }
+
+    async fn list_subscriptions(
+        &self,
+        session: &mut Session,
+        limit: Option<i32>,
+        cursor: Option<&str>,
+    ) -> Result<api::ApiSubscriptionList, Self::Error> {
+        let request = api::list_subscriptions(
+            &session.auth_token,
+            api::ApiListSubscriptionsRequest {
+                cursor: cursor.unwrap_or_default().to_owned(),
+                limit,
+            },
+        );
+        self.send(request).await
+    }
+
+    async fn list_tournament_records(
+        &self,
+        session: &mut Session,
+        tournament_id: &str,
+        owner_ids: &[&str],
+        expiry: Option<&str>,
+        limit: Option<i32>,
+        cursor: Option<&str>,
+    ) -> Result<api::ApiTournamentRecordList, Self::Error> {
+        let owner_ids = str_slice_to_owned(owner_ids);
+        let request = api::list_tournament_records(
+            &session.auth_token,
+            tournament_id,
+            &owner_ids,
+            limit,
+            cursor,
+            expiry,
+        );
+        self.send(request).await
+    }
+
+    async fn list_tournament_records_around_owner(
+        &self,
+        session: &mut Session,
+        tournament_id: &str,
+        owner_id: &str,
+        expiry: Option<&str>,
+        limit: Option<i32>,
+    ) -> Result<api::ApiTournamentRecordList, Self::Error> {
+        let request = api::list_tournament_records_around_owner(
+            &session.auth_token,
+            tournament_id,
+            owner_id,
+            limit,
+            expiry,
+        );
+        self.send(request).await
+    }
+
+    async fn list_tournaments(
+        &self,
+        session: &mut Session,
+        category_start: Option<i32>,
+        category_end: Option<i32>,
+        start_time: Option<i32>,
+        end_time: Option<i32>,
+        limit: Option<i32>,
+        cursor: Option<&str>,
+    ) -> Result<api::ApiTournamentList, Self::Error> {
+        let request = api::list_tournaments(
+            &session.auth_token,
+            category_start,
+            category_end,
+            start_time,
+            end_time,
+            limit,
+            cursor,
+        );
+        self.send(request).await
+    }
+
+    async fn list_user_groups(
+        &self,
+        session: &mut Session,
+        user_id: &str,
+        state: Option<GroupRole>,
+        limit: Option<i32>,
+        cursor: Option<&str>,
+    ) -> Result<api::ApiUserGroupList, Self::Error> {
+        let request = api::list_user_groups(
+            &session.auth_token,
+            user_id,
+            limit,
+            state.map(i32::from),
+            cursor,
+        );
+        self.send(request).await
+    }
+
+    async fn list_users_storage_objects(
+        &self,
+        session: &mut Session,
+        collection: &str,
+        user_id: &str,
+        limit: Option<i32>,
+        cursor: Option<&str>,
+    ) -> Result<api::ApiStorageObjectList, Self::Error> {
+        let request =
+            api::list_storage_objects2(&session.auth_token, collection, user_id, limit, cursor);
+        self.send(request).await
+    }
+
+    async fn promote_group_user(
+        &self,
+        session: &mut Session,
+        group_id: &str,
+        user_ids: &[&str],
+    ) -> Result<(), Self::Error> {
+        let user_ids = str_slice_to_owned(user_ids);
+        let request = api::promote_group_users(&session.auth_token, group_id, &user_ids);
+        self.send(request).await
+    }
+
+    async fn read_storage_objects(
+        &self,
+        session: &mut Session,
+        object_ids: &[api::ApiReadStorageObjectId],
+    ) -> Result<api::ApiStorageObjects, Self::Error> {
+        let request = api::read_storage_objects(
+            &session.auth_token,
+            api::ApiReadStorageObjectsRequest {
+                object_ids: object_ids.to_vec(),
+            },
+        );
+        self.send(request).await
+    }
+
+    async fn rpc(
+        &self,
+        session: &mut Session,
+        id: &str,
+        payload: Option<&str>,
+    ) -> Result<api::ApiRpc, Self::Error> {
+        let request = api::rpc_func2(&session.auth_token, id, payload, None);
+        self.send(request).await
+    }
+
+    async fn rpc_http_key(
+        &self,
+        http_key: &str,
+        id: &str,
+        payload: Option<&str>,
+    ) -> Result<api::ApiRpc, Self::Error> {
+        // The body of a POST request is the payload encoded as JSON string
+        let payload = payload.unwrap_or("").serialize_json();
+        let mut request = api::rpc_func("", id, &payload, Some(http_key));
+        request.authentication = api::Authentication::None;
+        self.send(request).await
+    }
+
+    async fn session_logout(&self, session: &mut Session) -> Result<(), Self::Error> {
+        let request = api::session_logout(
+            &session.auth_token,
+            api::ApiSessionLogoutRequest {
+                refresh_token: session.refresh_token.clone().unwrap_or_default(),
+                token: session.auth_token.clone(),
+            },
+        );
+        self.send(request).await
+    }
+
+    async fn session_logout_all(&self, session: &mut Session) -> Result<(), Self::Error> {
+        // Nakama invalidates all sessions of the user if neither token is given
+        let request = api::session_logout(
+            &session.auth_token,
+            api::ApiSessionLogoutRequest {
+                refresh_token: String::new(),
+                token: String::new(),
+            },
+        );
+        self.send(request).await
+    }
+
+    async fn session_refresh(
+        &self,
+        session: &mut Session,
+        vars: HashMap<String, String>,
+    ) -> Result<Session, Self::Error> {
+        let request = api::session_refresh(
+            &self.server_key,
+            "",
+            api::ApiSessionRefreshRequest {
+                token: session.auth_token.clone(),
+                vars,
+            },
+        );
+        self.send(request)
+            .await
+            .map(DefaultClient::<A>::map_session)
+    }
+
+    async fn unlink_apple(&self, session: &mut Session, token: &str) -> Result<(), Self::Error> {
+        let request = api::unlink_apple(
+            &session.auth_token,
+            api::ApiAccountApple {
+                token: token.to_owned(),
+                vars: HashMap::new(),
+            },
+        );
+        self.send(request).await
+    }
+
+    async fn unlink_custom(&self, session: &mut Session, id: &str) -> Result<(), Self::Error> {
+        let request = api::unlink_custom(
+            &session.auth_token,
+            api::ApiAccountCustom {
+                id: id.to_owned(),
+                vars: HashMap::new(),
+            },
+        );
+        self.send(request).await
+    }
+
+    async fn unlink_device(&self, session: &mut Session, id: &str) -> Result<(), Self::Error> {
+        let request = api::unlink_device(
+            &session.auth_token,
+            api::ApiAccountDevice {
+                id: id.to_owned(),
+                vars: HashMap::new(),
+            },
+        );
+        self.send(request).await
+    }
+
+    async fn unlink_email(
+        &self,
+        session: &mut Session,
+        email: &str,
+        password: &str,
+    ) -> Result<(), Self::Error> {
+        let request = api::unlink_email(
+            &session.auth_token,
+            api::ApiAccountEmail {
+                email: email.to_owned(),
+                password: password.to_owned(),
+                vars: HashMap::new(),
+            },
+        );
+        self.send(request).await
+    }
+
+    async fn unlink_facebook(&self, session: &mut Session, token: &str) -> Result<(), Self::Error> {
+        let request = api::unlink_facebook(
+            &session.auth_token,
+            api::ApiAccountFacebook {
+                token: token.to_owned(),
+                vars: HashMap::new(),
+            },
+        );
+        self.send(request).await
+    }
+
+    async fn unlink_facebook_instant_game(
+        &self,
+        session: &mut Session,
+        signed_player_info: &str,
+    ) -> Result<(), Self::Error> {
+        let request = api::unlink_facebook_instant_game(
+            &session.auth_token,
+            api::ApiAccountFacebookInstantGame {
+                signed_player_info: signed_player_info.to_owned(),
+                vars: HashMap::new(),
+            },
+        );
+        self.send(request).await
+    }
+
+    async fn unlink_game_center(
+        &self,
+        session: &mut Session,
+        bundle_id: &str,
+        player_id: &str,
+        public_key_url: &str,
+        salt: &str,
+        signature: &str,
+        timestamp_seconds: &str,
+    ) -> Result<(), Self::Error> {
+        let request = api::unlink_game_center(
+            &session.auth_token,
+            api::ApiAccountGameCenter {
+                bundle_id: bundle_id.to_owned(),
+                player_id: player_id.to_owned(),
+                public_key_url: public_key_url.to_owned(),
+                salt: salt.to_owned(),
+                signature: signature.to_owned(),
+                timestamp_seconds: timestamp_seconds.to_owned(),
+                vars: HashMap::new(),
+            },
+        );
+        self.send(request).await
+    }
+
+    async fn unlink_google(&self, session: &mut Session, token: &str) -> Result<(), Self::Error> {
+        let request = api::unlink_google(
+            &session.auth_token,
+            api::ApiAccountGoogle {
+                token: token.to_owned(),
+                vars: HashMap::new(),
+            },
+        );
+        self.send(request).await
+    }
+
+    async fn unlink_steam(&self, session: &mut Session, token: &str) -> Result<(), Self::Error> {
+        let request = api::unlink_steam(
+            &session.auth_token,
+            api::ApiAccountSteam {
+                token: token.to_owned(),
+                vars: HashMap::new(),
+            },
+        );
+        self.send(request).await
+    }
+
+    async fn update_account(
+        &self,
+        session: &mut Session,
+        username: &str,
+        display_name: Option<&str>,
+        avatar_url: Option<&str>,
+        lang_tag: Option<&str>,
+        location: Option<&str>,
+        timezone: Option<&str>,
+    ) -> Result<(), Self::Error> {
+        let request = api::update_account(
+            &session.auth_token,
+            api::ApiUpdateAccountRequest {
+                avatar_url: avatar_url.unwrap_or_default().to_owned(),
+                display_name: display_name.unwrap_or_default().to_owned(),
+                lang_tag: lang_tag.unwrap_or_default().to_owned(),
+                location: location.unwrap_or_default().to_owned(),
+                timezone: timezone.unwrap_or_default().to_owned(),
+                username: username.to_owned(),
+            },
+        );
+        self.send(request).await
+    }
+
+    async fn update_group(
+        &self,
+        session: &mut Session,
+        group_id: &str,
+        name: &str,
+        open: bool,
+        description: Option<&str>,
+        avatar_url: Option<&str>,
+        lang_tag: Option<&str>,
+    ) -> Result<(), Self::Error> {
+        let request = api::update_group(
+            &session.auth_token,
+            group_id,
+            api::ApiUpdateGroupRequest {
+                avatar_url: avatar_url.unwrap_or_default().to_owned(),
+                description: description.unwrap_or_default().to_owned(),
+                group_id: group_id.to_owned(),
+                lang_tag: lang_tag.unwrap_or_default().to_owned(),
+                name: name.to_owned(),
+                open,
+            },
+        );
+        self.send(request).await
+    }
+
+    async fn validate_purchase_apple(
+        &self,
+        session: &mut Session,
+        receipt: &str,
+        persist: bool,
+    ) -> Result<api::ApiValidatePurchaseResponse, Self::Error> {
+        let request = api::validate_purchase_apple(
+            &session.auth_token,
+            api::ApiValidatePurchaseAppleRequest {
+                persist: Some(persist),
+                receipt: receipt.to_owned(),
+            },
+        );
+        self.send(request).await
+    }
+
+    async fn validate_purchase_google(
+        &self,
+        session: &mut Session,
+        receipt: &str,
+        persist: bool,
+    ) -> Result<api::ApiValidatePurchaseResponse, Self::Error> {
+        let request = api::validate_purchase_google(
+            &session.auth_token,
+            api::ApiValidatePurchaseGoogleRequest {
+                persist: Some(persist),
+                purchase: receipt.to_owned(),
+            },
+        );
+        self.send(request).await
+    }
+
+    async fn validate_purchase_huawei(
+        &self,
+        session: &mut Session,
+        receipt: &str,
+        signature: &str,
+        persist: bool,
+    ) -> Result<api::ApiValidatePurchaseResponse, Self::Error> {
+        let request = api::validate_purchase_huawei(
+            &session.auth_token,
+            api::ApiValidatePurchaseHuaweiRequest {
+                persist: Some(persist),
+                purchase: receipt.to_owned(),
+                signature: signature.to_owned(),
+            },
+        );
+        self.send(request).await
+    }
+
+    async fn validate_subscription_apple(
+        &self,
+        session: &mut Session,
+        receipt: &str,
+        persist: bool,
+    ) -> Result<api::ApiValidateSubscriptionResponse, Self::Error> {
+        let request = api::validate_subscription_apple(
+            &session.auth_token,
+            api::ApiValidateSubscriptionAppleRequest {
+                persist: Some(persist),
+                receipt: receipt.to_owned(),
+            },
+        );
+        self.send(request).await
+    }
+
+    async fn validate_subscription_google(
+        &self,
+        session: &mut Session,
+        receipt: &str,
+        persist: bool,
+    ) -> Result<api::ApiValidateSubscriptionResponse, Self::Error> {
+        let request = api::validate_subscription_google(
+            &session.auth_token,
+            api::ApiValidateSubscriptionGoogleRequest {
+                persist: Some(persist),
+                receipt: receipt.to_owned(),
+            },
+        );
+        self.send(request).await
+    }
+
+    async fn write_leaderboard_record(
+        &self,
+        session: &mut Session,
+        leaderboard_id: &str,
+        score: i64,
+        sub_score: Option<i64>,
+        override_operator: api::ApiOverrideOperator,
+        metadata: Option<&(dyn SerJson + Sync)>,
+    ) -> Result<api::ApiLeaderboardRecord, Self::Error> {
+        let request = api::write_leaderboard_record(
+            &session.auth_token,
+            leaderboard_id,
+            api::WriteLeaderboardRecordRequestLeaderboardRecordWrite {
+                metadata: metadata.map_or(String::new(), |value| value.serialize_json()),
+                operator: override_operator,
+                score,
+                subscore: sub_score,
+            },
+        );
+        self.send(request).await
+    }
+
+    async fn write_storage_objects(
+        &self,
+        session: &mut Session,
+        objects: &[api::ApiWriteStorageObject],
+    ) -> Result<api::ApiStorageObjectAcks, Self::Error> {
+        let request = api::write_storage_objects(
+            &session.auth_token,
+            api::ApiWriteStorageObjectsRequest {
+                objects: objects.to_vec(),
+            },
+        );
+        self.send(request).await
+    }
+
+    async fn write_tournament_record(
+        &self,
+        session: &mut Session,
+        tournament_id: &str,
+        score: i64,
+        sub_score: Option<i64>,
+        override_operator: api::ApiOverrideOperator,
+        metadata: Option<&(dyn SerJson + Sync)>,
+    ) -> Result<api::ApiLeaderboardRecord, Self::Error> {
+        let request = api::write_tournament_record(
+            &session.auth_token,
+            tournament_id,
+            api::WriteTournamentRecordRequestTournamentRecordWrite {
+                metadata: metadata.map(|value| value.serialize_json()),
+                operator: override_operator,
+                score,
+                subscore: sub_score,
+            },
+        );
+        self.send(request).await
+    }
+}
@@ -0,0 +1,206 @@
+//! Typed versions of the integer states and codes of the Nakama API.
+use crate::api::{ApiFriend, ApiNotification, GroupUserListGroupUser, UserGroupListUserGroup};
+
+/// The state of a friendship, as seen by the user listing the friends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(from = "i32", into = "i32")
+)]
+pub enum FriendState {
+    /// The users are mutual friends.
+    Friend,
+    /// The user sent a friend request that was not accepted yet.
+    InviteSent,
+    /// The user received a friend request that was not accepted yet.
+    InviteReceived,
+    /// The user blocked the other user.
+    Blocked,
+    /// A state sent by the server that this client does not know about.
+    Unknown(i32),
+}
+
+impl From<i32> for FriendState {
+    fn from(state: i32) -> Self {
+        match state {
+            0 => FriendState::Friend,
+            1 => FriendState::InviteSent,
+            2 => FriendState::InviteReceived,
+            3 => FriendState::Blocked,
+            state => FriendState::Unknown(state),
+        }
+    }
+}
+
+impl From<FriendState> for i32 {
+    fn from(state: FriendState) -> Self {
+        match state {
+            FriendState::Friend => 0,
+            FriendState::InviteSent => 1,
+            FriendState::InviteReceived => 2,
+            FriendState::Blocked => 3,
+            FriendState::Unknown(state) => state,
+        }
+    }
+}
+
+/// The role of a user in a group.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(from = "i32", into = "i32")
+)]
+pub enum GroupRole {
+    /// The creator of the group, who can delete it.
+    SuperAdmin,
+    Admin,
+    Member,
+    /// The user asked to join a closed group and was not accepted yet.
+    JoinRequest,
+    /// A role sent by the server that this client does not know about.
+    Unknown(i32),
+}
+
+impl From<i32> for GroupRole {
+    fn from(role: i32) -> Self {
+        match role {
+            0 => GroupRole::SuperAdmin,
+            1 => GroupRole::Admin,
+            2 => GroupRole::Member,
+            3 => GroupRole::JoinRequest,
+            role => GroupRole::Unknown(role),
+        }
+    }
+}
+
+impl From<GroupRole> for i32 {
+    fn from(role: GroupRole) -> Self {
+        match role {
+            GroupRole::SuperAdmin => 0,
+            GroupRole::Admin => 1,
+            GroupRole::Member => 2,
+            GroupRole::JoinRequest => 3,
+            GroupRole::Unknown(role) => role,
+        }
+    }
+}
+
+/// The code of a notification. Negative codes are reserved for notifications sent by the server
+/// itself, non-negative codes can be used by the runtime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(from = "i32", into = "i32")
+)]
+pub enum NotificationCode {
+    /// A user wants to chat in a direct message.
+    DmRequest,
+    /// A user sent a friend request.
+    FriendRequest,
+    /// A user accepted a friend request.
+    FriendAccept,
+    /// The user was added to a group.
+    GroupAdd,
+    /// A user wants to join a group administrated by the user.
+    GroupJoinRequest,
+    /// A friend joined the game for the first time.
+    FriendJoinGame,
+    /// The user was disconnected because they connected with another socket.
+    SingleSocket,
+    /// The user was banned.
+    UserBanned,
+    /// A code sent by the runtime.
+    Custom(i32),
+    /// A reserved code that this client does not know about.
+    Unknown(i32),
+}
+
+impl From<i32> for NotificationCode {
+    fn from(code: i32) -> Self {
+        match code {
+            -1 => NotificationCode::DmRequest,
+            -2 => NotificationCode::FriendRequest,
+            -3 => NotificationCode::FriendAccept,
+            -4 => NotificationCode::GroupAdd,
+            -5 => NotificationCode::GroupJoinRequest,
+            -6 => NotificationCode::FriendJoinGame,
+            -7 => NotificationCode::SingleSocket,
+            -8 => NotificationCode::UserBanned,
+            code if code >= 0 => NotificationCode::Custom(code),
+            code => NotificationCode::Unknown(code),
+        }
+    }
+}
+
+impl From<NotificationCode> for i32 {
+    fn from(code: NotificationCode) -> Self {
+        match code {
+            NotificationCode::DmRequest => -1,
+            NotificationCode::FriendRequest => -2,
+            NotificationCode::FriendAccept => -3,
+            NotificationCode::GroupAdd => -4,
+            NotificationCode::GroupJoinRequest => -5,
+            NotificationCode::FriendJoinGame => -6,
+            NotificationCode::SingleSocket => -7,
+            NotificationCode::UserBanned => -8,
+            NotificationCode::Custom(code) | NotificationCode::Unknown(code) => code,
+        }
+    }
+}
+
+impl NotificationCode {
+    /// Whether the notification was sent by the server itself rather than the runtime.
+    pub fn is_system(&self) -> bool {
+        i32::from(*self) < 0
+    }
+}
+
+impl ApiFriend {
+    pub fn friend_state(&self) -> FriendState {
+        self.state.into()
+    }
+}
+
+impl GroupUserListGroupUser {
+    pub fn group_role(&self) -> GroupRole {
+        self.state.into()
+    }
+}
+
+impl UserGroupListUserGroup {
+    pub fn group_role(&self) -> GroupRole {
+        self.state.into()
+    }
+}
+
+impl ApiNotification {
+    pub fn notification_code(&self) -> NotificationCode {
+        self.code.into()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{FriendState, GroupRole, NotificationCode};
+
+    #[test]
+    fn test_conversion_is_lossless() {
+        for value in -10..10 {
+            assert_eq!(i32::from(FriendState::from(value)), value);
+            assert_eq!(i32::from(GroupRole::from(value)), value);
+            assert_eq!(i32::from(NotificationCode::from(value)), value);
+        }
+    }
+
+    #[test]
+    fn test_notification_code() {
+        assert_eq!(NotificationCode::from(-2), NotificationCode::FriendRequest);
+        assert_eq!(NotificationCode::from(7), NotificationCode::Custom(7));
+        assert_eq!(NotificationCode::from(-42), NotificationCode::Unknown(-42));
+        assert_eq!(NotificationCode::FriendAccept.is_system(), true);
+        assert_eq!(NotificationCode::Custom(0).is_system(), false);
+    }
+}
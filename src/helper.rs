@@ -1,14 +1,278 @@
+use crate::socket_adapter::{SocketAdapter, Wakeup};
 use crate::web_socket::WebSocket;
-use crate::web_socket_adapter::WebSocketAdapter;
-use std::thread::{sleep, spawn};
+use log::error;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{spawn, JoinHandle};
 use std::time::Duration;
 
-pub fn tick_socket(socket: &WebSocket<WebSocketAdapter>) {
-    spawn({
-        let socket = socket.clone();
-        move || loop {
-            socket.tick();
-            sleep(Duration::from_millis(16));
+/// Ticks one or more sockets on a background thread.
+///
+/// The thread stops when `stop` is called or the driver is dropped. It also stops once all of
+/// its sockets were dropped, because the driver doesn't keep them alive.
+///
+/// Sockets are ticked every interval. If the socket adapter supports it, the sockets are also
+/// ticked as soon as data was received.
+/// ```no_run
+/// # use nakama_rs::helper::SocketDriver;
+/// # use nakama_rs::std_web_socket_adapter::StdWebSocketAdapter;
+/// # use nakama_rs::web_socket::WebSocket;
+/// let socket = WebSocket::new(StdWebSocketAdapter::new());
+/// let driver = SocketDriver::new(&socket);
+/// // ...
+/// driver.stop();
+/// ```
+#[must_use = "the driver stops ticking when dropped"]
+pub struct SocketDriver {
+    stopped: Arc<AtomicBool>,
+    wakeup: Wakeup,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl SocketDriver {
+    /// Tick a single socket every 16 milliseconds.
+    pub fn new<A: SocketAdapter + Send + 'static>(socket: &WebSocket<A>) -> SocketDriver {
+        SocketDriverBuilder::new().socket(socket).spawn()
+    }
+
+    pub fn is_running(&self) -> bool {
+        match self.thread {
+            Some(ref thread) => !thread.is_finished(),
+            None => false,
+        }
+    }
+
+    /// Stop ticking and wait for the thread to finish.
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    /// Keep ticking after the driver is dropped, until all sockets were dropped.
+    pub fn detach(mut self) {
+        self.thread = None;
+    }
+
+    fn shutdown(&mut self) {
+        if let Some(thread) = self.thread.take() {
+            self.stopped.store(true, Ordering::SeqCst);
+            self.wakeup.wake();
+            if thread.join().is_err() {
+                error!("SocketDriver: Ticking thread panicked");
+            }
+        }
+    }
+}
+
+impl Drop for SocketDriver {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+pub struct SocketDriverBuilder {
+    interval: Duration,
+    wakeup: Wakeup,
+    tickers: Vec<Box<dyn Fn() -> bool + Send>>,
+}
+
+impl Default for SocketDriverBuilder {
+    fn default() -> Self {
+        SocketDriverBuilder::new()
+    }
+}
+
+impl SocketDriverBuilder {
+    pub fn new() -> SocketDriverBuilder {
+        SocketDriverBuilder {
+            interval: Duration::from_millis(16),
+            wakeup: Wakeup::new(),
+            tickers: Vec::new(),
+        }
+    }
+
+    /// The maximum time between two ticks. Defaults to 16 milliseconds.
+    pub fn interval(&mut self, interval: Duration) -> &mut Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Add a socket to tick. All sockets are ticked on the same thread.
+    pub fn socket<A: SocketAdapter + Send + 'static>(
+        &mut self,
+        socket: &WebSocket<A>,
+    ) -> &mut Self {
+        self.tickers.push(socket.weak_ticker(self.wakeup.clone()));
+        self
+    }
+
+    pub fn spawn(&mut self) -> SocketDriver {
+        let stopped = Arc::new(AtomicBool::new(false));
+        let interval = self.interval;
+        let wakeup = self.wakeup.clone();
+        let mut tickers = std::mem::take(&mut self.tickers);
+
+        let thread = spawn({
+            let stopped = stopped.clone();
+            let wakeup = wakeup.clone();
+            move || {
+                while !stopped.load(Ordering::SeqCst) {
+                    tickers.retain(|tick| tick());
+                    if tickers.is_empty() {
+                        return;
+                    }
+                    wakeup.wait_timeout(interval);
+                }
+            }
+        });
+
+        SocketDriver {
+            stopped,
+            wakeup,
+            thread: Some(thread),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{SocketDriver, SocketDriverBuilder};
+    use crate::socket_adapter::{SocketAdapter, Wakeup};
+    use crate::web_socket::WebSocket;
+    use std::fmt;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::thread::sleep;
+    use std::time::Duration;
+
+    #[derive(Debug)]
+    struct TestError;
+
+    impl fmt::Display for TestError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            fmt::Debug::fmt(self, f)
+        }
+    }
+
+    impl std::error::Error for TestError {}
+
+    // Counts ticks and exposes the wakeup set by the driver
+    #[derive(Default)]
+    struct TickCounter {
+        ticks: Arc<AtomicUsize>,
+        wakeup: Arc<Mutex<Option<Wakeup>>>,
+    }
+
+    impl SocketAdapter for TickCounter {
+        type Error = TestError;
+
+        fn on_connected<T>(&mut self, _callback: T)
+        where
+            T: Fn() + Send + 'static,
+        {
+        }
+
+        fn on_closed<T>(&mut self, _callback: T)
+        where
+            T: Fn() + Send + 'static,
+        {
+        }
+
+        fn on_received<T>(&mut self, _callback: T)
+        where
+            T: Fn(Result<String, Self::Error>) + Send + 'static,
+        {
+        }
+
+        fn set_wakeup(&mut self, wakeup: Wakeup) {
+            *self.wakeup.lock().unwrap() = Some(wakeup);
+        }
+
+        fn is_connected(&self) -> bool {
+            true
+        }
+
+        fn is_connecting(&self) -> bool {
+            false
         }
-    });
+
+        fn close(&mut self) {}
+
+        fn connect(&mut self, _addr: &str, _timeout: i32) {}
+
+        fn send(&self, _data: &str, _reliable: bool) -> Result<(), Self::Error> {
+            Ok(())
+        }
+
+        fn tick(&self) {
+            self.ticks.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    fn counted_socket() -> (
+        WebSocket<TickCounter>,
+        Arc<AtomicUsize>,
+        Arc<Mutex<Option<Wakeup>>>,
+    ) {
+        let adapter = TickCounter::default();
+        let ticks = adapter.ticks.clone();
+        let wakeup = adapter.wakeup.clone();
+        (WebSocket::new(adapter), ticks, wakeup)
+    }
+
+    #[test]
+    fn test_stop() {
+        let (socket, ticks, _) = counted_socket();
+        let driver = SocketDriverBuilder::new()
+            .interval(Duration::from_millis(1))
+            .socket(&socket)
+            .spawn();
+        sleep(Duration::from_millis(50));
+        assert!(driver.is_running());
+        driver.stop();
+
+        let count = ticks.load(Ordering::SeqCst);
+        assert!(count > 0);
+        sleep(Duration::from_millis(20));
+        assert_eq!(ticks.load(Ordering::SeqCst), count);
+    }
+
+    #[test]
+    fn test_multiple_sockets() {
+        let (socket, ticks, _) = counted_socket();
+        let (socket2, ticks2, _) = counted_socket();
+        let driver = SocketDriverBuilder::new()
+            .socket(&socket)
+            .socket(&socket2)
+            .spawn();
+        sleep(Duration::from_millis(50));
+        drop(driver);
+
+        assert!(ticks.load(Ordering::SeqCst) > 0);
+        assert!(ticks2.load(Ordering::SeqCst) > 0);
+    }
+
+    #[test]
+    fn test_wakeup() {
+        let (socket, ticks, wakeup) = counted_socket();
+        let driver = SocketDriverBuilder::new()
+            .interval(Duration::from_secs(60))
+            .socket(&socket)
+            .spawn();
+        sleep(Duration::from_millis(50));
+        assert_eq!(ticks.load(Ordering::SeqCst), 1);
+
+        wakeup.lock().unwrap().as_ref().unwrap().wake();
+        sleep(Duration::from_millis(50));
+        assert_eq!(ticks.load(Ordering::SeqCst), 2);
+        driver.stop();
+    }
+
+    #[test]
+    fn test_stops_when_sockets_dropped() {
+        let (socket, _, _) = counted_socket();
+        let driver = SocketDriver::new(&socket);
+        drop(socket);
+        sleep(Duration::from_millis(100));
+        assert!(!driver.is_running());
+    }
 }
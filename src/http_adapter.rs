@@ -4,6 +4,7 @@ use std::fmt::{Display, Formatter};
 
 use crate::api;
 use async_trait::async_trait;
+#[cfg(feature = "isahc-adapter")]
 use isahc::prelude::*;
 use nanoserde::{DeJson, DeJsonErr};
 use std::io;
@@ -19,6 +20,7 @@ pub trait ClientAdapter {
 
 #[derive(Debug)]
 pub enum RestHttpError {
+    #[cfg(feature = "isahc-adapter")]
     HttpError(isahc::Error),
     IoError(io::Error),
     JsonError(DeJsonErr),
@@ -35,11 +37,50 @@ impl Display for RestHttpError {
 
 impl Error for RestHttpError {}
 
+/// The value of the `Authorization` header for a request, or `None` if it has no such header.
+pub(crate) fn authorization_header(authentication: &api::Authentication) -> Option<String> {
+    match authentication {
+        api::Authentication::Basic { username, password } => Some(format!(
+            "Basic {}",
+            base64::encode(&format!("{}:{}", username, password))
+        )),
+        api::Authentication::Bearer { token } => Some(format!("Bearer {}", token)),
+        api::Authentication::None => None,
+    }
+}
+
+/// Map the status and body of a response to the deserialized response or a `RestHttpError`.
+pub(crate) fn parse_response<T: DeJson>(status: u16, response: String) -> Result<T, RestHttpError> {
+    match status {
+        status if status >= 200 && status < 300 => {
+            nanoserde::DeJson::deserialize_json(&response)
+                .or_else(|json_err| {
+                    // Nakama answers requests without a response body with `{}`, which can't be
+                    // deserialized as `()`
+                    if response.trim() == "{}" {
+                        nanoserde::DeJson::deserialize_json("null").map_err(|_| json_err)
+                    } else {
+                        Err(json_err)
+                    }
+                })
+                .map_err(|json_err| RestHttpError::JsonError(json_err))
+        }
+        status if status >= 400 && status < 500 => {
+            Err(RestHttpError::ClientError(status, response))
+        }
+        status if status >= 500 => Err(RestHttpError::ServerError(status, response)),
+        _ => Err(RestHttpError::OtherError("Unknown status".to_owned())),
+    }
+}
+
+/// A `ClientAdapter` using the isahc library, available with the `isahc-adapter` feature.
+#[cfg(feature = "isahc-adapter")]
 pub struct RestHttpAdapter {
     server: String,
     port: i32,
 }
 
+#[cfg(feature = "isahc-adapter")]
 impl RestHttpAdapter {
     pub fn new(server: &str, port: i32) -> RestHttpAdapter {
         RestHttpAdapter {
@@ -49,29 +90,23 @@ impl RestHttpAdapter {
     }
 }
 
+#[cfg(feature = "isahc-adapter")]
 #[async_trait]
 impl ClientAdapter for RestHttpAdapter {
     type Error = RestHttpError;
     async fn send<T: DeJson + Send>(&self, request: RestRequest<T>) -> Result<T, RestHttpError> {
-        let auth_header = match request.authentication {
-            api::Authentication::Basic { username, password } => {
-                format!(
-                    "Basic {}",
-                    base64::encode(&format!("{}:{}", username, password))
-                )
-            }
-            api::Authentication::Bearer { token } => {
-                format!("Bearer {}", token)
-            }
-        };
+        let auth_header = authorization_header(&request.authentication);
 
         let url = format!(
             "{}:{}{}?{}",
             self.server, self.port, request.urlpath, request.query_params
         );
 
-        let client = isahc::HttpClientBuilder::new()
-            .default_header("Authorization", &auth_header)
+        let mut client = isahc::HttpClientBuilder::new();
+        if let Some(auth_header) = &auth_header {
+            client = client.default_header("Authorization", auth_header);
+        }
+        let client = client
             .build()
             .map_err(|err| RestHttpError::HttpError(err))?;
 
@@ -83,31 +118,34 @@ impl ClientAdapter for RestHttpAdapter {
         }
         .map_err(|err| RestHttpError::HttpError(err))?;
 
-        match response.status().as_u16() {
-            status if status >= 200 && status < 300 => {
-                let response = response
-                    .text()
-                    .await
-                    .map_err(|err| RestHttpError::IoError(err))?;
-
-                nanoserde::DeJson::deserialize_json(&response)
-                    .map_err(|json_err| RestHttpError::JsonError(json_err))
-            }
-            status if status >= 400 && status < 500 => {
-                let response = response
-                    .text()
-                    .await
-                    .map_err(|err| RestHttpError::IoError(err))?;
-                Err(RestHttpError::ClientError(status, response))
-            }
-            status if status >= 500 => {
-                let response = response
-                    .text()
-                    .await
-                    .map_err(|err| RestHttpError::IoError(err))?;
-                Err(RestHttpError::ServerError(status, response))
-            }
-            _ => Err(RestHttpError::OtherError("Unknown status".to_owned())),
+        let status = response.status().as_u16();
+        let response = response
+            .text()
+            .await
+            .map_err(|err| RestHttpError::IoError(err))?;
+        parse_response(status, response)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{parse_response, RestHttpError};
+    use crate::api::ApiAccount;
+
+    #[test]
+    fn test_parse_empty_response() {
+        assert_eq!(parse_response::<()>(200, "{}".to_owned()).is_ok(), true);
+        assert_eq!(parse_response::<()>(200, " {} ".to_owned()).is_ok(), true);
+        // Types accepting the empty object are deserialized from it as usual
+        assert_eq!(
+            parse_response::<Option<ApiAccount>>(200, "{}".to_owned())
+                .unwrap()
+                .is_some(),
+            true
+        );
+        match parse_response::<()>(200, "[]".to_owned()) {
+            Err(RestHttpError::JsonError(_)) => {}
+            _ => panic!("expected a JsonError"),
         }
     }
 }
@@ -0,0 +1,533 @@
+//! A dynamic JSON value, for the fields of the Nakama API that contain JSON encoded as a string.
+use crate::api::{
+    ApiChannelMessage, ApiGroup, ApiLeaderboardRecord, ApiMatch, ApiNotification, ApiRpc,
+    ApiStorageObject, ApiUser,
+};
+use crate::socket::Match;
+use nanoserde::{DeJson, DeJsonErr, DeJsonState, DeJsonTok, SerJson, SerJsonState};
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::ops::Index;
+use std::str::{Chars, FromStr};
+
+/// Any JSON value. Useful to inspect metadata, storage values and payloads without defining a
+/// struct for each of them.
+///
+/// Indexing a missing key, an index out of bounds or a value that is not an object or array
+/// returns `JsonValue::Null`:
+/// ```
+/// # use nakama_rs::json::JsonValue;
+/// let value: JsonValue = r#"{"title":"Champion","wins":[3,5]}"#.parse().unwrap();
+/// assert_eq!(value["title"].as_str(), Some("Champion"));
+/// assert_eq!(value["wins"][1].as_i64(), Some(5));
+/// assert_eq!(value["losses"][0].is_null(), true);
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum JsonValue {
+    #[default]
+    Null,
+    Bool(bool),
+    /// A number without fraction or exponent that fits into an `i64`.
+    Int(i64),
+    Float(f64),
+    String(String),
+    Array(Vec<JsonValue>),
+    /// An object. The keys are sorted, the order of the input is not preserved.
+    Object(BTreeMap<String, JsonValue>),
+}
+
+static NULL: JsonValue = JsonValue::Null;
+
+// Deeper nested arrays and objects are rejected, parsing them recursively could overflow the stack
+const MAX_DEPTH: usize = 128;
+
+fn check_depth(state: &DeJsonState, depth: usize) -> Result<(), DeJsonErr> {
+    if depth > MAX_DEPTH {
+        return Err(DeJsonErr {
+            msg: format!("JSON nested deeper than {} levels", MAX_DEPTH),
+            line: state.line,
+            col: state.col,
+        });
+    }
+    Ok(())
+}
+
+impl JsonValue {
+    pub fn is_null(&self) -> bool {
+        matches!(self, JsonValue::Null)
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            JsonValue::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            JsonValue::Int(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns integers and floats as `f64`.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            JsonValue::Int(value) => Some(*value as f64),
+            JsonValue::Float(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            JsonValue::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&Vec<JsonValue>> {
+        match self {
+            JsonValue::Array(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_object(&self) -> Option<&BTreeMap<String, JsonValue>> {
+        match self {
+            JsonValue::Object(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of `key` if this is an object containing it.
+    pub fn get(&self, key: &str) -> Option<&JsonValue> {
+        self.as_object().and_then(|object| object.get(key))
+    }
+}
+
+impl Index<&str> for JsonValue {
+    type Output = JsonValue;
+
+    fn index(&self, key: &str) -> &JsonValue {
+        self.get(key).unwrap_or(&NULL)
+    }
+}
+
+impl Index<usize> for JsonValue {
+    type Output = JsonValue;
+
+    fn index(&self, index: usize) -> &JsonValue {
+        self.as_array()
+            .and_then(|array| array.get(index))
+            .unwrap_or(&NULL)
+    }
+}
+
+impl From<bool> for JsonValue {
+    fn from(value: bool) -> Self {
+        JsonValue::Bool(value)
+    }
+}
+
+impl From<i32> for JsonValue {
+    fn from(value: i32) -> Self {
+        JsonValue::Int(value.into())
+    }
+}
+
+impl From<i64> for JsonValue {
+    fn from(value: i64) -> Self {
+        JsonValue::Int(value)
+    }
+}
+
+impl From<f64> for JsonValue {
+    fn from(value: f64) -> Self {
+        JsonValue::Float(value)
+    }
+}
+
+impl From<&str> for JsonValue {
+    fn from(value: &str) -> Self {
+        JsonValue::String(value.to_owned())
+    }
+}
+
+impl From<String> for JsonValue {
+    fn from(value: String) -> Self {
+        JsonValue::String(value)
+    }
+}
+
+impl From<Vec<JsonValue>> for JsonValue {
+    fn from(value: Vec<JsonValue>) -> Self {
+        JsonValue::Array(value)
+    }
+}
+
+impl From<BTreeMap<String, JsonValue>> for JsonValue {
+    fn from(value: BTreeMap<String, JsonValue>) -> Self {
+        JsonValue::Object(value)
+    }
+}
+
+impl SerJson for JsonValue {
+    fn ser_json(&self, d: usize, s: &mut SerJsonState) {
+        match self {
+            JsonValue::Null => s.out.push_str("null"),
+            JsonValue::Bool(value) => value.ser_json(d, s),
+            JsonValue::Int(value) => value.ser_json(d, s),
+            // JSON has no representation for NaN and infinity
+            JsonValue::Float(value) if !value.is_finite() => s.out.push_str("null"),
+            JsonValue::Float(value) => {
+                let value = value.to_string();
+                s.out.push_str(&value);
+                // Keep floats floats when parsing the output again
+                if !value.contains('.') {
+                    s.out.push_str(".0");
+                }
+            }
+            JsonValue::String(value) => value.ser_json(d, s),
+            JsonValue::Array(array) => {
+                s.out.push('[');
+                for (i, value) in array.iter().enumerate() {
+                    if i > 0 {
+                        s.out.push(',');
+                    }
+                    value.ser_json(d + 1, s);
+                }
+                s.out.push(']');
+            }
+            JsonValue::Object(object) => {
+                s.out.push('{');
+                for (i, (key, value)) in object.iter().enumerate() {
+                    if i > 0 {
+                        s.out.push(',');
+                    }
+                    key.ser_json(d + 1, s);
+                    s.out.push(':');
+                    value.ser_json(d + 1, s);
+                }
+                s.out.push('}');
+            }
+        }
+    }
+}
+
+/// Arrays and objects nested deeper than 128 levels are an error.
+impl DeJson for JsonValue {
+    fn de_json(state: &mut DeJsonState, input: &mut Chars) -> Result<Self, DeJsonErr> {
+        JsonValue::parse(state, input, 0)
+    }
+}
+
+impl JsonValue {
+    fn parse(state: &mut DeJsonState, input: &mut Chars, depth: usize) -> Result<Self, DeJsonErr> {
+        let value = match state.tok {
+            DeJsonTok::Null => JsonValue::Null,
+            DeJsonTok::Bool(value) => JsonValue::Bool(value),
+            DeJsonTok::I64(value) => JsonValue::Int(value),
+            DeJsonTok::U64(value) if value <= i64::MAX as u64 => JsonValue::Int(value as i64),
+            DeJsonTok::U64(value) => JsonValue::Float(value as f64),
+            DeJsonTok::F64(value) => JsonValue::Float(value),
+            DeJsonTok::Str => JsonValue::String(state.as_string()?),
+            DeJsonTok::BlockOpen => {
+                check_depth(state, depth + 1)?;
+                state.block_open(input)?;
+                let mut array = Vec::new();
+                while state.tok != DeJsonTok::BlockClose {
+                    array.push(JsonValue::parse(state, input, depth + 1)?);
+                    state.eat_comma_block(input)?;
+                }
+                state.block_close(input)?;
+                return Ok(JsonValue::Array(array));
+            }
+            DeJsonTok::CurlyOpen => {
+                check_depth(state, depth + 1)?;
+                state.curly_open(input)?;
+                let mut object = BTreeMap::new();
+                while state.tok != DeJsonTok::CurlyClose {
+                    let key = state.as_string()?;
+                    state.next_colon(input)?;
+                    object.insert(key, JsonValue::parse(state, input, depth + 1)?);
+                    state.eat_comma_curly(input)?;
+                }
+                state.curly_close(input)?;
+                return Ok(JsonValue::Object(object));
+            }
+            _ => return Err(state.err_token("JSON value")),
+        };
+        state.next_tok(input)?;
+        Ok(value)
+    }
+}
+
+/// Formats the value as compact JSON.
+impl Display for JsonValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.serialize_json())
+    }
+}
+
+/// Parses a JSON document. Unlike `JsonValue::deserialize_json`, trailing input is an error.
+impl FromStr for JsonValue {
+    type Err = DeJsonErr;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let mut state = DeJsonState::default();
+        let mut chars = input.chars();
+        state.next(&mut chars);
+        state.next_tok(&mut chars)?;
+        let value = JsonValue::de_json(&mut state, &mut chars)?;
+        if state.tok != DeJsonTok::Eof {
+            return Err(state.err_token("end of input"));
+        }
+        Ok(value)
+    }
+}
+
+/// Wraps a serde type to use it where the client expects nanoserde's `SerJson` or `DeJson`, e.g. as
+/// the value of a `StorageCollection` or as leaderboard record metadata.
+///
+/// ```
+/// # use nakama_rs::json::Serde;
+/// # use nanoserde::{DeJson, SerJson};
+/// #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+/// struct Card {
+///     name: String,
+///     power: i32,
+/// }
+///
+/// let card = Serde(Card { name: "Dragon".to_owned(), power: 9 });
+/// assert_eq!(card.serialize_json(), r#"{"name":"Dragon","power":9}"#);
+/// assert_eq!(Serde::<Card>::deserialize_json(&card.serialize_json()).unwrap(), card);
+/// ```
+///
+/// `SerJson` can't report errors, so a value serde_json fails to serialize, e.g. a map with keys that are not
+/// strings or numbers, is written as `null`. Use `serde_json::to_string` to detect these errors.
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Serde<T>(pub T);
+
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize> SerJson for Serde<T> {
+    fn ser_json(&self, _d: usize, s: &mut SerJsonState) {
+        match serde_json::to_string(&self.0) {
+            Ok(json) => s.out.push_str(&json),
+            Err(_) => s.out.push_str("null"),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: serde::de::DeserializeOwned> DeJson for Serde<T> {
+    fn de_json(state: &mut DeJsonState, input: &mut Chars) -> Result<Self, DeJsonErr> {
+        // nanoserde already tokenized the input, so the value is parsed again by serde_json
+        let mut json = SerJsonState { out: String::new() };
+        copy_json(state, input, &mut json, 0)?;
+        serde_json::from_str(&json.out)
+            .map(Serde)
+            .map_err(|err| DeJsonErr {
+                msg: err.to_string(),
+                line: state.line,
+                col: state.col,
+            })
+    }
+}
+
+// Writes the next value of the input to `s`. Unlike `JsonValue`, integers beyond `i64` are kept as they are
+// instead of being converted to floats. Limited to `MAX_DEPTH` like `JsonValue`.
+#[cfg(feature = "serde")]
+fn copy_json(
+    state: &mut DeJsonState,
+    input: &mut Chars,
+    s: &mut SerJsonState,
+    depth: usize,
+) -> Result<(), DeJsonErr> {
+    match state.tok {
+        DeJsonTok::U64(value) => value.ser_json(0, s),
+        DeJsonTok::BlockOpen => {
+            check_depth(state, depth + 1)?;
+            state.block_open(input)?;
+            s.out.push('[');
+            let mut first = true;
+            while state.tok != DeJsonTok::BlockClose {
+                if !first {
+                    s.out.push(',');
+                }
+                first = false;
+                copy_json(state, input, s, depth + 1)?;
+                state.eat_comma_block(input)?;
+            }
+            s.out.push(']');
+            return state.block_close(input);
+        }
+        DeJsonTok::CurlyOpen => {
+            check_depth(state, depth + 1)?;
+            state.curly_open(input)?;
+            s.out.push('{');
+            let mut first = true;
+            while state.tok != DeJsonTok::CurlyClose {
+                if !first {
+                    s.out.push(',');
+                }
+                first = false;
+                state.as_string()?.ser_json(0, s);
+                s.out.push(':');
+                state.next_colon(input)?;
+                copy_json(state, input, s, depth + 1)?;
+                state.eat_comma_curly(input)?;
+            }
+            s.out.push('}');
+            return state.curly_close(input);
+        }
+        _ => return JsonValue::de_json(state, input).map(|value| value.ser_json(0, s)),
+    }
+    state.next_tok(input)
+}
+
+impl ApiRpc {
+    /// Parses the payload returned by the RPC function.
+    pub fn payload_json(&self) -> Result<JsonValue, DeJsonErr> {
+        self.payload.parse()
+    }
+
+    /// Deserializes the payload returned by the RPC function into a serde type. Serialize the payload of the
+    /// request with `serde_json::to_string`.
+    #[cfg(feature = "serde")]
+    pub fn payload_serde<T: serde::de::DeserializeOwned>(&self) -> Result<T, serde_json::Error> {
+        serde_json::from_str(&self.payload)
+    }
+}
+
+impl ApiUser {
+    pub fn metadata_json(&self) -> Result<JsonValue, DeJsonErr> {
+        self.metadata.parse()
+    }
+}
+
+impl ApiGroup {
+    pub fn metadata_json(&self) -> Result<JsonValue, DeJsonErr> {
+        self.metadata.parse()
+    }
+}
+
+impl ApiLeaderboardRecord {
+    pub fn metadata_json(&self) -> Result<JsonValue, DeJsonErr> {
+        self.metadata.parse()
+    }
+}
+
+impl ApiStorageObject {
+    pub fn value_json(&self) -> Result<JsonValue, DeJsonErr> {
+        self.value.parse()
+    }
+}
+
+impl ApiChannelMessage {
+    pub fn content_json(&self) -> Result<JsonValue, DeJsonErr> {
+        self.content.parse()
+    }
+}
+
+impl ApiNotification {
+    pub fn content_json(&self) -> Result<JsonValue, DeJsonErr> {
+        self.content.parse()
+    }
+}
+
+impl ApiMatch {
+    /// Parses the label of the match, which authoritative matches often set to a JSON object.
+    pub fn label_json(&self) -> Result<JsonValue, DeJsonErr> {
+        self.label.parse()
+    }
+}
+
+impl Match {
+    /// Parses the label of the match, which authoritative matches often set to a JSON object.
+    pub fn label_json(&self) -> Result<JsonValue, DeJsonErr> {
+        self.label.parse()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::JsonValue;
+    use nanoserde::{DeJson, SerJson};
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_parse() {
+        let value: JsonValue =
+            r#" {"a": [1, -2, 3.5, 18446744073709551615], "b": {"c": null, "d": true}, "e": "f\"\n"} "#
+                .parse()
+                .unwrap();
+        assert_eq!(value["a"][0], JsonValue::Int(1));
+        assert_eq!(value["a"][1], JsonValue::Int(-2));
+        assert_eq!(value["a"][2].as_f64(), Some(3.5));
+        assert_eq!(value["a"][3], JsonValue::Float(u64::MAX as f64));
+        assert_eq!(value["b"].get("c"), Some(&JsonValue::Null));
+        assert_eq!(value["b"]["d"].as_bool(), Some(true));
+        assert_eq!(value["e"].as_str(), Some("f\"\n"));
+        assert_eq!(value["missing"].is_null(), true);
+        assert_eq!(value["e"][0].is_null(), true);
+        assert_eq!("[]".parse::<JsonValue>().unwrap(), JsonValue::Array(vec![]));
+        assert_eq!(
+            "{}".parse::<JsonValue>().unwrap(),
+            JsonValue::Object(BTreeMap::new())
+        );
+    }
+
+    #[test]
+    fn test_parse_depth_limit() {
+        let nested = |depth: usize| format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+        assert_eq!(nested(128).parse::<JsonValue>().is_ok(), true);
+        assert_eq!(nested(129).parse::<JsonValue>().is_err(), true);
+        assert_eq!(
+            format!("{}1{}", r#"{"a":"#.repeat(129), "}".repeat(129))
+                .parse::<JsonValue>()
+                .is_err(),
+            true
+        );
+        // Fails without overflowing the stack
+        assert_eq!(nested(1_000_000).parse::<JsonValue>().is_err(), true);
+        assert_eq!(
+            JsonValue::deserialize_json(&"[".repeat(1_000_000)).is_err(),
+            true
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert_eq!("".parse::<JsonValue>().is_err(), true);
+        assert_eq!("[1,".parse::<JsonValue>().is_err(), true);
+        assert_eq!(r#"{"a" 1}"#.parse::<JsonValue>().is_err(), true);
+        assert_eq!("1 2".parse::<JsonValue>().is_err(), true);
+    }
+
+    #[test]
+    fn test_serialize() {
+        let mut object = BTreeMap::new();
+        object.insert("name".to_owned(), JsonValue::from("a \"b\""));
+        object.insert(
+            "values".to_owned(),
+            JsonValue::from(vec![
+                JsonValue::Null,
+                true.into(),
+                42.into(),
+                2.0.into(),
+                f64::NAN.into(),
+            ]),
+        );
+        let value = JsonValue::from(object);
+        let json = r#"{"name":"a \"b\"","values":[null,true,42,2.0,null]}"#;
+        assert_eq!(value.serialize_json(), json);
+        assert_eq!(value.to_string(), json);
+        assert_eq!(
+            JsonValue::deserialize_json(json).unwrap()["values"][3],
+            2.0.into()
+        );
+    }
+}
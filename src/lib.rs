@@ -1,8 +1,13 @@
 //! # Nakama rust client guide
 //! ## Setup
+//! The default features `isahc-adapter` and `qws-adapter` enable `RestHttpAdapter` and
+//! `WebSocketAdapter`. Without them, `StdHttpAdapter` and `StdWebSocketAdapter` connect to the
+//! server without further dependencies, but only over unencrypted connections.
 //! ## Authenticate
 //! 1. Build an instance of the client
 //! ```
+//! # #[cfg(feature = "isahc-adapter")]
+//! # {
 //! # use nakama_rs::client::Client;
 //! # use nakama_rs::http_adapter::RestHttpAdapter;
 //! # use nakama_rs::default_client::DefaultClient;
@@ -10,9 +15,12 @@
 //! # use std::collections::HashMap;
 //! let adapter = RestHttpAdapter::new("http://127.0.0.1", 7350);
 //! let client = DefaultClient::new(adapter);
+//! # }
 //! ```
 //! 2. Authenticate a user
 //! ```
+//! # #[cfg(feature = "isahc-adapter")]
+//! # {
 //! # use nakama_rs::client::Client;
 //! # use nakama_rs::http_adapter::RestHttpAdapter;
 //! # use nakama_rs::default_client::DefaultClient;
@@ -23,6 +31,7 @@
 //! block_on(async {
 //!     client.authenticate_device("testdeviceid", None, true, HashMap::new()).await;
 //! })
+//! # }
 //! ```
 //! ## Sessions
 //! ## Send requests
@@ -34,18 +43,36 @@ mod api_gen;
 
 pub mod config;
 
-mod api_gen_enum;
+mod api_gen_int64;
+#[cfg(feature = "serde")]
+mod api_gen_serde;
+pub mod chat;
 pub mod client;
 pub mod default_client;
+pub mod enums;
+#[cfg(feature = "isahc-adapter")]
 pub mod error;
 pub mod helper;
 pub mod http_adapter;
+pub mod json;
 pub mod matchmaker;
+pub mod mock_client_adapter;
+pub mod mock_socket_adapter;
+pub mod paginator;
+pub mod recording_adapter;
 pub mod session;
+pub mod simulated_network_adapter;
 pub mod socket;
 pub mod socket_adapter;
+pub mod std_http_adapter;
+pub mod std_web_socket_adapter;
+pub mod storage;
+pub mod sync_strategy;
+#[cfg(any(test, feature = "test-server"))]
 pub mod test_helpers;
+pub mod timestamp;
 pub mod web_socket;
+#[cfg(feature = "qws-adapter")]
 pub mod web_socket_adapter;
 
 pub mod api {
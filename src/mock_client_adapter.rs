@@ -0,0 +1,328 @@
+use crate::api::{Authentication, Method, RestRequest};
+use crate::http_adapter::{parse_response, ClientAdapter, RestHttpError};
+use async_trait::async_trait;
+use nanoserde::DeJson;
+use std::sync::{Arc, Mutex};
+
+/// A request sent through a `MockClientAdapter`.
+#[derive(Debug, Clone)]
+pub struct MockRequest {
+    pub method: Method,
+    pub urlpath: String,
+    pub query_params: String,
+    pub body: String,
+    pub authentication: Authentication,
+}
+
+struct Rule {
+    method: Method,
+    urlpath: String,
+    query_params: Option<String>,
+    body: Option<String>,
+    status: u16,
+    response: String,
+    // Expectations are consumed by the first matching request, stubs answer any number of requests
+    expected: bool,
+    consumed: bool,
+}
+
+impl Rule {
+    fn matches(&self, request: &MockRequest) -> bool {
+        !self.consumed
+            && self.method == request.method
+            && self.urlpath == request.urlpath
+            && self
+                .query_params
+                .as_ref()
+                .is_none_or(|query_params| *query_params == request.query_params)
+            && self.body.as_ref().is_none_or(|body| *body == request.body)
+    }
+}
+
+#[derive(Default)]
+struct MockState {
+    rules: Vec<Rule>,
+    requests: Vec<MockRequest>,
+    unexpected: Vec<MockRequest>,
+}
+
+/// A `ClientAdapter` answering requests with scripted responses, to test code using a `Client`
+/// without a server.
+///
+/// Responses are registered with expectations, which answer the first matching request, or stubs,
+/// which answer every matching request. Expectations are matched in the order they were
+/// registered and take precedence over stubs. Requests without a matching response fail with
+/// `RestHttpError::OtherError`.
+///
+/// The adapter can be cloned to keep a handle after passing it to the client. All clones share the
+/// same responses and recorded requests.
+///
+/// ```
+/// # use nakama_rs::client::Client;
+/// # use nakama_rs::default_client::DefaultClient;
+/// # use nakama_rs::mock_client_adapter::MockClientAdapter;
+/// # use futures::executor::block_on;
+/// # use std::collections::HashMap;
+/// let adapter = MockClientAdapter::new();
+/// adapter
+///     .expect_post("/v2/account/authenticate/device")
+///     .respond_with(r#"{"token":"auth-token","refresh_token":"refresh-token"}"#);
+///
+/// let client = DefaultClient::new(adapter.clone());
+/// let session = block_on(client.authenticate_device("deviceid", None, true, HashMap::new()));
+///
+/// assert_eq!(session.unwrap().auth_token, "auth-token");
+/// assert_eq!(adapter.requests()[0].query_params, "create=true&");
+/// adapter.verify();
+/// ```
+#[derive(Clone, Default)]
+pub struct MockClientAdapter {
+    state: Arc<Mutex<MockState>>,
+}
+
+/// A response registered with `MockClientAdapter`.
+///
+/// Without further configuration, it answers requests with the method and path with `{}`.
+pub struct MockResponse {
+    state: Arc<Mutex<MockState>>,
+    index: usize,
+}
+
+impl MockResponse {
+    fn with_rule(&mut self, f: impl FnOnce(&mut Rule)) -> &mut Self {
+        {
+            let mut state = self.state.lock().expect("panic inside other mutex!");
+            f(&mut state.rules[self.index]);
+        }
+        self
+    }
+
+    /// Only match requests with exactly these query parameters, e.g. `create=true&`.
+    pub fn with_query_params(&mut self, query_params: &str) -> &mut Self {
+        self.with_rule(|rule| rule.query_params = Some(query_params.to_owned()))
+    }
+
+    /// Only match requests with exactly this JSON body.
+    pub fn with_body(&mut self, body: &str) -> &mut Self {
+        self.with_rule(|rule| rule.body = Some(body.to_owned()))
+    }
+
+    /// Answer with status 200 and the JSON `response`.
+    pub fn respond_with(&mut self, response: &str) -> &mut Self {
+        self.respond_with_status(200, response)
+    }
+
+    /// Answer with the status and body, e.g. `404` and a Nakama error object.
+    pub fn respond_with_status(&mut self, status: u16, response: &str) -> &mut Self {
+        self.with_rule(|rule| {
+            rule.status = status;
+            rule.response = response.to_owned();
+        })
+    }
+}
+
+impl MockClientAdapter {
+    pub fn new() -> MockClientAdapter {
+        MockClientAdapter::default()
+    }
+
+    fn add_rule(&self, method: Method, urlpath: &str, expected: bool) -> MockResponse {
+        let mut state = self.state.lock().expect("panic inside other mutex!");
+        state.rules.push(Rule {
+            method,
+            urlpath: urlpath.to_owned(),
+            query_params: None,
+            body: None,
+            status: 200,
+            response: "{}".to_owned(),
+            expected,
+            consumed: false,
+        });
+        MockResponse {
+            state: self.state.clone(),
+            index: state.rules.len() - 1,
+        }
+    }
+
+    /// Expect a single request with the method and path. See `verify`.
+    pub fn expect(&self, method: Method, urlpath: &str) -> MockResponse {
+        self.add_rule(method, urlpath, true)
+    }
+
+    pub fn expect_get(&self, urlpath: &str) -> MockResponse {
+        self.expect(Method::Get, urlpath)
+    }
+
+    pub fn expect_post(&self, urlpath: &str) -> MockResponse {
+        self.expect(Method::Post, urlpath)
+    }
+
+    pub fn expect_put(&self, urlpath: &str) -> MockResponse {
+        self.expect(Method::Put, urlpath)
+    }
+
+    pub fn expect_delete(&self, urlpath: &str) -> MockResponse {
+        self.expect(Method::Delete, urlpath)
+    }
+
+    /// Answer any number of requests with the method and path.
+    pub fn stub(&self, method: Method, urlpath: &str) -> MockResponse {
+        self.add_rule(method, urlpath, false)
+    }
+
+    /// All requests sent so far, in order.
+    pub fn requests(&self) -> Vec<MockRequest> {
+        let state = self.state.lock().expect("panic inside other mutex!");
+        state.requests.clone()
+    }
+
+    /// Panic if an expectation was not consumed or a request had no matching response.
+    pub fn verify(&self) {
+        let state = self.state.lock().expect("panic inside other mutex!");
+        let pending: Vec<String> = state
+            .rules
+            .iter()
+            .filter(|rule| rule.expected && !rule.consumed)
+            .map(|rule| format!("{:?} {}", rule.method, rule.urlpath))
+            .collect();
+        if !pending.is_empty() {
+            panic!("Expected requests were not sent: {:?}", pending);
+        }
+        if !state.unexpected.is_empty() {
+            panic!("Unexpected requests: {:?}", state.unexpected);
+        }
+    }
+
+    fn respond(&self, request: MockRequest) -> Result<(u16, String), RestHttpError> {
+        let mut state = self.state.lock().expect("panic inside other mutex!");
+        state.requests.push(request.clone());
+
+        let index = state
+            .rules
+            .iter()
+            .position(|rule| rule.expected && rule.matches(&request))
+            .or_else(|| {
+                state
+                    .rules
+                    .iter()
+                    .position(|rule| !rule.expected && rule.matches(&request))
+            });
+        match index {
+            Some(index) => {
+                let rule = &mut state.rules[index];
+                rule.consumed = rule.expected;
+                Ok((rule.status, rule.response.clone()))
+            }
+            None => {
+                let message = format!(
+                    "No response for {:?} {}?{}",
+                    request.method, request.urlpath, request.query_params
+                );
+                state.unexpected.push(request);
+                Err(RestHttpError::OtherError(message))
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl ClientAdapter for MockClientAdapter {
+    type Error = RestHttpError;
+
+    async fn send<T: DeJson + Send>(&self, request: RestRequest<T>) -> Result<T, Self::Error> {
+        let (status, response) = self.respond(MockRequest {
+            method: request.method,
+            urlpath: request.urlpath,
+            query_params: request.query_params,
+            body: request.body,
+            authentication: request.authentication,
+        })?;
+        parse_response(status, response)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::client::Client;
+    use crate::default_client::{DefaultClient, DefaultClientError};
+    use crate::session::Session;
+    use futures::executor::block_on;
+
+    fn session() -> Session {
+        Session {
+            auth_token: "token".to_owned(),
+            refresh_token: None,
+        }
+    }
+
+    #[test]
+    fn test_expectations_are_consumed_in_order() {
+        let adapter = MockClientAdapter::new();
+        adapter
+            .expect_get("/v2/account")
+            .respond_with(r#"{"user":{"username":"first"}}"#);
+        adapter
+            .expect_get("/v2/account")
+            .respond_with(r#"{"user":{"username":"second"}}"#);
+        let client = DefaultClient::new(adapter.clone());
+
+        let mut session = session();
+        let first = block_on(client.get_account(&mut session)).unwrap();
+        let second = block_on(client.get_account(&mut session)).unwrap();
+        assert_eq!(first.user.username, "first");
+        assert_eq!(second.user.username, "second");
+
+        let result = block_on(client.get_account(&mut session));
+        assert!(matches!(
+            result,
+            Err(DefaultClientError::HttpAdapterError(
+                RestHttpError::OtherError(_)
+            ))
+        ));
+        assert_eq!(adapter.requests().len(), 3);
+    }
+
+    #[test]
+    fn test_stubs_and_errors() {
+        let adapter = MockClientAdapter::new();
+        adapter
+            .stub(Method::Delete, "/v2/friend")
+            .with_query_params("ids=friend&");
+        adapter
+            .expect_delete("/v2/friend")
+            .with_query_params("ids=other&")
+            .respond_with_status(
+                404,
+                r#"{"error":"User not found","code":5,"message":"User not found"}"#,
+            );
+        let client = DefaultClient::new(adapter.clone());
+
+        let mut session = session();
+        block_on(client.delete_friends(&mut session, &["friend"], &[])).unwrap();
+        block_on(client.delete_friends(&mut session, &["friend"], &[])).unwrap();
+        let result = block_on(client.delete_friends(&mut session, &["other"], &[]));
+        assert!(matches!(
+            result,
+            Err(DefaultClientError::HttpAdapterError(
+                RestHttpError::ClientError(404, _)
+            ))
+        ));
+
+        let requests = adapter.requests();
+        assert_eq!(requests[2].query_params, "ids=other&");
+        assert!(matches!(
+            &requests[2].authentication,
+            Authentication::Bearer { token } if token == "token"
+        ));
+        adapter.verify();
+    }
+
+    #[test]
+    #[should_panic(expected = "Expected requests were not sent")]
+    fn test_verify_pending_expectation() {
+        let adapter = MockClientAdapter::new();
+        adapter.expect_post("/v2/account/authenticate/device");
+        adapter.verify();
+    }
+}
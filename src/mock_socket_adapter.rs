@@ -0,0 +1,471 @@
+use crate::socket::WebSocketMessageEnvelopeHeader;
+use crate::socket_adapter::{SocketAdapter, Wakeup};
+use nanoserde::DeJson;
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+#[derive(Debug, Clone)]
+pub enum MockSocketError {
+    NotConnected,
+    /// An error injected with `MockSocketHandle::receive_error` or `fail_connection`.
+    Injected(String),
+}
+
+impl Display for MockSocketError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Debug::fmt(self, f)
+    }
+}
+
+impl Error for MockSocketError {}
+
+type Responder = Box<dyn FnMut(&str) -> Option<String> + Send + 'static>;
+
+enum Event {
+    Connected,
+    ConnectionFailed(String),
+    Closed,
+    Received(Result<String, MockSocketError>),
+}
+
+#[derive(Default)]
+struct MockSocketState {
+    auto_connect: bool,
+    connecting: bool,
+    connected: bool,
+    addr: Option<String>,
+    sent: Vec<String>,
+    taken: usize,
+    pending_cids: VecDeque<String>,
+    responder: Option<Responder>,
+    events: VecDeque<Event>,
+    wakeup: Option<Wakeup>,
+}
+
+impl MockSocketState {
+    fn push_event(&mut self, event: Event) {
+        self.events.push_back(event);
+        if let Some(ref wakeup) = self.wakeup {
+            wakeup.wake();
+        }
+    }
+}
+
+/// Insert `"cid"` as the first field of the JSON object `json`.
+fn with_cid(json: &str, cid: &str) -> String {
+    let fields = json.trim().trim_start_matches('{').trim_start();
+    if fields.starts_with('}') {
+        format!("{{\"cid\":\"{}\"}}", cid)
+    } else {
+        format!("{{\"cid\":\"{}\",{}", cid, fields)
+    }
+}
+
+type ConnectedCallback = Box<dyn Fn() + Send + 'static>;
+type ClosedCallback = Box<dyn Fn() + Send + 'static>;
+type ReceivedCallback = Box<dyn Fn(Result<String, MockSocketError>) + Send + 'static>;
+
+/// A `SocketAdapter` without a network connection, to test code using a `WebSocket` without a
+/// server.
+///
+/// Sent frames are captured and incoming frames are injected through a `MockSocketHandle`.
+/// Injected events are queued and only delivered by `tick`, on the calling thread, so tests are
+/// deterministic and need no threads or sleeps. By default, `connect` succeeds on the next `tick`.
+///
+/// ```
+/// # use nakama_rs::mock_socket_adapter::{block_on_ticked, MockSocketAdapter};
+/// # use nakama_rs::session::Session;
+/// # use nakama_rs::socket::Socket;
+/// # use nakama_rs::web_socket::WebSocket;
+/// let adapter = MockSocketAdapter::new();
+/// let handle = adapter.handle();
+/// // Answer requests creating a party with a party
+/// handle.on_send(|frame| {
+///     if frame.contains("party_create") {
+///         let party = r#"{"party_id":"party","max_size":2,"self":{},"leader":{},"presences":[]}"#;
+///         Some(format!(r#"{{"party":{}}}"#, party))
+///     } else {
+///         None
+///     }
+/// });
+///
+/// let socket = WebSocket::new(adapter);
+/// let mut session = Session {
+///     auth_token: "token".to_owned(),
+///     refresh_token: None,
+/// };
+/// block_on_ticked(|| socket.tick(), socket.connect(&mut session, true, -1));
+/// let party = block_on_ticked(|| socket.tick(), socket.create_party(true, 2)).unwrap();
+///
+/// assert_eq!(party.party_id, "party");
+/// assert!(handle.sent()[0].contains("party_create"));
+/// ```
+pub struct MockSocketAdapter {
+    on_connected: Option<ConnectedCallback>,
+    on_closed: Option<ClosedCallback>,
+    on_received: Option<ReceivedCallback>,
+    state: Arc<Mutex<MockSocketState>>,
+}
+
+/// Controls a `MockSocketAdapter` after it was passed to a `WebSocket`.
+#[derive(Clone)]
+pub struct MockSocketHandle {
+    state: Arc<Mutex<MockSocketState>>,
+}
+
+impl MockSocketAdapter {
+    pub fn new() -> MockSocketAdapter {
+        MockSocketAdapter {
+            on_connected: None,
+            on_closed: None,
+            on_received: None,
+            state: Arc::new(Mutex::new(MockSocketState {
+                auto_connect: true,
+                ..Default::default()
+            })),
+        }
+    }
+
+    pub fn handle(&self) -> MockSocketHandle {
+        MockSocketHandle {
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl Default for MockSocketAdapter {
+    fn default() -> Self {
+        MockSocketAdapter::new()
+    }
+}
+
+impl MockSocketHandle {
+    fn with<T>(&self, f: impl FnOnce(&mut MockSocketState) -> T) -> T {
+        let mut state = self.state.lock().expect("panic inside other mutex!");
+        f(&mut state)
+    }
+
+    /// Whether `connect` succeeds on the next `tick`. Defaults to `true`. Otherwise the connection
+    /// stays pending until `accept_connection` or `fail_connection` is called.
+    pub fn set_auto_connect(&self, auto_connect: bool) {
+        self.with(|state| state.auto_connect = auto_connect);
+    }
+
+    /// The address passed to the last `connect`.
+    pub fn connect_addr(&self) -> Option<String> {
+        self.with(|state| state.addr.clone())
+    }
+
+    /// Complete a pending connection.
+    pub fn accept_connection(&self) {
+        self.with(|state| state.push_event(Event::Connected));
+    }
+
+    /// Fail a pending connection. The error is passed to the `on_received` callback.
+    pub fn fail_connection(&self, error: &str) {
+        self.with(|state| state.push_event(Event::ConnectionFailed(error.to_owned())));
+    }
+
+    /// Close the connection as if the server closed it.
+    pub fn close(&self) {
+        self.with(|state| state.push_event(Event::Closed));
+    }
+
+    /// Inject an incoming frame.
+    pub fn receive(&self, json: &str) {
+        self.with(|state| state.push_event(Event::Received(Ok(json.to_owned()))));
+    }
+
+    /// Inject a receive error.
+    pub fn receive_error(&self, error: &str) {
+        self.with(|state| {
+            let error = MockSocketError::Injected(error.to_owned());
+            state.push_event(Event::Received(Err(error)))
+        });
+    }
+
+    /// Answer the oldest sent request that wasn't answered yet with the JSON envelope `json`,
+    /// adding the request's `cid`.
+    ///
+    /// Returns `false` if there is no request waiting for a response.
+    pub fn respond(&self, json: &str) -> bool {
+        self.with(|state| match state.pending_cids.pop_front() {
+            Some(cid) => {
+                let response = with_cid(json, &cid);
+                state.push_event(Event::Received(Ok(response)));
+                true
+            }
+            None => false,
+        })
+    }
+
+    /// Call `responder` with every sent frame. If it returns a JSON envelope and the frame has a
+    /// `cid`, the envelope is injected as the response with the same `cid`.
+    pub fn on_send<T>(&self, responder: T)
+    where
+        T: FnMut(&str) -> Option<String> + Send + 'static,
+    {
+        self.with(|state| state.responder = Some(Box::new(responder)));
+    }
+
+    /// All frames sent so far, in order.
+    pub fn sent(&self) -> Vec<String> {
+        self.with(|state| state.sent.clone())
+    }
+
+    /// The frames sent since the last call to `take_sent`.
+    pub fn take_sent(&self) -> Vec<String> {
+        self.with(|state| {
+            let sent = state.sent[state.taken..].to_vec();
+            state.taken = state.sent.len();
+            sent
+        })
+    }
+}
+
+impl SocketAdapter for MockSocketAdapter {
+    type Error = MockSocketError;
+
+    fn on_connected<T>(&mut self, callback: T)
+    where
+        T: Fn() + Send + 'static,
+    {
+        self.on_connected = Some(Box::new(callback));
+    }
+
+    fn on_closed<T>(&mut self, callback: T)
+    where
+        T: Fn() + Send + 'static,
+    {
+        self.on_closed = Some(Box::new(callback));
+    }
+
+    fn on_received<T>(&mut self, callback: T)
+    where
+        T: Fn(Result<String, MockSocketError>) + Send + 'static,
+    {
+        self.on_received = Some(Box::new(callback));
+    }
+
+    fn set_wakeup(&mut self, wakeup: Wakeup) {
+        self.handle().with(|state| state.wakeup = Some(wakeup));
+    }
+
+    fn is_connected(&self) -> bool {
+        self.handle().with(|state| state.connected)
+    }
+
+    fn is_connecting(&self) -> bool {
+        self.handle().with(|state| state.connecting)
+    }
+
+    fn close(&mut self) {
+        self.handle().with(|state| {
+            if state.connected || state.connecting {
+                state.push_event(Event::Closed);
+            }
+        });
+    }
+
+    fn connect(&mut self, addr: &str, _timeout: i32) {
+        self.handle().with(|state| {
+            state.addr = Some(addr.to_owned());
+            state.connecting = true;
+            if state.auto_connect {
+                state.push_event(Event::Connected);
+            }
+        });
+    }
+
+    fn send(&self, data: &str, _reliable: bool) -> Result<(), Self::Error> {
+        let handle = self.handle();
+        let (cid, responder) = handle.with(|state| {
+            if !state.connected {
+                return Err(MockSocketError::NotConnected);
+            }
+            state.sent.push(data.to_owned());
+            let cid = WebSocketMessageEnvelopeHeader::deserialize_json(data)
+                .ok()
+                .and_then(|header| header.cid);
+            if let Some(ref cid) = cid {
+                state.pending_cids.push_back(cid.clone());
+            }
+            // The responder is called without holding the lock, so it can use the handle
+            Ok((cid, state.responder.take()))
+        })?;
+
+        if let Some(mut responder) = responder {
+            let response = responder(data);
+            handle.with(|state| {
+                if state.responder.is_none() {
+                    state.responder = Some(responder);
+                }
+            });
+            if let (Some(cid), Some(response)) = (cid, response) {
+                handle.with(|state| state.pending_cids.retain(|pending| *pending != cid));
+                handle
+                    .with(|state| state.push_event(Event::Received(Ok(with_cid(&response, &cid)))));
+            }
+        }
+        Ok(())
+    }
+
+    fn tick(&self) {
+        let events = self
+            .handle()
+            .with(|state| std::mem::take(&mut state.events));
+        for event in events {
+            match event {
+                Event::Connected => {
+                    self.handle().with(|state| {
+                        state.connecting = false;
+                        state.connected = true;
+                    });
+                    if let Some(ref cb) = self.on_connected {
+                        cb();
+                    }
+                }
+                Event::ConnectionFailed(error) => {
+                    self.handle().with(|state| state.connecting = false);
+                    if let Some(ref cb) = self.on_received {
+                        cb(Err(MockSocketError::Injected(error)));
+                    }
+                }
+                Event::Closed => {
+                    self.handle().with(|state| {
+                        state.connecting = false;
+                        state.connected = false;
+                        state.pending_cids.clear();
+                    });
+                    if let Some(ref cb) = self.on_closed {
+                        cb();
+                    }
+                }
+                Event::Received(message) => {
+                    if let Some(ref cb) = self.on_received {
+                        cb(message);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Run `future` to completion on the current thread, calling `tick` whenever it is pending.
+///
+/// Intended for tests using a `MockSocketAdapter`, where all events are delivered by `tick`.
+/// Panics if the future is still pending after 1000 ticks, e.g. because a request was never
+/// answered.
+pub fn block_on_ticked<F: Future>(tick: impl Fn(), future: F) -> F::Output {
+    let mut future = Box::pin(future);
+    let mut context = Context::from_waker(Waker::noop());
+    for _ in 0..1000 {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+            return output;
+        }
+        tick();
+    }
+    panic!("block_on_ticked: future is still pending after 1000 ticks");
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::session::Session;
+    use crate::socket::{ChannelJoinType, Socket};
+    use crate::web_socket::WebSocket;
+    use std::sync::mpsc;
+
+    fn connected_socket() -> (WebSocket<MockSocketAdapter>, MockSocketHandle) {
+        let adapter = MockSocketAdapter::new();
+        let handle = adapter.handle();
+        let socket = WebSocket::new(adapter);
+        let mut session = Session {
+            auth_token: "token".to_owned(),
+            refresh_token: None,
+        };
+        block_on_ticked(|| socket.tick(), socket.connect(&mut session, true, -1));
+        (socket, handle)
+    }
+
+    #[test]
+    fn test_with_cid() {
+        assert_eq!(with_cid("{}", "1"), r#"{"cid":"1"}"#);
+        assert_eq!(with_cid(r#" { "a":1}"#, "2"), r#"{"cid":"2","a":1}"#);
+    }
+
+    #[test]
+    fn test_connect() {
+        let adapter = MockSocketAdapter::new();
+        let handle = adapter.handle();
+        handle.set_auto_connect(false);
+        let socket = WebSocket::new_with_server(adapter, "ws://example.com", 80);
+        let (tx, rx) = mpsc::channel();
+        socket.on_connected(move || tx.send(()).unwrap());
+
+        let mut session = Session {
+            auth_token: "token".to_owned(),
+            refresh_token: None,
+        };
+        let mut connect = Box::pin(socket.connect(&mut session, false, -1));
+        let mut context = Context::from_waker(Waker::noop());
+        assert!(connect.as_mut().poll(&mut context).is_pending());
+        socket.tick();
+        assert!(rx.try_recv().is_err());
+
+        handle.accept_connection();
+        socket.tick();
+        assert!(connect.as_mut().poll(&mut context).is_ready());
+        assert!(rx.try_recv().is_ok());
+        assert_eq!(
+            handle.connect_addr().unwrap(),
+            "ws://example.com:80/ws?lang=en&status=false&token=token"
+        );
+    }
+
+    #[test]
+    fn test_respond_and_receive() {
+        let (socket, handle) = connected_socket();
+        let (tx, rx) = mpsc::channel();
+        socket.on_received_channel_message(move |message| tx.send(message.content).unwrap());
+
+        let mut join = Box::pin(socket.join_chat("room", ChannelJoinType::Room, false, false));
+        let mut context = Context::from_waker(Waker::noop());
+        assert!(join.as_mut().poll(&mut context).is_pending());
+
+        let sent = handle.take_sent();
+        assert_eq!(sent.len(), 1);
+        assert!(sent[0].contains(r#""channel_join":{"#));
+        assert!(handle.respond(r#"{"channel":{"id":"2...room","self":{}}}"#));
+        assert!(!handle.respond("{}"));
+        socket.tick();
+        match join.as_mut().poll(&mut context) {
+            Poll::Ready(Ok(channel)) => assert_eq!(channel.id, "2...room"),
+            _ => panic!("join_chat did not complete"),
+        }
+
+        handle.receive(r#"{"channel_message":{"channel_id":"2...room","content":"{}"}}"#);
+        assert!(rx.try_recv().is_err());
+        socket.tick();
+        assert_eq!(rx.try_recv().unwrap(), "{}");
+        assert!(handle.take_sent().is_empty());
+    }
+
+    #[test]
+    fn test_close() {
+        let (socket, handle) = connected_socket();
+        let (tx, rx) = mpsc::channel();
+        socket.on_closed(move || tx.send(()).unwrap());
+
+        handle.close();
+        socket.tick();
+        assert!(rx.try_recv().is_ok());
+
+        let result = block_on_ticked(|| socket.tick(), socket.create_match());
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,579 @@
+use crate::api::{
+    ApiChannelMessage, ApiChannelMessageList, ApiFriend, ApiFriendList, ApiGroup, ApiGroupList,
+    ApiGroupUserList, ApiLeaderboardRecord, ApiLeaderboardRecordList, ApiNotification,
+    ApiNotificationList, ApiStorageObject, ApiStorageObjectList, ApiTournament, ApiTournamentList,
+    ApiTournamentRecordList, ApiUserGroupList, GroupUserListGroupUser, UserGroupListUserGroup,
+};
+use crate::client::Client;
+use crate::enums::{FriendState, GroupRole};
+use crate::session::Session;
+use std::collections::VecDeque;
+use std::convert::TryFrom;
+use std::future::Future;
+use std::pin::Pin;
+
+/// A list response that can be split into its items and the cursor of the next page.
+pub trait PagedList {
+    type Item;
+
+    /// The items of the page and the cursor of the next page, if there is one.
+    fn into_page(self) -> (Vec<Self::Item>, Option<String>);
+}
+
+fn non_empty(cursor: String) -> Option<String> {
+    if cursor.is_empty() {
+        None
+    } else {
+        Some(cursor)
+    }
+}
+
+macro_rules! paged_list {
+    ($list:ty, $item:ty, $items:ident, $cursor:ident) => {
+        impl PagedList for $list {
+            type Item = $item;
+
+            fn into_page(self) -> (Vec<$item>, Option<String>) {
+                (self.$items, non_empty(self.$cursor))
+            }
+        }
+    };
+    ($list:ty, $item:ty, $items:ident, optional $cursor:ident) => {
+        impl PagedList for $list {
+            type Item = $item;
+
+            fn into_page(self) -> (Vec<$item>, Option<String>) {
+                (self.$items, self.$cursor.and_then(non_empty))
+            }
+        }
+    };
+}
+
+paged_list!(
+    ApiChannelMessageList,
+    ApiChannelMessage,
+    messages,
+    next_cursor
+);
+paged_list!(ApiFriendList, ApiFriend, friends, cursor);
+paged_list!(ApiGroupList, ApiGroup, groups, cursor);
+paged_list!(
+    ApiGroupUserList,
+    GroupUserListGroupUser,
+    group_users,
+    cursor
+);
+paged_list!(
+    ApiLeaderboardRecordList,
+    ApiLeaderboardRecord,
+    records,
+    next_cursor
+);
+paged_list!(
+    ApiNotificationList,
+    ApiNotification,
+    notifications,
+    cacheable_cursor
+);
+paged_list!(ApiStorageObjectList, ApiStorageObject, objects, cursor);
+paged_list!(ApiTournamentList, ApiTournament, tournaments, optional cursor);
+paged_list!(ApiTournamentRecordList, ApiLeaderboardRecord, records, optional next_cursor);
+paged_list!(
+    ApiUserGroupList,
+    UserGroupListUserGroup,
+    user_groups,
+    cursor
+);
+
+type PageFuture<'a, L, E> = Pin<Box<dyn Future<Output = Result<L, E>> + Send + 'a>>;
+
+type Fetch<C, L> = Box<
+    dyn for<'a> Fn(
+            &'a C,
+            &'a mut Session,
+            Option<i32>,
+            Option<String>,
+        ) -> PageFuture<'a, L, <C as Client>::Error>
+        + Send
+        + Sync,
+>;
+
+/// Iterates over the pages of a list endpoint, following the cursors until the list is
+/// exhausted.
+///
+/// The paginator doesn't keep a reference to the client or session, they are passed to
+/// `next_page` and `next` instead.
+///
+/// ```
+/// # use nakama_rs::client::Client;
+/// # use nakama_rs::paginator::Paginator;
+/// # use nakama_rs::session::Session;
+/// async fn friend_names<C: Client + Sync + 'static>(
+///     client: &C,
+///     session: &mut Session,
+/// ) -> Result<Vec<String>, C::Error> {
+///     let mut friends = Paginator::friends(None);
+///     friends.set_page_size(Some(100));
+///     friends.set_max_items(Some(1000));
+///
+///     let mut names = Vec::new();
+///     while let Some(friend) = friends.next(client, session).await? {
+///         names.push(friend.user.username);
+///     }
+///     Ok(names)
+/// }
+/// ```
+pub struct Paginator<C: Client, L: PagedList> {
+    fetch: Fetch<C, L>,
+    cursor: Option<String>,
+    page_size: Option<i32>,
+    max_items: Option<usize>,
+    items_returned: usize,
+    exhausted: bool,
+    buffer: VecDeque<L::Item>,
+}
+
+impl<C: Client + Sync, L: PagedList> Paginator<C, L> {
+    /// A paginator fetching pages with `fetch`, which is called with the page size and cursor.
+    pub fn new<F>(fetch: F) -> Self
+    where
+        F: for<'a> Fn(
+                &'a C,
+                &'a mut Session,
+                Option<i32>,
+                Option<String>,
+            ) -> PageFuture<'a, L, C::Error>
+            + Send
+            + Sync
+            + 'static,
+    {
+        Paginator {
+            fetch: Box::new(fetch),
+            cursor: None,
+            page_size: None,
+            max_items: None,
+            items_returned: 0,
+            exhausted: false,
+            buffer: VecDeque::new(),
+        }
+    }
+
+    /// The number of items requested per page. Defaults to the server's default.
+    pub fn set_page_size(&mut self, page_size: Option<i32>) {
+        self.page_size = page_size;
+    }
+
+    /// Stop after `max_items` items. Defaults to no limit.
+    pub fn set_max_items(&mut self, max_items: Option<usize>) {
+        self.max_items = max_items;
+    }
+
+    /// Continue from a cursor saved with `cursor`.
+    pub fn set_cursor(&mut self, cursor: Option<&str>) {
+        self.cursor = cursor.map(str::to_owned);
+        self.exhausted = false;
+        self.buffer.clear();
+    }
+
+    /// The cursor of the next page to fetch, or `None` if the paginator hasn't started or the
+    /// list is exhausted.
+    ///
+    /// The `cacheable_cursor` of notifications is kept once they are exhausted, to fetch only
+    /// newer notifications later.
+    pub fn cursor(&self) -> Option<&str> {
+        self.cursor.as_deref()
+    }
+
+    fn remaining(&self) -> Option<usize> {
+        self.max_items
+            .map(|max_items| max_items.saturating_sub(self.items_returned))
+    }
+
+    /// The next page of items, or `None` once the list is exhausted or `max_items` items were
+    /// returned.
+    pub async fn next_page(
+        &mut self,
+        client: &C,
+        session: &mut Session,
+    ) -> Result<Option<Vec<L::Item>>, C::Error> {
+        let mut items: Vec<L::Item> = self.buffer.drain(..).collect();
+        if items.is_empty() {
+            if self.exhausted || self.remaining() == Some(0) {
+                return Ok(None);
+            }
+
+            let page_size = match (self.page_size, self.remaining()) {
+                (Some(page_size), Some(remaining)) => {
+                    Some(page_size.min(i32::try_from(remaining).unwrap_or(i32::MAX)))
+                }
+                (page_size, _) => page_size,
+            };
+            let list = (self.fetch)(client, session, page_size, self.cursor.clone()).await?;
+            let (page, cursor) = list.into_page();
+            // Some endpoints keep returning a cursor, e.g. `cacheable_cursor` of notifications
+            self.exhausted = page.is_empty() || cursor.is_none() || cursor == self.cursor;
+            self.cursor = cursor;
+            items = page;
+            if items.is_empty() {
+                return Ok(None);
+            }
+        }
+
+        if let Some(remaining) = self.remaining() {
+            items.truncate(remaining);
+        }
+        self.items_returned += items.len();
+        Ok(Some(items))
+    }
+
+    /// The next item, fetching the next page when needed.
+    pub async fn next(
+        &mut self,
+        client: &C,
+        session: &mut Session,
+    ) -> Result<Option<L::Item>, C::Error> {
+        if self.buffer.is_empty() {
+            match self.next_page(client, session).await? {
+                Some(page) => {
+                    // `next_page` counted the whole page as returned
+                    self.items_returned -= page.len();
+                    self.buffer.extend(page);
+                }
+                None => return Ok(None),
+            }
+        }
+        let item = self.buffer.pop_front();
+        if item.is_some() {
+            self.items_returned += 1;
+        }
+        Ok(item)
+    }
+
+    /// All remaining items.
+    pub async fn collect(
+        &mut self,
+        client: &C,
+        session: &mut Session,
+    ) -> Result<Vec<L::Item>, C::Error> {
+        let mut items = Vec::new();
+        while let Some(page) = self.next_page(client, session).await? {
+            items.extend(page);
+        }
+        Ok(items)
+    }
+}
+
+impl<C: Client + Sync + 'static> Paginator<C, ApiChannelMessageList> {
+    pub fn channel_messages(channel_id: &str, forward: Option<bool>) -> Self {
+        let channel_id = channel_id.to_owned();
+        Paginator::new(move |client: &C, session, limit, cursor| {
+            let channel_id = channel_id.clone();
+            Box::pin(async move {
+                client
+                    .list_channel_messages(session, &channel_id, limit, forward, cursor.as_deref())
+                    .await
+            })
+        })
+    }
+}
+
+impl<C: Client + Sync + 'static> Paginator<C, ApiFriendList> {
+    pub fn friends(state: Option<FriendState>) -> Self {
+        Paginator::new(move |client: &C, session, limit, cursor| {
+            Box::pin(async move {
+                client
+                    .list_friends(session, state, limit, cursor.as_deref())
+                    .await
+            })
+        })
+    }
+}
+
+impl<C: Client + Sync + 'static> Paginator<C, ApiGroupList> {
+    pub fn groups(name: Option<&str>) -> Self {
+        let name = name.map(str::to_owned);
+        Paginator::new(move |client: &C, session, limit, cursor| {
+            let name = name.clone();
+            Box::pin(async move {
+                client
+                    .list_groups(session, name.as_deref(), limit, cursor.as_deref())
+                    .await
+            })
+        })
+    }
+}
+
+impl<C: Client + Sync + 'static> Paginator<C, ApiGroupUserList> {
+    pub fn group_users(group_id: &str, state: Option<GroupRole>) -> Self {
+        let group_id = group_id.to_owned();
+        Paginator::new(move |client: &C, session, limit, cursor| {
+            let group_id = group_id.clone();
+            Box::pin(async move {
+                client
+                    .list_group_users(session, &group_id, state, limit, cursor.as_deref())
+                    .await
+            })
+        })
+    }
+}
+
+impl<C: Client + Sync + 'static> Paginator<C, ApiLeaderboardRecordList> {
+    pub fn leaderboard_records(
+        leaderboard_id: &str,
+        owner_ids: &[&str],
+        expiry: Option<&str>,
+    ) -> Self {
+        let leaderboard_id = leaderboard_id.to_owned();
+        let owner_ids: Vec<String> = owner_ids.iter().map(|id| (*id).to_owned()).collect();
+        let expiry = expiry.map(str::to_owned);
+        Paginator::new(move |client: &C, session, limit, cursor| {
+            let leaderboard_id = leaderboard_id.clone();
+            let owner_ids = owner_ids.clone();
+            let expiry = expiry.clone();
+            Box::pin(async move {
+                let owner_ids: Vec<&str> = owner_ids.iter().map(String::as_str).collect();
+                client
+                    .list_leaderboard_records(
+                        session,
+                        &leaderboard_id,
+                        &owner_ids,
+                        expiry.as_deref(),
+                        limit,
+                        cursor.as_deref(),
+                    )
+                    .await
+            })
+        })
+    }
+}
+
+impl<C: Client + Sync + 'static> Paginator<C, ApiNotificationList> {
+    pub fn notifications() -> Self {
+        Paginator::new(move |client: &C, session, limit, cursor| {
+            Box::pin(async move {
+                client
+                    .list_notifications(session, limit, cursor.as_deref())
+                    .await
+            })
+        })
+    }
+}
+
+impl<C: Client + Sync + 'static> Paginator<C, ApiStorageObjectList> {
+    pub fn storage_objects(collection: &str) -> Self {
+        let collection = collection.to_owned();
+        Paginator::new(move |client: &C, session, limit, cursor| {
+            let collection = collection.clone();
+            Box::pin(async move {
+                client
+                    .list_storage_objects(session, &collection, None, limit, cursor.as_deref())
+                    .await
+            })
+        })
+    }
+
+    pub fn users_storage_objects(collection: &str, user_id: &str) -> Self {
+        let collection = collection.to_owned();
+        let user_id = user_id.to_owned();
+        Paginator::new(move |client: &C, session, limit, cursor| {
+            let collection = collection.clone();
+            let user_id = user_id.clone();
+            Box::pin(async move {
+                client
+                    .list_users_storage_objects(
+                        session,
+                        &collection,
+                        &user_id,
+                        limit,
+                        cursor.as_deref(),
+                    )
+                    .await
+            })
+        })
+    }
+}
+
+impl<C: Client + Sync + 'static> Paginator<C, ApiTournamentList> {
+    pub fn tournaments(
+        category_start: Option<i32>,
+        category_end: Option<i32>,
+        start_time: Option<i32>,
+        end_time: Option<i32>,
+    ) -> Self {
+        Paginator::new(move |client: &C, session, limit, cursor| {
+            Box::pin(async move {
+                client
+                    .list_tournaments(
+                        session,
+                        category_start,
+                        category_end,
+                        start_time,
+                        end_time,
+                        limit,
+                        cursor.as_deref(),
+                    )
+                    .await
+            })
+        })
+    }
+}
+
+impl<C: Client + Sync + 'static> Paginator<C, ApiTournamentRecordList> {
+    pub fn tournament_records(
+        tournament_id: &str,
+        owner_ids: &[&str],
+        expiry: Option<&str>,
+    ) -> Self {
+        let tournament_id = tournament_id.to_owned();
+        let owner_ids: Vec<String> = owner_ids.iter().map(|id| (*id).to_owned()).collect();
+        let expiry = expiry.map(str::to_owned);
+        Paginator::new(move |client: &C, session, limit, cursor| {
+            let tournament_id = tournament_id.clone();
+            let owner_ids = owner_ids.clone();
+            let expiry = expiry.clone();
+            Box::pin(async move {
+                let owner_ids: Vec<&str> = owner_ids.iter().map(String::as_str).collect();
+                client
+                    .list_tournament_records(
+                        session,
+                        &tournament_id,
+                        &owner_ids,
+                        expiry.as_deref(),
+                        limit,
+                        cursor.as_deref(),
+                    )
+                    .await
+            })
+        })
+    }
+}
+
+impl<C: Client + Sync + 'static> Paginator<C, ApiUserGroupList> {
+    pub fn current_user_groups(state: Option<GroupRole>) -> Self {
+        Paginator::new(move |client: &C, session, limit, cursor| {
+            Box::pin(async move {
+                client
+                    .list_current_user_groups(session, state, limit, cursor.as_deref())
+                    .await
+            })
+        })
+    }
+
+    pub fn user_groups(user_id: &str, state: Option<GroupRole>) -> Self {
+        let user_id = user_id.to_owned();
+        Paginator::new(move |client: &C, session, limit, cursor| {
+            let user_id = user_id.clone();
+            Box::pin(async move {
+                client
+                    .list_user_groups(session, &user_id, state, limit, cursor.as_deref())
+                    .await
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::default_client::DefaultClient;
+    use crate::mock_client_adapter::MockClientAdapter;
+    use futures::executor::block_on;
+
+    fn session() -> Session {
+        Session {
+            auth_token: "token".to_owned(),
+            refresh_token: None,
+        }
+    }
+
+    fn friend_page(adapter: &MockClientAdapter, query_params: &str, names: &[&str], cursor: &str) {
+        let friends: Vec<String> = names
+            .iter()
+            .map(|name| format!(r#"{{"user":{{"username":"{}"}}}}"#, name))
+            .collect();
+        adapter
+            .expect_get("/v2/friend")
+            .with_query_params(query_params)
+            .respond_with(&format!(
+                r#"{{"friends":[{}],"cursor":"{}"}}"#,
+                friends.join(","),
+                cursor
+            ));
+    }
+
+    #[test]
+    fn test_pages() {
+        let adapter = MockClientAdapter::new();
+        friend_page(&adapter, "limit=2&", &["a", "b"], "c1");
+        friend_page(&adapter, "limit=2&cursor=c1&", &["c", "d"], "c2");
+        friend_page(&adapter, "limit=2&cursor=c2&", &["e"], "");
+        let client = DefaultClient::new(adapter.clone());
+        let mut session = session();
+
+        let mut friends = Paginator::friends(None);
+        friends.set_page_size(Some(2));
+        let mut names = Vec::new();
+        while let Some(page) = block_on(friends.next_page(&client, &mut session)).unwrap() {
+            names.push(page.len());
+        }
+        assert_eq!(names, [2, 2, 1]);
+        assert!(friends.cursor().is_none());
+        adapter.verify();
+    }
+
+    #[test]
+    fn test_max_items_and_saved_cursor() {
+        let adapter = MockClientAdapter::new();
+        friend_page(&adapter, "limit=2&cursor=saved&", &["a", "b"], "c1");
+        friend_page(&adapter, "limit=1&cursor=c1&", &["c"], "c2");
+        let client = DefaultClient::new(adapter.clone());
+        let mut session = session();
+
+        let mut friends = Paginator::friends(None);
+        friends.set_page_size(Some(2));
+        friends.set_max_items(Some(3));
+        friends.set_cursor(Some("saved"));
+        let mut names = Vec::new();
+        while let Some(friend) = block_on(friends.next(&client, &mut session)).unwrap() {
+            names.push(friend.user.username);
+        }
+        assert_eq!(names, ["a", "b", "c"]);
+        assert_eq!(friends.cursor(), Some("c2"));
+        adapter.verify();
+    }
+
+    #[test]
+    fn test_max_items_above_i32_max() {
+        let adapter = MockClientAdapter::new();
+        friend_page(&adapter, "limit=2&", &["a"], "");
+        let client = DefaultClient::new(adapter.clone());
+        let mut session = session();
+
+        let mut friends = Paginator::friends(None);
+        friends.set_page_size(Some(2));
+        friends.set_max_items(Some(i32::MAX as usize + 1));
+        let friends = block_on(friends.collect(&client, &mut session)).unwrap();
+        assert_eq!(friends.len(), 1);
+        adapter.verify();
+    }
+
+    #[test]
+    fn test_repeated_cacheable_cursor() {
+        let adapter = MockClientAdapter::new();
+        adapter
+            .expect_get("/v2/notification")
+            .respond_with(r#"{"notifications":[{"id":"1"}],"cacheable_cursor":"c"}"#);
+        adapter
+            .expect_get("/v2/notification")
+            .with_query_params("cacheable_cursor=c&")
+            .respond_with(r#"{"notifications":[],"cacheable_cursor":"c"}"#);
+        let client = DefaultClient::new(adapter.clone());
+        let mut session = session();
+
+        let mut paginator = Paginator::notifications();
+        let notifications = block_on(paginator.collect(&client, &mut session)).unwrap();
+        assert_eq!(notifications.len(), 1);
+        assert_eq!(paginator.cursor(), Some("c"));
+        adapter.verify();
+    }
+}
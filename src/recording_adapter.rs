@@ -0,0 +1,823 @@
+use crate::api::{Method, RestRequest};
+use crate::http_adapter::{parse_response, ClientAdapter, RestHttpError};
+use crate::socket_adapter::{SocketAdapter, Wakeup};
+use async_trait::async_trait;
+use log::error;
+use nanoserde::{DeJson, DeJsonErr, DeJsonState, DeJsonTok, SerJson};
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::io;
+use std::io::Write;
+use std::path::Path;
+use std::str::Chars;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+/// Fields whose values differ between recordings and are ignored when matching requests and
+/// frames, in JSON and in query strings.
+const VOLATILE_FIELDS: &[&str] = &[
+    "token",
+    "refresh_token",
+    "password",
+    "http_key",
+    "create_time",
+    "update_time",
+    "expiry_time",
+];
+
+/// Fields containing credentials, which are redacted before they are written to a cassette.
+const SECRET_FIELDS: &[&str] = &["token", "refresh_token", "password", "http_key"];
+
+/// A single line of a cassette.
+///
+/// `kind` is one of `request` (an HTTP request and its response), `connect`, `connected`, `send`,
+/// `receive`, `error`, `close` or `closed` (socket events of the socket `socket`).
+#[derive(Debug, Clone, Default, DeJson, SerJson)]
+#[nserde(default)]
+pub struct CassetteEntry {
+    /// Milliseconds since the recording started.
+    pub time: u64,
+    pub kind: String,
+    pub socket: u32,
+    pub method: String,
+    pub urlpath: String,
+    pub query_params: String,
+    pub body: String,
+    pub status: u16,
+    /// The HTTP response body or the socket frame, address or error.
+    pub data: String,
+}
+
+/// Replace the values of volatile fields with `*`.
+fn normalize(text: &str) -> String {
+    replace_values(text, VOLATILE_FIELDS, |_| "*".to_owned())
+}
+
+/// Redact the values of secret fields, so cassettes can be committed.
+fn redact(text: &str) -> String {
+    replace_values(text, SECRET_FIELDS, redact_token)
+}
+
+// Keeps the claims of a JWT, e.g. for `Session::user_id`, but drops its signature
+fn redact_token(token: &str) -> String {
+    match token.rsplit_once('.') {
+        Some((claims, _)) if claims.contains('.') => format!("{}.redacted", claims),
+        _ => "redacted".to_owned(),
+    }
+}
+
+// Replace the values of `fields`, in JSON and in query strings
+fn replace_values(text: &str, fields: &[&str], replace: impl Fn(&str) -> String) -> String {
+    let mut text = text.to_owned();
+    for field in fields {
+        text = map_values(&text, &format!("\"{}\":\"", field), '"', &replace);
+        text = map_values(&text, &format!("{}=", field), '&', &replace);
+    }
+    text
+}
+
+fn map_values(text: &str, prefix: &str, end: char, replace: &impl Fn(&str) -> String) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(position) = rest.find(prefix) {
+        let start = position + prefix.len();
+        out.push_str(&rest[..start]);
+
+        let mut escaped = false;
+        let value_len = rest[start..]
+            .char_indices()
+            .find(|(_, c)| {
+                let found = !escaped && *c == end;
+                escaped = !escaped && *c == '\\';
+                found
+            })
+            .map_or(rest.len() - start, |(index, _)| index);
+        out.push_str(&replace(&rest[start..start + value_len]));
+        rest = &rest[start + value_len..];
+    }
+    out.push_str(rest);
+    out
+}
+
+fn method_name(method: Method) -> &'static str {
+    match method {
+        Method::Post => "POST",
+        Method::Get => "GET",
+        Method::Put => "PUT",
+        Method::Delete => "DELETE",
+    }
+}
+
+/// The JSON text of a response, rebuilt from the parsed tokens.
+struct RawJson(String);
+
+impl RawJson {
+    fn write_value(s: &mut DeJsonState, i: &mut Chars, out: &mut String) -> Result<(), DeJsonErr> {
+        match s.tok {
+            DeJsonTok::CurlyOpen => {
+                out.push('{');
+                s.next_tok(i)?;
+                while s.tok != DeJsonTok::CurlyClose {
+                    if !out.ends_with('{') {
+                        out.push(',');
+                    }
+                    out.push_str(&s.as_string()?.serialize_json());
+                    out.push(':');
+                    s.next_colon(i)?;
+                    RawJson::write_value(s, i, out)?;
+                    s.eat_comma_curly(i)?;
+                }
+                out.push('}');
+            }
+            DeJsonTok::BlockOpen => {
+                out.push('[');
+                s.next_tok(i)?;
+                while s.tok != DeJsonTok::BlockClose {
+                    if !out.ends_with('[') {
+                        out.push(',');
+                    }
+                    RawJson::write_value(s, i, out)?;
+                    s.eat_comma_block(i)?;
+                }
+                out.push(']');
+            }
+            DeJsonTok::Str => out.push_str(&s.as_string()?.serialize_json()),
+            DeJsonTok::U64(value) => out.push_str(&value.to_string()),
+            DeJsonTok::I64(value) => out.push_str(&value.to_string()),
+            DeJsonTok::F64(value) => out.push_str(&value.to_string()),
+            DeJsonTok::Bool(value) => out.push_str(&value.to_string()),
+            DeJsonTok::Null => out.push_str("null"),
+            _ => return Err(s.err_token("JSON value")),
+        }
+        s.next_tok(i)
+    }
+}
+
+impl DeJson for RawJson {
+    fn de_json(s: &mut DeJsonState, i: &mut Chars) -> Result<Self, DeJsonErr> {
+        let mut out = String::new();
+        RawJson::write_value(s, i, &mut out)?;
+        Ok(RawJson(out))
+    }
+}
+
+struct RecorderState {
+    file: File,
+    started: Instant,
+    sockets: u32,
+}
+
+/// Writes `CassetteEntry`s to a JSONL file. Shared by the recording adapters of a session.
+///
+/// Tokens are redacted before they are written, only the claims of JWTs are kept.
+#[derive(Clone)]
+pub struct CassetteRecorder {
+    state: Arc<Mutex<RecorderState>>,
+}
+
+impl CassetteRecorder {
+    /// Create the cassette file at `path`, truncating an existing file.
+    pub fn create<P: AsRef<Path>>(path: P) -> io::Result<CassetteRecorder> {
+        Ok(CassetteRecorder {
+            state: Arc::new(Mutex::new(RecorderState {
+                file: File::create(path)?,
+                started: Instant::now(),
+                sockets: 0,
+            })),
+        })
+    }
+
+    fn next_socket(&self) -> u32 {
+        let mut state = self.state.lock().expect("panic inside other mutex!");
+        state.sockets += 1;
+        state.sockets
+    }
+
+    fn record(&self, mut entry: CassetteEntry) {
+        let mut state = self.state.lock().expect("panic inside other mutex!");
+        entry.time = state.started.elapsed().as_millis() as u64;
+        entry.query_params = redact(&entry.query_params);
+        entry.body = redact(&entry.body);
+        entry.data = redact(&entry.data);
+        let line = format!("{}\n", entry.serialize_json());
+        if let Err(err) = state.file.write_all(line.as_bytes()) {
+            error!("CassetteRecorder::record: {}", err);
+        }
+    }
+
+    fn record_socket(&self, socket: u32, kind: &str, data: &str) {
+        self.record(CassetteEntry {
+            kind: kind.to_owned(),
+            socket,
+            data: data.to_owned(),
+            ..Default::default()
+        });
+    }
+}
+
+struct CassetteState {
+    entries: Vec<CassetteEntry>,
+    sockets: u32,
+}
+
+/// The entries of a recorded cassette, shared by the replay adapters of a session.
+#[derive(Clone)]
+pub struct Cassette {
+    state: Arc<Mutex<CassetteState>>,
+}
+
+impl Cassette {
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Cassette> {
+        let jsonl = std::fs::read_to_string(path)?;
+        Cassette::from_jsonl(&jsonl).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    pub fn from_jsonl(jsonl: &str) -> Result<Cassette, DeJsonErr> {
+        let entries = jsonl
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(CassetteEntry::deserialize_json)
+            .collect::<Result<_, _>>()?;
+        Ok(Cassette {
+            state: Arc::new(Mutex::new(CassetteState {
+                entries,
+                sockets: 0,
+            })),
+        })
+    }
+
+    pub fn entries(&self) -> Vec<CassetteEntry> {
+        let state = self.state.lock().expect("panic inside other mutex!");
+        state.entries.clone()
+    }
+
+    fn next_socket(&self) -> u32 {
+        let mut state = self.state.lock().expect("panic inside other mutex!");
+        state.sockets += 1;
+        state.sockets
+    }
+}
+
+/// A `ClientAdapter` recording every request and response of the wrapped adapter to a cassette.
+///
+/// See `ReplayClientAdapter` to serve the recorded responses.
+pub struct RecordingClientAdapter<A> {
+    inner: A,
+    recorder: CassetteRecorder,
+}
+
+impl<A> RecordingClientAdapter<A> {
+    pub fn new(inner: A, recorder: &CassetteRecorder) -> RecordingClientAdapter<A> {
+        RecordingClientAdapter {
+            inner,
+            recorder: recorder.clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl<A: ClientAdapter<Error = RestHttpError> + Send + Sync> ClientAdapter
+    for RecordingClientAdapter<A>
+{
+    type Error = RestHttpError;
+
+    async fn send<T: DeJson + Send>(&self, request: RestRequest<T>) -> Result<T, Self::Error> {
+        let mut entry = CassetteEntry {
+            kind: "request".to_owned(),
+            method: method_name(request.method).to_owned(),
+            urlpath: request.urlpath.clone(),
+            query_params: request.query_params.clone(),
+            body: request.body.clone(),
+            ..Default::default()
+        };
+
+        let (status, response) = match self.inner.send(request.with_response::<RawJson>()).await {
+            Ok(RawJson(response)) => (200, response),
+            Err(RestHttpError::ClientError(status, response))
+            | Err(RestHttpError::ServerError(status, response)) => (status, response),
+            // Errors without a response are not recorded
+            Err(err) => return Err(err),
+        };
+        entry.status = status;
+        entry.data = response.clone();
+        self.recorder.record(entry);
+
+        parse_response(status, response)
+    }
+}
+
+/// A `ClientAdapter` answering requests with the responses recorded in a cassette.
+///
+/// A request is answered by the first recorded request with the same method, path, query
+/// parameters and body that wasn't used yet, ignoring volatile fields like tokens and
+/// timestamps. Requests without a recorded response fail with `RestHttpError::OtherError`.
+pub struct ReplayClientAdapter {
+    requests: Mutex<Vec<Option<CassetteEntry>>>,
+}
+
+impl ReplayClientAdapter {
+    pub fn new(cassette: &Cassette) -> ReplayClientAdapter {
+        let requests = cassette
+            .entries()
+            .into_iter()
+            .filter(|entry| entry.kind == "request")
+            .map(Some)
+            .collect();
+        ReplayClientAdapter {
+            requests: Mutex::new(requests),
+        }
+    }
+
+    fn take_response<T>(&self, request: &RestRequest<T>) -> Option<(u16, String)> {
+        let method = method_name(request.method);
+        let query_params = normalize(&request.query_params);
+        let body = normalize(&request.body);
+
+        let mut requests = self.requests.lock().expect("panic inside other mutex!");
+        let entry = requests.iter_mut().find(|entry| {
+            entry.as_ref().is_some_and(|entry| {
+                entry.method == method
+                    && entry.urlpath == request.urlpath
+                    && normalize(&entry.query_params) == query_params
+                    && normalize(&entry.body) == body
+            })
+        })?;
+        entry.take().map(|entry| (entry.status, entry.data))
+    }
+}
+
+#[async_trait]
+impl ClientAdapter for ReplayClientAdapter {
+    type Error = RestHttpError;
+
+    async fn send<T: DeJson + Send>(&self, request: RestRequest<T>) -> Result<T, Self::Error> {
+        match self.take_response(&request) {
+            Some((status, response)) => parse_response(status, response),
+            None => Err(RestHttpError::OtherError(format!(
+                "No recorded response for {} {}?{}",
+                method_name(request.method),
+                request.urlpath,
+                request.query_params
+            ))),
+        }
+    }
+}
+
+/// A `SocketAdapter` recording every frame and event of the wrapped adapter to a cassette.
+///
+/// See `ReplaySocketAdapter` to replay the recorded frames.
+pub struct RecordingSocketAdapter<A> {
+    inner: A,
+    recorder: CassetteRecorder,
+    socket: u32,
+}
+
+impl<A> RecordingSocketAdapter<A> {
+    pub fn new(inner: A, recorder: &CassetteRecorder) -> RecordingSocketAdapter<A> {
+        RecordingSocketAdapter {
+            inner,
+            recorder: recorder.clone(),
+            socket: recorder.next_socket(),
+        }
+    }
+}
+
+impl<A: SocketAdapter> SocketAdapter for RecordingSocketAdapter<A> {
+    type Error = A::Error;
+
+    fn on_connected<T>(&mut self, callback: T)
+    where
+        T: Fn() + Send + 'static,
+    {
+        let (recorder, socket) = (self.recorder.clone(), self.socket);
+        self.inner.on_connected(move || {
+            recorder.record_socket(socket, "connected", "");
+            callback();
+        });
+    }
+
+    fn on_closed<T>(&mut self, callback: T)
+    where
+        T: Fn() + Send + 'static,
+    {
+        let (recorder, socket) = (self.recorder.clone(), self.socket);
+        self.inner.on_closed(move || {
+            recorder.record_socket(socket, "closed", "");
+            callback();
+        });
+    }
+
+    fn on_received<T>(&mut self, callback: T)
+    where
+        T: Fn(Result<String, Self::Error>) + Send + 'static,
+    {
+        let (recorder, socket) = (self.recorder.clone(), self.socket);
+        self.inner.on_received(move |message| {
+            match &message {
+                Ok(data) => recorder.record_socket(socket, "receive", data),
+                Err(err) => recorder.record_socket(socket, "error", &err.to_string()),
+            }
+            callback(message);
+        });
+    }
+
+    fn set_wakeup(&mut self, wakeup: Wakeup) {
+        self.inner.set_wakeup(wakeup);
+    }
+
+    fn is_connected(&self) -> bool {
+        self.inner.is_connected()
+    }
+
+    fn is_connecting(&self) -> bool {
+        self.inner.is_connecting()
+    }
+
+    fn close(&mut self) {
+        self.recorder.record_socket(self.socket, "close", "");
+        self.inner.close();
+    }
+
+    fn connect(&mut self, addr: &str, timeout: i32) {
+        self.recorder.record_socket(self.socket, "connect", addr);
+        self.inner.connect(addr, timeout);
+    }
+
+    fn send(&self, data: &str, reliable: bool) -> Result<(), Self::Error> {
+        self.recorder.record_socket(self.socket, "send", data);
+        self.inner.send(data, reliable)
+    }
+
+    fn tick(&self) {
+        self.inner.tick();
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum ReplaySocketError {
+    NotConnected,
+    /// The socket sent something else than the next recorded frame.
+    Mismatch {
+        expected: String,
+        actual: String,
+    },
+    /// An error that was recorded.
+    Recorded(String),
+}
+
+impl Display for ReplaySocketError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Debug::fmt(self, f)
+    }
+}
+
+impl Error for ReplaySocketError {}
+
+type ConnectedCallback = Box<dyn Fn() + Send + 'static>;
+type ClosedCallback = Box<dyn Fn() + Send + 'static>;
+type ReceivedCallback = Box<dyn Fn(Result<String, ReplaySocketError>) + Send + 'static>;
+
+#[derive(Default)]
+struct ReplayState {
+    entries: VecDeque<CassetteEntry>,
+    released: VecDeque<CassetteEntry>,
+    connecting: bool,
+    connected: bool,
+    wakeup: Option<Wakeup>,
+}
+
+impl ReplayState {
+    // Consume the next entry if it is of `kind` and `data` matches, then release the recorded
+    // events up to the next frame sent by the socket.
+    fn advance(&mut self, kind: &str, data: &str) -> Result<(), ReplaySocketError> {
+        match self.entries.front() {
+            Some(entry) if entry.kind == kind && normalize(&entry.data) == normalize(data) => {
+                self.entries.pop_front();
+            }
+            entry => {
+                return Err(ReplaySocketError::Mismatch {
+                    expected: entry.map_or_else(String::new, |entry| {
+                        format!("{} {}", entry.kind, entry.data)
+                    }),
+                    actual: format!("{} {}", kind, data),
+                });
+            }
+        }
+
+        while let Some(entry) = self.entries.front() {
+            if !matches!(
+                entry.kind.as_str(),
+                "connected" | "receive" | "error" | "closed"
+            ) {
+                break;
+            }
+            self.released.extend(self.entries.pop_front());
+        }
+        if let Some(ref wakeup) = self.wakeup {
+            wakeup.wake();
+        }
+        Ok(())
+    }
+}
+
+/// A `SocketAdapter` replaying the frames recorded for a socket in a cassette.
+///
+/// Sockets are matched with the recorded sockets in the order they were created. The socket has
+/// to connect and send the same frames as the recorded socket, ignoring volatile fields like
+/// tokens and timestamps. After each sent frame, the events recorded before the next sent frame
+/// are delivered on the next `tick`, without waiting for the recorded timestamps.
+pub struct ReplaySocketAdapter {
+    on_connected: Option<ConnectedCallback>,
+    on_closed: Option<ClosedCallback>,
+    on_received: Option<ReceivedCallback>,
+    state: Mutex<ReplayState>,
+}
+
+impl ReplaySocketAdapter {
+    pub fn new(cassette: &Cassette) -> ReplaySocketAdapter {
+        let socket = cassette.next_socket();
+        let entries = cassette
+            .entries()
+            .into_iter()
+            .filter(|entry| entry.kind != "request" && entry.socket == socket)
+            .collect();
+        ReplaySocketAdapter {
+            on_connected: None,
+            on_closed: None,
+            on_received: None,
+            state: Mutex::new(ReplayState {
+                entries,
+                ..Default::default()
+            }),
+        }
+    }
+
+    fn with<T>(&self, f: impl FnOnce(&mut ReplayState) -> T) -> T {
+        let mut state = self.state.lock().expect("panic inside other mutex!");
+        f(&mut state)
+    }
+}
+
+impl SocketAdapter for ReplaySocketAdapter {
+    type Error = ReplaySocketError;
+
+    fn on_connected<T>(&mut self, callback: T)
+    where
+        T: Fn() + Send + 'static,
+    {
+        self.on_connected = Some(Box::new(callback));
+    }
+
+    fn on_closed<T>(&mut self, callback: T)
+    where
+        T: Fn() + Send + 'static,
+    {
+        self.on_closed = Some(Box::new(callback));
+    }
+
+    fn on_received<T>(&mut self, callback: T)
+    where
+        T: Fn(Result<String, ReplaySocketError>) + Send + 'static,
+    {
+        self.on_received = Some(Box::new(callback));
+    }
+
+    fn set_wakeup(&mut self, wakeup: Wakeup) {
+        self.with(|state| state.wakeup = Some(wakeup));
+    }
+
+    fn is_connected(&self) -> bool {
+        self.with(|state| state.connected)
+    }
+
+    fn is_connecting(&self) -> bool {
+        self.with(|state| state.connecting)
+    }
+
+    fn close(&mut self) {
+        if let Err(err) = self.with(|state| state.advance("close", "")) {
+            error!("ReplaySocketAdapter::close: {}", err);
+        }
+    }
+
+    fn connect(&mut self, addr: &str, _timeout: i32) {
+        let result = self.with(|state| {
+            state.connecting = true;
+            state.advance("connect", addr)
+        });
+        if let Err(err) = result {
+            if let Some(ref cb) = self.on_received {
+                cb(Err(err));
+            }
+        }
+    }
+
+    fn send(&self, data: &str, _reliable: bool) -> Result<(), Self::Error> {
+        self.with(|state| {
+            if !state.connected {
+                return Err(ReplaySocketError::NotConnected);
+            }
+            state.advance("send", data)
+        })
+    }
+
+    fn tick(&self) {
+        let released = self.with(|state| std::mem::take(&mut state.released));
+        for entry in released {
+            match entry.kind.as_str() {
+                "connected" => {
+                    self.with(|state| {
+                        state.connecting = false;
+                        state.connected = true;
+                    });
+                    if let Some(ref cb) = self.on_connected {
+                        cb();
+                    }
+                }
+                "closed" => {
+                    self.with(|state| {
+                        state.connecting = false;
+                        state.connected = false;
+                    });
+                    if let Some(ref cb) = self.on_closed {
+                        cb();
+                    }
+                }
+                "error" => {
+                    if let Some(ref cb) = self.on_received {
+                        cb(Err(ReplaySocketError::Recorded(entry.data)));
+                    }
+                }
+                _ => {
+                    if let Some(ref cb) = self.on_received {
+                        cb(Ok(entry.data));
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::client::Client;
+    use crate::default_client::DefaultClient;
+    use crate::mock_client_adapter::MockClientAdapter;
+    use crate::mock_socket_adapter::{block_on_ticked, MockSocketAdapter};
+    use crate::session::Session;
+    use crate::socket::Socket;
+    use crate::web_socket::WebSocket;
+    use futures::executor::block_on;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(
+            normalize(r#"{"token":"a\"b","vars":{},"create_time":"2021"}"#),
+            r#"{"token":"*","vars":{},"create_time":"*"}"#
+        );
+        assert_eq!(
+            normalize("ws://127.0.0.1:7350/ws?lang=en&token=abc"),
+            "ws://127.0.0.1:7350/ws?lang=en&token=*"
+        );
+    }
+
+    #[test]
+    fn test_redact() {
+        assert_eq!(
+            redact(r#"{"token":"header.claims.signature","refresh_token":"secret","vars":{}}"#),
+            r#"{"token":"header.claims.redacted","refresh_token":"redacted","vars":{}}"#
+        );
+        assert_eq!(
+            redact("ws://127.0.0.1:7350/ws?lang=en&token=a.b.c"),
+            "ws://127.0.0.1:7350/ws?lang=en&token=a.b.redacted"
+        );
+        assert_eq!(
+            redact(r#"{"email":"a@b.c","password":"hunter2"}"#),
+            r#"{"email":"a@b.c","password":"redacted"}"#
+        );
+        assert_eq!(
+            redact("http_key=defaulthttpkey&unwrap=true"),
+            "http_key=redacted&unwrap=true"
+        );
+    }
+
+    #[test]
+    fn test_record_redacts_credentials() {
+        let path = std::env::temp_dir().join(format!(
+            "nakama-rs-cassette-secrets-{}.jsonl",
+            std::process::id()
+        ));
+        let recorder = CassetteRecorder::create(&path).unwrap();
+
+        let mock_client = MockClientAdapter::new();
+        mock_client
+            .expect_post("/v2/account/authenticate/email")
+            .respond_with(r#"{"token":"header.claims.signature"}"#);
+        mock_client
+            .expect_post("/v2/rpc/echo")
+            .respond_with(r#"{"id":"echo","payload":"{}"}"#);
+        let client = DefaultClient::new(RecordingClientAdapter::new(mock_client, &recorder));
+        block_on(client.authenticate_email(
+            "user@example.com",
+            "secretpassword",
+            None,
+            true,
+            HashMap::new(),
+        ))
+        .unwrap();
+        block_on(client.rpc_http_key("secrethttpkey", "echo", Some("{}"))).unwrap();
+
+        let jsonl = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(jsonl.contains("user@example.com"));
+        assert!(!jsonl.contains("secretpassword"));
+        assert!(!jsonl.contains("secrethttpkey"));
+        assert!(!jsonl.contains("signature"));
+
+        // The redacted requests still match when replayed
+        let cassette = Cassette::from_jsonl(&jsonl).unwrap();
+        let client = DefaultClient::new(ReplayClientAdapter::new(&cassette));
+        block_on(client.authenticate_email(
+            "user@example.com",
+            "secretpassword",
+            None,
+            true,
+            HashMap::new(),
+        ))
+        .unwrap();
+        block_on(client.rpc_http_key("secrethttpkey", "echo", Some("{}"))).unwrap();
+    }
+
+    #[test]
+    fn test_raw_json() {
+        let json = r#"{"a":[1,-2,1.5,true,null,"x\"y"],"b":{}}"#;
+        let RawJson(raw) = RawJson::deserialize_json(json).unwrap();
+        assert_eq!(raw, json);
+    }
+
+    #[test]
+    fn test_record_and_replay() {
+        let path =
+            std::env::temp_dir().join(format!("nakama-rs-cassette-{}.jsonl", std::process::id()));
+        let recorder = CassetteRecorder::create(&path).unwrap();
+
+        // Record
+        let mock_client = MockClientAdapter::new();
+        mock_client
+            .expect_post("/v2/account/authenticate/device")
+            .respond_with(r#"{"token":"first-token"}"#);
+        mock_client.expect_get("/v2/account").respond_with_status(
+            401,
+            r#"{"error":"Auth token invalid","code":16,"message":"Auth token invalid"}"#,
+        );
+        let mock_socket = MockSocketAdapter::new();
+        mock_socket.handle().on_send(|_| Some("{}".to_owned()));
+
+        let client = DefaultClient::new(RecordingClientAdapter::new(mock_client, &recorder));
+        let socket = WebSocket::new(RecordingSocketAdapter::new(mock_socket, &recorder));
+        let mut session =
+            block_on(client.authenticate_device("deviceid", None, true, HashMap::new())).unwrap();
+        assert!(block_on(client.get_account(&mut session)).is_err());
+        block_on_ticked(|| socket.tick(), socket.connect(&mut session, true, -1));
+        block_on_ticked(|| socket.tick(), socket.leave_party("party")).unwrap();
+
+        // Replay
+        let jsonl = std::fs::read_to_string(&path).unwrap();
+        assert!(!jsonl.contains("first-token"));
+        let cassette = Cassette::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let kinds: Vec<String> = cassette.entries().into_iter().map(|e| e.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                "request",
+                "request",
+                "connect",
+                "connected",
+                "send",
+                "receive"
+            ]
+        );
+
+        let client = DefaultClient::new(ReplayClientAdapter::new(&cassette));
+        let socket = WebSocket::new(ReplaySocketAdapter::new(&cassette));
+        // The get_account request is matched first, the order of requests doesn't matter
+        let mut session = Session {
+            auth_token: "other-token".to_owned(),
+            refresh_token: None,
+        };
+        assert!(block_on(client.get_account(&mut session)).is_err());
+        assert!(block_on(client.get_account(&mut session)).is_err());
+        let mut session =
+            block_on(client.authenticate_device("deviceid", None, true, HashMap::new())).unwrap();
+        assert_eq!(session.auth_token, "redacted");
+
+        block_on_ticked(|| socket.tick(), socket.connect(&mut session, true, -1));
+        block_on_ticked(|| socket.tick(), socket.leave_party("party")).unwrap();
+        assert!(block_on_ticked(|| socket.tick(), socket.leave_party("other")).is_err());
+    }
+}
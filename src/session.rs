@@ -1,5 +1,38 @@
+use nanoserde::DeJson;
+
 #[derive(Debug)]
 pub struct Session {
     pub auth_token: String,
     pub refresh_token: Option<String>,
 }
+
+#[derive(DeJson, Default)]
+#[nserde(default)]
+struct TokenClaims {
+    uid: String,
+    usn: String,
+}
+
+impl Session {
+    // The claims of the auth token, a JWT
+    fn claims(&self) -> Option<TokenClaims> {
+        let payload = self.auth_token.split('.').nth(1)?;
+        let payload = base64::decode_config(payload, base64::URL_SAFE_NO_PAD).ok()?;
+        let payload = String::from_utf8(payload).ok()?;
+        TokenClaims::deserialize_json(&payload).ok()
+    }
+
+    /// The id of the authenticated user, read from the auth token.
+    pub fn user_id(&self) -> Option<String> {
+        self.claims()
+            .map(|claims| claims.uid)
+            .filter(|uid| !uid.is_empty())
+    }
+
+    /// The username of the authenticated user, read from the auth token.
+    pub fn username(&self) -> Option<String> {
+        self.claims()
+            .map(|claims| claims.usn)
+            .filter(|usn| !usn.is_empty())
+    }
+}
@@ -0,0 +1,554 @@
+use crate::socket_adapter::{SocketAdapter, Wakeup};
+use log::error;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// The simulated network conditions, applied in both directions.
+///
+/// Defaults to a perfect network.
+#[derive(Debug, Clone, Default)]
+pub struct NetworkConditions {
+    /// The one-way delay of every message.
+    pub latency: Duration,
+    /// A random delay between zero and `jitter` added to every message.
+    pub jitter: Duration,
+    /// The chance that an unreliable message is delayed by another `latency` and `jitter`,
+    /// arriving after messages sent later.
+    pub reorder_chance: f64,
+    /// The chance that an unreliable outgoing message is dropped.
+    pub loss_chance: f64,
+    /// The bandwidth in bytes per second. Messages queue up when it is exceeded.
+    pub bandwidth: Option<u32>,
+    /// The chance that a message causes the connection to be closed.
+    pub disconnect_chance: f64,
+}
+
+/// A manually advanced clock, to make `SimulatedNetworkAdapter` independent of real time.
+#[derive(Clone, Default)]
+pub struct SimulatedClock {
+    now: Arc<Mutex<Duration>>,
+}
+
+impl SimulatedClock {
+    pub fn new() -> SimulatedClock {
+        SimulatedClock::default()
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        *self.now.lock().expect("panic inside other mutex!") += duration;
+    }
+
+    pub fn now(&self) -> Duration {
+        *self.now.lock().expect("panic inside other mutex!")
+    }
+}
+
+enum Clock {
+    Real(Instant),
+    Simulated(SimulatedClock),
+}
+
+impl Clock {
+    fn now(&self) -> Duration {
+        match self {
+            Clock::Real(started) => started.elapsed(),
+            Clock::Simulated(clock) => clock.now(),
+        }
+    }
+}
+
+// SplitMix64, good enough to simulate a network and reproducible from a seed
+struct Rng(u64);
+
+impl Rng {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `[0, 1)`.
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn chance(&mut self, chance: f64) -> bool {
+        chance > 0.0 && self.next_f64() < chance
+    }
+}
+
+const OUTGOING: usize = 0;
+const INCOMING: usize = 1;
+
+enum Incoming<E> {
+    Connected,
+    Closed,
+    Received(Result<String, E>),
+}
+
+struct Delayed<T> {
+    at: Duration,
+    sequence: u64,
+    item: T,
+}
+
+struct NetworkState<E> {
+    conditions: NetworkConditions,
+    clock: Clock,
+    rng: Rng,
+    sequence: u64,
+    // When the link of each direction is free to transmit the next message
+    link_free: [Duration; 2],
+    // The latest delivery time of ordered messages in each direction
+    last_ordered: [Duration; 2],
+    outgoing: Vec<Delayed<(String, bool)>>,
+    incoming: Vec<Delayed<Incoming<E>>>,
+    disconnecting: bool,
+    disconnected: bool,
+}
+
+impl<E> NetworkState<E> {
+    fn delivery_time(&mut self, direction: usize, len: usize, ordered: bool) -> Duration {
+        let now = self.clock.now();
+        let start = now.max(self.link_free[direction]);
+        let transmit = self
+            .conditions
+            .bandwidth
+            .map_or(Duration::ZERO, |bandwidth| {
+                Duration::from_secs_f64(len as f64 / bandwidth.max(1) as f64)
+            });
+        self.link_free[direction] = start + transmit;
+
+        let mut at = self.link_free[direction] + self.delay();
+        if ordered {
+            at = at.max(self.last_ordered[direction]);
+            self.last_ordered[direction] = at;
+        } else if self.rng.chance(self.conditions.reorder_chance) {
+            at += self.delay();
+        }
+        at
+    }
+
+    fn delay(&mut self) -> Duration {
+        let jitter = self.conditions.jitter.mul_f64(self.rng.next_f64());
+        self.conditions.latency + jitter
+    }
+
+    fn next_sequence(&mut self) -> u64 {
+        self.sequence += 1;
+        self.sequence
+    }
+
+    fn push_incoming(&mut self, incoming: Incoming<E>) {
+        if self.disconnected {
+            return;
+        }
+        let len = match incoming {
+            Incoming::Received(Ok(ref data)) => data.len(),
+            _ => 0,
+        };
+        if len > 0 && self.rng.chance(self.conditions.disconnect_chance) {
+            self.disconnecting = true;
+            return;
+        }
+        let at = self.delivery_time(INCOMING, len, true);
+        let sequence = self.next_sequence();
+        self.incoming.push(Delayed {
+            at,
+            sequence,
+            item: incoming,
+        });
+    }
+}
+
+// Remove the items that are due, in order of delivery.
+fn take_due<T>(queue: &mut Vec<Delayed<T>>, now: Duration) -> Vec<T> {
+    queue.sort_by_key(|delayed| (delayed.at, delayed.sequence));
+    let due = queue.iter().take_while(|delayed| delayed.at <= now).count();
+    queue.drain(..due).map(|delayed| delayed.item).collect()
+}
+
+type ConnectedCallback = Box<dyn Fn() + Send + 'static>;
+type ClosedCallback = Box<dyn Fn() + Send + 'static>;
+type ReceivedCallback<E> = Box<dyn Fn(Result<String, E>) + Send + 'static>;
+
+/// A `SocketAdapter` simulating network conditions around another adapter.
+///
+/// Outgoing messages and incoming events are delayed by `NetworkConditions::latency`, `jitter`
+/// and the `bandwidth`, and are only passed on by `tick`. Unreliable outgoing messages can be
+/// dropped and reordered, while reliable messages and incoming messages keep their order, like
+/// they would on a WebSocket. Random events are drawn from a RNG seeded with `seed`, so a run can
+/// be reproduced.
+///
+/// With a `MockSocketAdapter` and a `SimulatedClock`, simulations are fully deterministic.
+///
+/// ```
+/// # use nakama_rs::mock_socket_adapter::MockSocketAdapter;
+/// # use nakama_rs::simulated_network_adapter::{NetworkConditions, SimulatedNetworkAdapter};
+/// # use nakama_rs::web_socket::WebSocket;
+/// # use std::time::Duration;
+/// let conditions = NetworkConditions {
+///     latency: Duration::from_millis(100),
+///     jitter: Duration::from_millis(20),
+///     loss_chance: 0.05,
+///     ..Default::default()
+/// };
+/// let adapter = SimulatedNetworkAdapter::new(MockSocketAdapter::new(), conditions, 42);
+/// let socket = WebSocket::new(adapter);
+/// ```
+pub struct SimulatedNetworkAdapter<A: SocketAdapter> {
+    inner: Mutex<A>,
+    state: Arc<Mutex<NetworkState<A::Error>>>,
+    on_connected: Option<ConnectedCallback>,
+    on_closed: Option<ClosedCallback>,
+    on_received: Option<ReceivedCallback<A::Error>>,
+}
+
+impl<A> SimulatedNetworkAdapter<A>
+where
+    A: SocketAdapter,
+    A::Error: Send + 'static,
+{
+    pub fn new(mut inner: A, conditions: NetworkConditions, seed: u64) -> Self {
+        let state = Arc::new(Mutex::new(NetworkState {
+            conditions,
+            clock: Clock::Real(Instant::now()),
+            rng: Rng(seed),
+            sequence: 0,
+            link_free: [Duration::ZERO; 2],
+            last_ordered: [Duration::ZERO; 2],
+            outgoing: Vec::new(),
+            incoming: Vec::new(),
+            disconnecting: false,
+            disconnected: false,
+        }));
+
+        inner.on_connected({
+            let state = state.clone();
+            move || {
+                let mut state = state.lock().expect("panic inside other mutex!");
+                state.push_incoming(Incoming::Connected);
+            }
+        });
+        inner.on_closed({
+            let state = state.clone();
+            move || {
+                let mut state = state.lock().expect("panic inside other mutex!");
+                state.push_incoming(Incoming::Closed);
+            }
+        });
+        inner.on_received({
+            let state = state.clone();
+            move |message| {
+                let mut state = state.lock().expect("panic inside other mutex!");
+                state.push_incoming(Incoming::Received(message));
+            }
+        });
+
+        SimulatedNetworkAdapter {
+            inner: Mutex::new(inner),
+            state,
+            on_connected: None,
+            on_closed: None,
+            on_received: None,
+        }
+    }
+}
+
+impl<A: SocketAdapter> SimulatedNetworkAdapter<A> {
+    fn with<T>(&self, f: impl FnOnce(&mut NetworkState<A::Error>) -> T) -> T {
+        let mut state = self.state.lock().expect("panic inside other mutex!");
+        f(&mut state)
+    }
+
+    fn with_inner<T>(&self, f: impl FnOnce(&mut A) -> T) -> T {
+        let mut inner = self.inner.lock().expect("panic inside other mutex!");
+        f(&mut inner)
+    }
+
+    /// Change the network conditions. Messages already in flight keep their delivery time.
+    pub fn set_conditions(&self, conditions: NetworkConditions) {
+        self.with(|state| state.conditions = conditions);
+    }
+
+    /// Use `clock` instead of the real time.
+    pub fn set_clock(&mut self, clock: SimulatedClock) {
+        self.with(|state| state.clock = Clock::Simulated(clock));
+    }
+}
+
+impl<A: SocketAdapter> SocketAdapter for SimulatedNetworkAdapter<A> {
+    type Error = A::Error;
+
+    fn on_connected<T>(&mut self, callback: T)
+    where
+        T: Fn() + Send + 'static,
+    {
+        self.on_connected = Some(Box::new(callback));
+    }
+
+    fn on_closed<T>(&mut self, callback: T)
+    where
+        T: Fn() + Send + 'static,
+    {
+        self.on_closed = Some(Box::new(callback));
+    }
+
+    fn on_received<T>(&mut self, callback: T)
+    where
+        T: Fn(Result<String, Self::Error>) + Send + 'static,
+    {
+        self.on_received = Some(Box::new(callback));
+    }
+
+    fn set_wakeup(&mut self, wakeup: Wakeup) {
+        self.with_inner(|inner| inner.set_wakeup(wakeup));
+    }
+
+    fn is_connected(&self) -> bool {
+        !self.with(|state| state.disconnected) && self.with_inner(|inner| inner.is_connected())
+    }
+
+    fn is_connecting(&self) -> bool {
+        self.with_inner(|inner| inner.is_connecting())
+    }
+
+    fn close(&mut self) {
+        self.with_inner(|inner| inner.close());
+    }
+
+    fn connect(&mut self, addr: &str, timeout: i32) {
+        self.with(|state| {
+            state.disconnecting = false;
+            state.disconnected = false;
+            state.outgoing.clear();
+            state.incoming.clear();
+        });
+        self.with_inner(|inner| inner.connect(addr, timeout));
+    }
+
+    fn send(&self, data: &str, reliable: bool) -> Result<(), Self::Error> {
+        if !self.is_connected() {
+            // Let the inner adapter report the error
+            return self.with_inner(|inner| inner.send(data, reliable));
+        }
+        self.with(|state| {
+            if state.rng.chance(state.conditions.disconnect_chance) {
+                state.disconnecting = true;
+                return;
+            }
+            if !reliable && state.rng.chance(state.conditions.loss_chance) {
+                return;
+            }
+            let at = state.delivery_time(OUTGOING, data.len(), reliable);
+            let sequence = state.next_sequence();
+            state.outgoing.push(Delayed {
+                at,
+                sequence,
+                item: (data.to_owned(), reliable),
+            });
+        });
+        Ok(())
+    }
+
+    fn tick(&self) {
+        self.with_inner(|inner| inner.tick());
+
+        let (outgoing, incoming, disconnect) = self.with(|state| {
+            let now = state.clock.now();
+            let outgoing = take_due(&mut state.outgoing, now);
+            let incoming = take_due(&mut state.incoming, now);
+            let disconnect = std::mem::replace(&mut state.disconnecting, false);
+            if disconnect {
+                state.disconnected = true;
+                state.outgoing.clear();
+                state.incoming.clear();
+            }
+            (outgoing, incoming, disconnect)
+        });
+
+        for (data, reliable) in outgoing {
+            if let Err(err) = self.with_inner(|inner| inner.send(&data, reliable)) {
+                error!("SimulatedNetworkAdapter::tick: {}", err);
+            }
+        }
+        for incoming in incoming {
+            match incoming {
+                Incoming::Connected => {
+                    if let Some(ref cb) = self.on_connected {
+                        cb();
+                    }
+                }
+                Incoming::Closed => {
+                    if let Some(ref cb) = self.on_closed {
+                        cb();
+                    }
+                }
+                Incoming::Received(message) => {
+                    if let Some(ref cb) = self.on_received {
+                        cb(message);
+                    }
+                }
+            }
+        }
+        if disconnect {
+            // Events of the inner adapter are ignored until the next `connect`
+            self.with_inner(|inner| inner.close());
+            if let Some(ref cb) = self.on_closed {
+                cb();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mock_socket_adapter::{MockSocketAdapter, MockSocketHandle};
+    use std::sync::mpsc;
+
+    struct Simulation {
+        adapter: SimulatedNetworkAdapter<MockSocketAdapter>,
+        handle: MockSocketHandle,
+        clock: SimulatedClock,
+        received: mpsc::Receiver<String>,
+        closed: mpsc::Receiver<()>,
+    }
+
+    impl Simulation {
+        fn new(conditions: NetworkConditions) -> Simulation {
+            let mock = MockSocketAdapter::new();
+            let handle = mock.handle();
+            let clock = SimulatedClock::new();
+            let mut adapter = SimulatedNetworkAdapter::new(mock, conditions, 7);
+            adapter.set_clock(clock.clone());
+
+            let (tx, received) = mpsc::channel();
+            adapter.on_received(move |message| tx.send(message.unwrap()).unwrap());
+            let (tx, closed) = mpsc::channel();
+            adapter.on_closed(move || tx.send(()).unwrap());
+
+            adapter.connect("ws://127.0.0.1", -1);
+            adapter.tick();
+            assert!(adapter.is_connected());
+            Simulation {
+                adapter,
+                handle,
+                clock,
+                received,
+                closed,
+            }
+        }
+
+        fn advance(&self, millis: u64) {
+            self.clock.advance(Duration::from_millis(millis));
+            self.adapter.tick();
+        }
+    }
+
+    #[test]
+    fn test_latency() {
+        let simulation = Simulation::new(NetworkConditions {
+            latency: Duration::from_millis(100),
+            ..Default::default()
+        });
+
+        simulation.adapter.send("out", true).unwrap();
+        simulation.handle.receive("in");
+        simulation.advance(50);
+        assert!(simulation.handle.take_sent().is_empty());
+        // The message was received by the inner adapter at 50ms
+        simulation.advance(50);
+        assert_eq!(simulation.handle.take_sent(), ["out"]);
+        assert!(simulation.received.try_recv().is_err());
+        simulation.advance(50);
+        assert_eq!(simulation.received.try_recv().unwrap(), "in");
+    }
+
+    #[test]
+    fn test_loss_and_order() {
+        let simulation = Simulation::new(NetworkConditions {
+            latency: Duration::from_millis(10),
+            jitter: Duration::from_millis(50),
+            reorder_chance: 0.5,
+            loss_chance: 0.5,
+            ..Default::default()
+        });
+
+        for i in 0..100 {
+            simulation.adapter.send(&i.to_string(), true).unwrap();
+            simulation.adapter.send(&format!("u{}", i), false).unwrap();
+            simulation.advance(1);
+        }
+        simulation.advance(1000);
+        let sent = simulation.handle.take_sent();
+        let reliable: Vec<String> = sent
+            .iter()
+            .filter(|data| !data.starts_with('u'))
+            .cloned()
+            .collect();
+        let expected: Vec<String> = (0..100).map(|i| i.to_string()).collect();
+        assert_eq!(reliable, expected);
+
+        let unreliable: Vec<u32> = sent
+            .iter()
+            .filter_map(|data| data.strip_prefix('u'))
+            .map(|data| data.parse().unwrap())
+            .collect();
+        assert!(unreliable.len() > 20 && unreliable.len() < 80);
+        assert!(unreliable.windows(2).any(|pair| pair[0] > pair[1]));
+    }
+
+    #[test]
+    fn test_bandwidth() {
+        let simulation = Simulation::new(NetworkConditions {
+            bandwidth: Some(1000),
+            ..Default::default()
+        });
+
+        let data = "x".repeat(100);
+        for _ in 0..3 {
+            simulation.adapter.send(&data, true).unwrap();
+        }
+        simulation.advance(100);
+        assert_eq!(simulation.handle.take_sent().len(), 1);
+        simulation.advance(200);
+        assert_eq!(simulation.handle.take_sent().len(), 2);
+    }
+
+    #[test]
+    fn test_disconnect() {
+        let simulation = Simulation::new(NetworkConditions {
+            disconnect_chance: 1.0,
+            ..Default::default()
+        });
+
+        simulation.adapter.send("out", true).unwrap();
+        simulation.advance(0);
+        assert!(simulation.closed.try_recv().is_ok());
+        assert!(!simulation.adapter.is_connected());
+        assert!(simulation.handle.take_sent().is_empty());
+        // The close of the inner adapter is not reported again
+        simulation.advance(0);
+        assert!(simulation.closed.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_seed_is_reproducible() {
+        let run = || {
+            let simulation = Simulation::new(NetworkConditions {
+                jitter: Duration::from_millis(100),
+                loss_chance: 0.3,
+                ..Default::default()
+            });
+            for i in 0..50 {
+                simulation.adapter.send(&i.to_string(), false).unwrap();
+            }
+            simulation.advance(200);
+            simulation.handle.take_sent()
+        };
+        assert_eq!(run(), run());
+    }
+}
@@ -1,55 +1,116 @@
 use crate::api::{ApiChannelMessage, ApiNotification, ApiNotificationList, ApiRpc};
+use crate::chat;
 use crate::session::Session;
 use async_trait::async_trait;
-use nanoserde::{DeJson, DeJsonErr, DeJsonState, SerJson};
+use nanoserde::{DeJson, DeJsonErr, DeJsonState, SerJson, SerJsonState};
 use std::collections::HashMap;
+use std::convert::TryFrom;
 use std::error;
 use std::str::Chars;
 
-#[derive(DeJson, SerJson, Debug, Clone, Default)]
-#[nserde(transparent)]
-pub struct Timestamp(String);
+pub use crate::timestamp::Timestamp;
 
 #[derive(DeJson, SerJson, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Channel {
     pub id: String,
     #[nserde(default)]
+    #[cfg_attr(feature = "serde", serde(default))]
     pub presences: Vec<UserPresence>,
     #[nserde(rename = "self")]
+    #[cfg_attr(feature = "serde", serde(rename = "self"))]
     pub _self: UserPresence,
     #[nserde(default)]
+    #[cfg_attr(feature = "serde", serde(default))]
     pub room_name: String,
     #[nserde(default)]
+    #[cfg_attr(feature = "serde", serde(default))]
     pub group_id: String,
     #[nserde(default)]
+    #[cfg_attr(feature = "serde", serde(default))]
     pub user_id_one: String,
     #[nserde(default)]
+    #[cfg_attr(feature = "serde", serde(default))]
     pub user_id_two: String,
 }
 
+/// The kind of chat channel to join, which decides the meaning of the target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[repr(i32)]
 pub enum ChannelJoinType {
+    #[default]
     Unspecified = 0,
+    /// A chat room, the target is the name of the room.
     Room = 1,
+    /// A direct message, the target is the ID of the other user.
     DirectMessage = 2,
+    /// The chat of a group, the target is the ID of the group.
     Group = 3,
 }
 
+impl ChannelJoinType {
+    pub fn from_i32(channel_type: i32) -> Option<ChannelJoinType> {
+        match channel_type {
+            0 => Some(ChannelJoinType::Unspecified),
+            1 => Some(ChannelJoinType::Room),
+            2 => Some(ChannelJoinType::DirectMessage),
+            3 => Some(ChannelJoinType::Group),
+            _ => None,
+        }
+    }
+}
+
+impl SerJson for ChannelJoinType {
+    fn ser_json(&self, d: usize, s: &mut SerJsonState) {
+        (*self as i32).ser_json(d, s);
+    }
+}
+
+impl DeJson for ChannelJoinType {
+    fn de_json(state: &mut DeJsonState, input: &mut Chars) -> Result<Self, DeJsonErr> {
+        let value = state.i64_range(i32::MIN as i64, i32::MAX as i64)?;
+        let value = ChannelJoinType::from_i32(value as i32)
+            .ok_or_else(|| state.err_parse("ChannelJoinType"))?;
+        state.next_tok(input)?;
+        Ok(value)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for ChannelJoinType {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i32(*self as i32)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for ChannelJoinType {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = i32::deserialize(deserializer)?;
+        ChannelJoinType::from_i32(value)
+            .ok_or_else(|| serde::de::Error::custom(format!("Invalid channel type {}", value)))
+    }
+}
+
 #[derive(DeJson, SerJson, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ChannelJoin {
     pub hidden: bool,
     pub persistence: bool,
     pub target: String,
     #[nserde(rename = "type")]
-    // TODO: Make ChannelJoinType
-    pub channel_type: i32,
+    #[cfg_attr(feature = "serde", serde(rename = "type"))]
+    pub channel_type: ChannelJoinType,
 }
 
 #[derive(DeJson, SerJson, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ChannelLeave {
     pub channel_id: String,
 }
 
 #[derive(DeJson, SerJson, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ChannelMessageAck {
     pub channel_id: String,
     pub message_id: String,
@@ -60,22 +121,28 @@ pub struct ChannelMessageAck {
     pub update_time: Timestamp,
     pub persistent: bool,
     #[nserde(default)]
+    #[cfg_attr(feature = "serde", serde(default))]
     pub room_name: String,
     #[nserde(default)]
+    #[cfg_attr(feature = "serde", serde(default))]
     pub group_id: String,
     #[nserde(default)]
+    #[cfg_attr(feature = "serde", serde(default))]
     pub user_id_one: String,
     #[nserde(default)]
+    #[cfg_attr(feature = "serde", serde(default))]
     pub user_id_two: String,
 }
 
 #[derive(DeJson, SerJson, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ChannelMessageSend {
     pub channel_id: String,
     pub content: String,
 }
 
 #[derive(DeJson, SerJson, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ChannelMesageUpdate {
     pub channel_id: String,
     pub message_id: String,
@@ -83,49 +150,115 @@ pub struct ChannelMesageUpdate {
 }
 
 #[derive(DeJson, SerJson, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ChannelMesageRemove {
     pub channel_id: String,
     pub message_id: String,
 }
 
 #[derive(DeJson, SerJson, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ChannelPresenceEvent {
     pub channel_id: String,
     #[nserde(default)]
+    #[cfg_attr(feature = "serde", serde(default))]
     pub joins: Vec<UserPresence>,
     #[nserde(default)]
+    #[cfg_attr(feature = "serde", serde(default))]
     pub leaves: Vec<UserPresence>,
     #[nserde(default)]
+    #[cfg_attr(feature = "serde", serde(default))]
     pub room_name: String,
     #[nserde(default)]
+    #[cfg_attr(feature = "serde", serde(default))]
     pub group_id: String,
     #[nserde(default)]
+    #[cfg_attr(feature = "serde", serde(default))]
     pub user_id_one: String,
     #[nserde(default)]
+    #[cfg_attr(feature = "serde", serde(default))]
     pub user_id_two: String,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(from = "i32", into = "i32")
+)]
 pub enum ErrorCode {
-    RuntimeException = 0,
-    UnrecognizedPayload = 1,
-    MissingPayload = 2,
-    BadInput = 3,
-    MatchNotFound = 4,
-    MatchJoinRejected = 5,
-    RuntimeFunctionNotFound = 6,
-    RuntimeFunctionException = 7,
+    #[default]
+    RuntimeException,
+    UnrecognizedPayload,
+    MissingPayload,
+    BadInput,
+    MatchNotFound,
+    MatchJoinRejected,
+    RuntimeFunctionNotFound,
+    RuntimeFunctionException,
+    /// A code sent by the server that this client does not know about.
+    Unknown(i32),
+}
+
+impl From<i32> for ErrorCode {
+    fn from(code: i32) -> Self {
+        match code {
+            0 => ErrorCode::RuntimeException,
+            1 => ErrorCode::UnrecognizedPayload,
+            2 => ErrorCode::MissingPayload,
+            3 => ErrorCode::BadInput,
+            4 => ErrorCode::MatchNotFound,
+            5 => ErrorCode::MatchJoinRejected,
+            6 => ErrorCode::RuntimeFunctionNotFound,
+            7 => ErrorCode::RuntimeFunctionException,
+            code => ErrorCode::Unknown(code),
+        }
+    }
+}
+
+impl From<ErrorCode> for i32 {
+    fn from(code: ErrorCode) -> Self {
+        match code {
+            ErrorCode::RuntimeException => 0,
+            ErrorCode::UnrecognizedPayload => 1,
+            ErrorCode::MissingPayload => 2,
+            ErrorCode::BadInput => 3,
+            ErrorCode::MatchNotFound => 4,
+            ErrorCode::MatchJoinRejected => 5,
+            ErrorCode::RuntimeFunctionNotFound => 6,
+            ErrorCode::RuntimeFunctionException => 7,
+            ErrorCode::Unknown(code) => code,
+        }
+    }
+}
+
+impl SerJson for ErrorCode {
+    fn ser_json(&self, d: usize, s: &mut SerJsonState) {
+        i32::from(*self).ser_json(d, s);
+    }
+}
+
+impl DeJson for ErrorCode {
+    fn de_json(state: &mut DeJsonState, input: &mut Chars) -> Result<Self, DeJsonErr> {
+        let value: i32 = DeJson::de_json(state, input)?;
+        Ok(value.into())
+    }
 }
 
 #[derive(DeJson, SerJson, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Error {
-    // TODO: Use ErrorCode
-    pub code: i32,
+    #[nserde(default)]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub code: ErrorCode,
     pub message: String,
     #[nserde(default)]
+    #[cfg_attr(feature = "serde", serde(default))]
     pub context: HashMap<String, String>,
 }
 
 #[derive(DeJson, SerJson, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Match {
     pub match_id: String,
     pub authoritative: bool,
@@ -133,13 +266,16 @@ pub struct Match {
     pub size: i32,
     pub presences: Vec<UserPresence>,
     #[nserde(rename = "self")]
+    #[cfg_attr(feature = "serde", serde(rename = "self"))]
     pub _self: UserPresence,
 }
 
 #[derive(DeJson, SerJson, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MatchCreate {}
 
 #[derive(DeJson, SerJson, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MatchData {
     pub match_id: String,
     pub presence: UserPresence,
@@ -149,6 +285,7 @@ pub struct MatchData {
 }
 
 #[derive(DeJson, SerJson, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MatchDataSend {
     pub match_id: String,
     pub op_code: i64,
@@ -158,27 +295,35 @@ pub struct MatchDataSend {
 }
 
 #[derive(DeJson, SerJson, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MatchJoin {
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub match_id: Option<String>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub token: Option<String>,
     pub metadata: HashMap<String, String>,
 }
 
 #[derive(DeJson, SerJson, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MatchLeave {
     pub match_id: String,
 }
 
 #[derive(DeJson, SerJson, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MatchPresenceEvent {
     pub match_id: String,
     #[nserde(default)]
+    #[cfg_attr(feature = "serde", serde(default))]
     pub joins: Vec<UserPresence>,
     #[nserde(default)]
+    #[cfg_attr(feature = "serde", serde(default))]
     pub leaves: Vec<UserPresence>,
 }
 
 #[derive(DeJson, SerJson, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MatchmakerAdd {
     pub min_count: i32,
     pub max_count: i32,
@@ -188,6 +333,7 @@ pub struct MatchmakerAdd {
 }
 
 #[derive(DeJson, SerJson, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MatchmakerUser {
     pub presence: UserPresence,
     pub party_id: String,
@@ -196,100 +342,122 @@ pub struct MatchmakerUser {
 }
 
 #[derive(DeJson, SerJson, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MatchmakerMatched {
     pub ticket: String,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub match_id: Option<String>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub token: Option<String>,
     pub users: Vec<MatchmakerUser>,
     #[nserde(rename = "self")]
+    #[cfg_attr(feature = "serde", serde(rename = "self"))]
     pub _self: MatchmakerUser,
 }
 
 #[derive(DeJson, SerJson, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MatchmakerRemove {
     pub ticket: String,
 }
 
 #[derive(DeJson, SerJson, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MatchmakerTicket {
     pub ticket: String,
 }
 
 #[derive(DeJson, SerJson, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Notifications {
     pub notifications: Vec<ApiNotification>,
 }
 
 #[derive(DeJson, SerJson, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Party {
     pub party_id: String,
     #[nserde(default)]
+    #[cfg_attr(feature = "serde", serde(default))]
     pub open: bool,
     pub max_size: i32,
     #[nserde(rename = "self")]
+    #[cfg_attr(feature = "serde", serde(rename = "self"))]
     pub _self: UserPresence,
     pub leader: UserPresence,
     pub presences: Vec<UserPresence>,
 }
 
 #[derive(DeJson, SerJson, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PartyCreate {
     pub open: bool,
     pub max_size: i32,
 }
 
 #[derive(DeJson, SerJson, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PartyJoin {
     pub party_id: String,
 }
 
 #[derive(DeJson, SerJson, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PartyLeave {
     pub party_id: String,
 }
 
 #[derive(DeJson, SerJson, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PartyPromote {
     pub party_id: String,
     pub presence: UserPresence,
 }
 
 #[derive(DeJson, SerJson, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PartyLeader {
     pub party_id: String,
     pub presence: UserPresence,
 }
 
 #[derive(DeJson, SerJson, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PartyAccept {
     pub party_id: String,
     pub presence: UserPresence,
 }
 
 #[derive(DeJson, SerJson, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PartyRemove {
     pub party_id: String,
     pub presence: UserPresence,
 }
 
 #[derive(DeJson, SerJson, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PartyClose {
     pub party_id: String,
 }
 
 #[derive(DeJson, SerJson, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PartyJoinRequestList {
     pub party_id: String,
 }
 
 #[derive(DeJson, SerJson, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PartyJoinRequest {
     pub party_id: String,
     #[nserde(default)]
+    #[cfg_attr(feature = "serde", serde(default))]
     pub presences: Vec<UserPresence>,
 }
 
 #[derive(DeJson, SerJson, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PartyMatchmakerAdd {
     pub party_id: String,
     pub min_count: i32,
@@ -300,18 +468,25 @@ pub struct PartyMatchmakerAdd {
 }
 
 #[derive(DeJson, SerJson, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PartyMatchmakerRemove {
     pub party_id: String,
     pub ticket: String,
 }
 
 #[derive(DeJson, SerJson, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PartyMatchmakerTicket {
     pub party_id: String,
     pub ticket: String,
 }
 
 #[derive(SerJson, Debug, Clone, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "PartyDataProxy")
+)]
 pub struct PartyData {
     pub party_id: String,
     pub presence: UserPresence,
@@ -320,6 +495,7 @@ pub struct PartyData {
 }
 
 #[derive(DeJson, SerJson, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PartyDataProxy {
     pub party_id: String,
     pub presence: UserPresence,
@@ -328,30 +504,36 @@ pub struct PartyDataProxy {
     pub data: String,
 }
 
+impl TryFrom<PartyDataProxy> for PartyData {
+    type Error = String;
+
+    fn try_from(proxy: PartyDataProxy) -> Result<Self, Self::Error> {
+        let op_code = proxy.op_code;
+        Ok(PartyData {
+            party_id: proxy.party_id,
+            presence: proxy.presence,
+            op_code: op_code
+                .parse()
+                .map_err(|_| format!("Invalid op code {:?}", op_code))?,
+            data: base64::decode(proxy.data).map_err(|err| err.to_string())?,
+        })
+    }
+}
+
 impl DeJson for PartyData {
     fn de_json(state: &mut DeJsonState, input: &mut Chars) -> Result<Self, DeJsonErr> {
         let proxy: PartyDataProxy = DeJson::de_json(state, input)?;
-        let data = base64::decode(proxy.data);
-        match data {
-            Ok(data) => Ok(PartyData {
-                party_id: proxy.party_id,
-                presence: proxy.presence,
-                op_code: proxy.op_code.parse().unwrap(),
-                data,
-            }),
-            Err(err) => {
-                Err(nanoserde::DeJsonErr {
-                    msg: err.to_string(),
-                    // TODO: Correct lines
-                    col: 0,
-                    line: 0,
-                })
-            }
-        }
+        PartyData::try_from(proxy).map_err(|msg| nanoserde::DeJsonErr {
+            msg,
+            // TODO: Correct lines
+            col: 0,
+            line: 0,
+        })
     }
 }
 
 #[derive(DeJson, SerJson, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PartyDataSend {
     pub party_id: String,
     pub op_code: i64,
@@ -359,50 +541,63 @@ pub struct PartyDataSend {
 }
 
 #[derive(DeJson, SerJson, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PartyPresenceEvent {
     pub party_id: String,
     #[nserde(default)]
+    #[cfg_attr(feature = "serde", serde(default))]
     pub joins: Vec<UserPresence>,
     #[nserde(default)]
+    #[cfg_attr(feature = "serde", serde(default))]
     pub leaves: Vec<UserPresence>,
 }
 
 #[derive(DeJson, SerJson, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Ping {}
 
 #[derive(DeJson, SerJson, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Pong {}
 
 #[derive(DeJson, SerJson, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Status {
     pub presences: Vec<UserPresence>,
 }
 
 #[derive(DeJson, SerJson, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct StatusFollow {
     pub user_ids: Vec<String>,
     pub usernames: Vec<String>,
 }
 
 #[derive(DeJson, SerJson, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct StatusPresenceEvent {
     #[nserde(default)]
+    #[cfg_attr(feature = "serde", serde(default))]
     pub joins: Vec<UserPresence>,
     #[nserde(default)]
+    #[cfg_attr(feature = "serde", serde(default))]
     pub leaves: Vec<UserPresence>,
 }
 
 #[derive(DeJson, SerJson, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct StatusUnfollow {
     pub user_ids: Vec<String>,
 }
 
 #[derive(DeJson, SerJson, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct StatusUpdate {
     pub status: String,
 }
 
 #[derive(DeJson, SerJson, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Stream {
     pub mode: i32,
     pub subject: String,
@@ -411,6 +606,7 @@ pub struct Stream {
 }
 
 #[derive(DeJson, SerJson, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct StreamData {
     pub stream: Stream,
     pub sender: UserPresence,
@@ -419,159 +615,272 @@ pub struct StreamData {
 }
 
 #[derive(DeJson, SerJson, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct StreamPresenceEvent {
     pub stream: Stream,
     #[nserde(default)]
+    #[cfg_attr(feature = "serde", serde(default))]
     pub joins: Vec<UserPresence>,
     #[nserde(default)]
+    #[cfg_attr(feature = "serde", serde(default))]
     pub leaves: Vec<UserPresence>,
 }
 
 #[derive(DeJson, SerJson, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct UserPresence {
     #[nserde(default)]
+    #[cfg_attr(feature = "serde", serde(default))]
     pub persistence: bool,
     #[nserde(default)]
+    #[cfg_attr(feature = "serde", serde(default))]
     pub session_id: String,
     #[nserde(default)]
+    #[cfg_attr(feature = "serde", serde(default))]
     pub status: String,
     #[nserde(default)]
+    #[cfg_attr(feature = "serde", serde(default))]
     pub username: String,
     #[nserde(default)]
+    #[cfg_attr(feature = "serde", serde(default))]
     pub user_id: String,
 }
 
 #[derive(DeJson, SerJson, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct WebSocketMessageEnvelopeHeader {
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub cid: Option<String>,
 }
 
 #[derive(DeJson, SerJson, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct WebSocketMessageEnvelope {
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub cid: Option<String>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub channel: Option<Channel>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub channel_join: Option<ChannelJoin>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub channel_leave: Option<ChannelLeave>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub channel_message: Option<ApiChannelMessage>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub channel_message_ack: Option<ChannelMessageAck>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub channel_message_remove: Option<ChannelMesageRemove>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub channel_message_send: Option<ChannelMessageSend>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub channel_message_update: Option<ChannelMesageUpdate>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub channel_presence_event: Option<ChannelPresenceEvent>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub error: Option<Error>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub matchmaker_add: Option<MatchmakerAdd>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub matchmaker_matched: Option<MatchmakerMatched>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub matchmaker_remove: Option<MatchmakerRemove>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub matchmaker_ticket: Option<MatchmakerTicket>,
     #[nserde(rename = "match")]
+    #[cfg_attr(feature = "serde", serde(rename = "match"))]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub new_match: Option<Match>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub match_create: Option<MatchCreate>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub match_join: Option<MatchJoin>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub match_leave: Option<MatchLeave>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub match_presence_event: Option<MatchPresenceEvent>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub match_data: Option<MatchData>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub match_data_send: Option<MatchDataSend>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub notifications: Option<ApiNotificationList>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub rpc: Option<ApiRpc>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub status: Option<Status>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub status_follow: Option<StatusFollow>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub status_presence_event: Option<StatusPresenceEvent>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub status_unfollow: Option<StatusUnfollow>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub status_update: Option<StatusUpdate>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub stream_presence_event: Option<StreamPresenceEvent>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub stream_data: Option<StreamData>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub party: Option<Party>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub party_create: Option<PartyCreate>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub party_join: Option<PartyJoin>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub party_leave: Option<PartyLeave>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub party_promote: Option<PartyPromote>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub party_leader: Option<PartyLeader>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub party_accept: Option<PartyAccept>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub party_remove: Option<PartyRemove>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub party_close: Option<PartyClose>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub party_join_request_list: Option<PartyJoinRequestList>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub party_join_request: Option<PartyJoinRequest>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub party_matchmaker_add: Option<PartyMatchmakerAdd>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub party_matchmaker_remove: Option<PartyMatchmakerRemove>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub party_matchmaker_ticket: Option<PartyMatchmakerTicket>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub party_data: Option<PartyData>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub party_data_send: Option<PartyDataSend>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub party_presence_event: Option<PartyPresenceEvent>,
 }
 
+/// Identifies a callback registered with one of the `Socket::on_*` functions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HandlerId(pub(crate) u64);
+
+/// Removes a registered callback when dropped.
+///
+/// Created with `Socket::handler_guard`.
+#[must_use = "the handler is removed when the guard is dropped"]
+pub struct HandlerGuard<S: Socket> {
+    socket: S,
+    id: Option<HandlerId>,
+}
+
+impl<S: Socket> HandlerGuard<S> {
+    pub fn new(socket: S, id: HandlerId) -> Self {
+        HandlerGuard {
+            socket,
+            id: Some(id),
+        }
+    }
+
+    pub fn id(&self) -> HandlerId {
+        self.id.expect("HandlerGuard without id")
+    }
+
+    /// Keep the handler registered after the guard is dropped.
+    pub fn forget(mut self) -> HandlerId {
+        self.id.take().expect("HandlerGuard without id")
+    }
+}
+
+impl<S: Socket> Drop for HandlerGuard<S> {
+    fn drop(&mut self) {
+        if let Some(id) = self.id.take() {
+            self.socket.remove_handler(id);
+        }
+    }
+}
+
 #[async_trait]
 pub trait Socket {
     type Error: error::Error;
 
     // It would make sense to have a future here
-    fn on_closed<T>(&mut self, callback: T)
+    fn on_closed<T>(&self, callback: T) -> HandlerId
     where
         T: Fn() + Send + 'static;
 
-    fn on_connected<T>(&mut self, callback: T)
+    fn on_connected<T>(&self, callback: T) -> HandlerId
     where
-        T: Fn() + Send + Send + 'static;
+        T: Fn() + Send + 'static;
 
-    fn on_received_channel_message<T>(&mut self, callback: T)
+    fn on_received_channel_message<T>(&self, callback: T) -> HandlerId
     where
-        T: Fn(ApiChannelMessage) + Send + Send + 'static;
+        T: Fn(ApiChannelMessage) + Send + 'static;
 
-    fn on_received_channel_presence<T>(&mut self, callback: T)
+    fn on_received_channel_presence<T>(&self, callback: T) -> HandlerId
     where
-        T: Fn(ChannelPresenceEvent) + Send + Send + 'static;
+        T: Fn(ChannelPresenceEvent) + Send + 'static;
 
-    fn on_received_error<T>(&mut self, callback: T)
+    fn on_received_error<T>(&self, callback: T) -> HandlerId
     where
-        T: Fn(Error) + Send + Send + 'static;
+        T: Fn(Error) + Send + 'static;
 
-    fn on_received_matchmaker_matched<T>(&mut self, callback: T)
+    fn on_received_matchmaker_matched<T>(&self, callback: T) -> HandlerId
     where
-        T: Fn(MatchmakerMatched) + Send + Send + 'static;
+        T: Fn(MatchmakerMatched) + Send + 'static;
 
-    fn on_received_match_state<T>(&mut self, callback: T)
+    fn on_received_match_state<T>(&self, callback: T) -> HandlerId
     where
-        T: Fn(MatchData) + Send + Send + 'static;
+        T: Fn(MatchData) + Send + 'static;
 
-    fn on_received_match_presence<T>(&mut self, callback: T)
+    fn on_received_match_presence<T>(&self, callback: T) -> HandlerId
     where
         T: Fn(MatchPresenceEvent) + Send + 'static;
 
-    fn on_received_notification<T>(&mut self, callback: T)
+    fn on_received_notification<T>(&self, callback: T) -> HandlerId
     where
         T: Fn(ApiNotification) + Send + 'static;
 
-    fn on_received_party_close<T>(&mut self, callback: T)
+    fn on_received_party_close<T>(&self, callback: T) -> HandlerId
     where
         T: Fn(PartyClose) + Send + 'static;
 
-    fn on_received_party_data<T>(&mut self, callback: T)
+    fn on_received_party_data<T>(&self, callback: T) -> HandlerId
     where
         T: Fn(PartyData) + Send + 'static;
 
-    fn on_received_party_join_request<T>(&mut self, callback: T)
+    fn on_received_party_join_request<T>(&self, callback: T) -> HandlerId
     where
         T: Fn(PartyJoinRequest) + Send + 'static;
 
-    fn on_received_party_leader<T>(&mut self, callback: T)
+    fn on_received_party_leader<T>(&self, callback: T) -> HandlerId
     where
         T: Fn(PartyLeader) + Send + 'static;
 
-    fn on_received_party_presence<T>(&mut self, callback: T)
+    fn on_received_party_presence<T>(&self, callback: T) -> HandlerId
     where
         T: Fn(PartyPresenceEvent) + Send + 'static;
 
-    fn on_received_status_presence<T>(&mut self, callback: T)
+    fn on_received_status_presence<T>(&self, callback: T) -> HandlerId
     where
         T: Fn(StatusPresenceEvent) + Send + 'static;
 
-    fn on_received_stream_presence<T>(&mut self, callback: T)
+    fn on_received_stream_presence<T>(&self, callback: T) -> HandlerId
     where
         T: Fn(StreamPresenceEvent) + Send + 'static;
 
-    fn on_received_stream_state<T>(&mut self, callback: T)
+    fn on_received_stream_state<T>(&self, callback: T) -> HandlerId
     where
         T: Fn(StreamData) + Send + 'static;
 
+    /// Remove a callback registered with one of the `on_*` functions.
+    ///
+    /// Returns `false` if the handler was already removed.
+    fn remove_handler(&self, id: HandlerId) -> bool;
+
+    /// Create a guard that removes the handler `id` when it is dropped.
+    fn handler_guard(&self, id: HandlerId) -> HandlerGuard<Self>
+    where
+        Self: Clone + Sized,
+    {
+        HandlerGuard::new(self.clone(), id)
+    }
+
     async fn accept_party_member(
         &self,
         party_id: &str,
@@ -613,14 +922,62 @@ pub trait Socket {
         usernames: &[&str],
     ) -> Result<Status, Self::Error>;
 
+    /// Join the channel `target` of type `channel_type`. See `join_room`, `join_direct` and
+    /// `join_group_chat` for a `chat::Channel` handle.
     async fn join_chat(
         &self,
-        room_name: &str,
-        channel_type: i32,
+        target: &str,
+        channel_type: ChannelJoinType,
         persistence: bool,
         hidden: bool,
     ) -> Result<Channel, Self::Error>;
 
+    /// Join the chat room `room_name`, which is created if it doesn't exist.
+    ///
+    /// Messages are stored for `chat::Channel::history` if `persistence` is set. A `hidden` user
+    /// doesn't appear in the presences of the channel.
+    async fn join_room(
+        &self,
+        room_name: &str,
+        persistence: bool,
+        hidden: bool,
+    ) -> Result<chat::Channel<Self>, Self::Error>
+    where
+        Self: Clone + Send + Sync + Sized,
+    {
+        let channel_type = ChannelJoinType::Room;
+        chat::Channel::join(self.clone(), room_name, channel_type, persistence, hidden).await
+    }
+
+    /// Start or continue a direct message with the user `user_id`, who receives a notification
+    /// to join as well.
+    async fn join_direct(
+        &self,
+        user_id: &str,
+        persistence: bool,
+        hidden: bool,
+    ) -> Result<chat::Channel<Self>, Self::Error>
+    where
+        Self: Clone + Send + Sync + Sized,
+    {
+        let channel_type = ChannelJoinType::DirectMessage;
+        chat::Channel::join(self.clone(), user_id, channel_type, persistence, hidden).await
+    }
+
+    /// Join the chat of the group `group_id`, which requires being a member of the group.
+    async fn join_group_chat(
+        &self,
+        group_id: &str,
+        persistence: bool,
+        hidden: bool,
+    ) -> Result<chat::Channel<Self>, Self::Error>
+    where
+        Self: Clone + Send + Sync + Sized,
+    {
+        let channel_type = ChannelJoinType::Group;
+        chat::Channel::join(self.clone(), group_id, channel_type, persistence, hidden).await
+    }
+
     async fn join_party(&self, party_id: &str) -> Result<(), Self::Error>;
 
     async fn join_match(&self, matched: MatchmakerMatched) -> Result<Match, Self::Error>;
@@ -656,7 +1013,11 @@ pub trait Socket {
 
     async fn remove_matchmaker(&self, ticket: &str) -> Result<(), Self::Error>;
 
-    async fn remove_matchmaker_party(&self, party_id: &str, ticket: &str) -> Result<(), Self::Error>;
+    async fn remove_matchmaker_party(
+        &self,
+        party_id: &str,
+        ticket: &str,
+    ) -> Result<(), Self::Error>;
 
     async fn remove_party_member(
         &self,
@@ -676,7 +1037,12 @@ pub trait Socket {
         presences: &[UserPresence],
     ) -> Result<(), Self::Error>;
 
-    async fn send_party_data(&self, party_id: &str, op_code: i64, data: &[u8]) -> Result<(), Self::Error>;
+    async fn send_party_data(
+        &self,
+        party_id: &str,
+        op_code: i64,
+        data: &[u8],
+    ) -> Result<(), Self::Error>;
 
     async fn unfollow_users(&self, user_ids: &[&str]) -> Result<(), Self::Error>;
 
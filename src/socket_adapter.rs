@@ -1,4 +1,39 @@
 use std::error::Error;
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
+
+/// Wakes up a thread waiting for data, e.g. a `SocketDriver`.
+///
+/// Adapters that support it call `wake` whenever data was received, so that `tick` can be
+/// called right away instead of on the next interval.
+#[derive(Clone, Default)]
+pub struct Wakeup {
+    inner: Arc<(Mutex<bool>, Condvar)>,
+}
+
+impl Wakeup {
+    pub fn new() -> Wakeup {
+        Wakeup::default()
+    }
+
+    pub fn wake(&self) {
+        let (woken, condvar) = &*self.inner;
+        *woken.lock().expect("panic inside other mutex!") = true;
+        condvar.notify_all();
+    }
+
+    /// Block until `wake` is called or the timeout elapsed.
+    ///
+    /// Returns `true` if the thread was woken up.
+    pub fn wait_timeout(&self, timeout: Duration) -> bool {
+        let (woken, condvar) = &*self.inner;
+        let guard = woken.lock().expect("panic inside other mutex!");
+        let (mut guard, _) = condvar
+            .wait_timeout_while(guard, timeout, |woken| !*woken)
+            .expect("panic inside other mutex!");
+        std::mem::replace(&mut *guard, false)
+    }
+}
 
 pub trait SocketAdapter {
     type Error: Error;
@@ -14,6 +49,11 @@ pub trait SocketAdapter {
     where
         T: Fn(Result<String, Self::Error>) + Send + 'static;
 
+    /// Register a `Wakeup` to notify when data was received.
+    ///
+    /// Adapters that don't support this are ticked periodically instead.
+    fn set_wakeup(&mut self, _wakeup: Wakeup) {}
+
     fn is_connected(&self) -> bool;
     fn is_connecting(&self) -> bool;
 
@@ -0,0 +1,513 @@
+use crate::api;
+use crate::api::RestRequest;
+use crate::http_adapter::{authorization_header, parse_response, ClientAdapter, RestHttpError};
+use async_trait::async_trait;
+use log::trace;
+use nanoserde::DeJson;
+use std::io;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::sync::Mutex;
+use std::time::Duration;
+
+type Connection = BufReader<TcpStream>;
+
+// Larger response bodies are rejected to avoid allocating arbitrary amounts of memory
+const MAX_BODY_SIZE: usize = 64 * 1024 * 1024;
+
+/// A `ClientAdapter` implementing HTTP/1.1 on top of `std::net::TcpStream`.
+///
+/// Unlike `RestHttpAdapter` it has no dependencies, but only supports unencrypted `http://`
+/// connections. Connections are kept alive and reused for subsequent requests.
+///
+/// Requests are blocking: the future returned by `send` blocks the executor until the response
+/// was received.
+pub struct StdHttpAdapter {
+    server: String,
+    port: u16,
+    timeout: Option<Duration>,
+    idle: Mutex<Vec<Connection>>,
+}
+
+struct Response {
+    status: u16,
+    body: String,
+}
+
+impl StdHttpAdapter {
+    pub fn new(server: &str, port: i32) -> StdHttpAdapter {
+        StdHttpAdapter {
+            server: server.to_owned(),
+            port: port as u16,
+            timeout: None,
+            idle: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// The timeout for connecting, reading and writing. Defaults to no timeout.
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.timeout = timeout;
+    }
+
+    fn host(&self) -> Result<&str, RestHttpError> {
+        if self.server.starts_with("https://") {
+            return Err(RestHttpError::OtherError(
+                "StdHttpAdapter does not support https".to_owned(),
+            ));
+        }
+        Ok(self.server.trim_start_matches("http://"))
+    }
+
+    fn execute<T>(&self, request: &RestRequest<T>) -> Result<Response, RestHttpError> {
+        let head = self.request_head(request)?;
+
+        let idle = self.idle.lock().expect("panic inside other mutex!").pop();
+        if let Some(connection) = idle {
+            match self.round_trip(connection, &head, &request.body) {
+                // The server may have closed the idle connection in the meantime
+                Err(err) if is_stale(&err) => {
+                    trace!("StdHttpAdapter: Idle connection was closed, reconnecting");
+                }
+                result => return result.map_err(RestHttpError::IoError),
+            }
+        }
+
+        let connection = self.connect().map_err(RestHttpError::IoError)?;
+        self.round_trip(connection, &head, &request.body)
+            .map_err(RestHttpError::IoError)
+    }
+
+    fn request_head<T>(&self, request: &RestRequest<T>) -> Result<String, RestHttpError> {
+        let method = match request.method {
+            api::Method::Post => "POST",
+            api::Method::Put => "PUT",
+            api::Method::Get => "GET",
+            api::Method::Delete => "DELETE",
+        };
+        let mut path = request.urlpath.clone();
+        if !request.query_params.is_empty() {
+            path += "?";
+            path += &request.query_params;
+        }
+
+        let authorization = authorization_header(&request.authentication)
+            .map_or(String::new(), |header| {
+                format!("Authorization: {}\r\n", header)
+            });
+        Ok(format!(
+            "{} {} HTTP/1.1\r\nHost: {}:{}\r\n{}Accept: application/json\r\n\
+             Content-Type: application/json\r\nContent-Length: {}\r\nConnection: keep-alive\r\n\r\n",
+            method,
+            path,
+            self.host()?,
+            self.port,
+            authorization,
+            request.body.len()
+        ))
+    }
+
+    fn connect(&self) -> io::Result<Connection> {
+        let host = self.host().map_err(io::Error::other)?;
+        let stream = match self.timeout {
+            Some(timeout) => {
+                let mut last_err = io::Error::new(io::ErrorKind::NotFound, "Unknown host");
+                let mut connected = None;
+                for addr in std::net::ToSocketAddrs::to_socket_addrs(&(host, self.port))? {
+                    match TcpStream::connect_timeout(&addr, timeout) {
+                        Ok(stream) => {
+                            connected = Some(stream);
+                            break;
+                        }
+                        Err(err) => last_err = err,
+                    }
+                }
+                connected.ok_or(last_err)?
+            }
+            None => TcpStream::connect((host, self.port))?,
+        };
+        stream.set_read_timeout(self.timeout)?;
+        stream.set_write_timeout(self.timeout)?;
+        stream.set_nodelay(true)?;
+        Ok(BufReader::new(stream))
+    }
+
+    fn round_trip(
+        &self,
+        mut connection: Connection,
+        head: &str,
+        body: &str,
+    ) -> io::Result<Response> {
+        let stream = connection.get_mut();
+        stream.write_all(head.as_bytes())?;
+        stream.write_all(body.as_bytes())?;
+        stream.flush()?;
+
+        let (status_line, headers) = read_http_head(&mut connection)?;
+        let mut parts = status_line.split_whitespace();
+        let version = parts.next().unwrap_or_default();
+        let status = parts
+            .next()
+            .and_then(|status| status.parse::<u16>().ok())
+            .ok_or_else(|| invalid_data(&format!("Invalid status line: {}", status_line)))?;
+
+        let (body, delimited) = read_body(&mut connection, &headers, status)?;
+        let close = header(&headers, "Connection")
+            .map(|value| value.eq_ignore_ascii_case("close"))
+            .unwrap_or(false);
+        if delimited && !close && version == "HTTP/1.1" {
+            self.idle
+                .lock()
+                .expect("panic inside other mutex!")
+                .push(connection);
+        }
+
+        let body = String::from_utf8(body).map_err(|_| invalid_data("Body is not UTF-8"))?;
+        Ok(Response { status, body })
+    }
+}
+
+#[async_trait]
+impl ClientAdapter for StdHttpAdapter {
+    type Error = RestHttpError;
+    async fn send<T: DeJson + Send>(&self, request: RestRequest<T>) -> Result<T, RestHttpError> {
+        let response = self.execute(&request)?;
+        parse_response(response.status, response.body)
+    }
+}
+
+// Errors that indicate a reused connection was closed before the request was processed
+fn is_stale(err: &io::Error) -> bool {
+    matches!(
+        err.kind(),
+        io::ErrorKind::UnexpectedEof
+            | io::ErrorKind::BrokenPipe
+            | io::ErrorKind::ConnectionReset
+            | io::ErrorKind::ConnectionAborted
+    )
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_owned())
+}
+
+fn body_too_large() -> io::Error {
+    invalid_data("Response body too large")
+}
+
+/// Read the body of a response. Returns whether the end of the body was known, i.e. if the
+/// connection can be reused.
+fn read_body<R: BufRead>(
+    reader: &mut R,
+    headers: &[(String, String)],
+    status: u16,
+) -> io::Result<(Vec<u8>, bool)> {
+    if status == 204 || status == 304 || (100..200).contains(&status) {
+        return Ok((Vec::new(), true));
+    }
+
+    let chunked = header(headers, "Transfer-Encoding")
+        .map(|value| value.to_ascii_lowercase().contains("chunked"))
+        .unwrap_or(false);
+    if chunked {
+        return read_chunked(reader).map(|body| (body, true));
+    }
+
+    match header(headers, "Content-Length") {
+        Some(len) => {
+            let len = len
+                .parse::<usize>()
+                .map_err(|_| invalid_data("Invalid Content-Length"))?;
+            if len > MAX_BODY_SIZE {
+                return Err(body_too_large());
+            }
+            let mut body = vec![0u8; len];
+            reader.read_exact(&mut body)?;
+            Ok((body, true))
+        }
+        None => {
+            let mut body = Vec::new();
+            reader
+                .take(MAX_BODY_SIZE as u64 + 1)
+                .read_to_end(&mut body)?;
+            if body.len() > MAX_BODY_SIZE {
+                return Err(body_too_large());
+            }
+            Ok((body, false))
+        }
+    }
+}
+
+fn read_chunked<R: BufRead>(reader: &mut R) -> io::Result<Vec<u8>> {
+    let mut body = Vec::new();
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        // Chunk extensions after ';' are ignored
+        let size = line.split(';').next().unwrap_or_default().trim();
+        let size =
+            usize::from_str_radix(size, 16).map_err(|_| invalid_data("Invalid chunk size"))?;
+        if size == 0 {
+            break;
+        }
+
+        let start = body.len();
+        let end = start
+            .checked_add(size)
+            .filter(|end| *end <= MAX_BODY_SIZE)
+            .ok_or_else(body_too_large)?;
+        body.resize(end, 0);
+        reader.read_exact(&mut body[start..])?;
+        let mut crlf = [0u8; 2];
+        reader.read_exact(&mut crlf)?;
+    }
+
+    // Skip trailers
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 || line.trim_end().is_empty() {
+            return Ok(body);
+        }
+    }
+}
+
+pub(crate) type Headers = Vec<(String, String)>;
+
+/// Read the status line and headers of a HTTP request or response.
+pub(crate) fn read_http_head<R: BufRead>(reader: &mut R) -> io::Result<(String, Headers)> {
+    let mut status_line = String::new();
+    reader.read_line(&mut status_line)?;
+    if status_line.is_empty() {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+
+    let mut headers = Vec::new();
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_owned(), value.trim().to_owned()));
+        }
+    }
+
+    Ok((status_line.trim_end().to_owned(), headers))
+}
+
+/// Find a header by its case-insensitive name.
+pub(crate) fn header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(header, _)| header.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.as_str())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::api::{ApiAccount, ApiAccountDevice};
+    use futures::executor::block_on;
+    use std::collections::HashMap;
+    use std::io::Read;
+    use std::net::TcpListener;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::mpsc;
+    use std::sync::Arc;
+
+    struct Request {
+        head: String,
+        headers: Headers,
+        body: String,
+    }
+
+    struct TestServer {
+        port: i32,
+        connections: Arc<AtomicUsize>,
+        requests: mpsc::Receiver<Request>,
+    }
+
+    // Answers the requests with `responses`, in order. A response of `None` closes the
+    // connection without responding.
+    fn test_server(responses: Vec<Option<&'static str>>) -> TestServer {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port() as i32;
+        let connections = Arc::new(AtomicUsize::new(0));
+        let (tx, requests) = mpsc::channel();
+
+        std::thread::spawn({
+            let connections = connections.clone();
+            move || {
+                let mut responses = responses.into_iter();
+                for stream in listener.incoming() {
+                    connections.fetch_add(1, Ordering::SeqCst);
+                    let mut stream = stream.unwrap();
+                    let mut reader = BufReader::new(stream.try_clone().unwrap());
+                    while let Ok((head, headers)) = read_http_head(&mut reader) {
+                        let len = header(&headers, "Content-Length").unwrap().parse().unwrap();
+                        let mut body = vec![0u8; len];
+                        reader.read_exact(&mut body).unwrap();
+                        tx.send(Request {
+                            head,
+                            headers,
+                            body: String::from_utf8(body).unwrap(),
+                        })
+                        .unwrap();
+
+                        match responses.next() {
+                            Some(Some(response)) => stream.write_all(response.as_bytes()).unwrap(),
+                            _ => break,
+                        }
+                    }
+                }
+            }
+        });
+
+        TestServer {
+            port,
+            connections,
+            requests,
+        }
+    }
+
+    fn test_adapter(server: &TestServer) -> StdHttpAdapter {
+        let mut adapter = StdHttpAdapter::new("http://127.0.0.1", server.port);
+        adapter.set_timeout(Some(Duration::from_secs(5)));
+        adapter
+    }
+
+    const ACCOUNT: &str = "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\
+                           Content-Length: 39\r\n\r\n{\"user\":{\"id\":\"1\"},\"wallet\":\"{}\",\"x\":1}";
+
+    #[test]
+    fn test_content_length_keep_alive() {
+        let server = test_server(vec![Some(ACCOUNT), Some(ACCOUNT)]);
+        let adapter = test_adapter(&server);
+
+        for _ in 0..2 {
+            let account: ApiAccount = block_on(adapter.send(api::get_account("token"))).unwrap();
+            assert_eq!(account.user.id, "1");
+            assert_eq!(account.wallet, "{}");
+        }
+        assert_eq!(server.connections.load(Ordering::SeqCst), 1);
+
+        let request = server.requests.recv().unwrap();
+        assert_eq!(request.head, "GET /v2/account HTTP/1.1");
+        assert_eq!(
+            header(&request.headers, "authorization"),
+            Some("Bearer token")
+        );
+    }
+
+    #[test]
+    fn test_chunked() {
+        let server = test_server(vec![Some(
+            "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n\
+             9;ext=1\r\n{\"user\":{\r\nE\r\n\"id\":\"chunked\"\r\n2\r\n}}\r\n0\r\nTrailer: 1\r\n\r\n",
+        )]);
+        let adapter = test_adapter(&server);
+
+        let account: ApiAccount = block_on(adapter.send(api::get_account("token"))).unwrap();
+        assert_eq!(account.user.id, "chunked");
+    }
+
+    #[test]
+    fn test_body_too_large() {
+        let read = |response: &str, headers: &[(&str, &str)]| {
+            let headers: Vec<(String, String)> = headers
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect();
+            read_body(&mut response.as_bytes(), &headers, 200).map(|(body, _)| body)
+        };
+        let error_kind = |result: io::Result<Vec<u8>>| result.unwrap_err().kind();
+
+        let too_large = (MAX_BODY_SIZE + 1).to_string();
+        let result = read("{}", &[("Content-Length", &too_large)]);
+        assert_eq!(error_kind(result), io::ErrorKind::InvalidData);
+        let result = read("{}", &[("Content-Length", &usize::MAX.to_string())]);
+        assert_eq!(error_kind(result), io::ErrorKind::InvalidData);
+
+        let chunked = [("Transfer-Encoding", "chunked")];
+        let result = read(&format!("{:x}\r\n", MAX_BODY_SIZE + 1), &chunked);
+        assert_eq!(error_kind(result), io::ErrorKind::InvalidData);
+        // The sum of the chunk sizes overflows
+        let result = read(&format!("1\r\na\r\n{:x}\r\n", usize::MAX), &chunked);
+        assert_eq!(error_kind(result), io::ErrorKind::InvalidData);
+
+        // Without Content-Length the body is read until the connection is closed
+        let mut reader = BufReader::new(io::repeat(b'a').take(MAX_BODY_SIZE as u64 + 1));
+        let result = read_body(&mut reader, &[], 200);
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_post_body() {
+        let server = test_server(vec![Some(
+            "HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\n{}",
+        )]);
+        let adapter = test_adapter(&server);
+
+        let body = ApiAccountDevice {
+            id: "device".to_owned(),
+            vars: HashMap::new(),
+        };
+        let request = api::authenticate_device("key", "", body, Some(true), None);
+        block_on(adapter.send(request)).unwrap();
+
+        let request = server.requests.recv().unwrap();
+        assert!(request
+            .head
+            .starts_with("POST /v2/account/authenticate/device?"));
+        assert!(request.body.contains("\"id\":\"device\""));
+        assert!(header(&request.headers, "authorization")
+            .unwrap()
+            .starts_with("Basic "));
+        assert!(adapter.idle.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_no_authorization_header() {
+        let server = test_server(vec![Some("HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\n{}")]);
+        let adapter = test_adapter(&server);
+
+        let mut request = api::rpc_func("", "echo", "\"\"", Some("httpkey"));
+        request.authentication = api::Authentication::None;
+        block_on(adapter.send(request)).unwrap();
+
+        let request = server.requests.recv().unwrap();
+        assert_eq!(header(&request.headers, "authorization"), None);
+    }
+
+    #[test]
+    fn test_status_errors() {
+        let server = test_server(vec![
+            Some("HTTP/1.1 404 Not Found\r\nContent-Length: 9\r\n\r\nnot found"),
+            Some("HTTP/1.1 500 Internal Server Error\r\nContent-Length: 5\r\n\r\nerror"),
+        ]);
+        let adapter = test_adapter(&server);
+
+        match block_on(adapter.send(api::get_account("token"))) {
+            Err(RestHttpError::ClientError(404, body)) => assert_eq!(body, "not found"),
+            other => panic!("Unexpected result {:?}", other),
+        }
+        match block_on(adapter.send(api::get_account("token"))) {
+            Err(RestHttpError::ServerError(500, body)) => assert_eq!(body, "error"),
+            other => panic!("Unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_reconnect_closed_connection() {
+        // The server closes the connection after the first response
+        let server = test_server(vec![Some(ACCOUNT), None, Some(ACCOUNT)]);
+        let adapter = test_adapter(&server);
+
+        block_on(adapter.send(api::get_account("token"))).unwrap();
+        block_on(adapter.send(api::get_account("token"))).unwrap();
+        assert_eq!(server.connections.load(Ordering::SeqCst), 2);
+    }
+}
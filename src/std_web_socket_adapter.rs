@@ -0,0 +1,953 @@
+use crate::socket_adapter::{SocketAdapter, Wakeup};
+use crate::std_http_adapter::{header, read_http_head};
+use log::{debug, error, trace};
+use std::collections::hash_map::RandomState;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::hash::{BuildHasher, Hasher};
+use std::io;
+use std::io::{BufReader, Read, Write};
+use std::net::{Shutdown, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc;
+use std::sync::mpsc::{Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const WEB_SOCKET_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+// Larger frames are rejected to avoid allocating arbitrary amounts of memory
+const MAX_PAYLOAD_SIZE: u64 = 64 * 1024 * 1024;
+const DEFAULT_CLOSE_TIMEOUT: Duration = Duration::from_secs(5);
+
+pub(crate) const OPCODE_CONTINUATION: u8 = 0x0;
+pub(crate) const OPCODE_TEXT: u8 = 0x1;
+pub(crate) const OPCODE_BINARY: u8 = 0x2;
+pub(crate) const OPCODE_CLOSE: u8 = 0x8;
+pub(crate) const OPCODE_PING: u8 = 0x9;
+pub(crate) const OPCODE_PONG: u8 = 0xA;
+
+enum Message {
+    Text(String),
+    Binary(Vec<u8>),
+    Connected,
+    Closed,
+    Error(StdWebSocketError),
+}
+
+#[derive(Debug)]
+pub enum StdWebSocketError {
+    IoError(io::Error),
+    InvalidUrl(String),
+    HandshakeError(String),
+    ProtocolError(String),
+    NotConnected,
+}
+
+impl From<io::Error> for StdWebSocketError {
+    fn from(err: io::Error) -> Self {
+        StdWebSocketError::IoError(err)
+    }
+}
+
+impl Display for StdWebSocketError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Debug::fmt(self, f)
+    }
+}
+
+impl Error for StdWebSocketError {}
+
+type ReceivedCallback = Box<dyn Fn(Result<String, StdWebSocketError>) + Send + 'static>;
+
+/// A `SocketAdapter` implementing RFC 6455 on top of `std::net::TcpStream`.
+///
+/// Unlike `WebSocketAdapter` it has no dependencies, but only supports unencrypted `ws://`
+/// connections. Connecting and reading happens on a background thread; callbacks are invoked
+/// in `tick`.
+pub struct StdWebSocketAdapter {
+    on_connected: Option<Box<dyn Fn() + Send + 'static>>,
+    on_closed: Option<Box<dyn Fn() + Send + 'static>>,
+    on_received: Option<ReceivedCallback>,
+    on_received_binary: Option<Box<dyn Fn(Vec<u8>) + Send + 'static>>,
+
+    rx_message: Option<Receiver<Message>>,
+    connection: Arc<Connection>,
+    max_frame_size: Option<usize>,
+    close_timeout: Duration,
+    wakeup: Arc<Mutex<Option<Wakeup>>>,
+}
+
+// State shared with the reading thread
+#[derive(Default)]
+struct Connection {
+    writer: Mutex<Option<TcpStream>>,
+    connecting: AtomicBool,
+    connected: AtomicBool,
+    closing: AtomicBool,
+}
+
+impl Connection {
+    fn write_frame(&self, fin: bool, opcode: u8, payload: &[u8]) -> Result<(), StdWebSocketError> {
+        let mut writer = self.writer.lock().expect("panic inside other mutex!");
+        match *writer {
+            Some(ref mut stream) => {
+                write_frame(stream, fin, opcode, payload, Some(random_mask()))?;
+                Ok(())
+            }
+            None => Err(StdWebSocketError::NotConnected),
+        }
+    }
+
+    fn write_message(
+        &self,
+        opcode: u8,
+        payload: &[u8],
+        max_frame_size: Option<usize>,
+    ) -> Result<(), StdWebSocketError> {
+        let mut writer = self.writer.lock().expect("panic inside other mutex!");
+        let stream = writer.as_mut().ok_or(StdWebSocketError::NotConnected)?;
+
+        let frame_size = max_frame_size.unwrap_or(payload.len()).max(1);
+        let mut chunks = payload.chunks(frame_size).peekable();
+        if chunks.peek().is_none() {
+            write_frame(stream, true, opcode, &[], Some(random_mask()))?;
+            return Ok(());
+        }
+
+        let mut opcode = opcode;
+        while let Some(chunk) = chunks.next() {
+            let fin = chunks.peek().is_none();
+            write_frame(stream, fin, opcode, chunk, Some(random_mask()))?;
+            opcode = OPCODE_CONTINUATION;
+        }
+        Ok(())
+    }
+
+    fn disconnect(&self) {
+        if let Some(stream) = self
+            .writer
+            .lock()
+            .expect("panic inside other mutex!")
+            .take()
+        {
+            let _ = stream.shutdown(Shutdown::Both);
+        }
+        // `closing` is kept, so the reading thread doesn't report the shut down connection as an
+        // error. A `Connection` isn't reused after it was disconnected.
+        self.connecting.store(false, Ordering::SeqCst);
+        self.connected.store(false, Ordering::SeqCst);
+    }
+}
+
+// Client on the reading thread
+struct WebSocketClient {
+    tx: Sender<Message>,
+    connection: Arc<Connection>,
+    wakeup: Arc<Mutex<Option<Wakeup>>>,
+}
+
+impl WebSocketClient {
+    fn send(&self, message: Message) {
+        if self.tx.send(message).is_err() {
+            // The adapter connected again or was dropped
+            return;
+        }
+        if let Some(ref wakeup) = *self.wakeup.lock().expect("panic inside other mutex!") {
+            wakeup.wake();
+        }
+    }
+
+    fn run(&self, url: Url, timeout: i32) {
+        let reader = match self.open(&url, timeout) {
+            Ok(reader) => reader,
+            Err(err) => {
+                error!(
+                    "StdWebSocketAdapter: Failed to connect to {}: {}",
+                    url.host, err
+                );
+                self.connection.disconnect();
+                self.send(Message::Error(err));
+                self.send(Message::Closed);
+                return;
+            }
+        };
+
+        debug!("Connection with {}:{} now open", url.host, url.port);
+        self.connection.connecting.store(false, Ordering::SeqCst);
+        self.connection.connected.store(true, Ordering::SeqCst);
+        self.send(Message::Connected);
+
+        if let Err(err) = self.read_messages(reader) {
+            if !self.connection.closing.load(Ordering::SeqCst) {
+                self.send(Message::Error(err));
+            }
+        }
+
+        self.connection.disconnect();
+        self.send(Message::Closed);
+    }
+
+    fn open(&self, url: &Url, timeout: i32) -> Result<BufReader<TcpStream>, StdWebSocketError> {
+        let mut last_err = None;
+        let mut stream = None;
+        for addr in (url.host.as_str(), url.port).to_socket_addrs()? {
+            let result = if timeout > 0 {
+                TcpStream::connect_timeout(&addr, Duration::from_secs(timeout as u64))
+            } else {
+                TcpStream::connect(addr)
+            };
+            match result {
+                Ok(connected) => {
+                    stream = Some(connected);
+                    break;
+                }
+                Err(err) => last_err = Some(err),
+            }
+        }
+        let mut stream = match (stream, last_err) {
+            (Some(stream), _) => stream,
+            (None, Some(err)) => return Err(err.into()),
+            (None, None) => return Err(StdWebSocketError::InvalidUrl(url.host.clone())),
+        };
+        stream.set_nodelay(true)?;
+
+        let key = base64::encode(random_bytes(16));
+        let request = format!(
+            "GET {} HTTP/1.1\r\nHost: {}:{}\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\
+             Sec-WebSocket-Key: {}\r\nSec-WebSocket-Version: 13\r\n\r\n",
+            url.path, url.host, url.port, key
+        );
+        stream.write_all(request.as_bytes())?;
+
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let (status_line, headers) = read_http_head(&mut reader)?;
+        if status_line.split_whitespace().nth(1) != Some("101") {
+            return Err(StdWebSocketError::HandshakeError(status_line));
+        }
+        let expected = accept_key(&key);
+        match header(&headers, "Sec-WebSocket-Accept") {
+            Some(accept) if accept == expected => {}
+            _ => {
+                return Err(StdWebSocketError::HandshakeError(
+                    "Invalid Sec-WebSocket-Accept".to_owned(),
+                ))
+            }
+        }
+
+        let mut writer = self
+            .connection
+            .writer
+            .lock()
+            .expect("panic inside other mutex!");
+        // The adapter connected again or was dropped in the meantime
+        if !self.connection.connecting.load(Ordering::SeqCst) {
+            let _ = stream.shutdown(Shutdown::Both);
+            return Err(StdWebSocketError::NotConnected);
+        }
+        *writer = Some(stream);
+        Ok(reader)
+    }
+
+    fn read_messages(&self, mut reader: BufReader<TcpStream>) -> Result<(), StdWebSocketError> {
+        // Opcode and payload of a fragmented message
+        let mut fragmented: Option<(u8, Vec<u8>)> = None;
+
+        loop {
+            let frame = read_frame(&mut reader)?;
+            trace!(
+                "StdWebSocketAdapter: Received frame {} ({} bytes)",
+                frame.opcode,
+                frame.payload.len()
+            );
+            match frame.opcode {
+                OPCODE_TEXT | OPCODE_BINARY if fragmented.is_some() => {
+                    return Err(protocol_error("Expected continuation frame"));
+                }
+                OPCODE_TEXT | OPCODE_BINARY if !frame.fin => {
+                    fragmented = Some((frame.opcode, frame.payload));
+                }
+                OPCODE_TEXT | OPCODE_BINARY => {
+                    self.deliver(frame.opcode, frame.payload)?;
+                }
+                OPCODE_CONTINUATION => {
+                    let (opcode, mut payload) = fragmented
+                        .take()
+                        .ok_or_else(|| protocol_error("Unexpected continuation frame"))?;
+                    // Each frame is limited, the reassembled message needs to be limited as well
+                    if (payload.len() + frame.payload.len()) as u64 > MAX_PAYLOAD_SIZE {
+                        return Err(protocol_error("Message too large"));
+                    }
+                    payload.extend_from_slice(&frame.payload);
+                    if frame.fin {
+                        self.deliver(opcode, payload)?;
+                    } else {
+                        fragmented = Some((opcode, payload));
+                    }
+                }
+                OPCODE_PING => {
+                    self.connection
+                        .write_frame(true, OPCODE_PONG, &frame.payload)?;
+                }
+                OPCODE_PONG => {}
+                OPCODE_CLOSE => {
+                    // Reply to the close frame unless we initiated closing the connection
+                    if !self.connection.closing.swap(true, Ordering::SeqCst) {
+                        let code = frame.payload.get(..2).unwrap_or(&[]);
+                        self.connection.write_frame(true, OPCODE_CLOSE, code)?;
+                    }
+                    return Ok(());
+                }
+                opcode => {
+                    return Err(protocol_error(&format!("Unknown opcode {}", opcode)));
+                }
+            }
+        }
+    }
+
+    fn deliver(&self, opcode: u8, payload: Vec<u8>) -> Result<(), StdWebSocketError> {
+        if opcode == OPCODE_TEXT {
+            let text = String::from_utf8(payload).map_err(|_| protocol_error("Invalid UTF-8"))?;
+            self.send(Message::Text(text));
+        } else {
+            self.send(Message::Binary(payload));
+        }
+        Ok(())
+    }
+}
+
+impl StdWebSocketAdapter {
+    pub fn new() -> StdWebSocketAdapter {
+        StdWebSocketAdapter {
+            on_connected: None,
+            on_closed: None,
+            on_received: None,
+            on_received_binary: None,
+
+            rx_message: None,
+            connection: Arc::new(Connection::default()),
+            max_frame_size: None,
+            close_timeout: DEFAULT_CLOSE_TIMEOUT,
+            wakeup: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Split outgoing messages into frames of at most `max_frame_size` bytes.
+    pub fn set_max_frame_size(&mut self, max_frame_size: Option<usize>) {
+        self.max_frame_size = max_frame_size;
+    }
+
+    /// How long `close` waits for the server to reply with a close frame before the connection is
+    /// shut down. Defaults to 5 seconds.
+    pub fn set_close_timeout(&mut self, close_timeout: Duration) {
+        self.close_timeout = close_timeout;
+    }
+
+    pub fn on_received_binary<T>(&mut self, callback: T)
+    where
+        T: Fn(Vec<u8>) + Send + 'static,
+    {
+        self.on_received_binary = Some(Box::new(callback));
+    }
+
+    pub fn send_binary(&self, data: &[u8]) -> Result<(), StdWebSocketError> {
+        self.connection
+            .write_message(OPCODE_BINARY, data, self.max_frame_size)
+    }
+}
+
+impl Default for StdWebSocketAdapter {
+    fn default() -> Self {
+        StdWebSocketAdapter::new()
+    }
+}
+
+impl SocketAdapter for StdWebSocketAdapter {
+    type Error = StdWebSocketError;
+
+    fn on_connected<T>(&mut self, callback: T)
+    where
+        T: Fn() + Send + 'static,
+    {
+        self.on_connected = Some(Box::new(callback));
+    }
+
+    fn on_closed<T>(&mut self, callback: T)
+    where
+        T: Fn() + Send + 'static,
+    {
+        self.on_closed = Some(Box::new(callback));
+    }
+
+    fn on_received<T>(&mut self, callback: T)
+    where
+        T: Fn(Result<String, StdWebSocketError>) + Send + 'static,
+    {
+        self.on_received = Some(Box::new(callback));
+    }
+
+    fn set_wakeup(&mut self, wakeup: Wakeup) {
+        *self.wakeup.lock().expect("panic inside other mutex!") = Some(wakeup);
+    }
+
+    fn is_connected(&self) -> bool {
+        self.connection.connected.load(Ordering::SeqCst)
+    }
+
+    fn is_connecting(&self) -> bool {
+        self.connection.connecting.load(Ordering::SeqCst)
+    }
+
+    fn close(&mut self) {
+        if !self.is_connected() || self.connection.closing.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        // The reading thread finishes when the server replies with a close frame
+        let code = 1000u16.to_be_bytes();
+        if let Err(err) = self.connection.write_frame(true, OPCODE_CLOSE, &code) {
+            error!("StdWebSocketAdapter::close: {}", err);
+            self.connection.disconnect();
+            return;
+        }
+
+        let connection = Arc::downgrade(&self.connection);
+        let close_timeout = self.close_timeout;
+        std::thread::spawn(move || {
+            std::thread::sleep(close_timeout);
+            match connection.upgrade() {
+                Some(connection) if connection.connected.load(Ordering::SeqCst) => {
+                    debug!("StdWebSocketAdapter: Timed out waiting for the close frame");
+                    connection.disconnect();
+                }
+                _ => {}
+            }
+        });
+    }
+
+    fn connect(&mut self, addr: &str, timeout: i32) {
+        let url = match Url::parse(addr) {
+            Ok(url) => url,
+            Err(err) => {
+                error!("StdWebSocketAdapter::connect: {}", err);
+                if let Some(ref cb) = self.on_received {
+                    cb(Err(err));
+                }
+                return;
+            }
+        };
+
+        // Stop the reading thread of the previous connection
+        self.connection.closing.store(true, Ordering::SeqCst);
+        self.connection.disconnect();
+
+        let (tx, rx) = mpsc::channel();
+        self.rx_message = Some(rx);
+        self.connection = Arc::new(Connection::default());
+        self.connection.connecting.store(true, Ordering::SeqCst);
+
+        let client = WebSocketClient {
+            tx,
+            connection: self.connection.clone(),
+            wakeup: self.wakeup.clone(),
+        };
+        std::thread::spawn(move || client.run(url, timeout));
+    }
+
+    fn send(&self, data: &str, _reliable: bool) -> Result<(), Self::Error> {
+        self.connection
+            .write_message(OPCODE_TEXT, data.as_bytes(), self.max_frame_size)
+    }
+
+    fn tick(&self) {
+        if let Some(ref rx) = self.rx_message {
+            while let Ok(message) = rx.try_recv() {
+                match message {
+                    Message::Text(msg) => {
+                        if let Some(ref cb) = self.on_received {
+                            cb(Ok(msg));
+                        }
+                    }
+                    Message::Binary(data) => {
+                        if let Some(ref cb) = self.on_received_binary {
+                            cb(data);
+                        }
+                    }
+                    Message::Connected => {
+                        if let Some(ref cb) = self.on_connected {
+                            cb();
+                        }
+                    }
+                    Message::Closed => {
+                        if let Some(ref cb) = self.on_closed {
+                            cb();
+                        }
+                    }
+                    Message::Error(err) => {
+                        if let Some(ref cb) = self.on_received {
+                            cb(Err(err));
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Drop for StdWebSocketAdapter {
+    fn drop(&mut self) {
+        self.connection.closing.store(true, Ordering::SeqCst);
+        self.connection.disconnect();
+    }
+}
+
+struct Url {
+    host: String,
+    port: u16,
+    // Path including the query
+    path: String,
+}
+
+impl Url {
+    fn parse(addr: &str) -> Result<Url, StdWebSocketError> {
+        let invalid = || StdWebSocketError::InvalidUrl(addr.to_owned());
+        let rest = match addr.strip_prefix("ws://") {
+            Some(rest) => rest,
+            None => return Err(invalid()),
+        };
+        let (authority, path) = match rest.find(['/', '?']) {
+            Some(index) if rest[index..].starts_with('?') => {
+                (&rest[..index], format!("/{}", &rest[index..]))
+            }
+            Some(index) => (&rest[..index], rest[index..].to_owned()),
+            None => (rest, "/".to_owned()),
+        };
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) => (host, port.parse().map_err(|_| invalid())?),
+            None => (authority, 80),
+        };
+        if host.is_empty() {
+            return Err(invalid());
+        }
+        Ok(Url {
+            host: host.to_owned(),
+            port,
+            path,
+        })
+    }
+}
+
+fn protocol_error(message: &str) -> StdWebSocketError {
+    StdWebSocketError::ProtocolError(message.to_owned())
+}
+
+pub(crate) struct Frame {
+    pub fin: bool,
+    pub opcode: u8,
+    pub payload: Vec<u8>,
+}
+
+/// Read a single frame, unmasking the payload if necessary.
+pub(crate) fn read_frame<R: Read>(reader: &mut R) -> io::Result<Frame> {
+    let mut head = [0u8; 2];
+    reader.read_exact(&mut head)?;
+    let fin = head[0] & 0x80 != 0;
+    let opcode = head[0] & 0x0F;
+    let masked = head[1] & 0x80 != 0;
+    let len = match head[1] & 0x7F {
+        126 => {
+            let mut len = [0u8; 2];
+            reader.read_exact(&mut len)?;
+            u16::from_be_bytes(len) as u64
+        }
+        127 => {
+            let mut len = [0u8; 8];
+            reader.read_exact(&mut len)?;
+            u64::from_be_bytes(len)
+        }
+        len => len as u64,
+    };
+    if len > MAX_PAYLOAD_SIZE {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Frame too large",
+        ));
+    }
+
+    let mut mask = [0u8; 4];
+    if masked {
+        reader.read_exact(&mut mask)?;
+    }
+    let mut payload = vec![0u8; len as usize];
+    reader.read_exact(&mut payload)?;
+    if masked {
+        apply_mask(&mut payload, mask);
+    }
+
+    Ok(Frame {
+        fin,
+        opcode,
+        payload,
+    })
+}
+
+/// Write a single frame. Clients need to mask their frames, servers must not.
+pub(crate) fn write_frame<W: Write>(
+    writer: &mut W,
+    fin: bool,
+    opcode: u8,
+    payload: &[u8],
+    mask: Option<[u8; 4]>,
+) -> io::Result<()> {
+    let mut frame = Vec::with_capacity(payload.len() + 14);
+    frame.push(if fin { 0x80 } else { 0x00 } | opcode);
+    let mask_bit = if mask.is_some() { 0x80 } else { 0x00 };
+    match payload.len() {
+        len if len < 126 => frame.push(mask_bit | len as u8),
+        len if len <= u16::MAX as usize => {
+            frame.push(mask_bit | 126);
+            frame.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        len => {
+            frame.push(mask_bit | 127);
+            frame.extend_from_slice(&(len as u64).to_be_bytes());
+        }
+    }
+    let start = frame.len();
+    match mask {
+        Some(mask) => {
+            frame.extend_from_slice(&mask);
+            frame.extend_from_slice(payload);
+            apply_mask(&mut frame[start + 4..], mask);
+        }
+        None => frame.extend_from_slice(payload),
+    }
+    writer.write_all(&frame)?;
+    writer.flush()
+}
+
+fn apply_mask(payload: &mut [u8], mask: [u8; 4]) {
+    for (i, byte) in payload.iter_mut().enumerate() {
+        *byte ^= mask[i % 4];
+    }
+}
+
+/// The `Sec-WebSocket-Accept` value the server responds with for a `Sec-WebSocket-Key`.
+pub(crate) fn accept_key(key: &str) -> String {
+    base64::encode(sha1(format!("{}{}", key, WEB_SOCKET_GUID).as_bytes()))
+}
+
+fn random_mask() -> [u8; 4] {
+    let mut mask = [0u8; 4];
+    mask.copy_from_slice(&random_bytes(4));
+    mask
+}
+
+// Masking keys and handshake nonces only need to be unpredictable for proxies, so the randomly
+// seeded std hasher is good enough.
+fn random_bytes(len: usize) -> Vec<u8> {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let mut bytes = Vec::with_capacity(len + 8);
+    while bytes.len() < len {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u64(COUNTER.fetch_add(1, Ordering::Relaxed));
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|time| time.as_nanos() as u64)
+            .unwrap_or_default();
+        hasher.write_u64(nanos);
+        bytes.extend_from_slice(&hasher.finish().to_le_bytes());
+    }
+    bytes.truncate(len);
+    bytes
+}
+
+fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut h: [u32; 5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&((data.len() as u64) * 8).to_be_bytes());
+
+    for chunk in message.chunks(64) {
+        let mut w = [0u32; 80];
+        for (i, word) in chunk.chunks(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..80 {
+            w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e] = h;
+        for (i, word) in w.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5A827999),
+                20..=39 => (b ^ c ^ d, 0x6ED9EBA1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1BBCDC),
+                _ => (b ^ c ^ d, 0xCA62C1D6),
+            };
+            let temp = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(*word);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+
+        for (h, value) in h.iter_mut().zip([a, b, c, d, e]) {
+            *h = h.wrapping_add(value);
+        }
+    }
+
+    let mut digest = [0u8; 20];
+    for (i, value) in h.iter().enumerate() {
+        digest[i * 4..i * 4 + 4].copy_from_slice(&value.to_be_bytes());
+    }
+    digest
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::net::TcpListener;
+    use std::thread::sleep;
+    use std::time::Instant;
+
+    // Accepts a single connection, completes the handshake and passes the stream to `serve`
+    fn server<F>(serve: F) -> String
+    where
+        F: FnOnce(TcpStream, BufReader<TcpStream>) + Send + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let (_, headers) = read_http_head(&mut reader).unwrap();
+            let key = header(&headers, "sec-websocket-key").unwrap();
+            let response = format!(
+                "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\
+                 Sec-WebSocket-Accept: {}\r\n\r\n",
+                accept_key(key)
+            );
+            stream.write_all(response.as_bytes()).unwrap();
+            serve(stream, reader);
+        });
+        format!("ws://{}/ws?token=abc", addr)
+    }
+
+    // Echoes all frames unmasked. Sends a ping when receiving "ping-me" and answers a pong with
+    // "pong-received".
+    fn echo_server() -> String {
+        server(|mut stream, mut reader| {
+            while let Ok(frame) = read_frame(&mut reader) {
+                match frame.opcode {
+                    OPCODE_TEXT if frame.payload == b"ping-me" => {
+                        write_frame(&mut stream, true, OPCODE_PING, b"p", None).unwrap();
+                    }
+                    OPCODE_PONG if frame.payload == b"p" => {
+                        write_frame(&mut stream, true, OPCODE_TEXT, b"pong-received", None)
+                            .unwrap();
+                    }
+                    OPCODE_CLOSE => {
+                        write_frame(&mut stream, true, OPCODE_CLOSE, &frame.payload, None).unwrap();
+                        return;
+                    }
+                    opcode => {
+                        write_frame(&mut stream, frame.fin, opcode, &frame.payload, None).unwrap();
+                    }
+                }
+            }
+        })
+    }
+
+    struct Received {
+        connected: mpsc::Receiver<()>,
+        closed: mpsc::Receiver<()>,
+        text: mpsc::Receiver<String>,
+        binary: mpsc::Receiver<Vec<u8>>,
+    }
+
+    fn connected_adapter() -> (StdWebSocketAdapter, Received) {
+        adapter_connected_to(&echo_server())
+    }
+
+    fn adapter_connected_to(url: &str) -> (StdWebSocketAdapter, Received) {
+        let (tx_connected, connected) = mpsc::channel();
+        let (tx_closed, closed) = mpsc::channel();
+        let (tx_text, text) = mpsc::channel();
+        let (tx_binary, binary) = mpsc::channel();
+
+        let mut adapter = StdWebSocketAdapter::new();
+        adapter.on_connected(move || tx_connected.send(()).unwrap());
+        adapter.on_closed(move || tx_closed.send(()).unwrap());
+        adapter.on_received(move |msg| tx_text.send(msg.unwrap()).unwrap());
+        adapter.on_received_binary(move |data| tx_binary.send(data).unwrap());
+        adapter.connect(url, 5);
+
+        let received = Received {
+            connected,
+            closed,
+            text,
+            binary,
+        };
+        tick_until(&adapter, &received.connected);
+        assert!(adapter.is_connected());
+        (adapter, received)
+    }
+
+    fn tick_until<T>(adapter: &StdWebSocketAdapter, rx: &mpsc::Receiver<T>) -> T {
+        let start = Instant::now();
+        while start.elapsed() < Duration::from_secs(5) {
+            adapter.tick();
+            if let Ok(value) = rx.try_recv() {
+                return value;
+            }
+            sleep(Duration::from_millis(1));
+        }
+        panic!("Timed out");
+    }
+
+    #[test]
+    fn test_accept_key() {
+        // Example from RFC 6455
+        assert_eq!(
+            accept_key("dGhlIHNhbXBsZSBub25jZQ=="),
+            "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="
+        );
+    }
+
+    #[test]
+    fn test_parse_url() {
+        let url = Url::parse("ws://127.0.0.1:7350/ws?lang=en").unwrap();
+        assert_eq!(url.host, "127.0.0.1");
+        assert_eq!(url.port, 7350);
+        assert_eq!(url.path, "/ws?lang=en");
+
+        let url = Url::parse("ws://localhost").unwrap();
+        assert_eq!(url.port, 80);
+        assert_eq!(url.path, "/");
+
+        assert!(Url::parse("wss://localhost").is_err());
+    }
+
+    #[test]
+    fn test_frame_lengths() {
+        for len in [0, 125, 126, 65535, 65536] {
+            let payload = vec![7u8; len];
+            let mut data = Vec::new();
+            write_frame(&mut data, true, OPCODE_BINARY, &payload, Some([1, 2, 3, 4])).unwrap();
+            let frame = read_frame(&mut data.as_slice()).unwrap();
+            assert!(frame.fin);
+            assert_eq!(frame.opcode, OPCODE_BINARY);
+            assert_eq!(frame.payload, payload);
+        }
+    }
+
+    #[test]
+    fn test_echo_text_and_binary() {
+        let (adapter, received) = connected_adapter();
+
+        adapter.send("Hello", false).unwrap();
+        assert_eq!(tick_until(&adapter, &received.text), "Hello");
+
+        adapter.send_binary(&[0, 159, 146, 150]).unwrap();
+        assert_eq!(
+            tick_until(&adapter, &received.binary),
+            vec![0, 159, 146, 150]
+        );
+    }
+
+    #[test]
+    fn test_fragmentation() {
+        let (mut adapter, received) = connected_adapter();
+        adapter.set_max_frame_size(Some(3));
+
+        // The server echoes the fragments, which need to be reassembled
+        adapter.send("Hello fragmented world", false).unwrap();
+        assert_eq!(
+            tick_until(&adapter, &received.text),
+            "Hello fragmented world"
+        );
+    }
+
+    #[test]
+    fn test_ping() {
+        let (adapter, received) = connected_adapter();
+
+        adapter.send("ping-me", false).unwrap();
+        assert_eq!(tick_until(&adapter, &received.text), "pong-received");
+    }
+
+    #[test]
+    fn test_close() {
+        let (mut adapter, received) = connected_adapter();
+
+        adapter.close();
+        tick_until(&adapter, &received.closed);
+        assert!(!adapter.is_connected());
+        assert!(adapter.send("Hello", false).is_err());
+    }
+
+    #[test]
+    fn test_fragmented_message_too_large() {
+        // Every frame is below the limit, but together they exceed it
+        let url = server(|mut stream, _| {
+            let fragment = vec![b'a'; (MAX_PAYLOAD_SIZE / 2) as usize];
+            write_frame(&mut stream, false, OPCODE_TEXT, &fragment, None).unwrap();
+            for _ in 0..2 {
+                let _ = write_frame(&mut stream, false, OPCODE_CONTINUATION, &fragment, None);
+            }
+        });
+        let (tx_error, rx_error) = mpsc::channel();
+        let (tx_closed, closed) = mpsc::channel();
+        let mut adapter = StdWebSocketAdapter::new();
+        adapter.on_received(move |msg| tx_error.send(msg.is_err()).unwrap());
+        adapter.on_closed(move || tx_closed.send(()).unwrap());
+        adapter.connect(&url, 5);
+
+        assert!(tick_until(&adapter, &rx_error));
+        tick_until(&adapter, &closed);
+    }
+
+    // Reads frames without replying until the connection is shut down
+    fn silent_server(tx_eof: mpsc::Sender<()>) -> String {
+        server(move |_stream, mut reader| {
+            while read_frame(&mut reader).is_ok() {}
+            tx_eof.send(()).unwrap();
+        })
+    }
+
+    #[test]
+    fn test_reconnect_disconnects_previous_connection() {
+        let (tx_eof, rx_eof) = mpsc::channel();
+        let (mut adapter, received) = adapter_connected_to(&silent_server(tx_eof));
+
+        adapter.connect(&echo_server(), 5);
+        rx_eof.recv_timeout(Duration::from_secs(5)).unwrap();
+        tick_until(&adapter, &received.connected);
+        adapter.send("Hello", false).unwrap();
+        assert_eq!(tick_until(&adapter, &received.text), "Hello");
+        // The previous connection doesn't report being closed
+        assert!(received.closed.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_close_timeout() {
+        let (tx_eof, rx_eof) = mpsc::channel();
+        let (mut adapter, received) = adapter_connected_to(&silent_server(tx_eof));
+        adapter.set_close_timeout(Duration::from_millis(100));
+
+        // The server never replies with a close frame
+        adapter.close();
+        tick_until(&adapter, &received.closed);
+        assert!(!adapter.is_connected());
+        rx_eof.recv_timeout(Duration::from_secs(5)).unwrap();
+    }
+}
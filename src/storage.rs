@@ -0,0 +1,363 @@
+use crate::api::{
+    ApiDeleteStorageObjectId, ApiReadStorageObjectId, ApiStorageObject, ApiStorageObjectList,
+    ApiWriteStorageObject,
+};
+use crate::client::Client;
+use crate::paginator::Paginator;
+use crate::session::Session;
+use crate::timestamp::Timestamp;
+use nanoserde::{DeJson, DeJsonErr, SerJson};
+use std::error::Error;
+use std::fmt::{Debug, Display, Formatter};
+use std::marker::PhantomData;
+
+/// Who can read a storage object.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(i32)]
+pub enum ReadPermission {
+    /// Only the server can read the object.
+    NoRead = 0,
+    /// Only the owner can read the object.
+    OwnerRead = 1,
+    /// Any user can read the object.
+    PublicRead = 2,
+}
+
+impl ReadPermission {
+    pub fn from_i32(permission: i32) -> Option<ReadPermission> {
+        match permission {
+            0 => Some(ReadPermission::NoRead),
+            1 => Some(ReadPermission::OwnerRead),
+            2 => Some(ReadPermission::PublicRead),
+            _ => None,
+        }
+    }
+}
+
+/// Who can write a storage object.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(i32)]
+pub enum WritePermission {
+    /// Only the server can write the object.
+    NoWrite = 0,
+    /// Only the owner can write the object.
+    OwnerWrite = 1,
+}
+
+impl WritePermission {
+    pub fn from_i32(permission: i32) -> Option<WritePermission> {
+        match permission {
+            0 => Some(WritePermission::NoWrite),
+            1 => Some(WritePermission::OwnerWrite),
+            _ => None,
+        }
+    }
+}
+
+/// A storage object with its value deserialized.
+#[derive(Debug, Clone)]
+pub struct StorageObject<T> {
+    pub collection: String,
+    pub key: String,
+    pub user_id: String,
+    pub value: T,
+    /// The version of the object, used for conditional writes.
+    pub version: String,
+    pub permission_read: ReadPermission,
+    pub permission_write: WritePermission,
+    pub create_time: Timestamp,
+    pub update_time: Timestamp,
+}
+
+impl<T: DeJson> StorageObject<T> {
+    fn from_api(object: ApiStorageObject) -> Result<StorageObject<T>, DeJsonErr> {
+        Ok(StorageObject {
+            value: T::deserialize_json(&object.value)?,
+            // Nakama only uses the values above, fall back to its defaults otherwise
+            permission_read: ReadPermission::from_i32(object.permission_read)
+                .unwrap_or(ReadPermission::OwnerRead),
+            permission_write: WritePermission::from_i32(object.permission_write)
+                .unwrap_or(WritePermission::OwnerWrite),
+            collection: object.collection,
+            key: object.key,
+            user_id: object.user_id,
+            version: object.version,
+            create_time: object.create_time.unwrap_or_default(),
+            update_time: object.update_time.unwrap_or_default(),
+        })
+    }
+}
+
+pub enum StorageError<C: Client> {
+    ClientError(C::Error),
+    /// The value of a storage object could not be deserialized.
+    JsonError(DeJsonErr),
+    /// The object was changed concurrently during every attempt of a conditional write.
+    VersionConflict,
+    /// The user ID could not be read from the auth token of the session.
+    InvalidSession,
+}
+
+impl<C: Client> Debug for StorageError<C> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StorageError::ClientError(err) => std::fmt::Debug::fmt(err, f),
+            StorageError::JsonError(err) => std::fmt::Debug::fmt(err, f),
+            StorageError::VersionConflict => write!(f, "VersionConflict"),
+            StorageError::InvalidSession => write!(f, "InvalidSession"),
+        }
+    }
+}
+
+impl<C: Client> Display for StorageError<C> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Debug::fmt(self, f)
+    }
+}
+
+impl<C: Client> Error for StorageError<C> {}
+
+/// A storage collection whose values are (de)serialized as `T`.
+///
+/// ```
+/// # use nakama_rs::client::Client;
+/// # use nakama_rs::session::Session;
+/// # use nakama_rs::storage::{ReadPermission, StorageCollection, StorageError, WritePermission};
+/// # use nanoserde::{DeJson, SerJson};
+/// #[derive(DeJson, SerJson)]
+/// struct Card {
+///     name: String,
+///     power: i32,
+/// }
+///
+/// async fn add_card<C: Client + Sync + 'static>(
+///     client: &C,
+///     session: &mut Session,
+/// ) -> Result<(), StorageError<C>> {
+///     let mut cards = StorageCollection::<Card>::new("cards");
+///     cards.set_permissions(ReadPermission::PublicRead, WritePermission::OwnerWrite);
+///
+///     let card = Card {
+///         name: "Dragon".to_owned(),
+///         power: 9,
+///     };
+///     cards.put(client, session, "dragon", &card).await?;
+///     let card = cards.get(client, session, "dragon").await?.unwrap();
+///     assert_eq!(card.value.power, 9);
+///     Ok(())
+/// }
+/// ```
+pub struct StorageCollection<T> {
+    collection: String,
+    permission_read: ReadPermission,
+    permission_write: WritePermission,
+    max_retries: u32,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T: SerJson + DeJson> StorageCollection<T> {
+    /// The collection `collection`. Objects are written with `OwnerRead` and `OwnerWrite`
+    /// permissions by default.
+    pub fn new(collection: &str) -> StorageCollection<T> {
+        StorageCollection {
+            collection: collection.to_owned(),
+            permission_read: ReadPermission::OwnerRead,
+            permission_write: WritePermission::OwnerWrite,
+            max_retries: 5,
+            _marker: PhantomData,
+        }
+    }
+
+    /// The permissions of objects written with `put`.
+    pub fn set_permissions(&mut self, read: ReadPermission, write: WritePermission) {
+        self.permission_read = read;
+        self.permission_write = write;
+    }
+
+    /// How often `update` retries after a version conflict. Defaults to 5.
+    pub fn set_max_retries(&mut self, max_retries: u32) {
+        self.max_retries = max_retries;
+    }
+
+    pub fn collection(&self) -> &str {
+        &self.collection
+    }
+
+    /// Read the object `key` of the authenticated user.
+    pub async fn get<C: Client>(
+        &self,
+        client: &C,
+        session: &mut Session,
+        key: &str,
+    ) -> Result<Option<StorageObject<T>>, StorageError<C>> {
+        let user_id = session.user_id().ok_or(StorageError::InvalidSession)?;
+        self.get_for_user(client, session, &user_id, key).await
+    }
+
+    /// Read the object `key` of the user `user_id`.
+    pub async fn get_for_user<C: Client>(
+        &self,
+        client: &C,
+        session: &mut Session,
+        user_id: &str,
+        key: &str,
+    ) -> Result<Option<StorageObject<T>>, StorageError<C>> {
+        let id = ApiReadStorageObjectId {
+            collection: self.collection.clone(),
+            key: key.to_owned(),
+            user_id: user_id.to_owned(),
+        };
+        let objects = client
+            .read_storage_objects(session, &[id])
+            .await
+            .map_err(StorageError::ClientError)?;
+        objects
+            .objects
+            .into_iter()
+            .next()
+            .map(StorageObject::from_api)
+            .transpose()
+            .map_err(StorageError::JsonError)
+    }
+
+    /// Write the object `key` of the authenticated user, returning its new version.
+    pub async fn put<C: Client>(
+        &self,
+        client: &C,
+        session: &mut Session,
+        key: &str,
+        value: &T,
+    ) -> Result<String, StorageError<C>> {
+        self.put_version(client, session, key, value, "").await
+    }
+
+    /// Write the object `key` of the authenticated user only if it does not exist yet,
+    /// returning its version.
+    pub async fn create<C: Client>(
+        &self,
+        client: &C,
+        session: &mut Session,
+        key: &str,
+        value: &T,
+    ) -> Result<String, StorageError<C>> {
+        self.put_version(client, session, key, value, "*").await
+    }
+
+    /// Write the object `key` of the authenticated user only if its current version is `version`,
+    /// returning its new version. An empty `version` writes unconditionally and `"*"` only
+    /// creates the object.
+    pub async fn put_version<C: Client>(
+        &self,
+        client: &C,
+        session: &mut Session,
+        key: &str,
+        value: &T,
+        version: &str,
+    ) -> Result<String, StorageError<C>> {
+        let object = ApiWriteStorageObject {
+            collection: self.collection.clone(),
+            key: key.to_owned(),
+            permission_read: self.permission_read as i32,
+            permission_write: self.permission_write as i32,
+            value: value.serialize_json(),
+            version: version.to_owned(),
+        };
+        let acks = client
+            .write_storage_objects(session, &[object])
+            .await
+            .map_err(StorageError::ClientError)?;
+        Ok(acks
+            .acks
+            .into_iter()
+            .next()
+            .map(|ack| ack.version)
+            .unwrap_or_default())
+    }
+
+    /// Read the object `key` of the authenticated user, replace its value with the result of
+    /// `update` and write it back if it was not changed in the meantime.
+    ///
+    /// Returns the written value and its new version. `update` receives `None` if the object
+    /// does not exist. On a version conflict, the object
+    /// is read again and `update` is called with the new value, up to `max_retries` times.
+    pub async fn update<C: Client, F: FnMut(Option<T>) -> T>(
+        &self,
+        client: &C,
+        session: &mut Session,
+        key: &str,
+        mut update: F,
+    ) -> Result<(T, String), StorageError<C>> {
+        let mut current = self.get(client, session, key).await?;
+        for _ in 0..=self.max_retries {
+            let version = match &current {
+                Some(object) => object.version.clone(),
+                None => "*".to_owned(),
+            };
+            let value = update(current.map(|object| object.value));
+            match self
+                .put_version(client, session, key, &value, &version)
+                .await
+            {
+                Ok(version) => return Ok((value, version)),
+                Err(err) => {
+                    // The adapter's error can't tell a version conflict apart, so check whether
+                    // the object was changed instead
+                    current = self.get(client, session, key).await?;
+                    let current_version = current.as_ref().map(|object| object.version.as_str());
+                    if current_version.unwrap_or("*") == version {
+                        return Err(err);
+                    }
+                }
+            }
+        }
+        Err(StorageError::VersionConflict)
+    }
+
+    /// Delete the object `key` of the authenticated user.
+    pub async fn delete<C: Client>(
+        &self,
+        client: &C,
+        session: &mut Session,
+        key: &str,
+    ) -> Result<(), StorageError<C>> {
+        let id = ApiDeleteStorageObjectId {
+            collection: self.collection.clone(),
+            key: key.to_owned(),
+            version: "".to_owned(),
+        };
+        client
+            .delete_storage_objects(session, &[id])
+            .await
+            .map_err(StorageError::ClientError)
+    }
+
+    /// All objects of the authenticated user in the collection.
+    pub async fn list<C: Client + Sync + 'static>(
+        &self,
+        client: &C,
+        session: &mut Session,
+    ) -> Result<Vec<StorageObject<T>>, StorageError<C>> {
+        let user_id = session.user_id().ok_or(StorageError::InvalidSession)?;
+        self.list_for_user(client, session, &user_id).await
+    }
+
+    /// All objects of the user `user_id` in the collection that can be read.
+    pub async fn list_for_user<C: Client + Sync + 'static>(
+        &self,
+        client: &C,
+        session: &mut Session,
+        user_id: &str,
+    ) -> Result<Vec<StorageObject<T>>, StorageError<C>> {
+        let mut paginator: Paginator<C, ApiStorageObjectList> =
+            Paginator::users_storage_objects(&self.collection, user_id);
+        let objects = paginator
+            .collect(client, session)
+            .await
+            .map_err(StorageError::ClientError)?;
+        objects
+            .into_iter()
+            .map(StorageObject::from_api)
+            .collect::<Result<_, _>>()
+            .map_err(StorageError::JsonError)
+    }
+}
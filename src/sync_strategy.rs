@@ -0,0 +1,49 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+
+/// Decides how `WebSocket` shares its state and which callbacks it accepts.
+///
+/// `Threaded` allows the socket and its futures to be sent between threads, but requires
+/// callbacks to be `Send`. `Local` accepts any callback but keeps the socket on a single thread,
+/// which is useful for WASM or single-threaded executors.
+pub trait SyncStrategy: Sized + 'static {
+    type Shared<T>: Clone;
+    type Callback<E: 'static>: Fn(E) + ?Sized;
+
+    fn new_shared<T>(value: T) -> Self::Shared<T>;
+
+    fn with<T, R>(shared: &Self::Shared<T>, f: impl FnOnce(&mut T) -> R) -> R;
+}
+
+/// Shares state using `Arc<Mutex<..>>`. Callbacks need to be `Send`.
+pub struct Threaded;
+
+impl SyncStrategy for Threaded {
+    type Shared<T> = Arc<Mutex<T>>;
+    type Callback<E: 'static> = dyn Fn(E) + Send + 'static;
+
+    fn new_shared<T>(value: T) -> Self::Shared<T> {
+        Arc::new(Mutex::new(value))
+    }
+
+    fn with<T, R>(shared: &Self::Shared<T>, f: impl FnOnce(&mut T) -> R) -> R {
+        f(&mut shared.lock().expect("panic inside other mutex!"))
+    }
+}
+
+/// Shares state using `Rc<RefCell<..>>`. Callbacks don't need to be `Send`.
+pub struct Local;
+
+impl SyncStrategy for Local {
+    type Shared<T> = Rc<RefCell<T>>;
+    type Callback<E: 'static> = dyn Fn(E) + 'static;
+
+    fn new_shared<T>(value: T) -> Self::Shared<T> {
+        Rc::new(RefCell::new(value))
+    }
+
+    fn with<T, R>(shared: &Self::Shared<T>, f: impl FnOnce(&mut T) -> R) -> R {
+        f(&mut shared.borrow_mut())
+    }
+}
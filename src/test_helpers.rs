@@ -1,14 +1,59 @@
 use crate::client::Client;
 use crate::default_client::DefaultClient;
-use crate::helper::tick_socket;
+use crate::helper::SocketDriverBuilder;
 use crate::socket::Socket;
 use crate::web_socket::WebSocket;
 
 use crate::api::{ApiAccount, ApiGroup};
+#[cfg(feature = "isahc-adapter")]
 use crate::http_adapter::RestHttpAdapter;
 use crate::session::Session;
+#[cfg(not(feature = "isahc-adapter"))]
+use crate::std_http_adapter::StdHttpAdapter;
+#[cfg(not(feature = "qws-adapter"))]
+use crate::std_web_socket_adapter::StdWebSocketAdapter;
+#[cfg(feature = "qws-adapter")]
 use crate::web_socket_adapter::WebSocketAdapter;
+use fake_server::FakeServer;
 use std::collections::HashMap;
+use std::sync::OnceLock;
+
+pub mod fake_server;
+
+/// The adapter of the test clients, `StdHttpAdapter` without the `isahc-adapter` feature.
+#[cfg(feature = "isahc-adapter")]
+pub type TestHttpAdapter = RestHttpAdapter;
+#[cfg(not(feature = "isahc-adapter"))]
+pub type TestHttpAdapter = StdHttpAdapter;
+
+/// The adapter of the test sockets, `StdWebSocketAdapter` without the `qws-adapter` feature.
+#[cfg(feature = "qws-adapter")]
+pub type TestSocketAdapter = WebSocketAdapter;
+#[cfg(not(feature = "qws-adapter"))]
+pub type TestSocketAdapter = StdWebSocketAdapter;
+
+/// The fake server shared by all tests of a test binary. Started on first use.
+pub fn fake_server() -> &'static FakeServer {
+    static SERVER: OnceLock<FakeServer> = OnceLock::new();
+    SERVER.get_or_init(FakeServer::start)
+}
+
+/// A client connected to the shared fake server.
+pub fn client() -> DefaultClient<TestHttpAdapter> {
+    DefaultClient::new(TestHttpAdapter::new(
+        "http://127.0.0.1",
+        fake_server().port(),
+    ))
+}
+
+/// A socket connected to the shared fake server.
+pub fn socket() -> WebSocket<TestSocketAdapter> {
+    WebSocket::new_with_server(
+        TestSocketAdapter::new(),
+        "ws://127.0.0.1",
+        fake_server().port(),
+    )
+}
 
 pub async fn remove_group_if_exists<C: Client>(
     client: &C,
@@ -40,8 +85,8 @@ pub async fn re_create_group<C: Client>(
         .unwrap()
 }
 
-pub async fn authenticated_client(id_one: &str) -> (DefaultClient<RestHttpAdapter>, Session) {
-    let client = DefaultClient::new_with_adapter();
+pub async fn authenticated_client(id_one: &str) -> (DefaultClient<TestHttpAdapter>, Session) {
+    let client = client();
     let session = client
         .authenticate_device(id_one, Some(id_one.clone()), true, HashMap::new())
         .await
@@ -54,8 +99,8 @@ pub async fn clients_with_users(
     id_one: &str,
     id_two: &str,
     id_three: &str,
-) -> (DefaultClient<RestHttpAdapter>, Session, Session, Session) {
-    let client = DefaultClient::new_with_adapter();
+) -> (DefaultClient<TestHttpAdapter>, Session, Session, Session) {
+    let client = client();
     let session = client
         .authenticate_device(id_one, Some(id_one.clone()), true, HashMap::new())
         .await
@@ -76,16 +121,19 @@ pub async fn sockets_with_users(
     id_one: &str,
     id_two: &str,
 ) -> (
-    WebSocket<WebSocketAdapter>,
-    WebSocket<WebSocketAdapter>,
+    WebSocket<TestSocketAdapter>,
+    WebSocket<TestSocketAdapter>,
     ApiAccount,
     ApiAccount,
 ) {
-    let client = DefaultClient::new_with_adapter();
-    let socket = WebSocket::new_with_adapter();
-    let socket2 = WebSocket::new_with_adapter();
-    tick_socket(&socket);
-    tick_socket(&socket2);
+    let client = client();
+    let (socket, socket2) = (self::socket(), self::socket());
+    // The driver stops once both sockets were dropped
+    SocketDriverBuilder::new()
+        .socket(&socket)
+        .socket(&socket2)
+        .spawn()
+        .detach();
 
     let mut session = client
         .authenticate_device(id_one, Some(id_one.clone()), true, HashMap::new())
@@ -0,0 +1,719 @@
+use crate::api::{
+    ApiAccount, ApiChannelMessage, ApiGroup, ApiNotification, ApiStorageObject, ApiTournament,
+    ApiUser,
+};
+use crate::std_http_adapter::{header, read_http_head};
+use crate::timestamp::Timestamp;
+use nanoserde::{DeJson, SerJson};
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::BuildHasher;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::spawn;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+mod realtime;
+mod rest;
+
+/// A server function registered with `FakeServer::register_rpc`. Receives the user id and the
+/// payload and returns the response payload or an error message.
+type Rpc = Box<dyn Fn(&str, &str) -> Result<String, String> + Send>;
+
+/// An in-memory Nakama server for tests.
+///
+/// The server listens on a random local port and implements the REST routes of `api` and the
+/// realtime messages of `/ws` without any persistence: accounts, sessions, friends, groups,
+/// storage, leaderboards, tournaments, notifications, chat channels, relayed matches, parties and
+/// a simple matchmaker. The server key is `defaultkey`.
+///
+/// Two tournaments, `example-tournament` and `weekly-tournament`, exist from the start.
+/// Leaderboards are created on the first write. The rpc `echo` returns its payload and sends it
+/// to the caller as a notification.
+/// ```
+/// # use nakama_rs::default_client::DefaultClient;
+/// # use nakama_rs::std_http_adapter::StdHttpAdapter;
+/// # use nakama_rs::std_web_socket_adapter::StdWebSocketAdapter;
+/// # use nakama_rs::test_helpers::fake_server::FakeServer;
+/// # use nakama_rs::web_socket::WebSocket;
+/// let server = FakeServer::start();
+/// let client = DefaultClient::new(StdHttpAdapter::new("http://127.0.0.1", server.port()));
+/// let socket = WebSocket::new_with_server(
+///     StdWebSocketAdapter::new(),
+///     "ws://127.0.0.1",
+///     server.port(),
+/// );
+/// ```
+pub struct FakeServer {
+    port: i32,
+    state: Arc<Mutex<State>>,
+    stopped: Arc<AtomicBool>,
+}
+
+impl FakeServer {
+    /// Start listening on a random port of 127.0.0.1.
+    pub fn start() -> FakeServer {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind fake server");
+        let port = listener
+            .local_addr()
+            .expect("Failed to get fake server address")
+            .port();
+        let state = Arc::new(Mutex::new(State::new()));
+        let stopped = Arc::new(AtomicBool::new(false));
+
+        spawn({
+            let state = state.clone();
+            let stopped = stopped.clone();
+            move || {
+                for stream in listener.incoming() {
+                    if stopped.load(Ordering::SeqCst) {
+                        return;
+                    }
+                    if let Ok(stream) = stream {
+                        let state = state.clone();
+                        spawn(move || handle_connection(stream, state));
+                    }
+                }
+            }
+        });
+
+        FakeServer {
+            port: port as i32,
+            state,
+            stopped,
+        }
+    }
+
+    pub fn port(&self) -> i32 {
+        self.port
+    }
+
+    /// Register a server function that can be called with `Client::rpc` and `Socket::rpc`.
+    pub fn register_rpc<F>(&self, id: &str, rpc: F)
+    where
+        F: Fn(&str, &str) -> Result<String, String> + Send + 'static,
+    {
+        let mut state = self.state.lock().expect("panic inside other mutex!");
+        state.rpcs.insert(id.to_owned(), Box::new(rpc));
+    }
+}
+
+impl Drop for FakeServer {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::SeqCst);
+        // Unblock the accepting thread
+        let _ = TcpStream::connect(("127.0.0.1", self.port as u16));
+    }
+}
+
+/// A HTTP request received by the fake server.
+struct Request {
+    method: String,
+    path: String,
+    query: Vec<(String, String)>,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+impl Request {
+    fn query(&self, name: &str) -> Option<&str> {
+        self.query
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn query_all(&self, name: &str) -> Vec<&str> {
+        self.query
+            .iter()
+            .filter(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+            .collect()
+    }
+
+    fn query_bool(&self, name: &str, default: bool) -> bool {
+        match self.query(name) {
+            Some(value) => value == "true",
+            None => default,
+        }
+    }
+
+    fn query_i32(&self, name: &str) -> Result<Option<i32>, ApiError> {
+        match self.query(name) {
+            Some(value) => value
+                .parse()
+                .map(Some)
+                .map_err(|_| ApiError::invalid_argument(&format!("Invalid {}.", name))),
+            None => Ok(None),
+        }
+    }
+
+    fn limit(&self) -> Result<usize, ApiError> {
+        match self.query_i32("limit")? {
+            Some(limit) if !(1..=100).contains(&limit) => Err(ApiError::invalid_argument(
+                "Invalid limit - limit must be between 1 and 100.",
+            )),
+            Some(limit) => Ok(limit as usize),
+            None => Ok(100),
+        }
+    }
+
+    fn header(&self, name: &str) -> Option<&str> {
+        header(&self.headers, name)
+    }
+}
+
+fn parse_target(target: &str) -> (String, Vec<(String, String)>) {
+    let (path, query) = match target.find('?') {
+        Some(index) => (&target[..index], &target[index + 1..]),
+        None => (target, ""),
+    };
+    let query = query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = match pair.find('=') {
+                Some(index) => (&pair[..index], &pair[index + 1..]),
+                None => (pair, ""),
+            };
+            (decode(key), decode(value))
+        })
+        .collect();
+    (decode(path), query)
+}
+
+fn decode(value: &str) -> String {
+    urlencoding::decode(value)
+        .map(|value| value.into_owned())
+        .unwrap_or_else(|_| value.to_owned())
+}
+
+fn handle_connection(stream: TcpStream, state: Arc<Mutex<State>>) {
+    let mut writer = match stream.try_clone() {
+        Ok(writer) => writer,
+        Err(_) => return,
+    };
+    let mut reader = BufReader::new(stream);
+
+    loop {
+        let (request_line, headers) = match read_http_head(&mut reader) {
+            Ok(head) => head,
+            Err(_) => return,
+        };
+        let mut parts = request_line.split_whitespace();
+        let method = parts.next().unwrap_or_default().to_owned();
+        let (path, query) = parse_target(parts.next().unwrap_or_default());
+
+        let is_upgrade = header(&headers, "Upgrade")
+            .map(|upgrade| upgrade.eq_ignore_ascii_case("websocket"))
+            .unwrap_or(false);
+        if is_upgrade {
+            let request = Request {
+                method,
+                path,
+                query,
+                headers,
+                body: String::new(),
+            };
+            realtime::serve(reader, writer, &request, state);
+            return;
+        }
+
+        if header(&headers, "Expect")
+            .map(|expect| expect.eq_ignore_ascii_case("100-continue"))
+            .unwrap_or(false)
+            && writer.write_all(b"HTTP/1.1 100 Continue\r\n\r\n").is_err()
+        {
+            return;
+        }
+
+        let body = match read_body(&mut reader, &headers) {
+            Ok(body) => body,
+            Err(_) => return,
+        };
+        let request = Request {
+            method,
+            path,
+            query,
+            headers,
+            body,
+        };
+
+        let response = state
+            .lock()
+            .expect("panic inside other mutex!")
+            .handle_request(&request);
+        let (status, body) = match response {
+            Ok(body) => (200, body),
+            Err(error) => (error.status, error.body()),
+        };
+
+        let head = format!(
+            "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n",
+            status,
+            reason(status),
+            body.len()
+        );
+        if writer.write_all(head.as_bytes()).is_err() || writer.write_all(body.as_bytes()).is_err()
+        {
+            return;
+        }
+
+        let close = request
+            .header("Connection")
+            .map(|connection| connection.eq_ignore_ascii_case("close"))
+            .unwrap_or(false);
+        if close {
+            return;
+        }
+    }
+}
+
+fn read_body<R: BufRead>(reader: &mut R, headers: &[(String, String)]) -> io::Result<String> {
+    let length = header(headers, "Content-Length")
+        .and_then(|length| length.trim().parse().ok())
+        .unwrap_or(0);
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+    String::from_utf8(body).map_err(io::Error::other)
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        409 => "Conflict",
+        501 => "Not Implemented",
+        _ => "Internal Server Error",
+    }
+}
+
+#[derive(SerJson)]
+struct ErrorBody {
+    error: String,
+    code: i32,
+    message: String,
+}
+
+/// An error of the REST API. `code` is the gRPC status code sent by Nakama.
+#[derive(Debug)]
+struct ApiError {
+    status: u16,
+    code: i32,
+    message: String,
+}
+
+impl ApiError {
+    fn new(status: u16, code: i32, message: &str) -> ApiError {
+        ApiError {
+            status,
+            code,
+            message: message.to_owned(),
+        }
+    }
+
+    fn invalid_argument(message: &str) -> ApiError {
+        ApiError::new(400, 3, message)
+    }
+
+    fn not_found(message: &str) -> ApiError {
+        ApiError::new(404, 5, message)
+    }
+
+    fn already_exists(message: &str) -> ApiError {
+        ApiError::new(409, 6, message)
+    }
+
+    fn permission_denied(message: &str) -> ApiError {
+        ApiError::new(403, 7, message)
+    }
+
+    fn failed_precondition(message: &str) -> ApiError {
+        ApiError::new(400, 9, message)
+    }
+
+    fn internal(message: &str) -> ApiError {
+        ApiError::new(500, 13, message)
+    }
+
+    fn unauthenticated(message: &str) -> ApiError {
+        ApiError::new(401, 16, message)
+    }
+
+    fn body(&self) -> String {
+        ErrorBody {
+            error: self.message.clone(),
+            code: self.code,
+            message: self.message.clone(),
+        }
+        .serialize_json()
+    }
+}
+
+fn parse_json<T: DeJson>(body: &str) -> Result<T, ApiError> {
+    let body = if body.trim().is_empty() { "{}" } else { body };
+    T::deserialize_json(body)
+        .map_err(|_| ApiError::invalid_argument("Unable to parse request body."))
+}
+
+fn is_json_object(value: &str) -> bool {
+    let value = value.trim();
+    value.starts_with('{') && value.ends_with('}')
+}
+
+/// Returns the items of the page starting at the offset stored in `cursor` and the cursors of
+/// the next and previous page, which are empty if there is no such page.
+fn paginate<T: Clone>(
+    items: &[T],
+    limit: usize,
+    cursor: Option<&str>,
+) -> Result<(Vec<T>, String, String), ApiError> {
+    let offset = match cursor {
+        Some(cursor) if !cursor.is_empty() => base64::decode(cursor)
+            .ok()
+            .and_then(|offset| String::from_utf8(offset).ok())
+            .and_then(|offset| offset.parse::<usize>().ok())
+            .ok_or_else(|| ApiError::invalid_argument("Malformed cursor was used."))?,
+        _ => 0,
+    };
+    let page = items.iter().skip(offset).take(limit).cloned().collect();
+    let next = if offset + limit < items.len() {
+        encode_cursor(offset + limit)
+    } else {
+        String::new()
+    };
+    let prev = if offset > 0 {
+        encode_cursor(offset.saturating_sub(limit))
+    } else {
+        String::new()
+    };
+    Ok((page, next, prev))
+}
+
+fn encode_cursor(offset: usize) -> String {
+    base64::encode(offset.to_string())
+}
+
+fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or(Duration::ZERO)
+        .as_secs()
+}
+
+/// The current time, for the time fields of API structs.
+fn now() -> Option<Timestamp> {
+    Some(Timestamp::now())
+}
+
+struct Account {
+    account: ApiAccount,
+    password: String,
+}
+
+struct FriendEdge {
+    user_id: String,
+    friend_id: String,
+    state: i32,
+    update_time: Option<Timestamp>,
+}
+
+struct Group {
+    group: ApiGroup,
+    // User ids and their state: superadmin, admin, member or join request
+    members: Vec<(String, i32)>,
+    banned: Vec<String>,
+}
+
+struct Record {
+    owner_id: String,
+    username: String,
+    score: i64,
+    subscore: i64,
+    num_score: i32,
+    metadata: String,
+    create_time: Option<Timestamp>,
+    update_time: Option<Timestamp>,
+    // Orders records with the same score by the time they were written
+    sequence: u64,
+}
+
+struct Leaderboard {
+    id: String,
+    operator: i32,
+    ascending: bool,
+    records: Vec<Record>,
+    tournament: Option<Tournament>,
+}
+
+struct Tournament {
+    tournament: ApiTournament,
+    join_required: bool,
+    joined: Vec<String>,
+}
+
+struct Notification {
+    sequence: u64,
+    user_id: String,
+    notification: ApiNotification,
+}
+
+/// All data of the fake server. Requests are handled one at a time while holding the lock.
+struct State {
+    hasher: RandomState,
+    sequence: u64,
+    accounts: Vec<Account>,
+    tokens: HashMap<String, String>,
+    refresh_tokens: HashMap<String, (String, HashMap<String, String>)>,
+    friends: Vec<FriendEdge>,
+    groups: Vec<Group>,
+    storage: Vec<ApiStorageObject>,
+    leaderboards: Vec<Leaderboard>,
+    notifications: Vec<Notification>,
+    messages: Vec<ApiChannelMessage>,
+    rpcs: HashMap<String, Rpc>,
+    realtime: realtime::Realtime,
+}
+
+impl State {
+    fn new() -> State {
+        let mut state = State {
+            hasher: RandomState::new(),
+            sequence: 0,
+            accounts: Vec::new(),
+            tokens: HashMap::new(),
+            refresh_tokens: HashMap::new(),
+            friends: Vec::new(),
+            groups: Vec::new(),
+            storage: Vec::new(),
+            leaderboards: Vec::new(),
+            notifications: Vec::new(),
+            messages: Vec::new(),
+            rpcs: HashMap::new(),
+            realtime: realtime::Realtime::default(),
+        };
+        state.add_tournament("example-tournament", "Example tournament", 1);
+        state.add_tournament("weekly-tournament", "Weekly tournament", 2);
+        state
+    }
+
+    fn add_tournament(&mut self, id: &str, title: &str, category: i32) {
+        let start = unix_time();
+        self.leaderboards.push(Leaderboard {
+            id: id.to_owned(),
+            operator: 1,
+            ascending: false,
+            records: Vec::new(),
+            tournament: Some(Tournament {
+                tournament: ApiTournament {
+                    id: id.to_owned(),
+                    title: title.to_owned(),
+                    category,
+                    can_enter: true,
+                    create_time: Some(Timestamp::from_unix(start as i64, 0)),
+                    start_time: Some(Timestamp::from_unix(start as i64, 0)),
+                    start_active: start as i32,
+                    max_size: 10000,
+                    max_num_score: 1000000,
+                    metadata: "{}".to_owned(),
+                    ..Default::default()
+                },
+                join_required: true,
+                joined: Vec::new(),
+            }),
+        });
+    }
+
+    fn next_sequence(&mut self) -> u64 {
+        self.sequence += 1;
+        self.sequence
+    }
+
+    fn random(&mut self) -> u64 {
+        let sequence = self.next_sequence();
+        self.hasher.hash_one(sequence)
+    }
+
+    /// A new random UUID.
+    fn new_id(&mut self) -> String {
+        let high = self.random();
+        let low = self.random();
+        format!(
+            "{:08x}-{:04x}-4{:03x}-{:04x}-{:012x}",
+            high >> 32,
+            (high >> 16) & 0xFFFF,
+            high & 0xFFF,
+            0x8000 | (low >> 48) & 0x3FFF,
+            low & 0xFFFF_FFFF_FFFF
+        )
+    }
+
+    fn account(&self, user_id: &str) -> Option<&Account> {
+        self.accounts
+            .iter()
+            .find(|account| account.account.user.id == user_id)
+    }
+
+    fn account_mut(&mut self, user_id: &str) -> Option<&mut Account> {
+        self.accounts
+            .iter_mut()
+            .find(|account| account.account.user.id == user_id)
+    }
+
+    /// The user with the fields that are computed on request.
+    fn user(&self, user_id: &str) -> Option<ApiUser> {
+        self.account(user_id).map(|account| ApiUser {
+            online: self.realtime.is_online(user_id),
+            edge_count: self
+                .friends
+                .iter()
+                .filter(|edge| edge.user_id == user_id && edge.state == 0)
+                .count() as i32,
+            ..account.account.user.clone()
+        })
+    }
+
+    fn user_by_username(&self, username: &str) -> Option<ApiUser> {
+        self.accounts
+            .iter()
+            .find(|account| account.account.user.username == username)
+            .and_then(|account| self.user(&account.account.user.id))
+    }
+
+    fn notify(
+        &mut self,
+        user_id: &str,
+        subject: &str,
+        content: &str,
+        code: i32,
+        sender_id: &str,
+        persistent: bool,
+    ) {
+        let notification = ApiNotification {
+            id: self.new_id(),
+            subject: subject.to_owned(),
+            content: content.to_owned(),
+            code,
+            sender_id: sender_id.to_owned(),
+            create_time: now(),
+            persistent,
+        };
+        self.realtime.notify(user_id, &notification);
+        if persistent {
+            let sequence = self.next_sequence();
+            self.notifications.push(Notification {
+                sequence,
+                user_id: user_id.to_owned(),
+                notification,
+            });
+        }
+    }
+
+    fn rpc(&mut self, user_id: &str, id: &str, payload: &str) -> Result<String, ApiError> {
+        if id == "echo" {
+            let content = format!("{{\"payload\":{}}}", payload.to_owned().serialize_json());
+            self.notify(user_id, "echo", &content, 1, "", true);
+            return Ok(payload.to_owned());
+        }
+
+        match self.rpcs.get(id) {
+            Some(rpc) => rpc(user_id, payload).map_err(|message| ApiError::internal(&message)),
+            None => Err(ApiError::not_found("RPC function not found")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{paginate, FakeServer};
+    use crate::client::Client;
+    use crate::default_client::DefaultClient;
+    use crate::socket::{ChannelJoinType, Socket};
+    use crate::std_web_socket_adapter::StdWebSocketAdapter;
+    use crate::test_helpers::TestHttpAdapter;
+    use crate::web_socket::WebSocket;
+    use futures::executor::block_on;
+    use std::collections::HashMap;
+    use std::sync::mpsc;
+    use std::thread::{sleep, spawn};
+    use std::time::Duration;
+
+    #[test]
+    fn test_paginate() {
+        let items = [1, 2, 3];
+        let (page, next, prev) = paginate(&items, 2, None).unwrap();
+        assert_eq!(page, vec![1, 2]);
+        assert_eq!(prev, "");
+        let (page, next, prev) = paginate(&items, 2, Some(&next)).unwrap();
+        assert_eq!(page, vec![3]);
+        assert_eq!(next, "");
+        assert!(!prev.is_empty());
+        assert!(paginate(&items, 2, Some("not a cursor")).is_err());
+    }
+
+    #[test]
+    fn test_rest_and_realtime() {
+        let server = FakeServer::start();
+        server.register_rpc("reverse", |_, payload| Ok(payload.chars().rev().collect()));
+        let client = DefaultClient::new(TestHttpAdapter::new("http://127.0.0.1", server.port()));
+
+        block_on(async {
+            assert!(client
+                .authenticate_device("fakedevice", None, false, HashMap::new())
+                .await
+                .is_err());
+            let mut session = client
+                .authenticate_device("fakedevice", Some("FakeUser"), true, HashMap::new())
+                .await
+                .unwrap();
+            let account = client.get_account(&mut session).await.unwrap();
+            assert_eq!(account.user.username, "FakeUser");
+            assert_eq!(account.devices[0].id, "fakedevice");
+
+            let rpc = client
+                .rpc(&mut session, "reverse", Some("abc"))
+                .await
+                .unwrap();
+            assert_eq!(rpc.payload, "cba");
+
+            let socket = WebSocket::new_with_server(
+                StdWebSocketAdapter::new(),
+                "ws://127.0.0.1",
+                server.port(),
+            );
+            let (tx, rx) = mpsc::channel();
+            socket.on_received_notification(move |notification| {
+                tx.send(notification).unwrap();
+            });
+            spawn({
+                let socket = socket.clone();
+                move || {
+                    for _ in 0..200 {
+                        socket.tick();
+                        sleep(Duration::from_millis(10));
+                    }
+                }
+            });
+            socket.connect(&mut session, true, -1).await;
+
+            let channel = socket
+                .join_chat("FakeRoom", ChannelJoinType::Room, false, false)
+                .await
+                .unwrap();
+            assert_eq!(channel.id, "2...FakeRoom");
+            assert_eq!(channel.presences.len(), 1);
+
+            client
+                .rpc(&mut session, "echo", Some("Hello"))
+                .await
+                .unwrap();
+            let notification = rx.recv_timeout(Duration::from_secs(2)).unwrap();
+            assert_eq!(notification.subject, "echo");
+        });
+    }
+}
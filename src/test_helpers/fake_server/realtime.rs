@@ -0,0 +1,1506 @@
+use super::{is_json_object, now, ApiError, Request, State};
+use crate::api::{ApiChannelMessage, ApiMatch, ApiNotification, ApiNotificationList, ApiRpc};
+use crate::socket::{
+    Channel, ChannelJoin, ChannelJoinType, ChannelMessageAck, ChannelPresenceEvent, Error,
+    ErrorCode, Match, MatchData, MatchDataSend, MatchJoin, MatchPresenceEvent, MatchmakerAdd,
+    MatchmakerMatched, MatchmakerTicket, MatchmakerUser, Party, PartyClose, PartyDataProxy,
+    PartyDataSend, PartyJoinRequest, PartyLeader, PartyMatchmakerAdd, PartyMatchmakerTicket,
+    PartyPresenceEvent, Status, StatusFollow, StatusPresenceEvent, UserPresence,
+    WebSocketMessageEnvelope,
+};
+use crate::std_web_socket_adapter::{
+    accept_key, read_frame, write_frame, OPCODE_BINARY, OPCODE_CLOSE, OPCODE_CONTINUATION,
+    OPCODE_PING, OPCODE_PONG, OPCODE_TEXT,
+};
+use nanoserde::{DeJson, SerJson};
+use std::collections::HashMap;
+use std::io::{BufReader, Write};
+use std::net::TcpStream;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+struct Connection {
+    session_id: String,
+    user_id: String,
+    username: String,
+    // `None` if the user doesn't appear online
+    status: Option<String>,
+    stream: TcpStream,
+}
+
+struct ChannelMember {
+    session_id: String,
+    hidden: bool,
+    persistence: bool,
+}
+
+struct ChatChannel {
+    id: String,
+    room_name: String,
+    group_id: String,
+    user_id_one: String,
+    user_id_two: String,
+    members: Vec<ChannelMember>,
+}
+
+struct RelayedMatch {
+    id: String,
+    members: Vec<String>,
+}
+
+struct PartyState {
+    id: String,
+    open: bool,
+    max_size: i32,
+    leader: String,
+    members: Vec<String>,
+    requests: Vec<String>,
+}
+
+struct Ticket {
+    ticket: String,
+    party_id: String,
+    sessions: Vec<String>,
+    min_count: i32,
+    max_count: i32,
+    string_properties: HashMap<String, String>,
+    numeric_properties: HashMap<String, f64>,
+}
+
+/// The sockets connected to the fake server and the realtime state shared between them.
+#[derive(Default)]
+pub(super) struct Realtime {
+    connections: Vec<Connection>,
+    channels: Vec<ChatChannel>,
+    matches: Vec<RelayedMatch>,
+    // The match ids of the tokens sent by the matchmaker
+    match_tokens: HashMap<String, String>,
+    parties: Vec<PartyState>,
+    tickets: Vec<Ticket>,
+    // Session ids and the user ids they follow
+    follows: Vec<(String, String)>,
+}
+
+impl Realtime {
+    fn connection(&self, session_id: &str) -> Option<&Connection> {
+        self.connections
+            .iter()
+            .find(|connection| connection.session_id == session_id)
+    }
+
+    fn user_id(&self, session_id: &str) -> String {
+        self.connection(session_id)
+            .map(|connection| connection.user_id.clone())
+            .unwrap_or_default()
+    }
+
+    fn presence(&self, session_id: &str) -> UserPresence {
+        match self.connection(session_id) {
+            Some(connection) => UserPresence {
+                persistence: false,
+                session_id: connection.session_id.clone(),
+                status: String::new(),
+                username: connection.username.clone(),
+                user_id: connection.user_id.clone(),
+            },
+            None => UserPresence::default(),
+        }
+    }
+
+    fn presences(&self, session_ids: &[String]) -> Vec<UserPresence> {
+        session_ids
+            .iter()
+            .map(|session_id| self.presence(session_id))
+            .collect()
+    }
+
+    fn status_presence(&self, connection: &Connection) -> UserPresence {
+        UserPresence {
+            status: connection.status.clone().unwrap_or_default(),
+            ..self.presence(&connection.session_id)
+        }
+    }
+
+    fn send_frame(&self, session_id: &str, opcode: u8, payload: &[u8]) {
+        if let Some(connection) = self.connection(session_id) {
+            // Write errors are noticed by the reading thread, which cleans up
+            let _ = write_frame(&mut &connection.stream, true, opcode, payload, None);
+        }
+    }
+
+    fn send_json(&self, session_id: &str, json: &str) {
+        self.send_frame(session_id, OPCODE_TEXT, json.as_bytes());
+    }
+
+    fn send(&self, session_id: &str, envelope: &WebSocketMessageEnvelope) {
+        self.send_json(session_id, &envelope.serialize_json());
+    }
+
+    fn send_all(&self, session_ids: &[String], envelope: &WebSocketMessageEnvelope) {
+        let json = envelope.serialize_json();
+        for session_id in session_ids {
+            self.send_json(session_id, &json);
+        }
+    }
+
+    pub(super) fn is_online(&self, user_id: &str) -> bool {
+        self.connections
+            .iter()
+            .any(|connection| connection.user_id == user_id)
+    }
+
+    pub(super) fn notify(&self, user_id: &str, notification: &ApiNotification) {
+        let envelope = WebSocketMessageEnvelope {
+            notifications: Some(ApiNotificationList {
+                notifications: vec![notification.clone()],
+                cacheable_cursor: String::new(),
+            }),
+            ..Default::default()
+        };
+        for connection in &self.connections {
+            if connection.user_id == user_id {
+                self.send(&connection.session_id, &envelope);
+            }
+        }
+    }
+
+    pub(super) fn matches(&self) -> Vec<ApiMatch> {
+        self.matches
+            .iter()
+            .map(|relayed| ApiMatch {
+                match_id: relayed.id.clone(),
+                authoritative: false,
+                size: relayed.members.len() as i32,
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    /// Send a status presence event to the followers and other sockets of `user_id`.
+    fn send_status_presence(&self, user_id: &str, event: StatusPresenceEvent) {
+        let envelope = WebSocketMessageEnvelope {
+            status_presence_event: Some(event),
+            ..Default::default()
+        };
+        let followers = self
+            .follows
+            .iter()
+            .filter(|(_, followed)| followed == user_id)
+            .map(|(session_id, _)| session_id.as_str());
+        let own = self
+            .connections
+            .iter()
+            .filter(|connection| connection.user_id == user_id)
+            .map(|connection| connection.session_id.as_str());
+        let mut recipients: Vec<&str> = followers.chain(own).collect();
+        recipients.sort_unstable();
+        recipients.dedup();
+        for session_id in recipients {
+            self.send(session_id, &envelope);
+        }
+    }
+
+    fn channel_index(&self, channel_id: &str, session_id: &str) -> Result<usize, Error> {
+        self.channels
+            .iter()
+            .position(|channel| {
+                channel.id == channel_id
+                    && channel
+                        .members
+                        .iter()
+                        .any(|member| member.session_id == session_id)
+            })
+            .ok_or_else(|| error(ErrorCode::BadInput, "Must join channel first."))
+    }
+
+    fn channel_presence(&self, member: &ChannelMember) -> UserPresence {
+        UserPresence {
+            persistence: member.persistence,
+            ..self.presence(&member.session_id)
+        }
+    }
+
+    fn send_channel_presence(
+        &self,
+        channel: &ChatChannel,
+        joins: Vec<UserPresence>,
+        leaves: Vec<UserPresence>,
+        recipients: &[String],
+    ) {
+        let envelope = WebSocketMessageEnvelope {
+            channel_presence_event: Some(ChannelPresenceEvent {
+                channel_id: channel.id.clone(),
+                joins,
+                leaves,
+                room_name: channel.room_name.clone(),
+                group_id: channel.group_id.clone(),
+                user_id_one: channel.user_id_one.clone(),
+                user_id_two: channel.user_id_two.clone(),
+            }),
+            ..Default::default()
+        };
+        self.send_all(recipients, &envelope);
+    }
+
+    fn leave_channel(&mut self, index: usize, session_id: &str) {
+        let channel = &mut self.channels[index];
+        let position = channel
+            .members
+            .iter()
+            .position(|member| member.session_id == session_id);
+        if let Some(position) = position {
+            let member = channel.members.remove(position);
+            let channel = &self.channels[index];
+            if !member.hidden {
+                let mut recipients = channel_sessions(channel);
+                recipients.push(member.session_id.clone());
+                let leave = self.channel_presence(&member);
+                self.send_channel_presence(channel, Vec::new(), vec![leave], &recipients);
+            }
+            if channel.members.is_empty() {
+                self.channels.remove(index);
+            }
+        }
+    }
+
+    fn match_index(&self, match_id: &str) -> Result<usize, Error> {
+        self.matches
+            .iter()
+            .position(|relayed| relayed.id == match_id)
+            .ok_or_else(|| error(ErrorCode::MatchNotFound, "Match not found."))
+    }
+
+    fn send_match_presence(
+        &self,
+        relayed: &RelayedMatch,
+        joins: Vec<UserPresence>,
+        leaves: Vec<UserPresence>,
+        except: &str,
+    ) {
+        let envelope = WebSocketMessageEnvelope {
+            match_presence_event: Some(MatchPresenceEvent {
+                match_id: relayed.id.clone(),
+                joins,
+                leaves,
+            }),
+            ..Default::default()
+        };
+        for session_id in &relayed.members {
+            if session_id != except {
+                self.send(session_id, &envelope);
+            }
+        }
+    }
+
+    fn leave_match(&mut self, index: usize, session_id: &str) {
+        self.matches[index]
+            .members
+            .retain(|member| member != session_id);
+        let leave = self.presence(session_id);
+        self.send_match_presence(&self.matches[index], Vec::new(), vec![leave], session_id);
+        if self.matches[index].members.is_empty() {
+            self.matches.remove(index);
+        }
+    }
+
+    fn party_index(&self, party_id: &str) -> Result<usize, Error> {
+        self.parties
+            .iter()
+            .position(|party| party.id == party_id)
+            .ok_or_else(|| error(ErrorCode::BadInput, "Party not found."))
+    }
+
+    fn party_leader_index(&self, party_id: &str, session_id: &str) -> Result<usize, Error> {
+        let index = self.party_index(party_id)?;
+        if self.parties[index].leader != session_id {
+            return Err(error(
+                ErrorCode::BadInput,
+                "Only the party leader can do this.",
+            ));
+        }
+        Ok(index)
+    }
+
+    fn party(&self, party: &PartyState, session_id: &str) -> Party {
+        Party {
+            party_id: party.id.clone(),
+            open: party.open,
+            max_size: party.max_size,
+            _self: self.presence(session_id),
+            leader: self.presence(&party.leader),
+            presences: self.presences(&party.members),
+        }
+    }
+
+    fn send_party_presence(
+        &self,
+        party: &PartyState,
+        joins: Vec<UserPresence>,
+        leaves: Vec<UserPresence>,
+        extra: Option<&str>,
+    ) {
+        let envelope = WebSocketMessageEnvelope {
+            party_presence_event: Some(PartyPresenceEvent {
+                party_id: party.id.clone(),
+                joins,
+                leaves,
+            }),
+            ..Default::default()
+        };
+        self.send_all(&party.members, &envelope);
+        if let Some(session_id) = extra {
+            self.send(session_id, &envelope);
+        }
+    }
+
+    fn send_party_leader(&self, party: &PartyState) {
+        let envelope = WebSocketMessageEnvelope {
+            party_leader: Some(PartyLeader {
+                party_id: party.id.clone(),
+                presence: self.presence(&party.leader),
+            }),
+            ..Default::default()
+        };
+        self.send_all(&party.members, &envelope);
+    }
+
+    /// Remove a member or join request from the party. Closes the party if it's empty.
+    fn remove_from_party(&mut self, index: usize, session_id: &str) {
+        let party = &mut self.parties[index];
+        party.requests.retain(|request| request != session_id);
+        if !party.members.iter().any(|member| member == session_id) {
+            return;
+        }
+        party.members.retain(|member| member != session_id);
+        let party_id = party.id.clone();
+        self.tickets.retain(|ticket| ticket.party_id != party_id);
+
+        let leave = self.presence(session_id);
+        let party = &self.parties[index];
+        self.send_party_presence(party, Vec::new(), vec![leave], Some(session_id));
+        if party.members.is_empty() {
+            self.parties.remove(index);
+        } else if party.leader == session_id {
+            let leader = party.members[0].clone();
+            self.parties[index].leader = leader;
+            self.send_party_leader(&self.parties[index]);
+        }
+    }
+
+    fn matchmaker_user(&self, session_id: &str, ticket: &Ticket) -> MatchmakerUser {
+        MatchmakerUser {
+            presence: self.presence(session_id),
+            party_id: ticket.party_id.clone(),
+            string_properties: ticket.string_properties.clone(),
+            numeric_properties: ticket.numeric_properties.clone(),
+        }
+    }
+}
+
+fn channel_sessions(channel: &ChatChannel) -> Vec<String> {
+    channel
+        .members
+        .iter()
+        .map(|member| member.session_id.clone())
+        .collect()
+}
+
+fn error(code: ErrorCode, message: &str) -> Error {
+    Error {
+        code,
+        message: message.to_owned(),
+        context: HashMap::new(),
+    }
+}
+
+fn ack() -> WebSocketMessageEnvelope {
+    WebSocketMessageEnvelope::default()
+}
+
+/// Handle a `/ws` upgrade request and the messages of the socket until it is closed.
+pub(super) fn serve(
+    mut reader: BufReader<TcpStream>,
+    mut writer: TcpStream,
+    request: &Request,
+    state: Arc<Mutex<State>>,
+) {
+    let user_id = request.query("token").and_then(|token| {
+        state
+            .lock()
+            .expect("panic inside other mutex!")
+            .user_of_token(token)
+    });
+    let (user_id, key) = match (user_id, request.header("Sec-WebSocket-Key")) {
+        (Some(user_id), Some(key)) => (user_id, key),
+        _ => {
+            let body = ApiError::unauthenticated("Auth token invalid").body();
+            let response = format!(
+                "HTTP/1.1 401 Unauthorized\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            let _ = writer.write_all(response.as_bytes());
+            return;
+        }
+    };
+
+    let response = format!(
+        "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+        accept_key(key)
+    );
+    if writer.write_all(response.as_bytes()).is_err() {
+        return;
+    }
+    // Don't block the whole server if a client stops reading
+    let _ = writer.set_write_timeout(Some(Duration::from_secs(5)));
+
+    let appear_online = request.query_bool("status", false);
+    let session_id =
+        state
+            .lock()
+            .expect("panic inside other mutex!")
+            .connect(&user_id, writer, appear_online);
+
+    let mut message = Vec::new();
+    while let Ok(frame) = read_frame(&mut reader) {
+        let mut state = state.lock().expect("panic inside other mutex!");
+        match frame.opcode {
+            OPCODE_TEXT | OPCODE_BINARY | OPCODE_CONTINUATION => {
+                message.extend(frame.payload);
+                if frame.fin {
+                    let text = String::from_utf8_lossy(&message).into_owned();
+                    message.clear();
+                    state.handle_envelope(&session_id, &text);
+                }
+            }
+            OPCODE_PING => state
+                .realtime
+                .send_frame(&session_id, OPCODE_PONG, &frame.payload),
+            OPCODE_CLOSE => {
+                state
+                    .realtime
+                    .send_frame(&session_id, OPCODE_CLOSE, &frame.payload);
+                break;
+            }
+            _ => {}
+        }
+    }
+
+    state
+        .lock()
+        .expect("panic inside other mutex!")
+        .disconnect(&session_id);
+}
+
+impl State {
+    fn connect(&mut self, user_id: &str, stream: TcpStream, appear_online: bool) -> String {
+        let session_id = self.new_id();
+        let username = self
+            .account(user_id)
+            .map(|account| account.account.user.username.clone())
+            .unwrap_or_default();
+        self.realtime.connections.push(Connection {
+            session_id: session_id.clone(),
+            user_id: user_id.to_owned(),
+            username,
+            status: if appear_online {
+                Some(String::new())
+            } else {
+                None
+            },
+            stream,
+        });
+
+        if appear_online {
+            let presence = self.realtime.presence(&session_id);
+            self.realtime.send_status_presence(
+                user_id,
+                StatusPresenceEvent {
+                    joins: vec![presence],
+                    leaves: Vec::new(),
+                },
+            );
+        }
+        session_id
+    }
+
+    fn disconnect(&mut self, session_id: &str) {
+        let realtime = &mut self.realtime;
+        while let Some(index) = realtime.channels.iter().position(|channel| {
+            channel
+                .members
+                .iter()
+                .any(|member| member.session_id == session_id)
+        }) {
+            realtime.leave_channel(index, session_id);
+        }
+        while let Some(index) = realtime
+            .matches
+            .iter()
+            .position(|relayed| relayed.members.iter().any(|member| member == session_id))
+        {
+            realtime.leave_match(index, session_id);
+        }
+        while let Some(index) = realtime.parties.iter().position(|party| {
+            party.members.iter().any(|member| member == session_id)
+                || party.requests.iter().any(|request| request == session_id)
+        }) {
+            realtime.remove_from_party(index, session_id);
+        }
+        realtime
+            .tickets
+            .retain(|ticket| !ticket.sessions.iter().any(|session| session == session_id));
+        realtime
+            .follows
+            .retain(|(follower, _)| follower != session_id);
+
+        if let Some(position) = realtime
+            .connections
+            .iter()
+            .position(|connection| connection.session_id == session_id)
+        {
+            let connection = realtime.connections.remove(position);
+            if connection.status.is_some() {
+                let presence = realtime.status_presence(&connection);
+                realtime.send_status_presence(
+                    &connection.user_id,
+                    StatusPresenceEvent {
+                        joins: Vec::new(),
+                        leaves: vec![presence],
+                    },
+                );
+            }
+        }
+    }
+
+    fn handle_envelope(&mut self, session_id: &str, text: &str) {
+        let envelope = match WebSocketMessageEnvelope::deserialize_json(text) {
+            Ok(envelope) => envelope,
+            Err(_) => {
+                let envelope = WebSocketMessageEnvelope {
+                    error: Some(error(
+                        ErrorCode::UnrecognizedPayload,
+                        "Could not unmarshal request.",
+                    )),
+                    ..Default::default()
+                };
+                self.realtime.send(session_id, &envelope);
+                return;
+            }
+        };
+
+        let cid = envelope.cid.clone();
+        let response = match self.handle_message(session_id, envelope) {
+            Ok(Some(response)) => Some(response),
+            Ok(None) => None,
+            Err(error) => Some(WebSocketMessageEnvelope {
+                error: Some(error),
+                ..Default::default()
+            }),
+        };
+        if let Some(mut response) = response {
+            // Acknowledgements are only sent to requests that wait for them
+            if cid.is_some() || response.serialize_json() != "{}" {
+                response.cid = cid;
+                self.realtime.send(session_id, &response);
+            }
+        }
+
+        self.matchmake();
+    }
+
+    fn handle_message(
+        &mut self,
+        session_id: &str,
+        envelope: WebSocketMessageEnvelope,
+    ) -> Result<Option<WebSocketMessageEnvelope>, Error> {
+        let me = self.realtime.user_id(session_id);
+
+        if let Some(join) = envelope.channel_join {
+            return self.channel_join(session_id, &me, join).map(Some);
+        }
+        if let Some(leave) = envelope.channel_leave {
+            let index = self.realtime.channel_index(&leave.channel_id, session_id)?;
+            self.realtime.leave_channel(index, session_id);
+            return Ok(Some(ack()));
+        }
+        if let Some(send) = envelope.channel_message_send {
+            return self
+                .channel_message(session_id, &send.channel_id, None, &send.content)
+                .map(Some);
+        }
+        if let Some(update) = envelope.channel_message_update {
+            return self
+                .channel_message(
+                    session_id,
+                    &update.channel_id,
+                    Some(&update.message_id),
+                    &update.content,
+                )
+                .map(Some);
+        }
+        if let Some(remove) = envelope.channel_message_remove {
+            return self
+                .channel_message_remove(session_id, &remove.channel_id, &remove.message_id)
+                .map(Some);
+        }
+        if envelope.match_create.is_some() {
+            let id = format!("{}.", self.new_id());
+            self.realtime.matches.push(RelayedMatch {
+                id: id.clone(),
+                members: Vec::new(),
+            });
+            return self.match_join(session_id, &id).map(Some);
+        }
+        if let Some(join) = envelope.match_join {
+            return self.match_join_request(session_id, join).map(Some);
+        }
+        if let Some(leave) = envelope.match_leave {
+            let index = self.realtime.match_index(&leave.match_id)?;
+            self.realtime.leave_match(index, session_id);
+            return Ok(Some(ack()));
+        }
+        if let Some(send) = envelope.match_data_send {
+            self.match_data(session_id, send)?;
+            return Ok(None);
+        }
+        if let Some(add) = envelope.matchmaker_add {
+            return self.matchmaker_add(session_id, add).map(Some);
+        }
+        if let Some(remove) = envelope.matchmaker_remove {
+            let position = self.realtime.tickets.iter().position(|ticket| {
+                ticket.ticket == remove.ticket
+                    && ticket.party_id.is_empty()
+                    && ticket.sessions.iter().any(|session| session == session_id)
+            });
+            return match position {
+                Some(position) => {
+                    self.realtime.tickets.remove(position);
+                    Ok(Some(ack()))
+                }
+                None => Err(error(ErrorCode::BadInput, "Matchmaker ticket not found.")),
+            };
+        }
+        if let Some(create) = envelope.party_create {
+            return self
+                .party_create(session_id, create.open, create.max_size)
+                .map(Some);
+        }
+        if let Some(join) = envelope.party_join {
+            return self.party_join(session_id, &join.party_id).map(Some);
+        }
+        if let Some(leave) = envelope.party_leave {
+            let index = self.realtime.party_index(&leave.party_id)?;
+            self.realtime.remove_from_party(index, session_id);
+            return Ok(Some(ack()));
+        }
+        if let Some(promote) = envelope.party_promote {
+            let index = self
+                .realtime
+                .party_leader_index(&promote.party_id, session_id)?;
+            let party = &mut self.realtime.parties[index];
+            if !party.members.contains(&promote.presence.session_id) {
+                return Err(error(ErrorCode::BadInput, "Party member not found."));
+            }
+            party.leader = promote.presence.session_id;
+            self.realtime
+                .send_party_leader(&self.realtime.parties[index]);
+            return Ok(Some(ack()));
+        }
+        if let Some(accept) = envelope.party_accept {
+            return self
+                .party_accept(session_id, &accept.party_id, &accept.presence.session_id)
+                .map(Some);
+        }
+        if let Some(remove) = envelope.party_remove {
+            let index = self
+                .realtime
+                .party_leader_index(&remove.party_id, session_id)?;
+            let target = remove.presence.session_id;
+            let party = &self.realtime.parties[index];
+            if !party.members.contains(&target) && !party.requests.contains(&target) {
+                return Err(error(ErrorCode::BadInput, "Party member not found."));
+            }
+            self.realtime.remove_from_party(index, &target);
+            return Ok(Some(ack()));
+        }
+        if let Some(close) = envelope.party_close {
+            let index = self
+                .realtime
+                .party_leader_index(&close.party_id, session_id)?;
+            let party = self.realtime.parties.remove(index);
+            self.realtime
+                .tickets
+                .retain(|ticket| ticket.party_id != party.id);
+            let envelope = WebSocketMessageEnvelope {
+                party_close: Some(PartyClose { party_id: party.id }),
+                ..Default::default()
+            };
+            self.realtime.send_all(&party.members, &envelope);
+            return Ok(Some(ack()));
+        }
+        if let Some(list) = envelope.party_join_request_list {
+            let index = self
+                .realtime
+                .party_leader_index(&list.party_id, session_id)?;
+            let party = &self.realtime.parties[index];
+            return Ok(Some(WebSocketMessageEnvelope {
+                party_join_request: Some(PartyJoinRequest {
+                    party_id: party.id.clone(),
+                    presences: self.realtime.presences(&party.requests),
+                }),
+                ..Default::default()
+            }));
+        }
+        if let Some(send) = envelope.party_data_send {
+            self.party_data(session_id, send)?;
+            return Ok(None);
+        }
+        if let Some(add) = envelope.party_matchmaker_add {
+            return self.party_matchmaker_add(session_id, add).map(Some);
+        }
+        if let Some(remove) = envelope.party_matchmaker_remove {
+            self.realtime
+                .party_leader_index(&remove.party_id, session_id)?;
+            let position = self.realtime.tickets.iter().position(|ticket| {
+                ticket.ticket == remove.ticket && ticket.party_id == remove.party_id
+            });
+            return match position {
+                Some(position) => {
+                    self.realtime.tickets.remove(position);
+                    Ok(Some(ack()))
+                }
+                None => Err(error(ErrorCode::BadInput, "Matchmaker ticket not found.")),
+            };
+        }
+        if let Some(follow) = envelope.status_follow {
+            return Ok(Some(self.status_follow(session_id, follow)));
+        }
+        if let Some(unfollow) = envelope.status_unfollow {
+            self.realtime.follows.retain(|(follower, followed)| {
+                follower != session_id || !unfollow.user_ids.contains(followed)
+            });
+            return Ok(Some(ack()));
+        }
+        if let Some(update) = envelope.status_update {
+            self.status_update(session_id, update.status);
+            return Ok(Some(ack()));
+        }
+        if let Some(rpc) = envelope.rpc {
+            return match self.rpc(&me, &rpc.id, &rpc.payload) {
+                Ok(payload) => Ok(Some(WebSocketMessageEnvelope {
+                    rpc: Some(ApiRpc {
+                        id: rpc.id,
+                        payload,
+                        http_key: String::new(),
+                    }),
+                    ..Default::default()
+                })),
+                Err(err) if err.code == 5 => {
+                    Err(error(ErrorCode::RuntimeFunctionNotFound, &err.message))
+                }
+                Err(err) => Err(error(ErrorCode::RuntimeFunctionException, &err.message)),
+            };
+        }
+
+        Err(error(
+            ErrorCode::UnrecognizedPayload,
+            "Unrecognized message.",
+        ))
+    }
+
+    fn channel_join(
+        &mut self,
+        session_id: &str,
+        me: &str,
+        join: ChannelJoin,
+    ) -> Result<WebSocketMessageEnvelope, Error> {
+        let target = join.target;
+        let (id, room_name, group_id, user_id_one, user_id_two) = match join.channel_type {
+            ChannelJoinType::Room => {
+                if target.is_empty() || target.len() > 64 {
+                    return Err(error(
+                        ErrorCode::BadInput,
+                        "Invalid room name, must be 1-64 bytes.",
+                    ));
+                }
+                (
+                    format!("2...{}", target),
+                    target,
+                    String::new(),
+                    String::new(),
+                    String::new(),
+                )
+            }
+            ChannelJoinType::DirectMessage => {
+                if target == me || self.account(&target).is_none() {
+                    return Err(error(ErrorCode::BadInput, "Invalid user ID."));
+                }
+                let (one, two) = if me < target.as_str() {
+                    (me.to_owned(), target)
+                } else {
+                    (target, me.to_owned())
+                };
+                (
+                    format!("4.{}.{}.", one, two),
+                    String::new(),
+                    String::new(),
+                    one,
+                    two,
+                )
+            }
+            ChannelJoinType::Group => {
+                let is_member = self
+                    .group_member_state(&target, me)
+                    .is_some_and(|state| state <= 2);
+                if !is_member {
+                    return Err(error(
+                        ErrorCode::BadInput,
+                        "Must be a member of the group to join its channel.",
+                    ));
+                }
+                (
+                    format!("3.{}..", target),
+                    String::new(),
+                    target,
+                    String::new(),
+                    String::new(),
+                )
+            }
+            _ => return Err(error(ErrorCode::BadInput, "Unrecognized channel type.")),
+        };
+
+        let index = match self
+            .realtime
+            .channels
+            .iter()
+            .position(|channel| channel.id == id)
+        {
+            Some(index) => index,
+            None => {
+                self.realtime.channels.push(ChatChannel {
+                    id: id.clone(),
+                    room_name,
+                    group_id,
+                    user_id_one,
+                    user_id_two,
+                    members: Vec::new(),
+                });
+                self.realtime.channels.len() - 1
+            }
+        };
+
+        let member = ChannelMember {
+            session_id: session_id.to_owned(),
+            hidden: join.hidden,
+            persistence: join.persistence,
+        };
+        let presence = self.realtime.channel_presence(&member);
+        let channel = &mut self.realtime.channels[index];
+        let joined = !channel
+            .members
+            .iter()
+            .any(|member| member.session_id == session_id);
+        if joined {
+            channel.members.push(member);
+        }
+
+        let channel = &self.realtime.channels[index];
+        if joined && !join.hidden {
+            let recipients = channel_sessions(channel);
+            self.realtime.send_channel_presence(
+                channel,
+                vec![presence.clone()],
+                Vec::new(),
+                &recipients,
+            );
+        }
+        let response = Channel {
+            id: channel.id.clone(),
+            presences: channel
+                .members
+                .iter()
+                .filter(|member| !member.hidden)
+                .map(|member| self.realtime.channel_presence(member))
+                .collect(),
+            _self: presence,
+            room_name: channel.room_name.clone(),
+            group_id: channel.group_id.clone(),
+            user_id_one: channel.user_id_one.clone(),
+            user_id_two: channel.user_id_two.clone(),
+        };
+
+        // Invite the other user of a direct message
+        if join.channel_type == ChannelJoinType::DirectMessage {
+            let other = if response.user_id_one == me {
+                response.user_id_two.clone()
+            } else {
+                response.user_id_one.clone()
+            };
+            let other_joined = channel
+                .members
+                .iter()
+                .any(|member| self.realtime.user_id(&member.session_id) == other);
+            if joined && !other_joined {
+                let subject = format!(
+                    "{} wants to chat",
+                    self.realtime.presence(session_id).username
+                );
+                self.notify(&other, &subject, "{}", -1, me, true);
+            }
+        }
+
+        Ok(WebSocketMessageEnvelope {
+            channel: Some(response),
+            ..Default::default()
+        })
+    }
+
+    /// Send a new message or update the message with `message_id`.
+    fn channel_message(
+        &mut self,
+        session_id: &str,
+        channel_id: &str,
+        message_id: Option<&str>,
+        content: &str,
+    ) -> Result<WebSocketMessageEnvelope, Error> {
+        let index = self.realtime.channel_index(channel_id, session_id)?;
+        if !is_json_object(content) {
+            return Err(error(
+                ErrorCode::BadInput,
+                "Message content must be a valid JSON object.",
+            ));
+        }
+        let presence = self.realtime.presence(session_id);
+        let channel = &self.realtime.channels[index];
+
+        let message = match message_id {
+            Some(message_id) => {
+                let message = self
+                    .messages
+                    .iter_mut()
+                    .find(|message| {
+                        message.channel_id == channel_id
+                            && message.message_id == message_id
+                            && message.sender_id == presence.user_id
+                    })
+                    .ok_or_else(|| error(ErrorCode::BadInput, "Message not found."))?;
+                message.content = content.to_owned();
+                message.code = 1;
+                message.update_time = now();
+                message.clone()
+            }
+            None => {
+                let persistent = channel
+                    .members
+                    .iter()
+                    .any(|member| member.session_id == session_id && member.persistence);
+                let message = ApiChannelMessage {
+                    channel_id: channel.id.clone(),
+                    message_id: String::new(),
+                    code: 0,
+                    sender_id: presence.user_id.clone(),
+                    username: presence.username.clone(),
+                    content: content.to_owned(),
+                    create_time: now(),
+                    update_time: now(),
+                    persistent,
+                    room_name: channel.room_name.clone(),
+                    group_id: channel.group_id.clone(),
+                    user_id_one: channel.user_id_one.clone(),
+                    user_id_two: channel.user_id_two.clone(),
+                };
+                let message = ApiChannelMessage {
+                    message_id: self.new_id(),
+                    ..message
+                };
+                self.messages.push(message.clone());
+                message
+            }
+        };
+
+        self.send_channel_message(index, &message);
+        Ok(message_ack(&message))
+    }
+
+    fn channel_message_remove(
+        &mut self,
+        session_id: &str,
+        channel_id: &str,
+        message_id: &str,
+    ) -> Result<WebSocketMessageEnvelope, Error> {
+        let index = self.realtime.channel_index(channel_id, session_id)?;
+        let user_id = self.realtime.user_id(session_id);
+        let position = self
+            .messages
+            .iter()
+            .position(|message| {
+                message.channel_id == channel_id
+                    && message.message_id == message_id
+                    && message.sender_id == user_id
+            })
+            .ok_or_else(|| error(ErrorCode::BadInput, "Message not found."))?;
+        let message = ApiChannelMessage {
+            code: 2,
+            content: "{}".to_owned(),
+            update_time: now(),
+            ..self.messages.remove(position)
+        };
+
+        self.send_channel_message(index, &message);
+        Ok(message_ack(&message))
+    }
+
+    fn send_channel_message(&self, index: usize, message: &ApiChannelMessage) {
+        let envelope = WebSocketMessageEnvelope {
+            channel_message: Some(message.clone()),
+            ..Default::default()
+        };
+        self.realtime
+            .send_all(&channel_sessions(&self.realtime.channels[index]), &envelope);
+    }
+
+    fn match_join_request(
+        &mut self,
+        session_id: &str,
+        join: MatchJoin,
+    ) -> Result<WebSocketMessageEnvelope, Error> {
+        let match_id = match (join.match_id, join.token) {
+            (Some(match_id), _) if !match_id.is_empty() => match_id,
+            (_, Some(token)) => {
+                let match_id = self
+                    .realtime
+                    .match_tokens
+                    .get(&token)
+                    .cloned()
+                    .ok_or_else(|| error(ErrorCode::MatchNotFound, "Invalid match token."))?;
+                // The match of a matchmaker token is created by the first user joining it
+                if self.realtime.match_index(&match_id).is_err() {
+                    self.realtime.matches.push(RelayedMatch {
+                        id: match_id.clone(),
+                        members: Vec::new(),
+                    });
+                }
+                match_id
+            }
+            _ => {
+                return Err(error(
+                    ErrorCode::BadInput,
+                    "A match ID or token is required.",
+                ))
+            }
+        };
+        self.match_join(session_id, &match_id)
+    }
+
+    fn match_join(
+        &mut self,
+        session_id: &str,
+        match_id: &str,
+    ) -> Result<WebSocketMessageEnvelope, Error> {
+        let index = self.realtime.match_index(match_id)?;
+        let presence = self.realtime.presence(session_id);
+        let relayed = &mut self.realtime.matches[index];
+        if !relayed.members.iter().any(|member| member == session_id) {
+            relayed.members.push(session_id.to_owned());
+            let relayed = &self.realtime.matches[index];
+            self.realtime.send_match_presence(
+                relayed,
+                vec![presence.clone()],
+                Vec::new(),
+                session_id,
+            );
+        }
+
+        let relayed = &self.realtime.matches[index];
+        Ok(WebSocketMessageEnvelope {
+            new_match: Some(Match {
+                match_id: relayed.id.clone(),
+                authoritative: false,
+                label: String::new(),
+                size: relayed.members.len() as i32,
+                presences: self.realtime.presences(&relayed.members),
+                _self: presence,
+            }),
+            ..Default::default()
+        })
+    }
+
+    fn match_data(&mut self, session_id: &str, send: MatchDataSend) -> Result<(), Error> {
+        let index = self.realtime.match_index(&send.match_id)?;
+        let relayed = &self.realtime.matches[index];
+        if !relayed.members.iter().any(|member| member == session_id) {
+            return Err(error(ErrorCode::BadInput, "Must join match first."));
+        }
+
+        let recipients: Vec<String> = relayed
+            .members
+            .iter()
+            .filter(|member| *member != session_id)
+            .filter(|member| {
+                send.presences.is_empty()
+                    || send
+                        .presences
+                        .iter()
+                        .any(|presence| presence.session_id == **member)
+            })
+            .cloned()
+            .collect();
+        let envelope = WebSocketMessageEnvelope {
+            match_data: Some(MatchData {
+                match_id: send.match_id,
+                presence: self.realtime.presence(session_id),
+                op_code: send.op_code,
+                data: send.data,
+                reliable: send.reliable,
+            }),
+            ..Default::default()
+        };
+        self.realtime.send_all(&recipients, &envelope);
+        Ok(())
+    }
+
+    fn check_counts(min_count: i32, max_count: i32) -> Result<(), Error> {
+        if min_count < 2 || max_count < min_count {
+            return Err(error(
+                ErrorCode::BadInput,
+                "Invalid counts, the minimum must be at least 2 and at most the maximum.",
+            ));
+        }
+        Ok(())
+    }
+
+    fn matchmaker_add(
+        &mut self,
+        session_id: &str,
+        add: MatchmakerAdd,
+    ) -> Result<WebSocketMessageEnvelope, Error> {
+        State::check_counts(add.min_count, add.max_count)?;
+        let ticket = self.new_id();
+        self.realtime.tickets.push(Ticket {
+            ticket: ticket.clone(),
+            party_id: String::new(),
+            sessions: vec![session_id.to_owned()],
+            min_count: add.min_count,
+            max_count: add.max_count,
+            string_properties: add.string_properties,
+            numeric_properties: add.numeric_properties,
+        });
+        Ok(WebSocketMessageEnvelope {
+            matchmaker_ticket: Some(MatchmakerTicket { ticket }),
+            ..Default::default()
+        })
+    }
+
+    fn party_matchmaker_add(
+        &mut self,
+        session_id: &str,
+        add: PartyMatchmakerAdd,
+    ) -> Result<WebSocketMessageEnvelope, Error> {
+        let index = self
+            .realtime
+            .party_leader_index(&add.party_id, session_id)?;
+        State::check_counts(add.min_count, add.max_count)?;
+        let ticket = self.new_id();
+        let sessions = self.realtime.parties[index].members.clone();
+        self.realtime.tickets.push(Ticket {
+            ticket: ticket.clone(),
+            party_id: add.party_id.clone(),
+            sessions,
+            min_count: add.min_count,
+            max_count: add.max_count,
+            string_properties: add.string_properties,
+            numeric_properties: add.numeric_properties,
+        });
+        Ok(WebSocketMessageEnvelope {
+            party_matchmaker_ticket: Some(PartyMatchmakerTicket {
+                party_id: add.party_id,
+                ticket,
+            }),
+            ..Default::default()
+        })
+    }
+
+    /// Match tickets in the order they were added. Queries are ignored.
+    fn matchmake(&mut self) {
+        loop {
+            let tickets = &self.realtime.tickets;
+            let mut selected: Vec<usize> = Vec::new();
+            let mut matched = false;
+            for (index, ticket) in tickets.iter().enumerate() {
+                let overlaps = selected.iter().any(|other| {
+                    tickets[*other]
+                        .sessions
+                        .iter()
+                        .any(|session| ticket.sessions.contains(session))
+                });
+                let count: usize = selected
+                    .iter()
+                    .map(|other| tickets[*other].sessions.len())
+                    .sum::<usize>()
+                    + ticket.sessions.len();
+                let max_count = selected
+                    .iter()
+                    .map(|other| tickets[*other].max_count)
+                    .chain(Some(ticket.max_count))
+                    .min()
+                    .unwrap_or_default();
+                if overlaps || count > max_count as usize {
+                    continue;
+                }
+                selected.push(index);
+
+                let min_count = selected
+                    .iter()
+                    .map(|other| tickets[*other].min_count)
+                    .max()
+                    .unwrap_or_default();
+                if count >= min_count as usize {
+                    matched = true;
+                    break;
+                }
+            }
+            if !matched {
+                return;
+            }
+
+            let token = self.new_id();
+            let match_id = format!("{}.", self.new_id());
+            self.realtime.match_tokens.insert(token.clone(), match_id);
+            let realtime = &self.realtime;
+            let users: Vec<MatchmakerUser> = selected
+                .iter()
+                .flat_map(|index| {
+                    let ticket = &realtime.tickets[*index];
+                    ticket
+                        .sessions
+                        .iter()
+                        .map(|session_id| realtime.matchmaker_user(session_id, ticket))
+                        .collect::<Vec<_>>()
+                })
+                .collect();
+            for index in &selected {
+                let ticket = &realtime.tickets[*index];
+                for session_id in &ticket.sessions {
+                    let envelope = WebSocketMessageEnvelope {
+                        matchmaker_matched: Some(MatchmakerMatched {
+                            ticket: ticket.ticket.clone(),
+                            match_id: None,
+                            token: Some(token.clone()),
+                            users: users.clone(),
+                            _self: realtime.matchmaker_user(session_id, ticket),
+                        }),
+                        ..Default::default()
+                    };
+                    realtime.send(session_id, &envelope);
+                }
+            }
+            let mut index = 0;
+            self.realtime.tickets.retain(|_| {
+                index += 1;
+                !selected.contains(&(index - 1))
+            });
+        }
+    }
+
+    fn party_create(
+        &mut self,
+        session_id: &str,
+        open: bool,
+        max_size: i32,
+    ) -> Result<WebSocketMessageEnvelope, Error> {
+        if !(1..=256).contains(&max_size) {
+            return Err(error(
+                ErrorCode::BadInput,
+                "Invalid party max size, must be 1-256.",
+            ));
+        }
+        let party = PartyState {
+            id: format!("{}.", self.new_id()),
+            open,
+            max_size,
+            leader: session_id.to_owned(),
+            members: vec![session_id.to_owned()],
+            requests: Vec::new(),
+        };
+        let presence = self.realtime.presence(session_id);
+        self.realtime
+            .send_party_presence(&party, vec![presence], Vec::new(), None);
+        let response = self.realtime.party(&party, session_id);
+        self.realtime.parties.push(party);
+        Ok(WebSocketMessageEnvelope {
+            party: Some(response),
+            ..Default::default()
+        })
+    }
+
+    fn party_join(
+        &mut self,
+        session_id: &str,
+        party_id: &str,
+    ) -> Result<WebSocketMessageEnvelope, Error> {
+        let index = self.realtime.party_index(party_id)?;
+        let party = &self.realtime.parties[index];
+        if party.members.iter().any(|member| member == session_id) {
+            return Ok(ack());
+        }
+        if party.open {
+            return self.party_add_member(index, session_id);
+        }
+
+        let party = &mut self.realtime.parties[index];
+        if !party.requests.iter().any(|request| request == session_id) {
+            party.requests.push(session_id.to_owned());
+        }
+        let envelope = WebSocketMessageEnvelope {
+            party_join_request: Some(PartyJoinRequest {
+                party_id: party_id.to_owned(),
+                presences: vec![self.realtime.presence(session_id)],
+            }),
+            ..Default::default()
+        };
+        self.realtime
+            .send(&self.realtime.parties[index].leader, &envelope);
+        Ok(ack())
+    }
+
+    fn party_accept(
+        &mut self,
+        session_id: &str,
+        party_id: &str,
+        target: &str,
+    ) -> Result<WebSocketMessageEnvelope, Error> {
+        let index = self.realtime.party_leader_index(party_id, session_id)?;
+        let party = &mut self.realtime.parties[index];
+        let position = party
+            .requests
+            .iter()
+            .position(|request| request == target)
+            .ok_or_else(|| error(ErrorCode::BadInput, "Join request not found."))?;
+        party.requests.remove(position);
+        self.party_add_member(index, target)
+    }
+
+    fn party_add_member(
+        &mut self,
+        index: usize,
+        session_id: &str,
+    ) -> Result<WebSocketMessageEnvelope, Error> {
+        let party = &mut self.realtime.parties[index];
+        if party.members.len() >= party.max_size as usize {
+            return Err(error(ErrorCode::BadInput, "Party is full."));
+        }
+        party.members.push(session_id.to_owned());
+
+        let party = &self.realtime.parties[index];
+        let presence = self.realtime.presence(session_id);
+        self.realtime
+            .send_party_presence(party, vec![presence], Vec::new(), None);
+        let envelope = WebSocketMessageEnvelope {
+            party: Some(self.realtime.party(party, session_id)),
+            ..Default::default()
+        };
+        self.realtime.send(session_id, &envelope);
+        Ok(ack())
+    }
+
+    fn party_data(&mut self, session_id: &str, send: PartyDataSend) -> Result<(), Error> {
+        let index = self.realtime.party_index(&send.party_id)?;
+        let party = &self.realtime.parties[index];
+        if !party.members.iter().any(|member| member == session_id) {
+            return Err(error(ErrorCode::BadInput, "Must join party first."));
+        }
+
+        // Nakama sends the op code as a string and the data base64 encoded
+        let data = PartyDataProxy {
+            party_id: send.party_id,
+            presence: self.realtime.presence(session_id),
+            op_code: send.op_code.to_string(),
+            data: send.data,
+        };
+        let json = format!("{{\"party_data\":{}}}", data.serialize_json());
+        for member in &party.members {
+            if member != session_id {
+                self.realtime.send_json(member, &json);
+            }
+        }
+        Ok(())
+    }
+
+    fn status_follow(
+        &mut self,
+        session_id: &str,
+        follow: StatusFollow,
+    ) -> WebSocketMessageEnvelope {
+        let user_ids: Vec<String> = self
+            .accounts
+            .iter()
+            .map(|account| &account.account.user)
+            .filter(|user| {
+                follow.user_ids.contains(&user.id) || follow.usernames.contains(&user.username)
+            })
+            .map(|user| user.id.clone())
+            .collect();
+        for user_id in &user_ids {
+            let follows = &mut self.realtime.follows;
+            if !follows
+                .iter()
+                .any(|(follower, followed)| follower == session_id && followed == user_id)
+            {
+                follows.push((session_id.to_owned(), user_id.clone()));
+            }
+        }
+
+        let presences = self
+            .realtime
+            .connections
+            .iter()
+            .filter(|connection| {
+                connection.status.is_some() && user_ids.contains(&connection.user_id)
+            })
+            .map(|connection| self.realtime.status_presence(connection))
+            .collect();
+        WebSocketMessageEnvelope {
+            status: Some(Status { presences }),
+            ..Default::default()
+        }
+    }
+
+    fn status_update(&mut self, session_id: &str, status: String) {
+        let realtime = &mut self.realtime;
+        let position = realtime
+            .connections
+            .iter()
+            .position(|connection| connection.session_id == session_id);
+        if let Some(position) = position {
+            let leaves = match realtime.connections[position].status {
+                Some(_) => vec![realtime.status_presence(&realtime.connections[position])],
+                None => Vec::new(),
+            };
+            realtime.connections[position].status = Some(status);
+            let connection = &realtime.connections[position];
+            let joins = vec![realtime.status_presence(connection)];
+            realtime
+                .send_status_presence(&connection.user_id, StatusPresenceEvent { joins, leaves });
+        }
+    }
+}
+
+fn message_ack(message: &ApiChannelMessage) -> WebSocketMessageEnvelope {
+    WebSocketMessageEnvelope {
+        channel_message_ack: Some(ChannelMessageAck {
+            channel_id: message.channel_id.clone(),
+            message_id: message.message_id.clone(),
+            code: message.code,
+            username: message.username.clone(),
+            create_time: message.create_time.unwrap_or_default(),
+            update_time: message.update_time.unwrap_or_default(),
+            persistent: message.persistent,
+            room_name: message.room_name.clone(),
+            group_id: message.group_id.clone(),
+            user_id_one: message.user_id_one.clone(),
+            user_id_two: message.user_id_two.clone(),
+        }),
+        ..Default::default()
+    }
+}
@@ -0,0 +1,1648 @@
+use super::{
+    encode_cursor, is_json_object, now, paginate, parse_json, unix_time, Account, ApiError,
+    FriendEdge, Group, Leaderboard, Record, Request, State,
+};
+use crate::api::{
+    ApiAccount, ApiAccountApple, ApiAccountCustom, ApiAccountDevice, ApiAccountEmail,
+    ApiAccountFacebookInstantGame, ApiAccountGameCenter, ApiChannelMessageList,
+    ApiCreateGroupRequest, ApiDeleteStorageObjectsRequest, ApiFriend, ApiFriendList, ApiGroup,
+    ApiGroupList, ApiGroupUserList, ApiLeaderboardRecord, ApiLeaderboardRecordList,
+    ApiListSubscriptionsRequest, ApiMatchList, ApiNotificationList, ApiOverrideOperator,
+    ApiReadStorageObjectsRequest, ApiRpc, ApiSession, ApiSessionLogoutRequest,
+    ApiSessionRefreshRequest, ApiStorageObject, ApiStorageObjectAck, ApiStorageObjectAcks,
+    ApiStorageObjectList, ApiStorageObjects, ApiSubscriptionList, ApiTournamentList,
+    ApiTournamentRecordList, ApiUpdateAccountRequest, ApiUpdateGroupRequest, ApiUser,
+    ApiUserGroupList, ApiUsers, ApiWriteStorageObjectsRequest, GroupUserListGroupUser,
+    UserGroupListUserGroup, WriteLeaderboardRecordRequestLeaderboardRecordWrite,
+    WriteTournamentRecordRequestTournamentRecordWrite,
+};
+use nanoserde::{DeJson, SerJson};
+use std::collections::HashMap;
+
+const SERVER_KEY: &str = "defaultkey";
+const HTTP_KEY: &str = "defaulthttpkey";
+// The response of requests without a response body
+const EMPTY: &str = "{}";
+
+const SUPERADMIN: i32 = 0;
+const ADMIN: i32 = 1;
+const MEMBER: i32 = 2;
+const JOIN_REQUEST: i32 = 3;
+
+const FRIEND: i32 = 0;
+const INVITE_SENT: i32 = 1;
+const INVITE_RECEIVED: i32 = 2;
+const BLOCKED: i32 = 3;
+
+/// The account identifier used to authenticate, link or unlink.
+enum Identity {
+    Device(String),
+    Email(String, String),
+    // The provider of the route and the id, which is the token for social providers
+    Provider(String, String),
+}
+
+fn identity(provider: &str, body: &str) -> Result<(Identity, HashMap<String, String>), ApiError> {
+    match provider {
+        "device" => {
+            let device: ApiAccountDevice = parse_json(body)?;
+            if !(10..=128).contains(&device.id.len()) {
+                return Err(ApiError::invalid_argument(
+                    "Device ID invalid, must be 10-128 bytes.",
+                ));
+            }
+            Ok((Identity::Device(device.id), device.vars))
+        }
+        "email" => {
+            let email: ApiAccountEmail = parse_json(body)?;
+            if !email.email.contains('@') || !(10..=255).contains(&email.email.len()) {
+                return Err(ApiError::invalid_argument(
+                    "Invalid email address, must be 10-255 bytes.",
+                ));
+            }
+            if email.password.len() < 8 {
+                return Err(ApiError::invalid_argument(
+                    "Password must be at least 8 characters long.",
+                ));
+            }
+            Ok((Identity::Email(email.email, email.password), email.vars))
+        }
+        "custom" => {
+            let custom: ApiAccountCustom = parse_json(body)?;
+            if !(6..=128).contains(&custom.id.len()) {
+                return Err(ApiError::invalid_argument(
+                    "Custom ID invalid, must be 6-128 bytes.",
+                ));
+            }
+            Ok((
+                Identity::Provider(provider.to_owned(), custom.id),
+                custom.vars,
+            ))
+        }
+        "apple" | "facebook" | "google" | "steam" => {
+            // All of them only contain a token
+            let account: ApiAccountApple = parse_json(body)?;
+            if account.token.is_empty() {
+                return Err(ApiError::invalid_argument("Access token is required."));
+            }
+            Ok((
+                Identity::Provider(provider.to_owned(), account.token),
+                account.vars,
+            ))
+        }
+        "gamecenter" => {
+            let account: ApiAccountGameCenter = parse_json(body)?;
+            if account.player_id.is_empty() {
+                return Err(ApiError::invalid_argument(
+                    "Game Center player ID is required.",
+                ));
+            }
+            Ok((
+                Identity::Provider(provider.to_owned(), account.player_id),
+                account.vars,
+            ))
+        }
+        "facebookinstantgame" => {
+            let account: ApiAccountFacebookInstantGame = parse_json(body)?;
+            if account.signed_player_info.is_empty() {
+                return Err(ApiError::invalid_argument(
+                    "Signed player info is required.",
+                ));
+            }
+            Ok((
+                Identity::Provider(provider.to_owned(), account.signed_player_info),
+                account.vars,
+            ))
+        }
+        _ => Err(ApiError::not_found("Not Found")),
+    }
+}
+
+fn provider_id<'a>(account: &'a mut ApiAccount, provider: &str) -> &'a mut String {
+    match provider {
+        "custom" => &mut account.custom_id,
+        "apple" => &mut account.user.apple_id,
+        "facebook" => &mut account.user.facebook_id,
+        "facebookinstantgame" => &mut account.user.facebook_instant_game_id,
+        "gamecenter" => &mut account.user.gamecenter_id,
+        "google" => &mut account.user.google_id,
+        _ => &mut account.user.steam_id,
+    }
+}
+
+fn has_identity(account: &mut Account, identity: &Identity) -> bool {
+    match identity {
+        Identity::Device(id) => account
+            .account
+            .devices
+            .iter()
+            .any(|device| &device.id == id),
+        Identity::Email(email, _) => &account.account.email == email,
+        Identity::Provider(provider, id) => provider_id(&mut account.account, provider) == id,
+    }
+}
+
+fn identity_count(account: &ApiAccount) -> usize {
+    let user = &account.user;
+    let ids = [
+        &account.custom_id,
+        &account.email,
+        &user.apple_id,
+        &user.facebook_id,
+        &user.facebook_instant_game_id,
+        &user.gamecenter_id,
+        &user.google_id,
+        &user.steam_id,
+    ];
+    account.devices.len() + ids.iter().filter(|id| !id.is_empty()).count()
+}
+
+#[derive(SerJson)]
+struct TokenClaims {
+    tid: String,
+    uid: String,
+    usn: String,
+    vrs: HashMap<String, String>,
+    exp: u64,
+}
+
+fn base64_url(value: &str) -> String {
+    base64::encode_config(value, base64::URL_SAFE_NO_PAD)
+}
+
+fn sort_records(leaderboard: &mut Leaderboard) {
+    let ascending = leaderboard.ascending;
+    leaderboard.records.sort_by(|a, b| {
+        let order = (a.score, a.subscore).cmp(&(b.score, b.subscore));
+        let order = if ascending { order } else { order.reverse() };
+        order.then(a.sequence.cmp(&b.sequence))
+    });
+}
+
+fn ranked_records(leaderboard: &Leaderboard) -> Vec<ApiLeaderboardRecord> {
+    leaderboard
+        .records
+        .iter()
+        .enumerate()
+        .map(|(index, record)| ApiLeaderboardRecord {
+            leaderboard_id: leaderboard.id.clone(),
+            owner_id: record.owner_id.clone(),
+            username: record.username.clone(),
+            score: record.score,
+            subscore: Some(record.subscore),
+            num_score: record.num_score,
+            max_num_score: 1000000,
+            metadata: record.metadata.clone(),
+            create_time: record.create_time,
+            update_time: record.update_time,
+            expiry_time: None,
+            rank: index as i64 + 1,
+        })
+        .collect()
+}
+
+/// The records of `owner_id` and its neighbours.
+fn records_around(
+    records: Vec<ApiLeaderboardRecord>,
+    owner_id: &str,
+    limit: usize,
+) -> Vec<ApiLeaderboardRecord> {
+    match records
+        .iter()
+        .position(|record| record.owner_id == owner_id)
+    {
+        Some(position) => {
+            let end = (position.saturating_sub(limit / 2) + limit).min(records.len());
+            let start = end.saturating_sub(limit);
+            records[start..end].to_vec()
+        }
+        None => Vec::new(),
+    }
+}
+
+fn non_empty(value: String) -> Option<String> {
+    if value.is_empty() {
+        None
+    } else {
+        Some(value)
+    }
+}
+
+fn replace_if_set(field: &mut String, value: String) {
+    if !value.is_empty() {
+        *field = value;
+    }
+}
+
+impl State {
+    pub(super) fn handle_request(&mut self, request: &Request) -> Result<String, ApiError> {
+        let path: Vec<&str> = request.path.trim_matches('/').split('/').collect();
+        match (request.method.as_str(), path.as_slice()) {
+            ("GET", ["healthcheck"]) => Ok(EMPTY.to_owned()),
+            ("POST", ["v2", "account", "authenticate", provider]) => {
+                self.check_server_key(request)?;
+                self.authenticate(provider, request)
+            }
+            ("POST", ["v2", "account", "session", "refresh"]) => {
+                self.check_server_key(request)?;
+                self.session_refresh(request)
+            }
+            // Like Nakama, the HTTP key is only checked if the request has no `Authorization` header
+            ("GET", ["v2", "rpc", id]) | ("POST", ["v2", "rpc", id])
+                if request.header("Authorization").is_none() =>
+            {
+                match request.query("http_key") {
+                    Some(HTTP_KEY) => self.call_rpc("", id, request),
+                    Some(_) => Err(ApiError::unauthenticated("HTTP key invalid")),
+                    None => Err(ApiError::unauthenticated("Auth token or HTTP key required")),
+                }
+            }
+            (method, path) => {
+                let user_id = self.authenticated_user(request)?;
+                self.handle_user_request(&user_id, method, path, request)
+            }
+        }
+    }
+
+    fn handle_user_request(
+        &mut self,
+        me: &str,
+        method: &str,
+        path: &[&str],
+        request: &Request,
+    ) -> Result<String, ApiError> {
+        match (method, path) {
+            ("GET", ["v2", "account"]) => self.get_account(me),
+            ("DELETE", ["v2", "account"]) => self.delete_account(me),
+            ("PUT", ["v2", "account"]) => self.update_account(me, request),
+            ("POST", ["v2", "account", "link", provider]) => self.link(me, provider, request),
+            ("POST", ["v2", "account", "unlink", provider]) => self.unlink(me, provider, request),
+            ("GET", ["v2", "channel", channel_id]) => {
+                self.list_channel_messages(me, channel_id, request)
+            }
+            ("POST", ["v2", "event"]) => Ok(EMPTY.to_owned()),
+            ("GET", ["v2", "friend"]) => self.list_friends(me, request),
+            ("POST", ["v2", "friend"]) => self.add_friends(me, request),
+            ("DELETE", ["v2", "friend"]) => self.delete_friends(me, request),
+            ("POST", ["v2", "friend", "block"]) => self.block_friends(me, request),
+            ("POST", ["v2", "friend", "facebook"]) | ("POST", ["v2", "friend", "steam"]) => {
+                // There is no social network to import friends from
+                identity(path[2], &request.body)?;
+                Ok(EMPTY.to_owned())
+            }
+            ("GET", ["v2", "group"]) => self.list_groups(request),
+            ("POST", ["v2", "group"]) => self.create_group(me, request),
+            ("PUT", ["v2", "group", group_id]) => self.update_group(me, group_id, request),
+            ("DELETE", ["v2", "group", group_id]) => self.delete_group(me, group_id),
+            ("POST", ["v2", "group", group_id, "join"]) => self.join_group(me, group_id),
+            ("POST", ["v2", "group", group_id, "leave"]) => self.leave_group(me, group_id),
+            ("POST", ["v2", "group", group_id, action]) => {
+                self.change_group_users(me, group_id, action, request)
+            }
+            ("GET", ["v2", "group", group_id, "user"]) => self.list_group_users(group_id, request),
+            ("POST", ["v2", "iap", "purchase", _])
+            | ("POST", ["v2", "iap", "subscription", "apple"])
+            | ("POST", ["v2", "iap", "subscription", "google"]) => Err(
+                ApiError::failed_precondition("In-app purchase validation is not configured."),
+            ),
+            // Nothing can be validated, so there are never any subscriptions
+            ("POST", ["v2", "iap", "subscription"]) => {
+                parse_json::<ApiListSubscriptionsRequest>(&request.body)?;
+                Ok(ApiSubscriptionList::default().serialize_json())
+            }
+            ("GET", ["v2", "iap", "subscription", _]) => {
+                Err(ApiError::not_found("Subscription not found."))
+            }
+            ("GET", ["v2", "leaderboard", id]) => self.list_leaderboard_records(id, request),
+            ("POST", ["v2", "leaderboard", id]) => self.write_leaderboard_record(me, id, request),
+            ("DELETE", ["v2", "leaderboard", id]) => self.delete_leaderboard_record(me, id),
+            ("GET", ["v2", "leaderboard", id, "owner", owner_id]) => {
+                self.list_leaderboard_records_around_owner(id, owner_id, request)
+            }
+            ("GET", ["v2", "match"]) => self.list_matches(request),
+            ("GET", ["v2", "notification"]) => self.list_notifications(me, request),
+            ("DELETE", ["v2", "notification"]) => {
+                let ids = request.query_all("ids");
+                self.notifications.retain(|notification| {
+                    notification.user_id != me
+                        || !ids.contains(&notification.notification.id.as_str())
+                });
+                Ok(EMPTY.to_owned())
+            }
+            ("GET", ["v2", "rpc", id]) | ("POST", ["v2", "rpc", id]) => {
+                self.call_rpc(me, id, request)
+            }
+            ("POST", ["v2", "session", "logout"]) => self.session_logout(me, request),
+            ("POST", ["v2", "storage"]) => self.read_storage_objects(me, request),
+            ("PUT", ["v2", "storage"]) => self.write_storage_objects(me, request),
+            ("PUT", ["v2", "storage", "delete"]) => self.delete_storage_objects(me, request),
+            ("GET", ["v2", "storage", collection]) => {
+                let user_id = request.query("user_id").filter(|id| !id.is_empty());
+                self.list_storage_objects(me, collection, user_id, request)
+            }
+            ("GET", ["v2", "storage", collection, user_id]) => {
+                self.list_storage_objects(me, collection, Some(user_id), request)
+            }
+            ("GET", ["v2", "tournament"]) => self.list_tournaments(request),
+            ("GET", ["v2", "tournament", id]) => self.list_tournament_records(id, request),
+            ("POST", ["v2", "tournament", id]) | ("PUT", ["v2", "tournament", id]) => {
+                self.write_tournament_record(me, id, request)
+            }
+            ("POST", ["v2", "tournament", id, "join"]) => self.join_tournament(me, id),
+            ("GET", ["v2", "tournament", id, "owner", owner_id]) => {
+                self.list_tournament_records_around_owner(id, owner_id, request)
+            }
+            ("GET", ["v2", "user"]) => self.get_users(request),
+            ("GET", ["v2", "user", user_id, "group"]) => self.list_user_groups(user_id, request),
+            _ => Err(ApiError::not_found("Not Found")),
+        }
+    }
+
+    fn check_server_key(&self, request: &Request) -> Result<(), ApiError> {
+        let credentials = request
+            .header("Authorization")
+            .and_then(|value| value.strip_prefix("Basic "))
+            .and_then(|value| base64::decode(value.trim()).ok())
+            .and_then(|value| String::from_utf8(value).ok())
+            .unwrap_or_default();
+        match credentials.split(':').next() {
+            Some(SERVER_KEY) => Ok(()),
+            _ => Err(ApiError::unauthenticated("Server key invalid")),
+        }
+    }
+
+    /// The id of the user of the bearer token.
+    pub(super) fn authenticated_user(&self, request: &Request) -> Result<String, ApiError> {
+        request
+            .header("Authorization")
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .and_then(|token| self.user_of_token(token.trim()))
+            .ok_or_else(|| ApiError::unauthenticated("Auth token invalid"))
+    }
+
+    pub(super) fn user_of_token(&self, token: &str) -> Option<String> {
+        self.tokens
+            .get(token)
+            .filter(|user_id| self.account(user_id).is_some())
+            .cloned()
+    }
+
+    fn token(&mut self, user_id: &str, vars: &HashMap<String, String>, expiry: u64) -> String {
+        let claims = TokenClaims {
+            tid: self.new_id(),
+            uid: user_id.to_owned(),
+            usn: self
+                .account(user_id)
+                .map(|account| account.account.user.username.clone())
+                .unwrap_or_default(),
+            vrs: vars.clone(),
+            exp: unix_time() + expiry,
+        };
+        let signature = format!("{:x}", self.random());
+        format!(
+            "{}.{}.{}",
+            base64_url(r#"{"alg":"HS256","typ":"JWT"}"#),
+            base64_url(&claims.serialize_json()),
+            base64_url(&signature)
+        )
+    }
+
+    fn create_session(
+        &mut self,
+        user_id: &str,
+        vars: HashMap<String, String>,
+        created: bool,
+    ) -> ApiSession {
+        let token = self.token(user_id, &vars, 60 * 60);
+        let refresh_token = self.token(user_id, &vars, 60 * 60 * 24 * 7);
+        self.tokens.insert(token.clone(), user_id.to_owned());
+        self.refresh_tokens
+            .insert(refresh_token.clone(), (user_id.to_owned(), vars));
+        ApiSession {
+            created,
+            token,
+            refresh_token,
+        }
+    }
+
+    fn random_username(&mut self) -> String {
+        let mut random = self.random();
+        (0..10)
+            .map(|_| {
+                let letter = (b'a' + (random % 26) as u8) as char;
+                random /= 26;
+                letter
+            })
+            .collect()
+    }
+
+    fn authenticate(&mut self, provider: &str, request: &Request) -> Result<String, ApiError> {
+        let (identity, vars) = identity(provider, &request.body)?;
+        let create = request.query_bool("create", true);
+
+        let existing = self
+            .accounts
+            .iter_mut()
+            .position(|account| has_identity(account, &identity));
+        let (user_id, created) = match existing {
+            Some(index) => {
+                if let Identity::Email(_, password) = &identity {
+                    if &self.accounts[index].password != password {
+                        return Err(ApiError::unauthenticated("Invalid credentials."));
+                    }
+                }
+                (self.accounts[index].account.user.id.clone(), false)
+            }
+            None if !create => return Err(ApiError::not_found("User account not found.")),
+            None => {
+                let username = match request.query("username").filter(|name| !name.is_empty()) {
+                    Some(username) => {
+                        if self.user_by_username(username).is_some() {
+                            return Err(ApiError::already_exists("Username is already in use."));
+                        }
+                        username.to_owned()
+                    }
+                    None => self.random_username(),
+                };
+                let user_id = self.new_id();
+                let mut account = Account {
+                    account: ApiAccount {
+                        user: ApiUser {
+                            id: user_id.clone(),
+                            username,
+                            metadata: "{}".to_owned(),
+                            create_time: now(),
+                            update_time: now(),
+                            ..Default::default()
+                        },
+                        wallet: "{}".to_owned(),
+                        ..Default::default()
+                    },
+                    password: String::new(),
+                };
+                link_identity(&mut account, identity);
+                self.accounts.push(account);
+                (user_id, true)
+            }
+        };
+
+        Ok(self
+            .create_session(&user_id, vars, created)
+            .serialize_json())
+    }
+
+    fn session_refresh(&mut self, request: &Request) -> Result<String, ApiError> {
+        let refresh: ApiSessionRefreshRequest = parse_json(&request.body)?;
+        let (user_id, vars) = self
+            .refresh_tokens
+            .get(&refresh.token)
+            .filter(|(user_id, _)| self.account(user_id).is_some())
+            .cloned()
+            .ok_or_else(|| ApiError::unauthenticated("Refresh token invalid or expired."))?;
+        let vars = if refresh.vars.is_empty() {
+            vars
+        } else {
+            refresh.vars
+        };
+
+        let token = self.token(&user_id, &vars, 60 * 60);
+        self.tokens.insert(token.clone(), user_id);
+        Ok(ApiSession {
+            created: false,
+            token,
+            refresh_token: refresh.token,
+        }
+        .serialize_json())
+    }
+
+    fn session_logout(&mut self, me: &str, request: &Request) -> Result<String, ApiError> {
+        let logout: ApiSessionLogoutRequest = parse_json(&request.body)?;
+        if logout.token.is_empty() && logout.refresh_token.is_empty() {
+            self.tokens.retain(|_, user_id| user_id != me);
+            self.refresh_tokens.retain(|_, (user_id, _)| user_id != me);
+        } else {
+            if self.tokens.get(&logout.token).map(String::as_str) == Some(me) {
+                self.tokens.remove(&logout.token);
+            }
+            if self
+                .refresh_tokens
+                .get(&logout.refresh_token)
+                .map(|(id, _)| id.as_str())
+                == Some(me)
+            {
+                self.refresh_tokens.remove(&logout.refresh_token);
+            }
+        }
+        Ok(EMPTY.to_owned())
+    }
+
+    fn delete_account(&mut self, me: &str) -> Result<String, ApiError> {
+        self.accounts
+            .retain(|account| account.account.user.id != me);
+        self.tokens.retain(|_, user_id| user_id != me);
+        self.refresh_tokens.retain(|_, (user_id, _)| user_id != me);
+        self.friends
+            .retain(|edge| edge.user_id != me && edge.friend_id != me);
+        for group in &mut self.groups {
+            group.members.retain(|(user_id, _)| user_id != me);
+        }
+        self.storage.retain(|object| object.user_id != me);
+        for leaderboard in &mut self.leaderboards {
+            leaderboard.records.retain(|record| record.owner_id != me);
+        }
+        self.notifications
+            .retain(|notification| notification.user_id != me);
+        Ok(EMPTY.to_owned())
+    }
+
+    fn get_account(&self, me: &str) -> Result<String, ApiError> {
+        let account = self
+            .account(me)
+            .ok_or_else(|| ApiError::not_found("Account not found."))?;
+        Ok(ApiAccount {
+            user: self.user(me).unwrap_or_default(),
+            ..account.account.clone()
+        }
+        .serialize_json())
+    }
+
+    fn update_account(&mut self, me: &str, request: &Request) -> Result<String, ApiError> {
+        let update: ApiUpdateAccountRequest = parse_json(&request.body)?;
+        if !update.username.is_empty() {
+            if let Some(user) = self.user_by_username(&update.username) {
+                if user.id != me {
+                    return Err(ApiError::already_exists("Username is already in use."));
+                }
+            }
+        }
+
+        let account = self
+            .account_mut(me)
+            .ok_or_else(|| ApiError::not_found("Account not found."))?;
+        let user = &mut account.account.user;
+        replace_if_set(&mut user.username, update.username);
+        replace_if_set(&mut user.display_name, update.display_name);
+        replace_if_set(&mut user.avatar_url, update.avatar_url);
+        replace_if_set(&mut user.lang_tag, update.lang_tag);
+        replace_if_set(&mut user.location, update.location);
+        replace_if_set(&mut user.timezone, update.timezone);
+        user.update_time = now();
+        Ok(EMPTY.to_owned())
+    }
+
+    fn link(&mut self, me: &str, provider: &str, request: &Request) -> Result<String, ApiError> {
+        let (identity, _) = identity(provider, &request.body)?;
+        let in_use = self
+            .accounts
+            .iter_mut()
+            .any(|account| account.account.user.id != me && has_identity(account, &identity));
+        if in_use {
+            return Err(ApiError::already_exists(
+                "Identifier is already in use by another user.",
+            ));
+        }
+
+        let account = self
+            .account_mut(me)
+            .ok_or_else(|| ApiError::not_found("Account not found."))?;
+        if let Identity::Device(_) = identity {
+            if has_identity(account, &identity) {
+                return Ok(EMPTY.to_owned());
+            }
+        }
+        link_identity(account, identity);
+        Ok(EMPTY.to_owned())
+    }
+
+    fn unlink(&mut self, me: &str, provider: &str, request: &Request) -> Result<String, ApiError> {
+        let (identity, _) = identity(provider, &request.body)?;
+        let account = self
+            .account_mut(me)
+            .ok_or_else(|| ApiError::not_found("Account not found."))?;
+        if !has_identity(account, &identity) || identity_count(&account.account) < 2 {
+            return Err(ApiError::permission_denied(
+                "Cannot unlink last account identifier. Check profile exists and is not last link.",
+            ));
+        }
+
+        match identity {
+            Identity::Device(id) => account.account.devices.retain(|device| device.id != id),
+            Identity::Email(..) => {
+                account.account.email.clear();
+                account.password.clear();
+            }
+            Identity::Provider(provider, _) => provider_id(&mut account.account, &provider).clear(),
+        }
+        Ok(EMPTY.to_owned())
+    }
+
+    fn get_users(&self, request: &Request) -> Result<String, ApiError> {
+        let ids = request.query_all("ids");
+        let usernames = request.query_all("usernames");
+        let facebook_ids = request.query_all("facebook_ids");
+        let users = self
+            .accounts
+            .iter()
+            .filter(|account| {
+                let user = &account.account.user;
+                ids.contains(&user.id.as_str())
+                    || usernames.contains(&user.username.as_str())
+                    || (!user.facebook_id.is_empty()
+                        && facebook_ids.contains(&user.facebook_id.as_str()))
+            })
+            .filter_map(|account| self.user(&account.account.user.id))
+            .collect();
+        Ok(ApiUsers { users }.serialize_json())
+    }
+
+    /// The ids of the existing users with one of the ids or usernames.
+    fn resolve_users(&self, request: &Request, ids: &str) -> Result<Vec<String>, ApiError> {
+        let ids = request.query_all(ids);
+        let usernames = request.query_all("usernames");
+        if ids.is_empty() && usernames.is_empty() {
+            return Err(ApiError::invalid_argument(
+                "Specify at least one ID or username.",
+            ));
+        }
+        Ok(self
+            .accounts
+            .iter()
+            .map(|account| &account.account.user)
+            .filter(|user| {
+                ids.contains(&user.id.as_str()) || usernames.contains(&user.username.as_str())
+            })
+            .map(|user| user.id.clone())
+            .collect())
+    }
+
+    fn friend_state(&self, user_id: &str, friend_id: &str) -> Option<i32> {
+        self.friends
+            .iter()
+            .find(|edge| edge.user_id == user_id && edge.friend_id == friend_id)
+            .map(|edge| edge.state)
+    }
+
+    fn set_friend_state(&mut self, user_id: &str, friend_id: &str, state: i32) {
+        let edge = self
+            .friends
+            .iter_mut()
+            .find(|edge| edge.user_id == user_id && edge.friend_id == friend_id);
+        match edge {
+            Some(edge) => {
+                edge.state = state;
+                edge.update_time = now();
+            }
+            None => self.friends.push(FriendEdge {
+                user_id: user_id.to_owned(),
+                friend_id: friend_id.to_owned(),
+                state,
+                update_time: now(),
+            }),
+        }
+    }
+
+    fn remove_friend_edge(&mut self, user_id: &str, friend_id: &str) {
+        self.friends
+            .retain(|edge| edge.user_id != user_id || edge.friend_id != friend_id);
+    }
+
+    fn username(&self, user_id: &str) -> String {
+        self.account(user_id)
+            .map(|account| account.account.user.username.clone())
+            .unwrap_or_default()
+    }
+
+    fn add_friends(&mut self, me: &str, request: &Request) -> Result<String, ApiError> {
+        for friend_id in self.resolve_users(request, "ids")? {
+            if friend_id == me {
+                return Err(ApiError::invalid_argument("Cannot add self as friend."));
+            }
+            let content = format!("{{\"username\":{}}}", self.username(me).serialize_json());
+            match (
+                self.friend_state(me, &friend_id),
+                self.friend_state(&friend_id, me),
+            ) {
+                (Some(INVITE_RECEIVED), _) => {
+                    self.set_friend_state(me, &friend_id, FRIEND);
+                    self.set_friend_state(&friend_id, me, FRIEND);
+                    let subject = format!("{} accepted your friend request", self.username(me));
+                    self.notify(&friend_id, &subject, &content, -3, me, true);
+                }
+                (Some(_), _) | (_, Some(BLOCKED)) => {}
+                (None, _) => {
+                    self.set_friend_state(me, &friend_id, INVITE_SENT);
+                    self.set_friend_state(&friend_id, me, INVITE_RECEIVED);
+                    let subject = format!("{} wants to add you as a friend", self.username(me));
+                    self.notify(&friend_id, &subject, &content, -2, me, true);
+                }
+            }
+        }
+        Ok(EMPTY.to_owned())
+    }
+
+    fn delete_friends(&mut self, me: &str, request: &Request) -> Result<String, ApiError> {
+        for friend_id in self.resolve_users(request, "ids")? {
+            self.remove_friend_edge(me, &friend_id);
+            if self.friend_state(&friend_id, me) != Some(BLOCKED) {
+                self.remove_friend_edge(&friend_id, me);
+            }
+        }
+        Ok(EMPTY.to_owned())
+    }
+
+    fn block_friends(&mut self, me: &str, request: &Request) -> Result<String, ApiError> {
+        for friend_id in self.resolve_users(request, "ids")? {
+            if friend_id == me {
+                return Err(ApiError::invalid_argument("Cannot block self."));
+            }
+            self.set_friend_state(me, &friend_id, BLOCKED);
+            if self.friend_state(&friend_id, me) != Some(BLOCKED) {
+                self.remove_friend_edge(&friend_id, me);
+            }
+        }
+        Ok(EMPTY.to_owned())
+    }
+
+    fn list_friends(&self, me: &str, request: &Request) -> Result<String, ApiError> {
+        let state = request.query_i32("state")?;
+        let friends: Vec<ApiFriend> = self
+            .friends
+            .iter()
+            .filter(|edge| edge.user_id == me && state.is_none_or(|state| edge.state == state))
+            .filter_map(|edge| {
+                self.user(&edge.friend_id).map(|user| ApiFriend {
+                    user,
+                    state: edge.state,
+                    update_time: edge.update_time,
+                })
+            })
+            .collect();
+        let (friends, cursor, _) = paginate(&friends, request.limit()?, request.query("cursor"))?;
+        Ok(ApiFriendList { friends, cursor }.serialize_json())
+    }
+
+    fn group_index(&self, group_id: &str) -> Result<usize, ApiError> {
+        self.groups
+            .iter()
+            .position(|group| group.group.id == group_id)
+            .ok_or_else(|| ApiError::not_found("Group not found."))
+    }
+
+    pub(super) fn group_member_state(&self, group_id: &str, user_id: &str) -> Option<i32> {
+        self.groups
+            .iter()
+            .find(|group| group.group.id == group_id)
+            .and_then(|group| group.members.iter().find(|(id, _)| id == user_id))
+            .map(|(_, state)| *state)
+    }
+
+    fn api_group(group: &Group) -> ApiGroup {
+        ApiGroup {
+            edge_count: group
+                .members
+                .iter()
+                .filter(|(_, state)| *state <= MEMBER)
+                .count() as i32,
+            ..group.group.clone()
+        }
+    }
+
+    fn check_group_name(&self, name: &str, group_id: &str) -> Result<(), ApiError> {
+        if name.is_empty() {
+            return Err(ApiError::invalid_argument("Group name must be set."));
+        }
+        let in_use = self
+            .groups
+            .iter()
+            .any(|group| group.group.name == name && group.group.id != group_id);
+        if in_use {
+            return Err(ApiError::already_exists("Group name is in use."));
+        }
+        Ok(())
+    }
+
+    fn create_group(&mut self, me: &str, request: &Request) -> Result<String, ApiError> {
+        let create: ApiCreateGroupRequest = parse_json(&request.body)?;
+        self.check_group_name(&create.name, "")?;
+        if create.max_count < 0 {
+            return Err(ApiError::invalid_argument(
+                "Group max count must be greater than zero.",
+            ));
+        }
+
+        let group = Group {
+            group: ApiGroup {
+                id: self.new_id(),
+                creator_id: me.to_owned(),
+                name: create.name,
+                description: create.description,
+                avatar_url: create.avatar_url,
+                lang_tag: create.lang_tag,
+                open: create.open,
+                max_count: if create.max_count == 0 {
+                    100
+                } else {
+                    create.max_count
+                },
+                metadata: "{}".to_owned(),
+                create_time: now(),
+                update_time: now(),
+                edge_count: 1,
+            },
+            members: vec![(me.to_owned(), SUPERADMIN)],
+            banned: Vec::new(),
+        };
+        let response = State::api_group(&group).serialize_json();
+        self.groups.push(group);
+        Ok(response)
+    }
+
+    fn require_group_admin(&self, me: &str, group_id: &str) -> Result<(usize, i32), ApiError> {
+        let index = self.group_index(group_id)?;
+        match self.group_member_state(group_id, me) {
+            Some(state) if state <= ADMIN => Ok((index, state)),
+            _ => Err(ApiError::permission_denied(
+                "User does not have permission to modify this group.",
+            )),
+        }
+    }
+
+    fn update_group(
+        &mut self,
+        me: &str,
+        group_id: &str,
+        request: &Request,
+    ) -> Result<String, ApiError> {
+        let update: ApiUpdateGroupRequest = parse_json(&request.body)?;
+        let (index, _) = self.require_group_admin(me, group_id)?;
+        if !update.name.is_empty() {
+            self.check_group_name(&update.name, group_id)?;
+        }
+
+        let group = &mut self.groups[index].group;
+        replace_if_set(&mut group.name, update.name);
+        replace_if_set(&mut group.description, update.description);
+        replace_if_set(&mut group.avatar_url, update.avatar_url);
+        replace_if_set(&mut group.lang_tag, update.lang_tag);
+        group.open = update.open;
+        group.update_time = now();
+        Ok(EMPTY.to_owned())
+    }
+
+    fn delete_group(&mut self, me: &str, group_id: &str) -> Result<String, ApiError> {
+        let index = self.group_index(group_id)?;
+        if self.group_member_state(group_id, me) != Some(SUPERADMIN) {
+            return Err(ApiError::permission_denied(
+                "User does not have permission to delete this group.",
+            ));
+        }
+        self.groups.remove(index);
+        Ok(EMPTY.to_owned())
+    }
+
+    fn join_group(&mut self, me: &str, group_id: &str) -> Result<String, ApiError> {
+        let index = self.group_index(group_id)?;
+        let group = &self.groups[index];
+        if group.banned.iter().any(|id| id == me) {
+            return Err(ApiError::permission_denied(
+                "User is banned from the group.",
+            ));
+        }
+        if self.group_member_state(group_id, me).is_some() {
+            return Ok(EMPTY.to_owned());
+        }
+        if State::api_group(group).edge_count >= group.group.max_count {
+            return Err(ApiError::invalid_argument("Group is full."));
+        }
+
+        if group.group.open {
+            self.groups[index].members.push((me.to_owned(), MEMBER));
+        } else {
+            self.groups[index]
+                .members
+                .push((me.to_owned(), JOIN_REQUEST));
+            let admins: Vec<String> = self.groups[index]
+                .members
+                .iter()
+                .filter(|(_, state)| *state <= ADMIN)
+                .map(|(id, _)| id.clone())
+                .collect();
+            let subject = format!("User {} wants to join your group", self.username(me));
+            for admin in admins {
+                self.notify(&admin, &subject, EMPTY, -5, me, true);
+            }
+        }
+        Ok(EMPTY.to_owned())
+    }
+
+    fn leave_group(&mut self, me: &str, group_id: &str) -> Result<String, ApiError> {
+        let index = self.group_index(group_id)?;
+        let members = &mut self.groups[index].members;
+        let superadmins = members
+            .iter()
+            .filter(|(_, state)| *state == SUPERADMIN)
+            .count();
+        let is_superadmin = members
+            .iter()
+            .any(|(id, state)| id == me && *state == SUPERADMIN);
+        if is_superadmin && superadmins == 1 {
+            return Err(ApiError::permission_denied(
+                "Cannot leave group when you are the last superadmin.",
+            ));
+        }
+        members.retain(|(id, _)| id != me);
+        Ok(EMPTY.to_owned())
+    }
+
+    /// Add, ban, kick, promote or demote users of a group.
+    fn change_group_users(
+        &mut self,
+        me: &str,
+        group_id: &str,
+        action: &str,
+        request: &Request,
+    ) -> Result<String, ApiError> {
+        if !["add", "ban", "kick", "promote", "demote"].contains(&action) {
+            return Err(ApiError::not_found("Not Found"));
+        }
+        let (index, my_state) = self.require_group_admin(me, group_id)?;
+        let user_ids: Vec<String> = request
+            .query_all("user_ids")
+            .into_iter()
+            .filter(|id| *id != me && self.account(id).is_some())
+            .map(str::to_owned)
+            .collect();
+        let group_name = self.groups[index].group.name.clone();
+
+        for user_id in user_ids {
+            let group = &mut self.groups[index];
+            let position = group.members.iter().position(|(id, _)| *id == user_id);
+            let state = position.map(|position| group.members[position].1);
+            // Admins can't change superadmins
+            if state == Some(SUPERADMIN) && my_state != SUPERADMIN {
+                continue;
+            }
+
+            match (action, position) {
+                ("add", Some(position)) if group.members[position].1 == JOIN_REQUEST => {
+                    group.members[position].1 = MEMBER;
+                }
+                ("add", None) => {
+                    group.banned.retain(|id| *id != user_id);
+                    group.members.push((user_id.clone(), MEMBER));
+                    let subject = format!("You've been added to group {}", group_name);
+                    self.notify(&user_id, &subject, EMPTY, -4, me, true);
+                }
+                ("ban", _) => {
+                    group.members.retain(|(id, _)| *id != user_id);
+                    if !group.banned.contains(&user_id) {
+                        group.banned.push(user_id);
+                    }
+                }
+                ("kick", _) => group.members.retain(|(id, _)| *id != user_id),
+                ("promote", Some(position)) => {
+                    let state = &mut group.members[position].1;
+                    if *state > ADMIN || my_state == SUPERADMIN {
+                        *state = (*state - 1).max(SUPERADMIN);
+                    }
+                }
+                ("demote", Some(position)) => {
+                    let state = &mut group.members[position].1;
+                    if *state < MEMBER {
+                        *state += 1;
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(EMPTY.to_owned())
+    }
+
+    fn list_group_users(&self, group_id: &str, request: &Request) -> Result<String, ApiError> {
+        let index = self.group_index(group_id)?;
+        let state = request.query_i32("state")?;
+        let group_users: Vec<GroupUserListGroupUser> = self.groups[index]
+            .members
+            .iter()
+            .filter(|(_, member_state)| state.is_none_or(|state| *member_state == state))
+            .filter_map(|(user_id, state)| {
+                self.user(user_id).map(|user| GroupUserListGroupUser {
+                    user,
+                    state: *state,
+                })
+            })
+            .collect();
+        let (group_users, cursor, _) =
+            paginate(&group_users, request.limit()?, request.query("cursor"))?;
+        Ok(ApiGroupUserList {
+            group_users,
+            cursor,
+        }
+        .serialize_json())
+    }
+
+    fn list_user_groups(&self, user_id: &str, request: &Request) -> Result<String, ApiError> {
+        if self.account(user_id).is_none() {
+            return Err(ApiError::not_found("User not found."));
+        }
+        let state = request.query_i32("state")?;
+        let user_groups: Vec<UserGroupListUserGroup> = self
+            .groups
+            .iter()
+            .filter_map(|group| {
+                group
+                    .members
+                    .iter()
+                    .find(|(id, _)| id == user_id)
+                    .map(|(_, state)| UserGroupListUserGroup {
+                        group: State::api_group(group),
+                        state: *state,
+                    })
+            })
+            .filter(|user_group| state.is_none_or(|state| user_group.state == state))
+            .collect();
+        let (user_groups, cursor, _) =
+            paginate(&user_groups, request.limit()?, request.query("cursor"))?;
+        Ok(ApiUserGroupList {
+            user_groups,
+            cursor,
+        }
+        .serialize_json())
+    }
+
+    fn list_groups(&self, request: &Request) -> Result<String, ApiError> {
+        let name = request.query("name").filter(|name| !name.is_empty());
+        let groups: Vec<ApiGroup> = self
+            .groups
+            .iter()
+            .filter(|group| match name {
+                Some(name) => match name.strip_suffix('%') {
+                    Some(prefix) => group.group.name.starts_with(prefix),
+                    None => group.group.name == name,
+                },
+                None => true,
+            })
+            .map(State::api_group)
+            .collect();
+        let (groups, cursor, _) = paginate(&groups, request.limit()?, request.query("cursor"))?;
+        Ok(ApiGroupList { groups, cursor }.serialize_json())
+    }
+
+    fn storage_index(&self, collection: &str, key: &str, user_id: &str) -> Option<usize> {
+        self.storage.iter().position(|object| {
+            object.collection == collection && object.key == key && object.user_id == user_id
+        })
+    }
+
+    fn write_storage_objects(&mut self, me: &str, request: &Request) -> Result<String, ApiError> {
+        let write: ApiWriteStorageObjectsRequest = parse_json(&request.body)?;
+        // Reject all writes if one of them is invalid
+        for object in &write.objects {
+            if object.collection.is_empty() || object.key.is_empty() {
+                return Err(ApiError::invalid_argument(
+                    "Invalid collection or key value supplied. They must be set.",
+                ));
+            }
+            if !(0..=2).contains(&object.permission_read) {
+                return Err(ApiError::invalid_argument(
+                    "Invalid read permission supplied. It must be 0, 1, or 2.",
+                ));
+            }
+            if !(0..=1).contains(&object.permission_write) {
+                return Err(ApiError::invalid_argument(
+                    "Invalid write permission supplied. It must be 0 or 1.",
+                ));
+            }
+            if !is_json_object(&object.value) {
+                return Err(ApiError::invalid_argument("Value must be a JSON object."));
+            }
+            let existing = self
+                .storage_index(&object.collection, &object.key, me)
+                .map(|index| &self.storage[index]);
+            let version_matches = match (object.version.as_str(), existing) {
+                ("", _) => true,
+                ("*", existing) => existing.is_none(),
+                (version, Some(existing)) => existing.version == version,
+                (_, None) => false,
+            };
+            if !version_matches {
+                return Err(ApiError::invalid_argument(
+                    "Storage write rejected - version check failed.",
+                ));
+            }
+        }
+
+        let mut acks = Vec::new();
+        for object in write.objects {
+            let version = format!("{:016x}{:016x}", self.random(), self.random());
+            let stored = ApiStorageObject {
+                collection: object.collection,
+                key: object.key,
+                user_id: me.to_owned(),
+                value: object.value,
+                version: version.clone(),
+                permission_read: object.permission_read,
+                permission_write: object.permission_write,
+                create_time: now(),
+                update_time: now(),
+            };
+            acks.push(ApiStorageObjectAck {
+                collection: stored.collection.clone(),
+                key: stored.key.clone(),
+                user_id: me.to_owned(),
+                version,
+            });
+            match self.storage_index(&stored.collection, &stored.key, me) {
+                Some(index) => {
+                    let create_time = self.storage[index].create_time;
+                    self.storage[index] = ApiStorageObject {
+                        create_time,
+                        ..stored
+                    };
+                }
+                None => self.storage.push(stored),
+            }
+        }
+        Ok(ApiStorageObjectAcks { acks }.serialize_json())
+    }
+
+    fn read_storage_objects(&self, me: &str, request: &Request) -> Result<String, ApiError> {
+        let read: ApiReadStorageObjectsRequest = parse_json(&request.body)?;
+        let objects = read
+            .object_ids
+            .iter()
+            .filter_map(|id| self.storage_index(&id.collection, &id.key, &id.user_id))
+            .map(|index| &self.storage[index])
+            .filter(|object| object.user_id == me || object.permission_read == 2)
+            .cloned()
+            .collect();
+        Ok(ApiStorageObjects { objects }.serialize_json())
+    }
+
+    fn delete_storage_objects(&mut self, me: &str, request: &Request) -> Result<String, ApiError> {
+        let delete: ApiDeleteStorageObjectsRequest = parse_json(&request.body)?;
+        for id in &delete.object_ids {
+            let existing = self
+                .storage_index(&id.collection, &id.key, me)
+                .map(|index| &self.storage[index]);
+            if let Some(existing) = existing {
+                if existing.permission_write == 0 {
+                    return Err(ApiError::permission_denied(
+                        "Storage delete rejected - permission denied.",
+                    ));
+                }
+            }
+            let version_matches = id.version.is_empty()
+                || existing.is_some_and(|existing| existing.version == id.version);
+            if !version_matches {
+                return Err(ApiError::invalid_argument(
+                    "Storage delete rejected - version check failed.",
+                ));
+            }
+        }
+
+        for id in delete.object_ids {
+            if let Some(index) = self.storage_index(&id.collection, &id.key, me) {
+                self.storage.remove(index);
+            }
+        }
+        Ok(EMPTY.to_owned())
+    }
+
+    fn list_storage_objects(
+        &self,
+        me: &str,
+        collection: &str,
+        user_id: Option<&str>,
+        request: &Request,
+    ) -> Result<String, ApiError> {
+        let mut objects: Vec<ApiStorageObject> = self
+            .storage
+            .iter()
+            .filter(|object| object.collection == collection)
+            .filter(|object| match user_id {
+                Some(user_id) => {
+                    object.user_id == user_id && (user_id == me || object.permission_read == 2)
+                }
+                None => object.permission_read == 2,
+            })
+            .cloned()
+            .collect();
+        objects.sort_by(|a, b| (&a.user_id, &a.key).cmp(&(&b.user_id, &b.key)));
+        let (objects, cursor, _) = paginate(&objects, request.limit()?, request.query("cursor"))?;
+        Ok(ApiStorageObjectList { objects, cursor }.serialize_json())
+    }
+
+    fn leaderboard_index(&self, id: &str) -> Option<usize> {
+        self.leaderboards
+            .iter()
+            .position(|leaderboard| leaderboard.id == id)
+    }
+
+    fn leaderboard(&self, id: &str) -> Result<&Leaderboard, ApiError> {
+        self.leaderboards
+            .iter()
+            .find(|leaderboard| leaderboard.id == id && leaderboard.tournament.is_none())
+            .ok_or_else(|| ApiError::not_found("Leaderboard not found."))
+    }
+
+    fn tournament_index(&self, id: &str) -> Result<usize, ApiError> {
+        self.leaderboards
+            .iter()
+            .position(|leaderboard| leaderboard.id == id && leaderboard.tournament.is_some())
+            .ok_or_else(|| ApiError::not_found("Tournament not found."))
+    }
+
+    fn write_record(
+        &mut self,
+        index: usize,
+        me: &str,
+        score: i64,
+        subscore: Option<i64>,
+        operator: ApiOverrideOperator,
+        metadata: Option<&str>,
+    ) -> Result<String, ApiError> {
+        let subscore = subscore.unwrap_or(0);
+        let metadata = metadata.filter(|metadata| !metadata.is_empty());
+        if !metadata.is_none_or(is_json_object) {
+            return Err(ApiError::invalid_argument(
+                "Metadata value must be a JSON object.",
+            ));
+        }
+
+        let username = self.username(me);
+        let sequence = self.next_sequence();
+        let leaderboard = &mut self.leaderboards[index];
+        let operator = match operator {
+            ApiOverrideOperator::NO_OVERRIDE => leaderboard.operator,
+            operator => operator as i32,
+        };
+        let ascending = leaderboard.ascending;
+
+        match leaderboard
+            .records
+            .iter_mut()
+            .find(|record| record.owner_id == me)
+        {
+            Some(record) => {
+                let old = (record.score, record.subscore);
+                let (score, subscore) = match operator {
+                    // Best
+                    1 if ascending => old.min((score, subscore)),
+                    1 => old.max((score, subscore)),
+                    // Increment
+                    3 => (old.0 + score, old.1 + subscore),
+                    // Decrement
+                    4 => ((old.0 - score).max(0), (old.1 - subscore).max(0)),
+                    // Set
+                    _ => (score, subscore),
+                };
+                if (score, subscore) != old {
+                    record.sequence = sequence;
+                }
+                record.score = score;
+                record.subscore = subscore;
+                record.num_score += 1;
+                record.username = username;
+                record.update_time = now();
+                if let Some(metadata) = metadata {
+                    record.metadata = metadata.to_owned();
+                }
+            }
+            None => leaderboard.records.push(Record {
+                owner_id: me.to_owned(),
+                username,
+                score,
+                subscore,
+                num_score: 1,
+                metadata: metadata.unwrap_or("{}").to_owned(),
+                create_time: now(),
+                update_time: now(),
+                sequence,
+            }),
+        }
+        sort_records(leaderboard);
+
+        let record = ranked_records(leaderboard)
+            .into_iter()
+            .find(|record| record.owner_id == me)
+            .unwrap_or_default();
+        Ok(record.serialize_json())
+    }
+
+    fn write_leaderboard_record(
+        &mut self,
+        me: &str,
+        id: &str,
+        request: &Request,
+    ) -> Result<String, ApiError> {
+        let write: WriteLeaderboardRecordRequestLeaderboardRecordWrite = parse_json(&request.body)?;
+        let index = match self.leaderboard_index(id) {
+            Some(index) if self.leaderboards[index].tournament.is_some() => {
+                return Err(ApiError::not_found("Leaderboard not found."))
+            }
+            Some(index) => index,
+            None => {
+                // Leaderboards are created on the first write, using the best score
+                self.leaderboards.push(Leaderboard {
+                    id: id.to_owned(),
+                    operator: 1,
+                    ascending: false,
+                    records: Vec::new(),
+                    tournament: None,
+                });
+                self.leaderboards.len() - 1
+            }
+        };
+        self.write_record(
+            index,
+            me,
+            write.score,
+            write.subscore,
+            write.operator,
+            Some(&write.metadata),
+        )
+    }
+
+    fn delete_leaderboard_record(&mut self, me: &str, id: &str) -> Result<String, ApiError> {
+        self.leaderboard(id)?;
+        if let Some(index) = self.leaderboard_index(id) {
+            self.leaderboards[index]
+                .records
+                .retain(|record| record.owner_id != me);
+        }
+        Ok(EMPTY.to_owned())
+    }
+
+    /// The page of records and the records of the owners of `owner_ids`.
+    fn record_page(
+        leaderboard: &Leaderboard,
+        request: &Request,
+    ) -> Result<
+        (
+            Vec<ApiLeaderboardRecord>,
+            Vec<ApiLeaderboardRecord>,
+            String,
+            String,
+        ),
+        ApiError,
+    > {
+        let records = ranked_records(leaderboard);
+        let owner_ids = request.query_all("owner_ids");
+        let owner_records = records
+            .iter()
+            .filter(|record| owner_ids.contains(&record.owner_id.as_str()))
+            .cloned()
+            .collect();
+        let (records, next, prev) = paginate(&records, request.limit()?, request.query("cursor"))?;
+        Ok((records, owner_records, next, prev))
+    }
+
+    fn list_leaderboard_records(&self, id: &str, request: &Request) -> Result<String, ApiError> {
+        let (records, owner_records, next_cursor, prev_cursor) =
+            State::record_page(self.leaderboard(id)?, request)?;
+        Ok(ApiLeaderboardRecordList {
+            records,
+            owner_records,
+            next_cursor,
+            prev_cursor,
+        }
+        .serialize_json())
+    }
+
+    fn list_leaderboard_records_around_owner(
+        &self,
+        id: &str,
+        owner_id: &str,
+        request: &Request,
+    ) -> Result<String, ApiError> {
+        let records = ranked_records(self.leaderboard(id)?);
+        Ok(ApiLeaderboardRecordList {
+            records: records_around(records, owner_id, request.limit()?),
+            ..Default::default()
+        }
+        .serialize_json())
+    }
+
+    fn list_tournaments(&self, request: &Request) -> Result<String, ApiError> {
+        let category_start = request.query_i32("category_start")?;
+        let category_end = request.query_i32("category_end")?;
+        let start_time = request.query_i32("start_time")?;
+        let end_time = request.query_i32("end_time")?;
+        let tournaments: Vec<_> = self
+            .leaderboards
+            .iter()
+            .filter_map(|leaderboard| leaderboard.tournament.as_ref())
+            .map(|tournament| crate::api::ApiTournament {
+                size: tournament.joined.len() as i32,
+                ..tournament.tournament.clone()
+            })
+            .filter(|tournament| {
+                category_start.is_none_or(|start| tournament.category >= start)
+                    && category_end.is_none_or(|end| tournament.category <= end)
+                    && start_time.is_none_or(|start| tournament.start_active >= start)
+                    && end_time.is_none_or(|end| {
+                        tournament.end_active == 0 || tournament.end_active <= end
+                    })
+            })
+            .collect();
+        let (tournaments, cursor, _) =
+            paginate(&tournaments, request.limit()?, request.query("cursor"))?;
+        Ok(ApiTournamentList {
+            tournaments,
+            cursor: non_empty(cursor),
+        }
+        .serialize_json())
+    }
+
+    fn join_tournament(&mut self, me: &str, id: &str) -> Result<String, ApiError> {
+        let index = self.tournament_index(id)?;
+        if let Some(tournament) = &mut self.leaderboards[index].tournament {
+            if !tournament.joined.iter().any(|user_id| user_id == me) {
+                tournament.joined.push(me.to_owned());
+            }
+        }
+        Ok(EMPTY.to_owned())
+    }
+
+    fn write_tournament_record(
+        &mut self,
+        me: &str,
+        id: &str,
+        request: &Request,
+    ) -> Result<String, ApiError> {
+        let write: WriteTournamentRecordRequestTournamentRecordWrite = parse_json(&request.body)?;
+        let index = self.tournament_index(id)?;
+        let may_write = self.leaderboards[index]
+            .tournament
+            .as_ref()
+            .is_some_and(|tournament| {
+                !tournament.join_required || tournament.joined.iter().any(|user_id| user_id == me)
+            });
+        if !may_write {
+            return Err(ApiError::invalid_argument(
+                "Must join tournament before attempting to write value.",
+            ));
+        }
+        self.write_record(
+            index,
+            me,
+            write.score,
+            write.subscore,
+            write.operator,
+            write.metadata.as_deref(),
+        )
+    }
+
+    fn list_tournament_records(&self, id: &str, request: &Request) -> Result<String, ApiError> {
+        let leaderboard = &self.leaderboards[self.tournament_index(id)?];
+        let (records, owner_records, next_cursor, prev_cursor) =
+            State::record_page(leaderboard, request)?;
+        Ok(ApiTournamentRecordList {
+            records,
+            owner_records,
+            next_cursor: non_empty(next_cursor),
+            prev_cursor: non_empty(prev_cursor),
+        }
+        .serialize_json())
+    }
+
+    fn list_tournament_records_around_owner(
+        &self,
+        id: &str,
+        owner_id: &str,
+        request: &Request,
+    ) -> Result<String, ApiError> {
+        let records = ranked_records(&self.leaderboards[self.tournament_index(id)?]);
+        Ok(ApiTournamentRecordList {
+            records: records_around(records, owner_id, request.limit()?),
+            ..Default::default()
+        }
+        .serialize_json())
+    }
+
+    fn list_notifications(&self, me: &str, request: &Request) -> Result<String, ApiError> {
+        let cursor = request
+            .query("cacheable_cursor")
+            .filter(|cursor| !cursor.is_empty());
+        let after = match cursor {
+            Some(cursor) => base64::decode(cursor)
+                .ok()
+                .and_then(|sequence| String::from_utf8(sequence).ok())
+                .and_then(|sequence| sequence.parse::<u64>().ok())
+                .ok_or_else(|| ApiError::invalid_argument("Malformed cursor was used."))?,
+            None => 0,
+        };
+
+        let notifications: Vec<_> = self
+            .notifications
+            .iter()
+            .filter(|notification| notification.user_id == me && notification.sequence > after)
+            .take(request.limit()?)
+            .collect();
+        // The cursor can be stored to only fetch newer notifications later
+        let cacheable_cursor = match notifications.last() {
+            Some(notification) => encode_cursor(notification.sequence as usize),
+            None => cursor.unwrap_or_default().to_owned(),
+        };
+        Ok(ApiNotificationList {
+            notifications: notifications
+                .into_iter()
+                .map(|notification| notification.notification.clone())
+                .collect(),
+            cacheable_cursor,
+        }
+        .serialize_json())
+    }
+
+    pub(super) fn can_read_channel(&self, me: &str, channel_id: &str) -> bool {
+        let parts: Vec<&str> = channel_id.split('.').collect();
+        match parts.as_slice() {
+            ["2", "", "", room] => !room.is_empty(),
+            ["3", group_id, "", ""] => self
+                .group_member_state(group_id, me)
+                .is_some_and(|state| state <= MEMBER),
+            ["4", one, two, ""] => *one == me || *two == me,
+            _ => false,
+        }
+    }
+
+    fn list_channel_messages(
+        &self,
+        me: &str,
+        channel_id: &str,
+        request: &Request,
+    ) -> Result<String, ApiError> {
+        if !self.can_read_channel(me, channel_id) {
+            return Err(ApiError::invalid_argument("Invalid channel ID."));
+        }
+        let mut messages: Vec<_> = self
+            .messages
+            .iter()
+            .filter(|message| message.channel_id == channel_id && message.persistent)
+            .cloned()
+            .collect();
+        if !request.query_bool("forward", true) {
+            messages.reverse();
+        }
+        let (messages, next_cursor, prev_cursor) =
+            paginate(&messages, request.limit()?, request.query("cursor"))?;
+        Ok(ApiChannelMessageList {
+            messages,
+            next_cursor,
+            prev_cursor,
+            cacheable_cursor: String::new(),
+        }
+        .serialize_json())
+    }
+
+    fn list_matches(&self, request: &Request) -> Result<String, ApiError> {
+        // Only relayed matches exist
+        if request.query_bool("authoritative", false) {
+            return Ok(ApiMatchList::default().serialize_json());
+        }
+        let min_size = request.query_i32("min_size")?;
+        let max_size = request.query_i32("max_size")?;
+        let matches = self
+            .realtime
+            .matches()
+            .into_iter()
+            .filter(|api_match| {
+                min_size.is_none_or(|min| api_match.size >= min)
+                    && max_size.is_none_or(|max| api_match.size <= max)
+            })
+            .take(request.limit()?)
+            .collect();
+        Ok(ApiMatchList { matches }.serialize_json())
+    }
+
+    fn call_rpc(&mut self, me: &str, id: &str, request: &Request) -> Result<String, ApiError> {
+        let payload = if request.method == "GET" {
+            request.query("payload").unwrap_or_default().to_owned()
+        } else {
+            // The payload is sent as a JSON string
+            String::deserialize_json(&request.body).unwrap_or_else(|_| request.body.clone())
+        };
+        let payload = self.rpc(me, id, &payload)?;
+        Ok(ApiRpc {
+            id: id.to_owned(),
+            payload,
+            http_key: String::new(),
+        }
+        .serialize_json())
+    }
+}
+
+fn link_identity(account: &mut Account, identity: Identity) {
+    match identity {
+        Identity::Device(id) => account.account.devices.push(ApiAccountDevice {
+            id,
+            vars: HashMap::new(),
+        }),
+        Identity::Email(email, password) => {
+            account.account.email = email;
+            account.password = password;
+        }
+        Identity::Provider(provider, id) => *provider_id(&mut account.account, &provider) = id,
+    }
+}
@@ -0,0 +1,505 @@
+use nanoserde::{DeJson, DeJsonErr, DeJsonState, SerJson, SerJsonState};
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt::{Debug, Display, Formatter};
+use std::ops::{Add, Sub};
+use std::str::{Chars, FromStr};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const SECS_PER_DAY: i64 = 86400;
+const NANOS_PER_SEC: u32 = 1_000_000_000;
+
+/// A point in time, sent by the server as a RFC 3339 timestamp, e.g. `2021-06-21T12:00:00Z`.
+///
+/// Timestamps are ordered and can be converted to and from `SystemTime`. Arithmetic and conversions saturate
+/// instead of overflowing:
+/// ```
+/// # use nakama_rs::timestamp::Timestamp;
+/// # use std::time::Duration;
+/// let start: Timestamp = "2021-06-21T12:00:00Z".parse().unwrap();
+/// let end: Timestamp = "2021-06-21T14:30:00+02:00".parse().unwrap();
+/// assert_eq!(end.duration_since(start), Some(Duration::from_secs(30 * 60)));
+/// assert_eq!(start + Duration::from_millis(1500), "2021-06-21T12:00:01.5Z".parse().unwrap());
+/// assert_eq!(end.to_string(), "2021-06-21T12:30:00Z");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Timestamp {
+    // Seconds since the unix epoch and the nanoseconds within that second
+    secs: i64,
+    nanos: u32,
+}
+
+impl Timestamp {
+    /// The earliest representable timestamp.
+    pub const MIN: Timestamp = Timestamp {
+        secs: i64::MIN,
+        nanos: 0,
+    };
+
+    /// The latest representable timestamp.
+    pub const MAX: Timestamp = Timestamp {
+        secs: i64::MAX,
+        nanos: NANOS_PER_SEC - 1,
+    };
+
+    pub fn now() -> Timestamp {
+        SystemTime::now().into()
+    }
+
+    /// The timestamp `secs` seconds and `nanos` nanoseconds after the unix epoch.
+    pub fn from_unix(secs: i64, nanos: u32) -> Timestamp {
+        Timestamp::from_nanos(secs as i128, nanos as i128).unwrap_or(Timestamp::MAX)
+    }
+
+    // `secs` seconds plus `nanos` nanoseconds after the unix epoch, `nanos` may be negative or
+    // exceed a second
+    fn from_nanos(secs: i128, nanos: i128) -> Option<Timestamp> {
+        let nanos_per_sec = NANOS_PER_SEC as i128;
+        let secs = secs + nanos.div_euclid(nanos_per_sec);
+        Some(Timestamp {
+            secs: i64::try_from(secs).ok()?,
+            nanos: nanos.rem_euclid(nanos_per_sec) as u32,
+        })
+    }
+
+    /// The timestamp `duration` after this one, or `None` if it is out of range.
+    pub fn checked_add(&self, duration: Duration) -> Option<Timestamp> {
+        Timestamp::from_nanos(
+            self.secs as i128 + duration.as_secs() as i128,
+            self.nanos as i128 + duration.subsec_nanos() as i128,
+        )
+    }
+
+    /// The timestamp `duration` before this one, or `None` if it is out of range.
+    pub fn checked_sub(&self, duration: Duration) -> Option<Timestamp> {
+        Timestamp::from_nanos(
+            self.secs as i128 - duration.as_secs() as i128,
+            self.nanos as i128 - duration.subsec_nanos() as i128,
+        )
+    }
+
+    /// The whole seconds since the unix epoch, negative for timestamps before it.
+    pub fn unix_seconds(&self) -> i64 {
+        self.secs
+    }
+
+    /// The nanoseconds after `unix_seconds`.
+    pub fn subsec_nanos(&self) -> u32 {
+        self.nanos
+    }
+
+    /// The time elapsed from `earlier` to this timestamp, or `None` if `earlier` is later.
+    pub fn duration_since(&self, earlier: Timestamp) -> Option<Duration> {
+        if *self < earlier {
+            return None;
+        }
+        // The difference of two `i64` always fits into an `u64`
+        let secs = (self.secs as i128 - earlier.secs as i128) as u64;
+        if self.nanos >= earlier.nanos {
+            Some(Duration::new(secs, self.nanos - earlier.nanos))
+        } else {
+            Some(Duration::new(
+                secs - 1,
+                self.nanos + NANOS_PER_SEC - earlier.nanos,
+            ))
+        }
+    }
+
+    pub fn to_system_time(&self) -> SystemTime {
+        (*self).into()
+    }
+}
+
+impl From<SystemTime> for Timestamp {
+    fn from(time: SystemTime) -> Self {
+        match time.duration_since(UNIX_EPOCH) {
+            Ok(duration) => Timestamp::from_unix(0, 0) + duration,
+            Err(err) => Timestamp::from_unix(0, 0) - err.duration(),
+        }
+    }
+}
+
+/// Timestamps outside of the range of `SystemTime` saturate to the earliest or latest `SystemTime` the platform
+/// can represent, with whole seconds.
+impl From<Timestamp> for SystemTime {
+    fn from(timestamp: Timestamp) -> Self {
+        let after_epoch = timestamp.secs >= 0;
+        let offset = |duration: Duration| match after_epoch {
+            true => UNIX_EPOCH.checked_add(duration),
+            false => UNIX_EPOCH.checked_sub(duration),
+        };
+        let secs = timestamp.secs.unsigned_abs();
+        let duration = match after_epoch {
+            true => Duration::new(secs, timestamp.nanos),
+            false => Duration::new(secs, 0) - Duration::from_nanos(timestamp.nanos as u64),
+        };
+        if let Some(time) = offset(duration) {
+            return time;
+        }
+        // Binary search for the largest offset in seconds that can be represented
+        let (mut valid, mut invalid) = (0, secs);
+        while invalid - valid > 1 {
+            let middle = valid + (invalid - valid) / 2;
+            match offset(Duration::from_secs(middle)) {
+                Some(_) => valid = middle,
+                None => invalid = middle,
+            }
+        }
+        offset(Duration::from_secs(valid)).unwrap_or(UNIX_EPOCH)
+    }
+}
+
+/// Saturates at `Timestamp::MAX`.
+impl Add<Duration> for Timestamp {
+    type Output = Timestamp;
+
+    fn add(self, duration: Duration) -> Timestamp {
+        self.checked_add(duration).unwrap_or(Timestamp::MAX)
+    }
+}
+
+/// Saturates at `Timestamp::MIN`.
+impl Sub<Duration> for Timestamp {
+    type Output = Timestamp;
+
+    fn sub(self, duration: Duration) -> Timestamp {
+        self.checked_sub(duration).unwrap_or(Timestamp::MIN)
+    }
+}
+
+// Days to civil date and back from http://howardhinnant.github.io/date_algorithms.html
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month_index = if month > 2 { month - 3 } else { month + 9 };
+    let day_of_year = (153 * month_index + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Formats the timestamp in UTC with 0, 3, 6 or 9 fractional digits, like the server does.
+impl Display for Timestamp {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let (year, month, day) = civil_from_days(self.secs.div_euclid(SECS_PER_DAY));
+        let secs_of_day = self.secs.rem_euclid(SECS_PER_DAY);
+        write!(
+            f,
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+            year,
+            month,
+            day,
+            secs_of_day / 3600,
+            secs_of_day / 60 % 60,
+            secs_of_day % 60
+        )?;
+        match self.nanos {
+            0 => write!(f, "Z"),
+            nanos if nanos % 1_000_000 == 0 => write!(f, ".{:03}Z", nanos / 1_000_000),
+            nanos if nanos % 1000 == 0 => write!(f, ".{:06}Z", nanos / 1000),
+            nanos => write!(f, ".{:09}Z", nanos),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseTimestampError {
+    pub input: String,
+}
+
+impl Display for ParseTimestampError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Debug::fmt(self, f)
+    }
+}
+
+impl Error for ParseTimestampError {}
+
+impl FromStr for Timestamp {
+    type Err = ParseTimestampError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        parse(input.as_bytes()).ok_or_else(|| ParseTimestampError {
+            input: input.to_owned(),
+        })
+    }
+}
+
+fn parse_number(bytes: &[u8]) -> Option<i64> {
+    bytes.iter().try_fold(0, |number, byte| match byte {
+        b'0'..=b'9' => Some(number * 10 + (byte - b'0') as i64),
+        _ => None,
+    })
+}
+
+// Parses `YYYY-MM-DDTHH:MM:SS[.fraction](Z|+HH:MM|-HH:MM)`
+fn parse(bytes: &[u8]) -> Option<Timestamp> {
+    if bytes.len() < 20
+        || bytes[4] != b'-'
+        || bytes[7] != b'-'
+        || !matches!(bytes[10], b'T' | b't' | b' ')
+        || bytes[13] != b':'
+        || bytes[16] != b':'
+    {
+        return None;
+    }
+    let year = parse_number(&bytes[0..4])?;
+    let month = parse_number(&bytes[5..7])?;
+    let day = parse_number(&bytes[8..10])?;
+    let hour = parse_number(&bytes[11..13])?;
+    let minute = parse_number(&bytes[14..16])?;
+    // 60 is a leap second
+    let second = parse_number(&bytes[17..19])?;
+    if !(1..=12).contains(&month)
+        || !(1..=days_in_month(year, month)).contains(&day)
+        || hour > 23
+        || minute > 59
+        || second > 60
+    {
+        return None;
+    }
+
+    let mut rest = &bytes[19..];
+    let mut nanos = 0;
+    if rest[0] == b'.' {
+        let digits = rest[1..]
+            .iter()
+            .take_while(|byte| byte.is_ascii_digit())
+            .count();
+        if digits == 0 {
+            return None;
+        }
+        // Digits beyond nanoseconds are truncated
+        let fraction = parse_number(&rest[1..1 + digits.min(9)])?;
+        nanos = (fraction * 10i64.pow(9 - digits.min(9) as u32)) as u32;
+        rest = &rest[1 + digits..];
+    }
+
+    let offset = match rest {
+        [b'Z'] | [b'z'] => 0,
+        [sign @ (b'+' | b'-'), hours @ .., b':', m1, m2] if hours.len() == 2 => {
+            let hours = parse_number(hours)?;
+            let minutes = parse_number(&[*m1, *m2])?;
+            if hours > 23 || minutes > 59 {
+                return None;
+            }
+            let offset = hours * 3600 + minutes * 60;
+            if *sign == b'+' {
+                offset
+            } else {
+                -offset
+            }
+        }
+        _ => return None,
+    };
+
+    let days = days_from_civil(year, month, day);
+    let secs = days * SECS_PER_DAY + hour * 3600 + minute * 60 + second - offset;
+    Some(Timestamp::from_unix(secs, nanos))
+}
+
+impl SerJson for Timestamp {
+    fn ser_json(&self, d: usize, s: &mut SerJsonState) {
+        self.to_string().ser_json(d, s);
+    }
+}
+
+/// An empty string, sent by some servers for unset timestamps, is read as the default timestamp, the unix epoch.
+impl DeJson for Timestamp {
+    fn de_json(state: &mut DeJsonState, input: &mut Chars) -> Result<Self, DeJsonErr> {
+        let value: String = DeJson::de_json(state, input)?;
+        if value.is_empty() {
+            return Ok(Timestamp::default());
+        }
+        // TODO: macro for line number
+        value.parse().map_err(|_| DeJsonErr {
+            col: 0,
+            line: 0,
+            msg: format!("Invalid RFC 3339 timestamp {:?}", value),
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Timestamp {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Timestamp {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        if value.is_empty() {
+            return Ok(Timestamp::default());
+        }
+        value.parse().map_err(|_| {
+            serde::de::Error::custom(format!("Invalid RFC 3339 timestamp {:?}", value))
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Timestamp, NANOS_PER_SEC};
+    use crate::api::ApiUser;
+    use nanoserde::{DeJson, SerJson};
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn parse(input: &str) -> Timestamp {
+        input.parse().unwrap()
+    }
+
+    #[test]
+    fn test_format() {
+        assert_eq!(
+            Timestamp::from_unix(0, 0).to_string(),
+            "1970-01-01T00:00:00Z"
+        );
+        assert_eq!(
+            Timestamp::from_unix(951782400, 0).to_string(),
+            "2000-02-29T00:00:00Z"
+        );
+        assert_eq!(
+            Timestamp::from_unix(1624276800, 120_000_000).to_string(),
+            "2021-06-21T12:00:00.120Z"
+        );
+        assert_eq!(
+            Timestamp::from_unix(1624276800, 1_000).to_string(),
+            "2021-06-21T12:00:00.000001Z"
+        );
+        assert_eq!(
+            Timestamp::from_unix(-1, 5).to_string(),
+            "1969-12-31T23:59:59.000000005Z"
+        );
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse("1970-01-01T00:00:00Z"), Timestamp::from_unix(0, 0));
+        assert_eq!(
+            parse("2000-02-29t01:00:00.5+01:00"),
+            Timestamp::from_unix(951782400, 500_000_000)
+        );
+        assert_eq!(
+            parse("2021-06-21 11:00:00.1234567891-01:00"),
+            Timestamp::from_unix(1624276800, 123_456_789)
+        );
+
+        for invalid in &[
+            "",
+            "2021-06-21",
+            "2021-06-21T12:00:00",
+            "2021-13-01T12:00:00Z",
+            "2021-02-29T12:00:00Z",
+            "2021-06-21T24:00:00Z",
+            "2021-06-21T12:00:00.Z",
+            "2021-06-21T12:00:00+0100",
+            "2021-06-21T12:00:00Zjunk",
+        ] {
+            assert_eq!(invalid.parse::<Timestamp>().is_err(), true, "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let timestamp = parse("2021-06-21T12:00:00.750Z");
+        let later = timestamp + Duration::from_millis(500);
+        assert_eq!(later, parse("2021-06-21T12:00:01.250Z"));
+        assert_eq!(later - Duration::from_millis(500), timestamp);
+        assert_eq!(timestamp < later, true);
+        assert_eq!(
+            later.duration_since(timestamp),
+            Some(Duration::from_millis(500))
+        );
+        assert_eq!(timestamp.duration_since(later), None);
+    }
+
+    #[test]
+    fn test_overflow() {
+        let max_duration = Duration::new(u64::MAX, 999_999_999);
+        assert_eq!(Timestamp::MAX + Duration::from_nanos(1), Timestamp::MAX);
+        assert_eq!(Timestamp::from_unix(0, 0) + max_duration, Timestamp::MAX);
+        assert_eq!(Timestamp::MIN - Duration::from_nanos(1), Timestamp::MIN);
+        assert_eq!(Timestamp::from_unix(0, 0) - max_duration, Timestamp::MIN);
+        assert_eq!(Timestamp::MAX.checked_add(Duration::from_nanos(1)), None);
+        assert_eq!(
+            Timestamp::MIN.checked_add(Duration::new(u64::MAX, 0)),
+            Some(Timestamp::from_unix(i64::MAX, 0))
+        );
+        assert_eq!(
+            Timestamp::from_unix(i64::MAX, NANOS_PER_SEC),
+            Timestamp::MAX
+        );
+        assert_eq!(
+            Timestamp::MAX.duration_since(Timestamp::MIN),
+            Some(max_duration)
+        );
+    }
+
+    #[test]
+    fn test_system_time() {
+        let time = UNIX_EPOCH + Duration::new(1624276800, 42);
+        assert_eq!(Timestamp::from(time).to_system_time(), time);
+        let time = UNIX_EPOCH - Duration::new(10, 42);
+        assert_eq!(
+            Timestamp::from(time),
+            Timestamp::from_unix(-11, 999_999_958)
+        );
+        assert_eq!(Timestamp::from(time).to_system_time(), time);
+
+        // Saturates instead of panicking
+        assert_eq!(Timestamp::MAX.to_system_time() > time, true);
+        assert_eq!(Timestamp::MIN.to_system_time() < time, true);
+    }
+
+    #[test]
+    fn test_json() {
+        let timestamp = parse("2021-06-21T12:00:00Z");
+        assert_eq!(timestamp.serialize_json(), "\"2021-06-21T12:00:00Z\"");
+        assert_eq!(
+            Timestamp::deserialize_json("\"2021-06-21T12:00:00Z\"").unwrap(),
+            timestamp
+        );
+        assert_eq!(Timestamp::deserialize_json("\"yesterday\"").is_err(), true);
+        assert_eq!(
+            Timestamp::deserialize_json("\"\"").unwrap(),
+            Timestamp::default()
+        );
+
+        let user = ApiUser::deserialize_json(
+            r#"{"id":"1","create_time":"2021-06-21T12:00:00Z","update_time":""}"#,
+        )
+        .unwrap();
+        assert_eq!(user.create_time, Some(timestamp));
+        assert_eq!(user.update_time, Some(Timestamp::default()));
+    }
+}
@@ -1,28 +1,34 @@
 use crate::api::{ApiChannelMessage, ApiNotification, ApiRpc};
+use crate::chat;
 use crate::session::Session;
 use crate::socket::{
-    Channel, ChannelJoin, ChannelLeave, ChannelMesageRemove, ChannelMesageUpdate,
-    ChannelMessageAck, ChannelMessageSend, ChannelPresenceEvent, Error, Match, MatchCreate,
-    MatchData, MatchDataSend, MatchJoin, MatchLeave, MatchPresenceEvent, MatchmakerAdd,
-    MatchmakerMatched, MatchmakerRemove, MatchmakerTicket, Party, PartyAccept, PartyClose,
-    PartyCreate, PartyData, PartyDataSend, PartyJoin, PartyJoinRequest, PartyJoinRequestList,
-    PartyLeader, PartyLeave, PartyMatchmakerAdd, PartyMatchmakerRemove, PartyMatchmakerTicket,
-    PartyPresenceEvent, PartyPromote, PartyRemove, Socket, Status, StatusFollow,
-    StatusPresenceEvent, StatusUnfollow, StatusUpdate, StreamData, StreamPresenceEvent,
-    UserPresence, WebSocketMessageEnvelope, WebSocketMessageEnvelopeHeader,
+    Channel, ChannelJoin, ChannelJoinType, ChannelLeave, ChannelMesageRemove, ChannelMesageUpdate,
+    ChannelMessageAck, ChannelMessageSend, ChannelPresenceEvent, Error, ErrorCode, HandlerId,
+    Match, MatchCreate, MatchData, MatchDataSend, MatchJoin, MatchLeave, MatchPresenceEvent,
+    MatchmakerAdd, MatchmakerMatched, MatchmakerRemove, MatchmakerTicket, Party, PartyAccept,
+    PartyClose, PartyCreate, PartyData, PartyDataSend, PartyJoin, PartyJoinRequest,
+    PartyJoinRequestList, PartyLeader, PartyLeave, PartyMatchmakerAdd, PartyMatchmakerRemove,
+    PartyMatchmakerTicket, PartyPresenceEvent, PartyPromote, PartyRemove, Socket, Status,
+    StatusFollow, StatusPresenceEvent, StatusUnfollow, StatusUpdate, StreamData,
+    StreamPresenceEvent, UserPresence, WebSocketMessageEnvelope, WebSocketMessageEnvelopeHeader,
 };
-use crate::socket_adapter::SocketAdapter;
+use crate::socket_adapter::{SocketAdapter, Wakeup};
+use crate::sync_strategy::{Local, SyncStrategy, Threaded};
 use async_trait::async_trait;
 use log::{error, trace};
 use nanoserde::{DeJson, DeJsonErr, SerJson};
 use std::collections::HashMap;
 use std::error;
-use std::sync::{Arc, Mutex};
+use std::sync::mpsc;
+use std::sync::mpsc::Receiver;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use crate::default_client::str_slice_to_owned;
-use crate::web_socket_adapter::{WebSocketAdapter};
+#[cfg(feature = "qws-adapter")]
+use crate::web_socket_adapter::WebSocketAdapter;
 use oneshot;
-use oneshot::{RecvError};
+use oneshot::RecvError;
 use std::fmt::{Debug, Display, Formatter};
 
 pub enum WebSocketError<A: SocketAdapter> {
@@ -53,37 +59,168 @@ impl<A: SocketAdapter> Display for WebSocketError<A> {
 
 impl<A: SocketAdapter> error::Error for WebSocketError<A> {}
 
-#[derive(Default)]
-struct SharedState {
+impl<A: SocketAdapter> WebSocketError<A> {
+    /// The error sent by the server, if the request was rejected by the server.
+    pub fn api_error(&self) -> Option<&Error> {
+        match self {
+            WebSocketError::ApiError(err) => Some(err),
+            _ => None,
+        }
+    }
+
+    /// The code of the error sent by the server, e.g. `ErrorCode::MatchJoinRejected` when a
+    /// match join was rejected.
+    pub fn error_code(&self) -> Option<ErrorCode> {
+        self.api_error().map(|err| err.code)
+    }
+}
+
+struct Handlers<C> {
+    entries: Vec<(HandlerId, C)>,
+}
+
+impl<C> Default for Handlers<C> {
+    fn default() -> Self {
+        Handlers {
+            entries: Vec::new(),
+        }
+    }
+}
+
+impl<C> Handlers<C> {
+    fn remove(&mut self, id: HandlerId) -> bool {
+        let len = self.entries.len();
+        self.entries.retain(|(handler_id, _)| *handler_id != id);
+        self.entries.len() != len
+    }
+}
+
+type Callbacks<S, E> = Handlers<Box<<S as SyncStrategy>::Callback<E>>>;
+
+// Events sent by the adapter callbacks. They are handled in `tick` after the adapter is ticked,
+// so that the adapter is not borrowed while the socket callbacks are invoked.
+enum AdapterEvent {
+    Connected,
+    Closed,
+    Received(String),
+}
+
+struct SharedState<S: SyncStrategy> {
+    server: String,
+    port: i32,
     cid: i64,
     connected: Vec<oneshot::Sender<()>>,
     responses: HashMap<i64, oneshot::Sender<Result<WebSocketMessageEnvelope, DeJsonErr>>>,
-    timeouts: HashMap<i64, i64>,
-    on_closed: Option<Box<dyn Fn() + Send + 'static>>,
-    on_connected: Option<Box<dyn Fn() + Send + 'static>>,
-    on_received_channel_message: Option<Box<dyn Fn(ApiChannelMessage) + Send + 'static>>,
-    on_received_channel_presence: Option<Box<dyn Fn(ChannelPresenceEvent) + Send + 'static>>,
-    on_received_error: Option<Box<dyn Fn(Error) + Send + 'static>>,
-    on_received_matchmaker_matched: Option<Box<dyn Fn(MatchmakerMatched) + Send + 'static>>,
-    on_received_match_state: Option<Box<dyn Fn(MatchData) + Send + 'static>>,
-    on_received_match_presence: Option<Box<dyn Fn(MatchPresenceEvent) + Send + 'static>>,
-    on_received_notification: Option<Box<dyn Fn(ApiNotification) + Send + 'static>>,
-    on_received_party_close: Option<Box<dyn Fn(PartyClose) + Send + 'static>>,
-    on_received_party_data: Option<Box<dyn Fn(PartyData) + Send + 'static>>,
-    on_received_party_join_request: Option<Box<dyn Fn(PartyJoinRequest) + Send + 'static>>,
-    on_received_party_leader: Option<Box<dyn Fn(PartyLeader) + Send + 'static>>,
-    on_received_party_presence: Option<Box<dyn Fn(PartyPresenceEvent) + Send + 'static>>,
-    on_received_status_presence: Option<Box<dyn Fn(StatusPresenceEvent) + Send + 'static>>,
-    on_received_stream_presence: Option<Box<dyn Fn(StreamPresenceEvent) + Send + 'static>>,
-    on_received_stream_state: Option<Box<dyn Fn(StreamData) + Send + 'static>>,
+    timeouts: HashMap<i64, Instant>,
+    events: Receiver<AdapterEvent>,
+    next_handler_id: u64,
+    // Handlers that are currently being invoked and are therefore not part of their list
+    dispatching: Vec<HandlerId>,
+    // Handlers that were removed while being invoked
+    removed_while_dispatching: Vec<HandlerId>,
+    on_closed: Callbacks<S, ()>,
+    on_connected: Callbacks<S, ()>,
+    on_received_channel_message: Callbacks<S, ApiChannelMessage>,
+    on_received_channel_presence: Callbacks<S, ChannelPresenceEvent>,
+    on_received_error: Callbacks<S, Error>,
+    on_received_matchmaker_matched: Callbacks<S, MatchmakerMatched>,
+    on_received_match_state: Callbacks<S, MatchData>,
+    on_received_match_presence: Callbacks<S, MatchPresenceEvent>,
+    on_received_notification: Callbacks<S, ApiNotification>,
+    on_received_party_close: Callbacks<S, PartyClose>,
+    on_received_party_data: Callbacks<S, PartyData>,
+    on_received_party_join_request: Callbacks<S, PartyJoinRequest>,
+    on_received_party_leader: Callbacks<S, PartyLeader>,
+    on_received_party_presence: Callbacks<S, PartyPresenceEvent>,
+    on_received_status_presence: Callbacks<S, StatusPresenceEvent>,
+    on_received_stream_presence: Callbacks<S, StreamPresenceEvent>,
+    on_received_stream_state: Callbacks<S, StreamData>,
+}
+
+impl<S: SyncStrategy> SharedState<S> {
+    fn new(events: Receiver<AdapterEvent>) -> Self {
+        SharedState {
+            server: "ws://127.0.0.1".to_owned(),
+            port: 7350,
+            cid: 0,
+            connected: Vec::new(),
+            responses: HashMap::new(),
+            timeouts: HashMap::new(),
+            events,
+            next_handler_id: 0,
+            dispatching: Vec::new(),
+            removed_while_dispatching: Vec::new(),
+            on_closed: Handlers::default(),
+            on_connected: Handlers::default(),
+            on_received_channel_message: Handlers::default(),
+            on_received_channel_presence: Handlers::default(),
+            on_received_error: Handlers::default(),
+            on_received_matchmaker_matched: Handlers::default(),
+            on_received_match_state: Handlers::default(),
+            on_received_match_presence: Handlers::default(),
+            on_received_notification: Handlers::default(),
+            on_received_party_close: Handlers::default(),
+            on_received_party_data: Handlers::default(),
+            on_received_party_join_request: Handlers::default(),
+            on_received_party_leader: Handlers::default(),
+            on_received_party_presence: Handlers::default(),
+            on_received_status_presence: Handlers::default(),
+            on_received_stream_presence: Handlers::default(),
+            on_received_stream_state: Handlers::default(),
+        }
+    }
+
+    fn add_handler<C>(
+        &mut self,
+        handlers: fn(&mut Self) -> &mut Handlers<C>,
+        callback: C,
+    ) -> HandlerId {
+        self.next_handler_id += 1;
+        let id = HandlerId(self.next_handler_id);
+        handlers(self).entries.push((id, callback));
+        id
+    }
+
+    fn remove_handler(&mut self, id: HandlerId) -> bool {
+        if self.dispatching.contains(&id) {
+            if self.removed_while_dispatching.contains(&id) {
+                return false;
+            }
+            self.removed_while_dispatching.push(id);
+            return true;
+        }
+
+        self.on_closed.remove(id)
+            || self.on_connected.remove(id)
+            || self.on_received_channel_message.remove(id)
+            || self.on_received_channel_presence.remove(id)
+            || self.on_received_error.remove(id)
+            || self.on_received_matchmaker_matched.remove(id)
+            || self.on_received_match_state.remove(id)
+            || self.on_received_match_presence.remove(id)
+            || self.on_received_notification.remove(id)
+            || self.on_received_party_close.remove(id)
+            || self.on_received_party_data.remove(id)
+            || self.on_received_party_join_request.remove(id)
+            || self.on_received_party_leader.remove(id)
+            || self.on_received_party_presence.remove(id)
+            || self.on_received_status_presence.remove(id)
+            || self.on_received_stream_presence.remove(id)
+            || self.on_received_stream_state.remove(id)
+    }
 }
 
-pub struct WebSocket<A: SocketAdapter> {
-    adapter: Arc<Mutex<A>>,
-    shared_state: Arc<Mutex<SharedState>>,
+/// An implementation of `Socket` on top of a `SocketAdapter`.
+///
+/// The `SyncStrategy` decides how the state is shared between clones of the socket. By default
+/// `WebSocket` can be sent between threads. See `LocalWebSocket` for a socket that accepts
+/// callbacks that are not `Send`.
+pub struct WebSocket<A: SocketAdapter, S: SyncStrategy = Threaded> {
+    adapter: S::Shared<A>,
+    shared_state: S::Shared<SharedState<S>>,
 }
 
-impl<A: SocketAdapter> Clone for WebSocket<A> {
+impl<A: SocketAdapter, S: SyncStrategy> Clone for WebSocket<A, S> {
     fn clone(&self) -> Self {
         WebSocket {
             adapter: self.adapter.clone(),
@@ -92,242 +229,351 @@ impl<A: SocketAdapter> Clone for WebSocket<A> {
     }
 }
 
-fn handle_message(shared_state: &Arc<Mutex<SharedState>>, msg: &String) {
-    trace!("handle_message: Received message: {:?}", msg);
-    let result: Result<WebSocketMessageEnvelope, DeJsonErr> = DeJson::deserialize_json(&msg);
-    let mut shared_state = shared_state.lock().unwrap();
-    match result {
-        Ok(event) => {
-            if let Some(ref cid) = event.cid {
-                trace!("handle_message: Received message with cid");
-                let cid = cid.parse::<i64>().unwrap();
-                if let Some(response_event) = shared_state.responses.remove(&cid) {
-                    let result = response_event.send(Ok(event));
-                    if let Err(err) = result {
-                        error!("handle_message: send error: {}", err);
-                    }
+#[cfg(feature = "qws-adapter")]
+impl WebSocket<WebSocketAdapter> {
+    pub fn new_with_adapter() -> Self {
+        let adapter = WebSocketAdapter::new();
+        WebSocket::new(adapter)
+    }
+}
+
+impl<A: SocketAdapter> WebSocket<A> {
+    pub fn new(adapter: A) -> Self {
+        WebSocket::new_with_strategy(adapter)
+    }
+
+    /// Connect to `server:port` instead of the default `ws://127.0.0.1:7350`.
+    pub fn new_with_server(adapter: A, server: &str, port: i32) -> Self {
+        let socket = WebSocket::new_with_strategy(adapter);
+        socket.set_server(server, port);
+        socket
+    }
+
+    // Used by `SocketDriver`: ticks the socket without keeping it alive. Returns `false` once the
+    // socket was dropped.
+    pub(crate) fn weak_ticker(&self, wakeup: Wakeup) -> Box<dyn Fn() -> bool + Send>
+    where
+        A: Send + 'static,
+    {
+        Threaded::with(&self.adapter, |adapter| adapter.set_wakeup(wakeup));
+        let adapter = Arc::downgrade(&self.adapter);
+        let shared_state = Arc::downgrade(&self.shared_state);
+        Box::new(move || match (adapter.upgrade(), shared_state.upgrade()) {
+            (Some(adapter), Some(shared_state)) => {
+                WebSocket::<A> {
+                    adapter,
+                    shared_state,
                 }
-                return;
+                .tick();
+                true
             }
-            if let Some(message) = event.channel_message {
-                if let Some(ref cb) = shared_state.on_received_channel_message {
-                    cb(message)
+            _ => false,
+        })
+    }
+}
+
+impl<A: SocketAdapter, S: SyncStrategy> WebSocket<A, S> {
+    fn new_with_strategy(mut adapter: A) -> Self {
+        let (tx, rx) = mpsc::channel();
+
+        adapter.on_received({
+            let tx = tx.clone();
+            move |msg| match msg {
+                Err(error) => {
+                    error!("on_received: {}", error);
                 }
-                return;
-            }
-            if let Some(message) = event.channel_presence_event {
-                if let Some(ref cb) = shared_state.on_received_channel_presence {
-                    cb(message)
+                Ok(msg) => {
+                    trace!("on_received: {}", msg);
+                    if tx.send(AdapterEvent::Received(msg)).is_err() {
+                        trace!("on_received: Socket was dropped");
+                    }
                 }
-                return;
             }
-            if let Some(message) = event.error {
-                if let Some(ref cb) = shared_state.on_received_error {
-                    cb(message)
+        });
+
+        adapter.on_closed({
+            let tx = tx.clone();
+            move || {
+                if tx.send(AdapterEvent::Closed).is_err() {
+                    trace!("on_closed: Socket was dropped");
                 }
-                return;
             }
-            if let Some(message) = event.matchmaker_matched {
-                if let Some(ref cb) = shared_state.on_received_matchmaker_matched {
-                    cb(message)
-                }
-                return;
+        });
+
+        adapter.on_connected(move || {
+            if tx.send(AdapterEvent::Connected).is_err() {
+                trace!("on_connected: Socket was dropped");
             }
-            if let Some(message) = event.match_data {
-                if let Some(ref cb) = shared_state.on_received_match_state {
-                    cb(message)
-                }
-                return;
+        });
+
+        WebSocket {
+            adapter: S::new_shared(adapter),
+            shared_state: S::new_shared(SharedState::new(rx)),
+        }
+    }
+
+    fn set_server(&self, server: &str, port: i32) {
+        S::with(&self.shared_state, |state| {
+            state.server = server.to_owned();
+            state.port = port;
+        });
+    }
+
+    pub fn tick(&self) {
+        S::with(&self.adapter, |adapter| adapter.tick());
+
+        let events: Vec<AdapterEvent> = S::with(&self.shared_state, |state| {
+            state.events.try_iter().collect()
+        });
+        for event in events {
+            match event {
+                AdapterEvent::Connected => self.handle_connected(),
+                AdapterEvent::Closed => self.dispatch(|state| &mut state.on_closed, |cb| cb(())),
+                AdapterEvent::Received(msg) => self.handle_message(&msg),
             }
-            if let Some(message) = event.match_presence_event {
-                if let Some(ref cb) = shared_state.on_received_match_presence {
-                    cb(message)
+        }
+
+        S::with(&self.shared_state, |shared_state| {
+            let now = Instant::now();
+            let (timeout_finished, timeouts): (HashMap<_, _>, _) = shared_state
+                .timeouts
+                .iter()
+                .partition(|&(_, deadline)| *deadline <= now);
+            shared_state.timeouts = timeouts;
+            timeout_finished.iter().for_each(|(k, _)| {
+                shared_state.responses.remove(k);
+            })
+        });
+    }
+
+    fn remove_handler(&self, id: HandlerId) -> bool {
+        S::with(&self.shared_state, |state| state.remove_handler(id))
+    }
+
+    fn add_handler<C>(
+        &self,
+        handlers: fn(&mut SharedState<S>) -> &mut Handlers<C>,
+        callback: C,
+    ) -> HandlerId {
+        S::with(&self.shared_state, |state| {
+            state.add_handler(handlers, callback)
+        })
+    }
+
+    // Invokes the handlers without borrowing the shared state, so that handlers can register or
+    // remove other handlers.
+    fn dispatch<C>(
+        &self,
+        handlers: fn(&mut SharedState<S>) -> &mut Handlers<C>,
+        call: impl Fn(&C),
+    ) {
+        let mut entries = S::with(&self.shared_state, |shared_state| {
+            let entries = std::mem::take(&mut handlers(shared_state).entries);
+            shared_state
+                .dispatching
+                .extend(entries.iter().map(|(id, _)| *id));
+            entries
+        });
+
+        for (_, callback) in entries.iter() {
+            call(callback);
+        }
+
+        S::with(&self.shared_state, |shared_state| {
+            let SharedState {
+                dispatching,
+                removed_while_dispatching,
+                ..
+            } = shared_state;
+            dispatching.retain(|id| !entries.iter().any(|(handler_id, _)| handler_id == id));
+            entries.retain(|(id, _)| {
+                if let Some(index) = removed_while_dispatching
+                    .iter()
+                    .position(|removed| removed == id)
+                {
+                    removed_while_dispatching.swap_remove(index);
+                    return false;
                 }
-                return;
+                true
+            });
+            // Keep handlers registered during the dispatch after the existing ones
+            let registered = &mut handlers(shared_state).entries;
+            entries.append(registered);
+            *registered = entries;
+        });
+    }
+
+    fn handle_connected(&self) {
+        self.dispatch(|state| &mut state.on_connected, |cb| cb(()));
+
+        let connected = S::with(&self.shared_state, |state| {
+            std::mem::take(&mut state.connected)
+        });
+        connected.into_iter().for_each(|sender| {
+            let result = sender.send(());
+            if let Err(err) = result {
+                error!("on_connected: Received send error: {}", err)
+            }
+        });
+    }
+
+    fn respond(&self, cid: &str, response: Result<WebSocketMessageEnvelope, DeJsonErr>) {
+        let cid = cid.parse::<i64>().unwrap();
+        let response_event = S::with(&self.shared_state, |state| state.responses.remove(&cid));
+        if let Some(response_event) = response_event {
+            let result = response_event.send(response);
+            if let Err(err) = result {
+                error!("handle_message: send error: {}", err);
             }
-            if let Some(mut message) = event.notifications {
-                if let Some(ref cb) = shared_state.on_received_notification {
-                    for message in message.notifications.drain(..) {
-                        cb(message)
+        }
+    }
+
+    fn handle_message(&self, msg: &str) {
+        trace!("handle_message: Received message: {:?}", msg);
+        let result: Result<WebSocketMessageEnvelope, DeJsonErr> = DeJson::deserialize_json(msg);
+        match result {
+            Ok(event) => {
+                if let Some(ref cid) = event.cid {
+                    trace!("handle_message: Received message with cid");
+                    let cid = cid.clone();
+                    self.respond(&cid, Ok(event));
+                    return;
+                }
+                if let Some(message) = event.channel_message {
+                    self.dispatch(
+                        |state| &mut state.on_received_channel_message,
+                        |cb| cb(message.clone()),
+                    );
+                    return;
+                }
+                if let Some(message) = event.channel_presence_event {
+                    self.dispatch(
+                        |state| &mut state.on_received_channel_presence,
+                        |cb| cb(message.clone()),
+                    );
+                    return;
+                }
+                if let Some(message) = event.error {
+                    self.dispatch(
+                        |state| &mut state.on_received_error,
+                        |cb| cb(message.clone()),
+                    );
+                    return;
+                }
+                if let Some(message) = event.matchmaker_matched {
+                    self.dispatch(
+                        |state| &mut state.on_received_matchmaker_matched,
+                        |cb| cb(message.clone()),
+                    );
+                    return;
+                }
+                if let Some(message) = event.match_data {
+                    self.dispatch(
+                        |state| &mut state.on_received_match_state,
+                        |cb| cb(message.clone()),
+                    );
+                    return;
+                }
+                if let Some(message) = event.match_presence_event {
+                    self.dispatch(
+                        |state| &mut state.on_received_match_presence,
+                        |cb| cb(message.clone()),
+                    );
+                    return;
+                }
+                if let Some(message) = event.notifications {
+                    for message in message.notifications {
+                        self.dispatch(
+                            |state| &mut state.on_received_notification,
+                            |cb| cb(message.clone()),
+                        );
                     }
+                    return;
                 }
-                return;
-            }
-            if let Some(message) = event.party_close {
-                if let Some(ref cb) = shared_state.on_received_party_close {
-                    cb(message)
+                if let Some(message) = event.party_close {
+                    self.dispatch(
+                        |state| &mut state.on_received_party_close,
+                        |cb| cb(message.clone()),
+                    );
+                    return;
                 }
-                return;
-            }
-            if let Some(message) = event.party_data {
-                if let Some(ref cb) = shared_state.on_received_party_data {
-                    cb(message)
+                if let Some(message) = event.party_data {
+                    self.dispatch(
+                        |state| &mut state.on_received_party_data,
+                        |cb| cb(message.clone()),
+                    );
+                    return;
                 }
-                return;
-            }
-            if let Some(message) = event.party_join_request {
-                if let Some(ref cb) = shared_state.on_received_party_join_request {
-                    cb(message)
+                if let Some(message) = event.party_join_request {
+                    self.dispatch(
+                        |state| &mut state.on_received_party_join_request,
+                        |cb| cb(message.clone()),
+                    );
+                    return;
                 }
-                return;
-            }
-            if let Some(message) = event.party_leader {
-                if let Some(ref cb) = shared_state.on_received_party_leader {
-                    cb(message)
+                if let Some(message) = event.party_leader {
+                    self.dispatch(
+                        |state| &mut state.on_received_party_leader,
+                        |cb| cb(message.clone()),
+                    );
+                    return;
                 }
-                return;
-            }
-            if let Some(message) = event.party_presence_event {
-                if let Some(ref cb) = shared_state.on_received_party_presence {
-                    cb(message)
+                if let Some(message) = event.party_presence_event {
+                    self.dispatch(
+                        |state| &mut state.on_received_party_presence,
+                        |cb| cb(message.clone()),
+                    );
+                    return;
                 }
-                return;
-            }
-            if let Some(message) = event.status_presence_event {
-                if let Some(ref cb) = shared_state.on_received_status_presence {
-                    cb(message)
+                if let Some(message) = event.status_presence_event {
+                    self.dispatch(
+                        |state| &mut state.on_received_status_presence,
+                        |cb| cb(message.clone()),
+                    );
+                    return;
                 }
-                return;
-            }
-            if let Some(message) = event.stream_presence_event {
-                if let Some(ref cb) = shared_state.on_received_stream_presence {
-                    cb(message)
+                if let Some(message) = event.stream_presence_event {
+                    self.dispatch(
+                        |state| &mut state.on_received_stream_presence,
+                        |cb| cb(message.clone()),
+                    );
+                    return;
                 }
-                return;
-            }
-            if let Some(message) = event.stream_data {
-                if let Some(ref cb) = shared_state.on_received_stream_state {
-                    cb(message)
+                if let Some(message) = event.stream_data {
+                    self.dispatch(
+                        |state| &mut state.on_received_stream_state,
+                        |cb| cb(message.clone()),
+                    );
                 }
-                return;
             }
-        }
-        Err(err) => {
-            error!("handle_message: Failed to parse json: {}", err);
-            let result: Result<WebSocketMessageEnvelopeHeader, DeJsonErr> =
-                DeJson::deserialize_json(&msg);
-            match result {
-                Ok(event) => {
-                    // Inform the future about the API error
-                    if let Some(ref cid) = event.cid {
-                        trace!("handle_message: Received error message with cid");
-                        let cid = cid.parse::<i64>().unwrap();
-                        if let Some(response_event) = shared_state.responses.remove(&cid) {
+            Err(err) => {
+                error!("handle_message: Failed to parse json: {}", err);
+                let result: Result<WebSocketMessageEnvelopeHeader, DeJsonErr> =
+                    DeJson::deserialize_json(msg);
+                match result {
+                    Ok(event) => {
+                        // Inform the future about the API error
+                        if let Some(ref cid) = event.cid {
+                            trace!("handle_message: Received error message with cid");
                             // Send DeJsonErr
-                            let result = response_event.send(Err(err));
-                            if let Err(err) = result {
-                                error!("handle_message: Received send error: {}", err)
-                            }
+                            self.respond(cid, Err(err));
                         }
-                        return;
-                    }
-                }
-                Err(_) => {
-                    // We can't parse more information. Forward the json parse error
-                    error!("{:?}", err)
-                }
-            }
-        }
-    }
-}
-
-impl WebSocket<WebSocketAdapter> {
-    pub fn new_with_adapter() -> Self {
-        let adapter = WebSocketAdapter::new();
-        WebSocket::new(adapter)
-    }
-}
-
-impl<A: SocketAdapter + Send> WebSocket<A> {
-    pub fn new(adapter: A) -> Self {
-        let web_socket = WebSocket {
-            adapter: Arc::new(Mutex::new(adapter)),
-            shared_state: Arc::new(Mutex::new(SharedState {
-                ..Default::default()
-            })),
-        };
-
-        web_socket
-            .adapter
-            .lock()
-            .expect("panic inside other mutex!")
-            .on_received({
-                let shared_state = web_socket.shared_state.clone();
-                move |msg| match msg {
-                    Err(error) => {
-                        error!("on_received: {}", error);
-                        return;
-                    }
-                    Ok(msg) => {
-                        trace!("on_received: {}", msg);
-                        handle_message(&shared_state, &msg);
                     }
-                }
-            });
-
-        {
-            let mut adapter = web_socket.adapter.lock().unwrap();
-            adapter.on_closed({
-                let shared_state = web_socket.shared_state.clone();
-                move || {
-                    if let Some(ref cb) = shared_state.lock().unwrap().on_closed {
-                        cb()
-                    }
-                }
-            });
-
-            adapter.on_connected({
-                let shared_state = web_socket.shared_state.clone();
-                move || {
-                    if let Some(ref cb) = shared_state.lock().unwrap().on_connected {
-                        cb()
+                    Err(_) => {
+                        // We can't parse more information. Forward the json parse error
+                        error!("{:?}", err)
                     }
-
-                    shared_state
-                        .lock()
-                        .unwrap()
-                        .connected
-                        .drain(..)
-                        .for_each(|sender| {
-                            let result = sender.send(());
-                            if let Err(err) = result {
-                                error!("on_connected: Received send error: {}", err)
-                            }
-                        });
                 }
-            });
+            }
         }
-
-        web_socket
-    }
-
-    pub fn tick(&self) {
-        self.adapter
-            .lock()
-            .expect("panic inside other mutex!")
-            .tick();
-
-        let mut shared_state = self.shared_state.lock().unwrap();
-
-        // TODO: Use a clock!
-        let (timeout_finished, timeouts) = shared_state
-            .timeouts
-            .iter()
-            .map(|(k, v)| (*k, *v - 16))
-            .partition(|&(_, timeout)| {
-                return timeout <= 0;
-            });
-        shared_state.timeouts = timeouts;
-        timeout_finished.iter().for_each(|(k, _)| {
-            shared_state.responses.remove(k);
-        })
     }
 
     fn make_envelope_with_cid(&self) -> (WebSocketMessageEnvelope, i64) {
         let cid = {
-            let mut state = self.shared_state.lock().expect("Panic inside other mutex!");
-            state.cid += 1;
-            state.cid
+            S::with(&self.shared_state, |state| {
+                state.cid += 1;
+                state.cid
+            })
         };
 
         (
@@ -348,24 +594,19 @@ impl<A: SocketAdapter + Send> WebSocket<A> {
     #[inline]
     fn send(&self, data: &str, reliable: bool) -> Result<(), WebSocketError<A>> {
         trace!("send: Sending message: {:?}", data);
-        self.adapter
-            .lock()
-            .expect("panic inside other mutex!")
-            .send(data, reliable)
-            .map_err(|err| WebSocketError::AdapterError(err))
+        S::with(&self.adapter, |adapter| adapter.send(data, reliable))
+            .map_err(WebSocketError::AdapterError)
     }
 
-    async fn wait_response(
-        &self,
-        cid: i64,
-    ) -> Result<WebSocketMessageEnvelope, <Self as Socket>::Error> {
+    async fn wait_response(&self, cid: i64) -> Result<WebSocketMessageEnvelope, WebSocketError<A>> {
         let (tx, rx) = oneshot::channel::<Result<WebSocketMessageEnvelope, DeJsonErr>>();
 
-        {
-            let mut shared_state = self.shared_state.lock().unwrap();
+        S::with(&self.shared_state, |shared_state| {
             shared_state.responses.insert(cid, tx);
-            shared_state.timeouts.insert(cid, 2000);
-        }
+            shared_state
+                .timeouts
+                .insert(cid, Instant::now() + Duration::from_millis(2000));
+        });
 
         let result = rx.await.map_err(|err| WebSocketError::RecvError(err))?;
         match result {
@@ -380,150 +621,12 @@ impl<A: SocketAdapter + Send> WebSocket<A> {
             }
         }
     }
-}
-
-#[async_trait]
-impl<A: SocketAdapter + Send> Socket for WebSocket<A> {
-    type Error = WebSocketError<A>;
-
-    fn on_closed<T>(&mut self, callback: T)
-    where
-        T: Fn() + Send + 'static,
-    {
-        self.shared_state.lock().unwrap().on_closed = Some(Box::new(callback));
-    }
-
-    fn on_connected<T>(&mut self, callback: T)
-    where
-        T: Fn() + Send + 'static,
-    {
-        self.shared_state.lock().unwrap().on_connected = Some(Box::new(callback));
-    }
-
-    fn on_received_channel_message<T>(&mut self, callback: T)
-    where
-        T: Fn(ApiChannelMessage) + Send + 'static,
-    {
-        self.shared_state
-            .lock()
-            .unwrap()
-            .on_received_channel_message = Some(Box::new(callback));
-    }
-
-    fn on_received_channel_presence<T>(&mut self, callback: T)
-    where
-        T: Fn(ChannelPresenceEvent) + Send + 'static,
-    {
-        self.shared_state
-            .lock()
-            .unwrap()
-            .on_received_channel_presence = Some(Box::new(callback));
-    }
-
-    fn on_received_error<T>(&mut self, callback: T)
-    where
-        T: Fn(Error) + Send + 'static,
-    {
-        self.shared_state.lock().unwrap().on_received_error = Some(Box::new(callback));
-    }
-
-    fn on_received_matchmaker_matched<T>(&mut self, callback: T)
-    where
-        T: Fn(MatchmakerMatched) + Send + 'static,
-    {
-        self.shared_state
-            .lock()
-            .unwrap()
-            .on_received_matchmaker_matched = Some(Box::new(callback));
-    }
-
-    fn on_received_match_state<T>(&mut self, callback: T)
-    where
-        T: Fn(MatchData) + Send + 'static,
-    {
-        self.shared_state.lock().unwrap().on_received_match_state = Some(Box::new(callback));
-    }
-
-    fn on_received_match_presence<T>(&mut self, callback: T)
-    where
-        T: Fn(MatchPresenceEvent) + Send + 'static,
-    {
-        self.shared_state.lock().unwrap().on_received_match_presence = Some(Box::new(callback));
-    }
-
-    fn on_received_notification<T>(&mut self, callback: T)
-    where
-        T: Fn(ApiNotification) + Send + 'static,
-    {
-        self.shared_state.lock().unwrap().on_received_notification = Some(Box::new(callback));
-    }
-
-    fn on_received_party_close<T>(&mut self, callback: T)
-    where
-        T: Fn(PartyClose) + Send + 'static,
-    {
-        self.shared_state.lock().unwrap().on_received_party_close = Some(Box::new(callback));
-    }
-
-    fn on_received_party_data<T>(&mut self, callback: T)
-    where
-        T: Fn(PartyData) + Send + 'static,
-    {
-        self.shared_state.lock().unwrap().on_received_party_data = Some(Box::new(callback));
-    }
-
-    fn on_received_party_join_request<T>(&mut self, callback: T)
-    where
-        T: Fn(PartyJoinRequest) + Send + 'static,
-    {
-        self.shared_state
-            .lock()
-            .unwrap()
-            .on_received_party_join_request = Some(Box::new(callback));
-    }
-
-    fn on_received_party_leader<T>(&mut self, callback: T)
-    where
-        T: Fn(PartyLeader) + Send + 'static,
-    {
-        self.shared_state.lock().unwrap().on_received_party_leader = Some(Box::new(callback));
-    }
-
-    fn on_received_party_presence<T>(&mut self, callback: T)
-    where
-        T: Fn(PartyPresenceEvent) + Send + 'static,
-    {
-        self.shared_state.lock().unwrap().on_received_party_presence = Some(Box::new(callback));
-    }
-
-    fn on_received_status_presence<T>(&mut self, callback: T)
-    where
-        T: Fn(StatusPresenceEvent) + Send + 'static,
-    {
-        self.shared_state
-            .lock()
-            .unwrap()
-            .on_received_status_presence = Some(Box::new(callback));
-    }
-
-    fn on_received_stream_presence<T>(&mut self, callback: T)
-    where
-        T: Fn(StreamPresenceEvent) + Send + 'static,
-    {
-        self.shared_state
-            .lock()
-            .unwrap()
-            .on_received_stream_presence = Some(Box::new(callback));
-    }
 
-    fn on_received_stream_state<T>(&mut self, callback: T)
-    where
-        T: Fn(StreamData) + Send + 'static,
-    {
-        self.shared_state.lock().unwrap().on_received_stream_state = Some(Box::new(callback));
-    }
-
-    async fn accept_party_member(&self, party_id: &str, user_presence: &UserPresence) -> Result<(), Self::Error> {
+    async fn accept_party_member(
+        &self,
+        party_id: &str,
+        user_presence: &UserPresence,
+    ) -> Result<(), WebSocketError<A>> {
         let (mut envelope, cid) = self.make_envelope_with_cid();
         envelope.party_accept = Some(PartyAccept {
             party_id: party_id.to_owned(),
@@ -544,7 +647,7 @@ impl<A: SocketAdapter + Send> Socket for WebSocket<A> {
         max_count: Option<i32>,
         string_properties: HashMap<String, String>,
         numeric_properties: HashMap<String, f64>,
-    ) -> Result<MatchmakerTicket, Self::Error> {
+    ) -> Result<MatchmakerTicket, WebSocketError<A>> {
         let (mut envelope, cid) = self.make_envelope_with_cid();
         envelope.matchmaker_add = Some(MatchmakerAdd {
             query: query.to_owned(),
@@ -570,7 +673,7 @@ impl<A: SocketAdapter + Send> Socket for WebSocket<A> {
         max_count: i32,
         string_properties: HashMap<String, String>,
         numeric_properties: HashMap<String, f64>,
-    ) -> Result<PartyMatchmakerTicket, Self::Error> {
+    ) -> Result<PartyMatchmakerTicket, WebSocketError<A>> {
         let (mut envelope, cid) = self.make_envelope_with_cid();
         envelope.party_matchmaker_add = Some(PartyMatchmakerAdd {
             query: query.to_owned(),
@@ -589,7 +692,7 @@ impl<A: SocketAdapter + Send> Socket for WebSocket<A> {
         Ok(envelope.party_matchmaker_ticket.unwrap())
     }
 
-    async fn close_party(&self, party_id: &str) -> Result<(), Self::Error> {
+    async fn close_party(&self, party_id: &str) -> Result<(), WebSocketError<A>> {
         let (mut envelope, cid) = self.make_envelope_with_cid();
         envelope.party_close = Some(PartyClose {
             party_id: party_id.to_owned(),
@@ -603,13 +706,14 @@ impl<A: SocketAdapter + Send> Socket for WebSocket<A> {
         Ok(())
     }
 
-    async fn close(&self) -> Result<(), Self::Error> {
+    async fn close(&self) -> Result<(), WebSocketError<A>> {
         todo!()
     }
 
     async fn connect(&self, session: &mut Session, appear_online: bool, connect_timeout: i32) {
-        let ws_url = "ws://127.0.0.1";
-        let port = 7350;
+        let (ws_url, port) = S::with(&self.shared_state, |state| {
+            (state.server.clone(), state.port)
+        });
 
         let ws_addr = format!(
             "{}:{}/ws?lang=en&status={}&token={}",
@@ -618,12 +722,11 @@ impl<A: SocketAdapter + Send> Socket for WebSocket<A> {
 
         let (tx, rx) = oneshot::channel();
 
-        self.shared_state.lock().unwrap().connected.push(tx);
+        S::with(&self.shared_state, |state| state.connected.push(tx));
 
-        self.adapter
-            .lock()
-            .unwrap()
-            .connect(&ws_addr, connect_timeout);
+        S::with(&self.adapter, |adapter| {
+            adapter.connect(&ws_addr, connect_timeout)
+        });
 
         let result = rx.await;
         if let Err(err) = result {
@@ -631,7 +734,7 @@ impl<A: SocketAdapter + Send> Socket for WebSocket<A> {
         }
     }
 
-    async fn create_match(&self) -> Result<Match, Self::Error> {
+    async fn create_match(&self) -> Result<Match, WebSocketError<A>> {
         let (mut envelope, cid) = self.make_envelope_with_cid();
         envelope.match_create = Some(MatchCreate {});
 
@@ -643,7 +746,7 @@ impl<A: SocketAdapter + Send> Socket for WebSocket<A> {
         Ok(envelope.new_match.unwrap())
     }
 
-    async fn create_party(&self, open: bool, max_size: i32) -> Result<Party, Self::Error> {
+    async fn create_party(&self, open: bool, max_size: i32) -> Result<Party, WebSocketError<A>> {
         let (mut envelope, cid) = self.make_envelope_with_cid();
         envelope.party_create = Some(PartyCreate { max_size, open });
 
@@ -658,7 +761,7 @@ impl<A: SocketAdapter + Send> Socket for WebSocket<A> {
         &self,
         user_ids: &[&str],
         usernames: &[&str],
-    ) -> Result<Status, Self::Error> {
+    ) -> Result<Status, WebSocketError<A>> {
         let (mut envelope, cid) = self.make_envelope_with_cid();
         envelope.status_follow = Some(StatusFollow {
             user_ids: str_slice_to_owned(user_ids),
@@ -674,17 +777,17 @@ impl<A: SocketAdapter + Send> Socket for WebSocket<A> {
 
     async fn join_chat(
         &self,
-        room_name: &str,
-        channel_type: i32,
+        target: &str,
+        channel_type: ChannelJoinType,
         persistence: bool,
         hidden: bool,
-    ) -> Result<Channel, Self::Error> {
+    ) -> Result<Channel, WebSocketError<A>> {
         let (mut envelope, cid) = self.make_envelope_with_cid();
         envelope.channel_join = Some(ChannelJoin {
             channel_type,
             hidden,
             persistence,
-            target: room_name.to_owned(),
+            target: target.to_owned(),
         });
 
         let json = envelope.serialize_json();
@@ -694,7 +797,7 @@ impl<A: SocketAdapter + Send> Socket for WebSocket<A> {
         Ok(result_envelope.channel.unwrap())
     }
 
-    async fn join_party(&self, party_id: &str) -> Result<(), Self::Error> {
+    async fn join_party(&self, party_id: &str) -> Result<(), WebSocketError<A>> {
         let (mut envelope, cid) = self.make_envelope_with_cid();
         envelope.party_join = Some(PartyJoin {
             party_id: party_id.to_owned(),
@@ -707,7 +810,7 @@ impl<A: SocketAdapter + Send> Socket for WebSocket<A> {
         Ok(())
     }
 
-    async fn join_match(&self, matched: MatchmakerMatched) -> Result<Match, Self::Error> {
+    async fn join_match(&self, matched: MatchmakerMatched) -> Result<Match, WebSocketError<A>> {
         let (mut envelope, cid) = self.make_envelope_with_cid();
         envelope.match_join = Some(MatchJoin {
             token: matched.token,
@@ -726,7 +829,7 @@ impl<A: SocketAdapter + Send> Socket for WebSocket<A> {
         &self,
         match_id: &str,
         metadata: HashMap<String, String>,
-    ) -> Result<Match, Self::Error> {
+    ) -> Result<Match, WebSocketError<A>> {
         let (mut envelope, cid) = self.make_envelope_with_cid();
         envelope.match_join = Some(MatchJoin {
             match_id: Some(match_id.to_owned()),
@@ -741,7 +844,7 @@ impl<A: SocketAdapter + Send> Socket for WebSocket<A> {
         Ok(result_envelope.new_match.unwrap())
     }
 
-    async fn leave_chat(&self, channel_id: &str) -> Result<(), Self::Error> {
+    async fn leave_chat(&self, channel_id: &str) -> Result<(), WebSocketError<A>> {
         let mut envelope = self.make_envelope();
         envelope.channel_leave = Some(ChannelLeave {
             channel_id: channel_id.to_owned(),
@@ -751,8 +854,8 @@ impl<A: SocketAdapter + Send> Socket for WebSocket<A> {
         self.send(&json, false)
     }
 
-    async fn leave_match(&self, match_id: &str) -> Result<(), Self::Error> {
-        let mut envelope= self.make_envelope();
+    async fn leave_match(&self, match_id: &str) -> Result<(), WebSocketError<A>> {
+        let mut envelope = self.make_envelope();
         envelope.match_leave = Some(MatchLeave {
             match_id: match_id.to_owned(),
         });
@@ -761,7 +864,7 @@ impl<A: SocketAdapter + Send> Socket for WebSocket<A> {
         self.send(&json, false)
     }
 
-    async fn leave_party(&self, party_id: &str) -> Result<(), Self::Error> {
+    async fn leave_party(&self, party_id: &str) -> Result<(), WebSocketError<A>> {
         let (mut envelope, cid) = self.make_envelope_with_cid();
         envelope.party_leave = Some(PartyLeave {
             party_id: party_id.to_owned(),
@@ -777,7 +880,7 @@ impl<A: SocketAdapter + Send> Socket for WebSocket<A> {
     async fn list_party_join_requests(
         &self,
         party_id: &str,
-    ) -> Result<PartyJoinRequest, Self::Error> {
+    ) -> Result<PartyJoinRequest, WebSocketError<A>> {
         let (mut envelope, cid) = self.make_envelope_with_cid();
         envelope.party_join_request_list = Some(PartyJoinRequestList {
             party_id: party_id.to_owned(),
@@ -790,7 +893,11 @@ impl<A: SocketAdapter + Send> Socket for WebSocket<A> {
         Ok(result_envelope.party_join_request.unwrap())
     }
 
-    async fn promote_party_member(&self, party_id: &str, party_member: UserPresence) -> Result<(), Self::Error> {
+    async fn promote_party_member(
+        &self,
+        party_id: &str,
+        party_member: UserPresence,
+    ) -> Result<(), WebSocketError<A>> {
         let (mut envelope, cid) = self.make_envelope_with_cid();
         envelope.party_promote = Some(PartyPromote {
             party_id: party_id.to_owned(),
@@ -808,7 +915,7 @@ impl<A: SocketAdapter + Send> Socket for WebSocket<A> {
         &self,
         channel_id: &str,
         message_id: &str,
-    ) -> Result<ChannelMessageAck, Self::Error> {
+    ) -> Result<ChannelMessageAck, WebSocketError<A>> {
         let (mut envelope, cid) = self.make_envelope_with_cid();
         envelope.channel_message_remove = Some(ChannelMesageRemove {
             channel_id: channel_id.to_owned(),
@@ -822,7 +929,7 @@ impl<A: SocketAdapter + Send> Socket for WebSocket<A> {
         Ok(result_envelope.channel_message_ack.unwrap())
     }
 
-    async fn remove_matchmaker(&self, ticket: &str) -> Result<(), Self::Error> {
+    async fn remove_matchmaker(&self, ticket: &str) -> Result<(), WebSocketError<A>> {
         let mut envelope = self.make_envelope();
         envelope.matchmaker_remove = Some(MatchmakerRemove {
             ticket: ticket.to_owned(),
@@ -832,7 +939,11 @@ impl<A: SocketAdapter + Send> Socket for WebSocket<A> {
         self.send(&json, false)
     }
 
-    async fn remove_matchmaker_party(&self, party_id: &str, ticket: &str) -> Result<(), Self::Error> {
+    async fn remove_matchmaker_party(
+        &self,
+        party_id: &str,
+        ticket: &str,
+    ) -> Result<(), WebSocketError<A>> {
         let mut envelope = self.make_envelope();
         envelope.party_matchmaker_remove = Some(PartyMatchmakerRemove {
             party_id: party_id.to_owned(),
@@ -843,7 +954,11 @@ impl<A: SocketAdapter + Send> Socket for WebSocket<A> {
         self.send(&json, false)
     }
 
-    async fn remove_party_member(&self, party_id: &str, presence: UserPresence) -> Result<(), Self::Error> {
+    async fn remove_party_member(
+        &self,
+        party_id: &str,
+        presence: UserPresence,
+    ) -> Result<(), WebSocketError<A>> {
         let (mut envelope, cid) = self.make_envelope_with_cid();
         envelope.party_remove = Some(PartyRemove {
             party_id: party_id.to_owned(),
@@ -857,7 +972,7 @@ impl<A: SocketAdapter + Send> Socket for WebSocket<A> {
         Ok(())
     }
 
-    async fn rpc(&self, func_id: &str, payload: &str) -> Result<ApiRpc, Self::Error> {
+    async fn rpc(&self, func_id: &str, payload: &str) -> Result<ApiRpc, WebSocketError<A>> {
         let (mut envelope, cid) = self.make_envelope_with_cid();
         envelope.rpc = Some(ApiRpc {
             id: func_id.to_owned(),
@@ -872,7 +987,7 @@ impl<A: SocketAdapter + Send> Socket for WebSocket<A> {
         Ok(result_envelope.rpc.unwrap())
     }
 
-    async fn rpc_bytes(&self, func_id: &str, _payload: &[u8]) -> Result<ApiRpc, Self::Error> {
+    async fn rpc_bytes(&self, func_id: &str, _payload: &[u8]) -> Result<ApiRpc, WebSocketError<A>> {
         let (mut envelope, cid) = self.make_envelope_with_cid();
         envelope.rpc = Some(ApiRpc {
             id: func_id.to_owned(),
@@ -894,7 +1009,7 @@ impl<A: SocketAdapter + Send> Socket for WebSocket<A> {
         op_code: i64,
         state: &[u8],
         presences: &[UserPresence],
-    ) -> Result<(), Self::Error> {
+    ) -> Result<(), WebSocketError<A>> {
         let mut envelope = self.make_envelope();
         envelope.match_data_send = Some(MatchDataSend {
             match_id: match_id.to_owned(),
@@ -909,7 +1024,12 @@ impl<A: SocketAdapter + Send> Socket for WebSocket<A> {
         self.send(&json, false)
     }
 
-    async fn send_party_data(&self, party_id: &str, op_code: i64, data: &[u8]) -> Result<(), Self::Error> {
+    async fn send_party_data(
+        &self,
+        party_id: &str,
+        op_code: i64,
+        data: &[u8],
+    ) -> Result<(), WebSocketError<A>> {
         let mut envelope = self.make_envelope();
         envelope.party_data_send = Some(PartyDataSend {
             party_id: party_id.to_owned(),
@@ -921,7 +1041,7 @@ impl<A: SocketAdapter + Send> Socket for WebSocket<A> {
         self.send(&json, false)
     }
 
-    async fn unfollow_users(&self, user_ids: &[&str]) -> Result<(), Self::Error> {
+    async fn unfollow_users(&self, user_ids: &[&str]) -> Result<(), WebSocketError<A>> {
         let mut envelope = self.make_envelope();
         envelope.status_unfollow = Some(StatusUnfollow {
             user_ids: str_slice_to_owned(user_ids),
@@ -936,7 +1056,7 @@ impl<A: SocketAdapter + Send> Socket for WebSocket<A> {
         channel_id: &str,
         message_id: &str,
         content: &str,
-    ) -> Result<ChannelMessageAck, Self::Error> {
+    ) -> Result<ChannelMessageAck, WebSocketError<A>> {
         let (mut envelope, cid) = self.make_envelope_with_cid();
         envelope.channel_message_update = Some(ChannelMesageUpdate {
             channel_id: channel_id.to_owned(),
@@ -951,7 +1071,7 @@ impl<A: SocketAdapter + Send> Socket for WebSocket<A> {
         Ok(result_envelope.channel_message_ack.unwrap())
     }
 
-    async fn update_status(&self, status: &str) -> Result<(), Self::Error> {
+    async fn update_status(&self, status: &str) -> Result<(), WebSocketError<A>> {
         let mut envelope = self.make_envelope();
         envelope.status_update = Some(StatusUpdate {
             status: status.to_owned(),
@@ -965,7 +1085,7 @@ impl<A: SocketAdapter + Send> Socket for WebSocket<A> {
         &self,
         channel_id: &str,
         content: &str,
-    ) -> Result<ChannelMessageAck, Self::Error> {
+    ) -> Result<ChannelMessageAck, WebSocketError<A>> {
         let (mut envelope, cid) = self.make_envelope_with_cid();
         envelope.channel_message_send = Some(ChannelMessageSend {
             channel_id: channel_id.to_owned(),
@@ -980,32 +1100,996 @@ impl<A: SocketAdapter + Send> Socket for WebSocket<A> {
     }
 }
 
-#[cfg(test)]
-mod test {
-    use nanoserde::SerJson;
-    #[derive(SerJson)]
-    struct TestStruct {
-        a: Option<String>,
-        b: Option<String>,
-        c: Option<String>,
+#[async_trait]
+impl<A: SocketAdapter + Send> Socket for WebSocket<A> {
+    type Error = WebSocketError<A>;
+
+    fn on_closed<T>(&self, callback: T) -> HandlerId
+    where
+        T: Fn() + Send + 'static,
+    {
+        self.add_handler(
+            |state| &mut state.on_closed,
+            Box::new(move |()| callback()) as Box<_>,
+        )
     }
-    #[test]
-    fn test_serialization() {
-        let test_struct = TestStruct {
-            a: Some("string".to_owned()),
-            b: Some("hello".to_owned()),
-            c: None,
-        };
-        let test_struct2 = TestStruct {
-            a: None,
-            b: Some("string".to_owned()),
-            c: Some("hello".to_owned()),
-        };
-        let result = test_struct.serialize_json();
-        let result2 = test_struct2.serialize_json();
 
-        // This one is correct
-        assert_eq!(result2, "{\"b\":\"string\",\"c\":\"hello\"}");
-        assert_eq!(result, "{\"a\":\"string\",\"b\":\"hello\"}");
+    fn on_connected<T>(&self, callback: T) -> HandlerId
+    where
+        T: Fn() + Send + 'static,
+    {
+        self.add_handler(
+            |state| &mut state.on_connected,
+            Box::new(move |()| callback()) as Box<_>,
+        )
+    }
+
+    fn on_received_channel_message<T>(&self, callback: T) -> HandlerId
+    where
+        T: Fn(ApiChannelMessage) + Send + 'static,
+    {
+        self.add_handler(
+            |state| &mut state.on_received_channel_message,
+            Box::new(callback) as Box<_>,
+        )
+    }
+
+    fn on_received_channel_presence<T>(&self, callback: T) -> HandlerId
+    where
+        T: Fn(ChannelPresenceEvent) + Send + 'static,
+    {
+        self.add_handler(
+            |state| &mut state.on_received_channel_presence,
+            Box::new(callback) as Box<_>,
+        )
+    }
+
+    fn on_received_error<T>(&self, callback: T) -> HandlerId
+    where
+        T: Fn(Error) + Send + 'static,
+    {
+        self.add_handler(
+            |state| &mut state.on_received_error,
+            Box::new(callback) as Box<_>,
+        )
+    }
+
+    fn on_received_matchmaker_matched<T>(&self, callback: T) -> HandlerId
+    where
+        T: Fn(MatchmakerMatched) + Send + 'static,
+    {
+        self.add_handler(
+            |state| &mut state.on_received_matchmaker_matched,
+            Box::new(callback) as Box<_>,
+        )
+    }
+
+    fn on_received_match_state<T>(&self, callback: T) -> HandlerId
+    where
+        T: Fn(MatchData) + Send + 'static,
+    {
+        self.add_handler(
+            |state| &mut state.on_received_match_state,
+            Box::new(callback) as Box<_>,
+        )
+    }
+
+    fn on_received_match_presence<T>(&self, callback: T) -> HandlerId
+    where
+        T: Fn(MatchPresenceEvent) + Send + 'static,
+    {
+        self.add_handler(
+            |state| &mut state.on_received_match_presence,
+            Box::new(callback) as Box<_>,
+        )
+    }
+
+    fn on_received_notification<T>(&self, callback: T) -> HandlerId
+    where
+        T: Fn(ApiNotification) + Send + 'static,
+    {
+        self.add_handler(
+            |state| &mut state.on_received_notification,
+            Box::new(callback) as Box<_>,
+        )
+    }
+
+    fn on_received_party_close<T>(&self, callback: T) -> HandlerId
+    where
+        T: Fn(PartyClose) + Send + 'static,
+    {
+        self.add_handler(
+            |state| &mut state.on_received_party_close,
+            Box::new(callback) as Box<_>,
+        )
+    }
+
+    fn on_received_party_data<T>(&self, callback: T) -> HandlerId
+    where
+        T: Fn(PartyData) + Send + 'static,
+    {
+        self.add_handler(
+            |state| &mut state.on_received_party_data,
+            Box::new(callback) as Box<_>,
+        )
+    }
+
+    fn on_received_party_join_request<T>(&self, callback: T) -> HandlerId
+    where
+        T: Fn(PartyJoinRequest) + Send + 'static,
+    {
+        self.add_handler(
+            |state| &mut state.on_received_party_join_request,
+            Box::new(callback) as Box<_>,
+        )
+    }
+
+    fn on_received_party_leader<T>(&self, callback: T) -> HandlerId
+    where
+        T: Fn(PartyLeader) + Send + 'static,
+    {
+        self.add_handler(
+            |state| &mut state.on_received_party_leader,
+            Box::new(callback) as Box<_>,
+        )
+    }
+
+    fn on_received_party_presence<T>(&self, callback: T) -> HandlerId
+    where
+        T: Fn(PartyPresenceEvent) + Send + 'static,
+    {
+        self.add_handler(
+            |state| &mut state.on_received_party_presence,
+            Box::new(callback) as Box<_>,
+        )
+    }
+
+    fn on_received_status_presence<T>(&self, callback: T) -> HandlerId
+    where
+        T: Fn(StatusPresenceEvent) + Send + 'static,
+    {
+        self.add_handler(
+            |state| &mut state.on_received_status_presence,
+            Box::new(callback) as Box<_>,
+        )
+    }
+
+    fn on_received_stream_presence<T>(&self, callback: T) -> HandlerId
+    where
+        T: Fn(StreamPresenceEvent) + Send + 'static,
+    {
+        self.add_handler(
+            |state| &mut state.on_received_stream_presence,
+            Box::new(callback) as Box<_>,
+        )
+    }
+
+    fn on_received_stream_state<T>(&self, callback: T) -> HandlerId
+    where
+        T: Fn(StreamData) + Send + 'static,
+    {
+        self.add_handler(
+            |state| &mut state.on_received_stream_state,
+            Box::new(callback) as Box<_>,
+        )
+    }
+
+    fn remove_handler(&self, id: HandlerId) -> bool {
+        WebSocket::remove_handler(self, id)
+    }
+
+    async fn accept_party_member(
+        &self,
+        party_id: &str,
+        user_presence: &UserPresence,
+    ) -> Result<(), Self::Error> {
+        WebSocket::accept_party_member(self, party_id, user_presence).await
+    }
+
+    async fn add_matchmaker(
+        &self,
+        query: &str,
+        min_count: Option<i32>,
+        max_count: Option<i32>,
+        string_properties: HashMap<String, String>,
+        numeric_properties: HashMap<String, f64>,
+    ) -> Result<MatchmakerTicket, Self::Error> {
+        WebSocket::add_matchmaker(
+            self,
+            query,
+            min_count,
+            max_count,
+            string_properties,
+            numeric_properties,
+        )
+        .await
+    }
+
+    async fn add_matchmaker_party(
+        &self,
+        party_id: &str,
+        query: &str,
+        min_count: i32,
+        max_count: i32,
+        string_properties: HashMap<String, String>,
+        numeric_properties: HashMap<String, f64>,
+    ) -> Result<PartyMatchmakerTicket, Self::Error> {
+        WebSocket::add_matchmaker_party(
+            self,
+            party_id,
+            query,
+            min_count,
+            max_count,
+            string_properties,
+            numeric_properties,
+        )
+        .await
+    }
+
+    async fn close_party(&self, party_id: &str) -> Result<(), Self::Error> {
+        WebSocket::close_party(self, party_id).await
+    }
+
+    async fn close(&self) -> Result<(), Self::Error> {
+        WebSocket::close(self).await
+    }
+
+    async fn connect(&self, session: &mut Session, appear_online: bool, connect_timeout: i32) {
+        WebSocket::connect(self, session, appear_online, connect_timeout).await;
+    }
+
+    async fn create_match(&self) -> Result<Match, Self::Error> {
+        WebSocket::create_match(self).await
+    }
+
+    async fn create_party(&self, open: bool, max_size: i32) -> Result<Party, Self::Error> {
+        WebSocket::create_party(self, open, max_size).await
+    }
+
+    async fn follow_users(
+        &self,
+        user_ids: &[&str],
+        usernames: &[&str],
+    ) -> Result<Status, Self::Error> {
+        WebSocket::follow_users(self, user_ids, usernames).await
+    }
+
+    async fn join_chat(
+        &self,
+        target: &str,
+        channel_type: ChannelJoinType,
+        persistence: bool,
+        hidden: bool,
+    ) -> Result<Channel, Self::Error> {
+        WebSocket::join_chat(self, target, channel_type, persistence, hidden).await
+    }
+
+    async fn join_party(&self, party_id: &str) -> Result<(), Self::Error> {
+        WebSocket::join_party(self, party_id).await
+    }
+
+    async fn join_match(&self, matched: MatchmakerMatched) -> Result<Match, Self::Error> {
+        WebSocket::join_match(self, matched).await
+    }
+
+    async fn join_match_by_id(
+        &self,
+        match_id: &str,
+        metadata: HashMap<String, String>,
+    ) -> Result<Match, Self::Error> {
+        WebSocket::join_match_by_id(self, match_id, metadata).await
+    }
+
+    async fn leave_chat(&self, channel_id: &str) -> Result<(), Self::Error> {
+        WebSocket::leave_chat(self, channel_id).await
+    }
+
+    async fn leave_match(&self, match_id: &str) -> Result<(), Self::Error> {
+        WebSocket::leave_match(self, match_id).await
+    }
+
+    async fn leave_party(&self, party_id: &str) -> Result<(), Self::Error> {
+        WebSocket::leave_party(self, party_id).await
+    }
+
+    async fn list_party_join_requests(
+        &self,
+        party_id: &str,
+    ) -> Result<PartyJoinRequest, Self::Error> {
+        WebSocket::list_party_join_requests(self, party_id).await
+    }
+
+    async fn promote_party_member(
+        &self,
+        party_id: &str,
+        party_member: UserPresence,
+    ) -> Result<(), Self::Error> {
+        WebSocket::promote_party_member(self, party_id, party_member).await
+    }
+
+    async fn remove_chat_message(
+        &self,
+        channel_id: &str,
+        message_id: &str,
+    ) -> Result<ChannelMessageAck, Self::Error> {
+        WebSocket::remove_chat_message(self, channel_id, message_id).await
+    }
+
+    async fn remove_matchmaker(&self, ticket: &str) -> Result<(), Self::Error> {
+        WebSocket::remove_matchmaker(self, ticket).await
+    }
+
+    async fn remove_matchmaker_party(
+        &self,
+        party_id: &str,
+        ticket: &str,
+    ) -> Result<(), Self::Error> {
+        WebSocket::remove_matchmaker_party(self, party_id, ticket).await
+    }
+
+    async fn remove_party_member(
+        &self,
+        party_id: &str,
+        presence: UserPresence,
+    ) -> Result<(), Self::Error> {
+        WebSocket::remove_party_member(self, party_id, presence).await
+    }
+
+    async fn rpc(&self, func_id: &str, payload: &str) -> Result<ApiRpc, Self::Error> {
+        WebSocket::rpc(self, func_id, payload).await
+    }
+
+    async fn rpc_bytes(&self, func_id: &str, payload: &[u8]) -> Result<ApiRpc, Self::Error> {
+        WebSocket::rpc_bytes(self, func_id, payload).await
+    }
+
+    async fn send_match_state(
+        &self,
+        match_id: &str,
+        op_code: i64,
+        state: &[u8],
+        presences: &[UserPresence],
+    ) -> Result<(), Self::Error> {
+        WebSocket::send_match_state(self, match_id, op_code, state, presences).await
+    }
+
+    async fn send_party_data(
+        &self,
+        party_id: &str,
+        op_code: i64,
+        data: &[u8],
+    ) -> Result<(), Self::Error> {
+        WebSocket::send_party_data(self, party_id, op_code, data).await
+    }
+
+    async fn unfollow_users(&self, user_ids: &[&str]) -> Result<(), Self::Error> {
+        WebSocket::unfollow_users(self, user_ids).await
+    }
+
+    async fn update_chat_message(
+        &self,
+        channel_id: &str,
+        message_id: &str,
+        content: &str,
+    ) -> Result<ChannelMessageAck, Self::Error> {
+        WebSocket::update_chat_message(self, channel_id, message_id, content).await
+    }
+
+    async fn update_status(&self, status: &str) -> Result<(), Self::Error> {
+        WebSocket::update_status(self, status).await
+    }
+
+    async fn write_chat_message(
+        &self,
+        channel_id: &str,
+        content: &str,
+    ) -> Result<ChannelMessageAck, Self::Error> {
+        WebSocket::write_chat_message(self, channel_id, content).await
+    }
+}
+
+/// A `WebSocket` for single-threaded targets, e.g. WASM or a single-threaded executor.
+///
+/// Callbacks don't need to be `Send` and the state is shared using `Rc<RefCell<..>>` instead of
+/// `Arc<Mutex<..>>`. `LocalWebSocket` does not implement `Socket`, because its futures can't be
+/// sent between threads. It offers the same functions as inherent functions instead.
+pub struct LocalWebSocket<A: SocketAdapter>(WebSocket<A, Local>);
+
+impl<A: SocketAdapter> Clone for LocalWebSocket<A> {
+    fn clone(&self) -> Self {
+        LocalWebSocket(self.0.clone())
+    }
+}
+
+#[cfg(feature = "qws-adapter")]
+impl LocalWebSocket<WebSocketAdapter> {
+    pub fn new_with_adapter() -> Self {
+        let adapter = WebSocketAdapter::new();
+        LocalWebSocket::new(adapter)
+    }
+}
+
+impl<A: SocketAdapter> LocalWebSocket<A> {
+    pub fn new(adapter: A) -> Self {
+        LocalWebSocket(WebSocket::new_with_strategy(adapter))
+    }
+
+    /// Connect to `server:port` instead of the default `ws://127.0.0.1:7350`.
+    pub fn new_with_server(adapter: A, server: &str, port: i32) -> Self {
+        let socket = WebSocket::new_with_strategy(adapter);
+        socket.set_server(server, port);
+        LocalWebSocket(socket)
+    }
+
+    pub fn tick(&self) {
+        self.0.tick()
+    }
+
+    pub fn on_closed<T>(&self, callback: T) -> HandlerId
+    where
+        T: Fn() + 'static,
+    {
+        self.0.add_handler(
+            |state| &mut state.on_closed,
+            Box::new(move |()| callback()) as Box<_>,
+        )
+    }
+
+    pub fn on_connected<T>(&self, callback: T) -> HandlerId
+    where
+        T: Fn() + 'static,
+    {
+        self.0.add_handler(
+            |state| &mut state.on_connected,
+            Box::new(move |()| callback()) as Box<_>,
+        )
+    }
+
+    pub fn on_received_channel_message<T>(&self, callback: T) -> HandlerId
+    where
+        T: Fn(ApiChannelMessage) + 'static,
+    {
+        self.0.add_handler(
+            |state| &mut state.on_received_channel_message,
+            Box::new(callback) as Box<_>,
+        )
+    }
+
+    pub fn on_received_channel_presence<T>(&self, callback: T) -> HandlerId
+    where
+        T: Fn(ChannelPresenceEvent) + 'static,
+    {
+        self.0.add_handler(
+            |state| &mut state.on_received_channel_presence,
+            Box::new(callback) as Box<_>,
+        )
+    }
+
+    pub fn on_received_error<T>(&self, callback: T) -> HandlerId
+    where
+        T: Fn(Error) + 'static,
+    {
+        self.0.add_handler(
+            |state| &mut state.on_received_error,
+            Box::new(callback) as Box<_>,
+        )
+    }
+
+    pub fn on_received_matchmaker_matched<T>(&self, callback: T) -> HandlerId
+    where
+        T: Fn(MatchmakerMatched) + 'static,
+    {
+        self.0.add_handler(
+            |state| &mut state.on_received_matchmaker_matched,
+            Box::new(callback) as Box<_>,
+        )
+    }
+
+    pub fn on_received_match_state<T>(&self, callback: T) -> HandlerId
+    where
+        T: Fn(MatchData) + 'static,
+    {
+        self.0.add_handler(
+            |state| &mut state.on_received_match_state,
+            Box::new(callback) as Box<_>,
+        )
+    }
+
+    pub fn on_received_match_presence<T>(&self, callback: T) -> HandlerId
+    where
+        T: Fn(MatchPresenceEvent) + 'static,
+    {
+        self.0.add_handler(
+            |state| &mut state.on_received_match_presence,
+            Box::new(callback) as Box<_>,
+        )
+    }
+
+    pub fn on_received_notification<T>(&self, callback: T) -> HandlerId
+    where
+        T: Fn(ApiNotification) + 'static,
+    {
+        self.0.add_handler(
+            |state| &mut state.on_received_notification,
+            Box::new(callback) as Box<_>,
+        )
+    }
+
+    pub fn on_received_party_close<T>(&self, callback: T) -> HandlerId
+    where
+        T: Fn(PartyClose) + 'static,
+    {
+        self.0.add_handler(
+            |state| &mut state.on_received_party_close,
+            Box::new(callback) as Box<_>,
+        )
+    }
+
+    pub fn on_received_party_data<T>(&self, callback: T) -> HandlerId
+    where
+        T: Fn(PartyData) + 'static,
+    {
+        self.0.add_handler(
+            |state| &mut state.on_received_party_data,
+            Box::new(callback) as Box<_>,
+        )
+    }
+
+    pub fn on_received_party_join_request<T>(&self, callback: T) -> HandlerId
+    where
+        T: Fn(PartyJoinRequest) + 'static,
+    {
+        self.0.add_handler(
+            |state| &mut state.on_received_party_join_request,
+            Box::new(callback) as Box<_>,
+        )
+    }
+
+    pub fn on_received_party_leader<T>(&self, callback: T) -> HandlerId
+    where
+        T: Fn(PartyLeader) + 'static,
+    {
+        self.0.add_handler(
+            |state| &mut state.on_received_party_leader,
+            Box::new(callback) as Box<_>,
+        )
+    }
+
+    pub fn on_received_party_presence<T>(&self, callback: T) -> HandlerId
+    where
+        T: Fn(PartyPresenceEvent) + 'static,
+    {
+        self.0.add_handler(
+            |state| &mut state.on_received_party_presence,
+            Box::new(callback) as Box<_>,
+        )
+    }
+
+    pub fn on_received_status_presence<T>(&self, callback: T) -> HandlerId
+    where
+        T: Fn(StatusPresenceEvent) + 'static,
+    {
+        self.0.add_handler(
+            |state| &mut state.on_received_status_presence,
+            Box::new(callback) as Box<_>,
+        )
+    }
+
+    pub fn on_received_stream_presence<T>(&self, callback: T) -> HandlerId
+    where
+        T: Fn(StreamPresenceEvent) + 'static,
+    {
+        self.0.add_handler(
+            |state| &mut state.on_received_stream_presence,
+            Box::new(callback) as Box<_>,
+        )
+    }
+
+    pub fn on_received_stream_state<T>(&self, callback: T) -> HandlerId
+    where
+        T: Fn(StreamData) + 'static,
+    {
+        self.0.add_handler(
+            |state| &mut state.on_received_stream_state,
+            Box::new(callback) as Box<_>,
+        )
+    }
+
+    /// Remove a callback registered with one of the `on_*` functions.
+    ///
+    /// Returns `false` if the handler was already removed.
+    pub fn remove_handler(&self, id: HandlerId) -> bool {
+        self.0.remove_handler(id)
+    }
+
+    pub async fn accept_party_member(
+        &self,
+        party_id: &str,
+        user_presence: &UserPresence,
+    ) -> Result<(), WebSocketError<A>> {
+        self.0.accept_party_member(party_id, user_presence).await
+    }
+
+    pub async fn add_matchmaker(
+        &self,
+        query: &str,
+        min_count: Option<i32>,
+        max_count: Option<i32>,
+        string_properties: HashMap<String, String>,
+        numeric_properties: HashMap<String, f64>,
+    ) -> Result<MatchmakerTicket, WebSocketError<A>> {
+        self.0
+            .add_matchmaker(
+                query,
+                min_count,
+                max_count,
+                string_properties,
+                numeric_properties,
+            )
+            .await
+    }
+
+    pub async fn add_matchmaker_party(
+        &self,
+        party_id: &str,
+        query: &str,
+        min_count: i32,
+        max_count: i32,
+        string_properties: HashMap<String, String>,
+        numeric_properties: HashMap<String, f64>,
+    ) -> Result<PartyMatchmakerTicket, WebSocketError<A>> {
+        self.0
+            .add_matchmaker_party(
+                party_id,
+                query,
+                min_count,
+                max_count,
+                string_properties,
+                numeric_properties,
+            )
+            .await
+    }
+
+    pub async fn close_party(&self, party_id: &str) -> Result<(), WebSocketError<A>> {
+        self.0.close_party(party_id).await
+    }
+
+    pub async fn close(&self) -> Result<(), WebSocketError<A>> {
+        self.0.close().await
+    }
+
+    pub async fn connect(&self, session: &mut Session, appear_online: bool, connect_timeout: i32) {
+        self.0
+            .connect(session, appear_online, connect_timeout)
+            .await;
+    }
+
+    pub async fn create_match(&self) -> Result<Match, WebSocketError<A>> {
+        self.0.create_match().await
+    }
+
+    pub async fn create_party(
+        &self,
+        open: bool,
+        max_size: i32,
+    ) -> Result<Party, WebSocketError<A>> {
+        self.0.create_party(open, max_size).await
+    }
+
+    pub async fn follow_users(
+        &self,
+        user_ids: &[&str],
+        usernames: &[&str],
+    ) -> Result<Status, WebSocketError<A>> {
+        self.0.follow_users(user_ids, usernames).await
+    }
+
+    pub async fn join_chat(
+        &self,
+        target: &str,
+        channel_type: ChannelJoinType,
+        persistence: bool,
+        hidden: bool,
+    ) -> Result<Channel, WebSocketError<A>> {
+        self.0
+            .join_chat(target, channel_type, persistence, hidden)
+            .await
+    }
+
+    /// Join the chat room `room_name`, see `Socket::join_room`.
+    pub async fn join_room(
+        &self,
+        room_name: &str,
+        persistence: bool,
+        hidden: bool,
+    ) -> Result<chat::Channel<Self>, WebSocketError<A>> {
+        let channel_type = ChannelJoinType::Room;
+        chat::Channel::join(self.clone(), room_name, channel_type, persistence, hidden).await
+    }
+
+    /// Start or continue a direct message with the user `user_id`, see `Socket::join_direct`.
+    pub async fn join_direct(
+        &self,
+        user_id: &str,
+        persistence: bool,
+        hidden: bool,
+    ) -> Result<chat::Channel<Self>, WebSocketError<A>> {
+        let channel_type = ChannelJoinType::DirectMessage;
+        chat::Channel::join(self.clone(), user_id, channel_type, persistence, hidden).await
+    }
+
+    /// Join the chat of the group `group_id`, see `Socket::join_group_chat`.
+    pub async fn join_group_chat(
+        &self,
+        group_id: &str,
+        persistence: bool,
+        hidden: bool,
+    ) -> Result<chat::Channel<Self>, WebSocketError<A>> {
+        let channel_type = ChannelJoinType::Group;
+        chat::Channel::join(self.clone(), group_id, channel_type, persistence, hidden).await
+    }
+
+    pub async fn join_party(&self, party_id: &str) -> Result<(), WebSocketError<A>> {
+        self.0.join_party(party_id).await
+    }
+
+    pub async fn join_match(&self, matched: MatchmakerMatched) -> Result<Match, WebSocketError<A>> {
+        self.0.join_match(matched).await
+    }
+
+    pub async fn join_match_by_id(
+        &self,
+        match_id: &str,
+        metadata: HashMap<String, String>,
+    ) -> Result<Match, WebSocketError<A>> {
+        self.0.join_match_by_id(match_id, metadata).await
+    }
+
+    pub async fn leave_chat(&self, channel_id: &str) -> Result<(), WebSocketError<A>> {
+        self.0.leave_chat(channel_id).await
+    }
+
+    pub async fn leave_match(&self, match_id: &str) -> Result<(), WebSocketError<A>> {
+        self.0.leave_match(match_id).await
+    }
+
+    pub async fn leave_party(&self, party_id: &str) -> Result<(), WebSocketError<A>> {
+        self.0.leave_party(party_id).await
+    }
+
+    pub async fn list_party_join_requests(
+        &self,
+        party_id: &str,
+    ) -> Result<PartyJoinRequest, WebSocketError<A>> {
+        self.0.list_party_join_requests(party_id).await
+    }
+
+    pub async fn promote_party_member(
+        &self,
+        party_id: &str,
+        party_member: UserPresence,
+    ) -> Result<(), WebSocketError<A>> {
+        self.0.promote_party_member(party_id, party_member).await
+    }
+
+    pub async fn remove_chat_message(
+        &self,
+        channel_id: &str,
+        message_id: &str,
+    ) -> Result<ChannelMessageAck, WebSocketError<A>> {
+        self.0.remove_chat_message(channel_id, message_id).await
+    }
+
+    pub async fn remove_matchmaker(&self, ticket: &str) -> Result<(), WebSocketError<A>> {
+        self.0.remove_matchmaker(ticket).await
+    }
+
+    pub async fn remove_matchmaker_party(
+        &self,
+        party_id: &str,
+        ticket: &str,
+    ) -> Result<(), WebSocketError<A>> {
+        self.0.remove_matchmaker_party(party_id, ticket).await
+    }
+
+    pub async fn remove_party_member(
+        &self,
+        party_id: &str,
+        presence: UserPresence,
+    ) -> Result<(), WebSocketError<A>> {
+        self.0.remove_party_member(party_id, presence).await
+    }
+
+    pub async fn rpc(&self, func_id: &str, payload: &str) -> Result<ApiRpc, WebSocketError<A>> {
+        self.0.rpc(func_id, payload).await
+    }
+
+    pub async fn rpc_bytes(
+        &self,
+        func_id: &str,
+        payload: &[u8],
+    ) -> Result<ApiRpc, WebSocketError<A>> {
+        self.0.rpc_bytes(func_id, payload).await
+    }
+
+    pub async fn send_match_state(
+        &self,
+        match_id: &str,
+        op_code: i64,
+        state: &[u8],
+        presences: &[UserPresence],
+    ) -> Result<(), WebSocketError<A>> {
+        self.0
+            .send_match_state(match_id, op_code, state, presences)
+            .await
+    }
+
+    pub async fn send_party_data(
+        &self,
+        party_id: &str,
+        op_code: i64,
+        data: &[u8],
+    ) -> Result<(), WebSocketError<A>> {
+        self.0.send_party_data(party_id, op_code, data).await
+    }
+
+    pub async fn unfollow_users(&self, user_ids: &[&str]) -> Result<(), WebSocketError<A>> {
+        self.0.unfollow_users(user_ids).await
+    }
+
+    pub async fn update_chat_message(
+        &self,
+        channel_id: &str,
+        message_id: &str,
+        content: &str,
+    ) -> Result<ChannelMessageAck, WebSocketError<A>> {
+        self.0
+            .update_chat_message(channel_id, message_id, content)
+            .await
+    }
+
+    pub async fn update_status(&self, status: &str) -> Result<(), WebSocketError<A>> {
+        self.0.update_status(status).await
+    }
+
+    pub async fn write_chat_message(
+        &self,
+        channel_id: &str,
+        content: &str,
+    ) -> Result<ChannelMessageAck, WebSocketError<A>> {
+        self.0.write_chat_message(channel_id, content).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{LocalWebSocket, WebSocket};
+    use crate::socket::{ErrorCode, Socket, WebSocketMessageEnvelope};
+    use crate::std_web_socket_adapter::StdWebSocketAdapter;
+    use nanoserde::{DeJson, SerJson};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::sync::mpsc;
+    #[derive(SerJson)]
+    struct TestStruct {
+        a: Option<String>,
+        b: Option<String>,
+        c: Option<String>,
+    }
+    #[test]
+    fn test_serialization() {
+        let test_struct = TestStruct {
+            a: Some("string".to_owned()),
+            b: Some("hello".to_owned()),
+            c: None,
+        };
+        let test_struct2 = TestStruct {
+            a: None,
+            b: Some("string".to_owned()),
+            c: Some("hello".to_owned()),
+        };
+        let result = test_struct.serialize_json();
+        let result2 = test_struct2.serialize_json();
+
+        // This one is correct
+        assert_eq!(result2, "{\"b\":\"string\",\"c\":\"hello\"}");
+        assert_eq!(result, "{\"a\":\"string\",\"b\":\"hello\"}");
+    }
+
+    #[test]
+    fn test_error_code_deserialization() {
+        let envelope: WebSocketMessageEnvelope = DeJson::deserialize_json(
+            r#"{"cid":"1","error":{"code":5,"message":"Match join rejected"}}"#,
+        )
+        .unwrap();
+        assert_eq!(envelope.error.unwrap().code, ErrorCode::MatchJoinRejected);
+
+        let envelope: WebSocketMessageEnvelope =
+            DeJson::deserialize_json(r#"{"error":{"code":42,"message":"New error"}}"#).unwrap();
+        assert_eq!(envelope.error.unwrap().code, ErrorCode::Unknown(42));
+
+        // The server omits the code for RuntimeException
+        let envelope: WebSocketMessageEnvelope =
+            DeJson::deserialize_json(r#"{"error":{"message":"Runtime exception"}}"#).unwrap();
+        assert_eq!(envelope.error.unwrap().code, ErrorCode::RuntimeException);
+    }
+
+    const CHANNEL_MESSAGE: &str = r#"{"channel_message":{"channel_id":"1","content":"{}"}}"#;
+
+    #[test]
+    fn test_multiple_handlers() {
+        let socket = WebSocket::new(StdWebSocketAdapter::new());
+        let (tx, rx) = mpsc::channel();
+
+        let first = socket.on_received_channel_message({
+            let tx = tx.clone();
+            move |_| tx.send(1).unwrap()
+        });
+        socket.on_received_channel_message(move |_| tx.send(2).unwrap());
+
+        socket.handle_message(CHANNEL_MESSAGE);
+        assert_eq!(rx.try_iter().collect::<Vec<_>>(), vec![1, 2]);
+
+        assert!(socket.remove_handler(first));
+        assert!(!socket.remove_handler(first));
+
+        socket.handle_message(CHANNEL_MESSAGE);
+        assert_eq!(rx.try_iter().collect::<Vec<_>>(), vec![2]);
+    }
+
+    #[test]
+    fn test_handler_guard() {
+        let socket = WebSocket::new(StdWebSocketAdapter::new());
+        let (tx, rx) = mpsc::channel();
+
+        let guard = socket.handler_guard(socket.on_received_channel_message(move |_| {
+            tx.send(()).unwrap();
+        }));
+
+        socket.handle_message(CHANNEL_MESSAGE);
+        assert_eq!(rx.try_iter().count(), 1);
+
+        drop(guard);
+        socket.handle_message(CHANNEL_MESSAGE);
+        assert_eq!(rx.try_iter().count(), 0);
+    }
+
+    #[test]
+    fn test_register_handler_inside_handler() {
+        let socket = WebSocket::new(StdWebSocketAdapter::new());
+        let (tx, rx) = mpsc::channel();
+
+        let id = socket.on_received_channel_message({
+            let socket = socket.clone();
+            move |_| {
+                let tx = tx.clone();
+                socket.on_received_channel_message(move |_| tx.send(()).unwrap());
+            }
+        });
+
+        socket.handle_message(CHANNEL_MESSAGE);
+        assert_eq!(rx.try_iter().count(), 0);
+        socket.remove_handler(id);
+
+        socket.handle_message(CHANNEL_MESSAGE);
+        assert_eq!(rx.try_iter().count(), 1);
+    }
+
+    #[test]
+    fn test_local_web_socket_non_send_callback() {
+        let socket = LocalWebSocket::new(StdWebSocketAdapter::new());
+        let received = Rc::new(RefCell::new(Vec::new()));
+
+        let id = socket.on_received_channel_message({
+            let received = received.clone();
+            move |message| received.borrow_mut().push(message.channel_id)
+        });
+
+        socket.0.handle_message(CHANNEL_MESSAGE);
+        assert_eq!(*received.borrow(), vec!["1".to_owned()]);
+
+        assert!(socket.remove_handler(id));
+        socket.0.handle_message(CHANNEL_MESSAGE);
+        assert_eq!(received.borrow().len(), 1);
     }
 }
@@ -1,4 +1,4 @@
-use crate::socket_adapter::SocketAdapter;
+use crate::socket_adapter::{SocketAdapter, Wakeup};
 use log::{debug, error, trace};
 use qws;
 use qws::{CloseCode, Handshake};
@@ -6,6 +6,7 @@ use std::error::Error;
 use std::fmt::{Display, Formatter};
 use std::sync::mpsc;
 use std::sync::mpsc::{Receiver, SendError, Sender};
+use std::sync::{Arc, Mutex};
 
 enum Message {
     StringMessage(String),
@@ -20,16 +21,23 @@ pub struct WebSocketAdapter {
 
     rx_message: Option<Receiver<Message>>,
     tx_message: Option<qws::Sender>,
+    // Shared with the websocket thread, because the wakeup may be set after connecting
+    wakeup: Arc<Mutex<Option<Wakeup>>>,
 }
 
 // Client on the websocket thread
 struct WebSocketClient {
     tx: Sender<Message>,
+    wakeup: Arc<Mutex<Option<Wakeup>>>,
 }
 
 impl WebSocketClient {
     fn send(&self, message: Message) -> Result<(), SendError<Message>> {
-        self.tx.send(message)
+        self.tx.send(message)?;
+        if let Some(ref wakeup) = *self.wakeup.lock().expect("panic inside other mutex!") {
+            wakeup.wake();
+        }
+        Ok(())
     }
 }
 
@@ -98,6 +106,7 @@ impl WebSocketAdapter {
 
             rx_message: None,
             tx_message: None,
+            wakeup: Arc::new(Mutex::new(None)),
         }
     }
 }
@@ -146,6 +155,10 @@ impl SocketAdapter for WebSocketAdapter {
         self.on_received = Some(Box::new(callback));
     }
 
+    fn set_wakeup(&mut self, wakeup: Wakeup) {
+        *self.wakeup.lock().expect("panic inside other mutex!") = Some(wakeup);
+    }
+
     fn is_connected(&self) -> bool {
         todo!()
     }
@@ -163,6 +176,7 @@ impl SocketAdapter for WebSocketAdapter {
         let (tx_init, rx_init) = mpsc::channel();
 
         let addr = addr.to_owned();
+        let wakeup = self.wakeup.clone();
 
         std::thread::spawn({
             move || {
@@ -171,7 +185,10 @@ impl SocketAdapter for WebSocketAdapter {
                     if let Err(err) = response {
                         error!("connect (Thread): Error sending data {}", err);
                     }
-                    return WebSocketClient { tx: tx.clone() };
+                    return WebSocketClient {
+                        tx: tx.clone(),
+                        wakeup: wakeup.clone(),
+                    };
                 })
             }
         });
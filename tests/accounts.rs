@@ -1,11 +1,11 @@
 use futures::executor::block_on;
 use nakama_rs::client::Client;
-use nakama_rs::default_client::DefaultClient;
+use nakama_rs::test_helpers;
 use std::collections::HashMap;
 
 #[test]
 fn test_get_account() {
-    let client = DefaultClient::new_with_adapter();
+    let client = test_helpers::client();
 
     let result = block_on(async {
         let mut session = client
@@ -23,7 +23,7 @@ fn test_get_account() {
 
 #[test]
 fn test_update_account() {
-    let client = DefaultClient::new_with_adapter();
+    let client = test_helpers::client();
 
     let result = block_on(async {
         let mut session = client
@@ -54,3 +54,23 @@ fn test_update_account() {
     assert_eq!(account.user.location, "Austria");
     assert_eq!(account.user.timezone, "Europe/Vienna");
 }
+
+#[test]
+fn test_delete_account() {
+    let client = test_helpers::client();
+
+    block_on(async {
+        let mut session = client
+            .authenticate_device("deleteaccountdeviceid", None, true, HashMap::new())
+            .await
+            .unwrap();
+        client.delete_account(&mut session).await.unwrap();
+
+        let result = client.get_account(&mut session).await;
+        assert_eq!(result.is_err(), true);
+        let result = client
+            .authenticate_device("deleteaccountdeviceid", None, false, HashMap::new())
+            .await;
+        assert_eq!(result.is_err(), true);
+    });
+}
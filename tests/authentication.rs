@@ -1,11 +1,11 @@
 use futures::executor::block_on;
 use nakama_rs::client::Client;
-use nakama_rs::default_client::DefaultClient;
+use nakama_rs::test_helpers;
 use std::collections::HashMap;
 
 #[test]
 fn test_authenticate_device_id_too_short() {
-    let client = DefaultClient::new_with_adapter();
+    let client = test_helpers::client();
 
     let result = block_on(async {
         client
@@ -19,7 +19,7 @@ fn test_authenticate_device_id_too_short() {
 
 #[test]
 fn test_authenticate_device_id() {
-    let client = DefaultClient::new_with_adapter();
+    let client = test_helpers::client();
 
     let result = block_on(async {
         client
@@ -33,7 +33,7 @@ fn test_authenticate_device_id() {
 
 #[test]
 fn test_authenticating_with_unknown_credentials() {
-    let client = DefaultClient::new_with_adapter();
+    let client = test_helpers::client();
     let result = block_on(async {
         client
             .authenticate_email(
@@ -52,7 +52,7 @@ fn test_authenticating_with_unknown_credentials() {
 
 #[test]
 fn test_link_email() {
-    let client = DefaultClient::new_with_adapter();
+    let client = test_helpers::client();
     let result = block_on(async {
         let mut session = client
             .authenticate_device("usersdeviceid", None, true, HashMap::new())
@@ -79,7 +79,7 @@ fn test_link_email() {
 
 #[test]
 fn test_unlink_email() {
-    let client = DefaultClient::new_with_adapter();
+    let client = test_helpers::client();
     let result = block_on(async {
         let mut session = client
             .authenticate_device("usersdeviceid", None, true, HashMap::new())
@@ -106,3 +106,30 @@ fn test_unlink_email() {
     println!("Result: {:?}", result);
     assert_eq!(result.is_err(), true)
 }
+
+#[test]
+fn test_facebook_instant_game() {
+    let client = test_helpers::client();
+    let result = block_on(async {
+        let mut session = client
+            .authenticate_device("instantgamedeviceid", None, true, HashMap::new())
+            .await?;
+
+        client
+            .link_facebook_instant_game(&mut session, "signedplayerinfo")
+            .await?;
+        client
+            .authenticate_facebook_instant_game("signedplayerinfo", None, false, HashMap::new())
+            .await?;
+        client
+            .unlink_facebook_instant_game(&mut session, "signedplayerinfo")
+            .await?;
+
+        client
+            .authenticate_facebook_instant_game("signedplayerinfo", None, false, HashMap::new())
+            .await
+    });
+
+    println!("Session: {:?}", result);
+    assert_eq!(result.is_err(), true)
+}
@@ -1,16 +1,23 @@
+use cassette::{yield_now, Cassette};
 use futures::executor::block_on;
-use nakama_rs::socket::Socket;
+use futures::pin_mut;
+use nakama_rs::helper::SocketDriverBuilder;
+use nakama_rs::socket::{ChannelJoinType, Socket};
 use nakama_rs::test_helpers;
+use nakama_rs::test_helpers::TestSocketAdapter;
+use nakama_rs::web_socket::LocalWebSocket;
 use simple_logger::SimpleLogger;
 use std::thread::sleep;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 #[test]
 fn test_channel_room_creation() {
     let future = async {
         let (socket1, ..) =
             test_helpers::sockets_with_users("socketchannel1", "socketchannel2").await;
-        let channel = socket1.join_chat("MyRoom", 1, false, false).await;
+        let channel = socket1
+            .join_chat("MyRoom", ChannelJoinType::Room, false, false)
+            .await;
         assert_eq!(channel.unwrap().room_name, "MyRoom".to_owned())
     };
 
@@ -23,14 +30,26 @@ fn test_channel_direct_message_creation() {
         .init()
         .expect("Failed to initialize logger");
     let future = async {
-        let (socket1, mut socket2, account1, account2) =
+        let (socket1, socket2, account1, account2) =
             test_helpers::sockets_with_users("socketchannel1", "socketchannel2").await;
         socket1
-            .join_chat(&account2.user.id, 2, false, false)
+            .join_chat(
+                &account2.user.id,
+                ChannelJoinType::DirectMessage,
+                false,
+                false,
+            )
             .await
             .expect("Failed to join chat");
         // The user will receive a notification that a user wants to chat and can then join.
-        let _ = socket2.join_chat(&account1.user.id, 2, false, false).await;
+        let _ = socket2
+            .join_chat(
+                &account1.user.id,
+                ChannelJoinType::DirectMessage,
+                false,
+                false,
+            )
+            .await;
         socket2.on_received_channel_presence(|presence| {
             println!("{:?}", presence);
         });
@@ -51,7 +70,10 @@ fn test_channel_leave() {
     block_on(async {
         let (socket1, ..) =
             test_helpers::sockets_with_users("socketchannel1", "socketchannel2").await;
-        let channel = socket1.join_chat("MyRoom", 1, false, false).await.unwrap();
+        let channel = socket1
+            .join_chat("MyRoom", ChannelJoinType::Room, false, false)
+            .await
+            .unwrap();
         socket1
             .leave_chat(&channel.id)
             .await
@@ -64,7 +86,10 @@ fn test_channel_message() {
     block_on(async {
         let (socket1, ..) =
             test_helpers::sockets_with_users("socketchannel1", "socketchannel2").await;
-        let channel = socket1.join_chat("MyRoom", 1, true, false).await.unwrap();
+        let channel = socket1
+            .join_chat("MyRoom", ChannelJoinType::Room, true, false)
+            .await
+            .unwrap();
         let ack = socket1
             .write_chat_message(&channel.id, r#"{"text":"Hello, World!"}"#)
             .await
@@ -83,3 +108,161 @@ fn test_channel_message() {
         println!("{:?}", ack);
     })
 }
+
+fn wait_until(condition: impl Fn() -> bool) {
+    for _ in 0..100 {
+        if condition() {
+            return;
+        }
+        sleep(Duration::from_millis(20));
+    }
+    panic!("Timed out waiting for the condition");
+}
+
+#[test]
+fn test_channel_tracks_presences() {
+    block_on(async {
+        let (socket1, socket2, account1, _) =
+            test_helpers::sockets_with_users("channelpresence1", "channelpresence2").await;
+        let channel1 = socket1
+            .join_room("PresenceRoom", false, false)
+            .await
+            .unwrap();
+        assert_eq!(channel1.room_name(), "PresenceRoom");
+        assert_eq!(channel1.self_presence().user_id, account1.user.id);
+        assert_eq!(channel1.presences().len(), 1);
+
+        let channel2 = socket2
+            .join_room("PresenceRoom", false, false)
+            .await
+            .unwrap();
+        assert_eq!(channel2.id(), channel1.id());
+        assert_eq!(channel2.presences().len(), 2);
+        wait_until(|| channel1.presences().len() == 2);
+
+        channel2.leave().await.unwrap();
+        wait_until(|| channel1.presences().len() == 1);
+        assert_eq!(channel1.presences()[0].user_id, account1.user.id);
+    });
+}
+
+#[test]
+fn test_channel_join_direct() {
+    block_on(async {
+        let (socket1, _, account1, account2) =
+            test_helpers::sockets_with_users("channeldirect1", "channeldirect2").await;
+        let channel = socket1
+            .join_direct(&account2.user.id, false, false)
+            .await
+            .unwrap();
+        let (one, two) = channel.user_ids();
+        let mut user_ids = vec![one, two];
+        user_ids.sort();
+        let mut expected = vec![account1.user.id.as_str(), account2.user.id.as_str()];
+        expected.sort();
+        assert_eq!(user_ids, expected);
+        assert_eq!(channel.room_name(), "");
+    });
+}
+
+#[test]
+fn test_channel_group_chat_history() {
+    block_on(async {
+        let (client, mut session) = test_helpers::authenticated_client("channelgroupchat").await;
+        let group = test_helpers::re_create_group(&client, &mut session, "ChannelGroupChat").await;
+        let socket = test_helpers::socket();
+        SocketDriverBuilder::new().socket(&socket).spawn().detach();
+        socket.connect(&mut session, true, -1).await;
+
+        let channel = socket
+            .join_group_chat(&group.id, true, false)
+            .await
+            .unwrap();
+        assert_eq!(channel.group_id(), group.id);
+        let ack = channel.send(r#"{"text":"First"}"#).await.unwrap();
+        channel
+            .update(&ack.message_id, r#"{"text":"Edited"}"#)
+            .await
+            .unwrap();
+        let ack = channel.send(r#"{"text":"Second"}"#).await.unwrap();
+        channel.remove(&ack.message_id).await.unwrap();
+
+        let messages = channel
+            .history(Some(true))
+            .collect(&client, &mut session)
+            .await
+            .unwrap();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].content, r#"{"text":"Edited"}"#);
+        channel.leave().await.unwrap();
+    });
+}
+
+#[test]
+fn test_channel_join_group_chat_requires_membership() {
+    block_on(async {
+        let (client, mut session) = test_helpers::authenticated_client("channelgroupowner").await;
+        let group =
+            test_helpers::re_create_group(&client, &mut session, "ChannelGroupClosed").await;
+        let (socket, ..) =
+            test_helpers::sockets_with_users("channelgroupother1", "channelgroupother2").await;
+        assert_eq!(
+            socket
+                .join_group_chat(&group.id, false, false)
+                .await
+                .is_err(),
+            true
+        );
+    });
+}
+
+fn local_socket() -> LocalWebSocket<TestSocketAdapter> {
+    LocalWebSocket::new_with_server(
+        TestSocketAdapter::new(),
+        "ws://127.0.0.1",
+        test_helpers::fake_server().port(),
+    )
+}
+
+#[test]
+fn test_local_web_socket_channel() {
+    let (socket1, socket2) = (local_socket(), local_socket());
+    let future = async {
+        let client = test_helpers::client();
+        let mut session1 = test_helpers::authenticated_client("localchannel1").await.1;
+        let mut session2 = test_helpers::authenticated_client("localchannel2").await.1;
+        socket1.connect(&mut session1, true, -1).await;
+        socket2.connect(&mut session2, true, -1).await;
+
+        let channel1 = socket1.join_room("LocalRoom", true, false).await.unwrap();
+        assert_eq!(channel1.room_name(), "LocalRoom");
+        assert_eq!(channel1.presences().len(), 1);
+        let channel2 = socket2.join_room("LocalRoom", true, false).await.unwrap();
+        assert_eq!(channel2.presences().len(), 2);
+        while channel1.presences().len() != 2 {
+            yield_now().await;
+        }
+
+        channel2.send(r#"{"text":"Hello"}"#).await.unwrap();
+        let mut history = channel1.history(Some(true));
+        let message = history.next(&client, &mut session1).await.unwrap();
+        assert_eq!(message.unwrap().content, r#"{"text":"Hello"}"#);
+
+        channel2.leave().await.unwrap();
+        while channel1.presences().len() != 1 {
+            yield_now().await;
+        }
+        channel1.leave().await.unwrap();
+    };
+
+    // `LocalWebSocket` is ticked on the thread running the future
+    pin_mut!(future);
+    let mut cassette = Cassette::new(future);
+    let start = Instant::now();
+    while cassette.poll_on().is_none() {
+        assert!(start.elapsed() < Duration::from_secs(10), "Timed out");
+        socket1.tick();
+        socket2.tick();
+        sleep(Duration::from_millis(1));
+    }
+}
@@ -1,5 +1,6 @@
 use futures::executor::block_on;
 use nakama_rs::client::Client;
+use nakama_rs::enums::FriendState;
 use nakama_rs::test_helpers;
 
 #[test]
@@ -103,3 +104,42 @@ fn test_block_friend() {
         assert_eq!(result.is_ok(), true);
     })
 }
+
+#[test]
+fn test_list_friend_state() {
+    block_on(async {
+        let (client, mut session1, _, _) = test_helpers::clients_with_users(
+            "friendstateuser1",
+            "friendstateuser2",
+            "friendstateuser3",
+        )
+        .await;
+        client
+            .add_friends(&mut session1, &[], &["friendstateuser2"])
+            .await
+            .unwrap();
+        client
+            .block_friends(&mut session1, &[], &["friendstateuser3"])
+            .await
+            .unwrap();
+
+        let friends = client
+            .list_friends(&mut session1, Some(FriendState::InviteSent), None, None)
+            .await
+            .unwrap();
+        assert_eq!(friends.friends.len(), 1);
+        assert_eq!(friends.friends[0].user.username, "friendstateuser2");
+        let friends = client
+            .list_friends(&mut session1, Some(FriendState::Blocked), None, None)
+            .await
+            .unwrap();
+        assert_eq!(friends.friends.len(), 1);
+        assert_eq!(friends.friends[0].user.username, "friendstateuser3");
+        assert_eq!(friends.friends[0].friend_state(), FriendState::Blocked);
+        let friends = client
+            .list_friends(&mut session1, Some(FriendState::Friend), None, None)
+            .await
+            .unwrap();
+        assert_eq!(friends.friends.len(), 0);
+    });
+}
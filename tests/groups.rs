@@ -1,5 +1,6 @@
 use futures::executor::block_on;
 use nakama_rs::client::Client;
+use nakama_rs::enums::GroupRole;
 use nakama_rs::test_helpers;
 
 #[test]
@@ -266,24 +267,27 @@ fn test_list_groups() {
 
 #[test]
 fn test_list_current_user_groups() {
-    // block_on(async {
-    //     let (client, mut session1, mut session2, mut session3) =
-    //         test_helpers::clients_with_users("friendtestuser1", "friendtestuser2", "friendtestuser3")
-    //             .await;
-    //
-    //     test_helpers::re_create_group(&client, &mut session1, "ListGroups1").await;
-    //     test_helpers::re_create_group(&client, &mut session1, "ListGroups2").await;
-    //     let groups1 = client
-    //         .list_current_user_groups()
-    //         .list_groups(&mut session1, None, Some(1), None)
-    //         .await
-    //         .unwrap();
-    //     let groups2 = client
-    //         .list_groups(&mut session1, None, None, Some(&groups1.cursor))
-    //         .await;
-    //     println!("{:?}", groups2);
-    //     assert_eq!(groups2.is_ok(), true);
-    // })
+    block_on(async {
+        let (client, mut session1, _, _) = test_helpers::clients_with_users(
+            "friendtestuser1",
+            "friendtestuser2",
+            "friendtestuser3",
+        )
+        .await;
+
+        test_helpers::re_create_group(&client, &mut session1, "ListCurrentUserGroups1").await;
+        test_helpers::re_create_group(&client, &mut session1, "ListCurrentUserGroups2").await;
+        let groups1 = client
+            .list_current_user_groups(&mut session1, None, Some(1), None)
+            .await
+            .unwrap();
+        assert_eq!(groups1.user_groups.len(), 1);
+        let groups2 = client
+            .list_current_user_groups(&mut session1, None, None, Some(&groups1.cursor))
+            .await;
+        println!("{:?}", groups2);
+        assert_eq!(groups2.is_ok(), true);
+    })
 }
 
 #[test]
@@ -316,3 +320,44 @@ fn test_list_user_groups() {
         assert_eq!(groups2.is_ok(), true);
     })
 }
+
+#[test]
+fn test_list_group_users_role() {
+    block_on(async {
+        let (client, mut session1, mut session2, _) =
+            test_helpers::clients_with_users("grouproleuser1", "grouproleuser2", "grouproleuser3")
+                .await;
+        let group = test_helpers::re_create_group(&client, &mut session1, "GroupUsersRole").await;
+        let account2 = client.get_account(&mut session2).await.unwrap();
+        client
+            .add_group_users(&mut session1, &group.id, &[&account2.user.id])
+            .await
+            .unwrap();
+
+        let users = client
+            .list_group_users(
+                &mut session1,
+                &group.id,
+                Some(GroupRole::Member),
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(users.group_users.len(), 1);
+        assert_eq!(users.group_users[0].user.id, account2.user.id);
+        assert_eq!(users.group_users[0].group_role(), GroupRole::Member);
+
+        let groups = client
+            .list_current_user_groups(&mut session1, Some(GroupRole::SuperAdmin), None, None)
+            .await
+            .unwrap();
+        assert_eq!(
+            groups
+                .user_groups
+                .iter()
+                .any(|user_group| user_group.group.id == group.id),
+            true
+        );
+    });
+}
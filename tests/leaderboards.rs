@@ -2,13 +2,21 @@ use futures::executor::block_on;
 use nakama_rs::api::ApiOverrideOperator;
 use nakama_rs::client::Client;
 use nakama_rs::test_helpers;
+use nanoserde::{DeJson, SerJson};
 
 #[test]
 fn test_write_leaderboard_record() {
     block_on(async {
         let (client, mut session) = test_helpers::authenticated_client("leaderboardclient1").await;
         let result = client
-            .write_leaderboard_record(&mut session, "wins", 1, None, None, None)
+            .write_leaderboard_record(
+                &mut session,
+                "wins",
+                1,
+                None,
+                ApiOverrideOperator::NO_OVERRIDE,
+                None,
+            )
             .await;
         println!("{:?}", result);
         assert_eq!(result.is_ok(), true);
@@ -25,13 +33,13 @@ fn test_write_leaderboard_record_subscore_and_override_operator() {
                 "wins",
                 1,
                 Some(50),
-                Some(ApiOverrideOperator::SET),
+                ApiOverrideOperator::SET,
                 None,
             )
             .await;
         println!("{:?}", result);
         assert_eq!(result.is_ok(), true);
-        assert_eq!(result.unwrap().subscore, Some("50".to_owned()));
+        assert_eq!(result.unwrap().subscore, Some(50));
     });
 }
 
@@ -45,7 +53,7 @@ fn test_delete_leaderboard_record() {
                 "wins",
                 1,
                 Some(50),
-                Some(ApiOverrideOperator::SET),
+                ApiOverrideOperator::SET,
                 None,
             )
             .await
@@ -67,7 +75,7 @@ fn test_list_leaderboard_records() {
                 "wins",
                 1,
                 Some(50),
-                Some(ApiOverrideOperator::SET),
+                ApiOverrideOperator::SET,
                 None,
             )
             .await
@@ -78,7 +86,7 @@ fn test_list_leaderboard_records() {
                 "wins",
                 2,
                 Some(50),
-                Some(ApiOverrideOperator::SET),
+                ApiOverrideOperator::SET,
                 None,
             )
             .await
@@ -116,7 +124,7 @@ fn test_list_leaderboard_records_around_owner() {
                 "wins",
                 1,
                 Some(50),
-                Some(ApiOverrideOperator::SET),
+                ApiOverrideOperator::SET,
                 None,
             )
             .await
@@ -127,7 +135,7 @@ fn test_list_leaderboard_records_around_owner() {
                 "wins",
                 2,
                 Some(50),
-                Some(ApiOverrideOperator::SET),
+                ApiOverrideOperator::SET,
                 None,
             )
             .await
@@ -141,3 +149,112 @@ fn test_list_leaderboard_records_around_owner() {
         assert_eq!(result.records.len() >= 1, true);
     });
 }
+
+#[test]
+fn test_leaderboard_record_scores_and_ranks() {
+    block_on(async {
+        let (client, mut session) = test_helpers::authenticated_client("leaderboardclient1").await;
+        let (_, mut session2) = test_helpers::authenticated_client("leaderboardclient2").await;
+        let large_score = 1 << 40;
+        client
+            .write_leaderboard_record(
+                &mut session,
+                "scores",
+                7,
+                None,
+                ApiOverrideOperator::NO_OVERRIDE,
+                None,
+            )
+            .await
+            .unwrap();
+        client
+            .write_leaderboard_record(
+                &mut session2,
+                "scores",
+                large_score,
+                Some(3),
+                ApiOverrideOperator::NO_OVERRIDE,
+                None,
+            )
+            .await
+            .unwrap();
+
+        let result = client
+            .list_leaderboard_records(&mut session, "scores", &[], None, None, None)
+            .await
+            .unwrap();
+        let scores: Vec<_> = result
+            .records
+            .iter()
+            .map(|record| (record.rank, record.score, record.subscore))
+            .collect();
+        assert_eq!(scores, [(1, large_score, Some(3)), (2, 7, Some(0))]);
+    });
+}
+
+#[derive(SerJson, DeJson, Debug, PartialEq)]
+struct RaceMetadata {
+    car: String,
+    laps: i32,
+}
+
+#[test]
+fn test_write_leaderboard_record_metadata() {
+    block_on(async {
+        let (client, mut session) = test_helpers::authenticated_client("leaderboardclient1").await;
+        let metadata = RaceMetadata {
+            car: "red".to_owned(),
+            laps: 3,
+        };
+        let record = client
+            .write_leaderboard_record(
+                &mut session,
+                "races",
+                90,
+                None,
+                ApiOverrideOperator::BEST,
+                Some(&metadata),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            RaceMetadata::deserialize_json(&record.metadata).unwrap(),
+            metadata
+        );
+
+        // BEST keeps the higher score
+        let record = client
+            .write_leaderboard_record(
+                &mut session,
+                "races",
+                80,
+                None,
+                ApiOverrideOperator::BEST,
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(record.score, 90);
+    });
+}
+
+#[test]
+fn test_override_operator_json() {
+    assert_eq!(
+        ApiOverrideOperator::INCREMENT.serialize_json(),
+        "\"INCREMENT\""
+    );
+    assert_eq!(
+        ApiOverrideOperator::deserialize_json("\"DECREMENT\"").unwrap(),
+        ApiOverrideOperator::DECREMENT
+    );
+    assert_eq!(
+        ApiOverrideOperator::deserialize_json("2").unwrap(),
+        ApiOverrideOperator::SET
+    );
+    assert_eq!(
+        ApiOverrideOperator::deserialize_json("\"WORST\"").is_err(),
+        true
+    );
+    assert_eq!(ApiOverrideOperator::deserialize_json("5").is_err(), true);
+}
@@ -41,7 +41,7 @@ fn join_and_leave_party() {
 fn promote_and_remove_party_member() {
     block_on(async {
         let (tx, rx) = mpsc::channel();
-        let (mut socket1, socket2, ..) =
+        let (socket1, socket2, ..) =
             test_helpers::sockets_with_users("partyuserone", "partyusertwo").await;
 
         socket1.on_received_party_presence(move |presence| {
@@ -104,7 +104,7 @@ fn test_send_party_data() {
         .unwrap();
     block_on(async {
         let (tx, rx) = mpsc::channel();
-        let (socket1, mut socket2, _, _) =
+        let (socket1, socket2, _, _) =
             test_helpers::sockets_with_users("partyuserone", "partyusertwo").await;
 
         let party = socket1.create_party(true, 2).await.unwrap();
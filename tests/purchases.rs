@@ -0,0 +1,37 @@
+use futures::executor::block_on;
+use nakama_rs::client::Client;
+use nakama_rs::test_helpers;
+
+#[test]
+fn test_validate_purchase() {
+    block_on(async {
+        let (client, mut session) = test_helpers::authenticated_client("purchasedeviceid").await;
+
+        // The test server has no in-app purchase providers configured
+        let result = client
+            .validate_purchase_apple(&mut session, "receipt", false)
+            .await;
+        assert_eq!(result.is_err(), true);
+        let result = client
+            .validate_subscription_google(&mut session, "receipt", true)
+            .await;
+        assert_eq!(result.is_err(), true);
+    });
+}
+
+#[test]
+fn test_list_subscriptions() {
+    block_on(async {
+        let (client, mut session) =
+            test_helpers::authenticated_client("subscriptiondeviceid").await;
+
+        let subscriptions = client
+            .list_subscriptions(&mut session, Some(10), None)
+            .await
+            .unwrap();
+        assert_eq!(subscriptions.validated_subscriptions.len(), 0);
+
+        let result = client.get_subscription(&mut session, "premium").await;
+        assert_eq!(result.is_err(), true);
+    });
+}
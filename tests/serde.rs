@@ -0,0 +1,160 @@
+#![cfg(feature = "serde")]
+
+use futures::executor::block_on;
+use nakama_rs::api::{ApiLeaderboardRecord, ApiOverrideOperator};
+use nakama_rs::client::Client;
+use nakama_rs::json::Serde;
+use nakama_rs::socket::{ErrorCode, WebSocketMessageEnvelope};
+use nakama_rs::storage::StorageCollection;
+use nakama_rs::test_helpers;
+use nanoserde::{DeJson, SerJson};
+use serde::{Deserialize, Serialize};
+
+#[test]
+fn test_api_types_match_wire_format() {
+    let json = r#"{"leaderboard_id":"wins","owner_id":"user","rank":"1","score":"9007199254740993","create_time":"2021-06-21T12:00:00Z"}"#;
+    let record: ApiLeaderboardRecord = serde_json::from_str(json).unwrap();
+    assert_eq!(record.rank, 1);
+    assert_eq!(record.score, 9007199254740993);
+    assert_eq!(record.subscore, None);
+    assert_eq!(
+        record.create_time,
+        Some("2021-06-21T12:00:00Z".parse().unwrap())
+    );
+    assert_eq!(
+        serde_json::to_string(&record).unwrap(),
+        record.serialize_json()
+    );
+
+    assert_eq!(
+        serde_json::to_string(&ApiOverrideOperator::SET).unwrap(),
+        r#""SET""#
+    );
+    let operators: Vec<ApiOverrideOperator> = serde_json::from_str(r#"["BEST", 4]"#).unwrap();
+    assert_eq!(
+        operators,
+        vec![ApiOverrideOperator::BEST, ApiOverrideOperator::DECREMENT]
+    );
+    assert_eq!(
+        serde_json::from_str::<ApiOverrideOperator>(r#""BETTER""#).is_err(),
+        true
+    );
+}
+
+#[test]
+fn test_socket_types_match_wire_format() {
+    let json = r#"{"cid":"1","match":{"match_id":"m","authoritative":false,"label":"","size":1,"presences":[],"self":{"user_id":"u"}}}"#;
+    let envelope: WebSocketMessageEnvelope = serde_json::from_str(json).unwrap();
+    assert_eq!(envelope.new_match.as_ref().unwrap()._self.user_id, "u");
+    assert_eq!(
+        serde_json::to_string(&envelope).unwrap(),
+        envelope.serialize_json()
+    );
+
+    let envelope: WebSocketMessageEnvelope =
+        serde_json::from_str(r#"{"error":{"code":4,"message":"Not found"}}"#).unwrap();
+    assert_eq!(envelope.error.unwrap().code, ErrorCode::MatchNotFound);
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Card {
+    name: String,
+    power: i32,
+}
+
+#[test]
+fn test_storage_collection_of_serde_type() {
+    block_on(async {
+        let (client, mut session) = test_helpers::authenticated_client("serdestorageid").await;
+        let cards = StorageCollection::<Serde<Card>>::new("SerdeCards");
+        let card = Card {
+            name: "Dragon".to_owned(),
+            power: 9,
+        };
+        cards
+            .put(&client, &mut session, "dragon", &Serde(card))
+            .await
+            .unwrap();
+
+        let object = cards.get(&client, &mut session, "dragon").await.unwrap();
+        assert_eq!(object.unwrap().value.0.power, 9);
+    });
+}
+
+#[test]
+fn test_serde_leaderboard_metadata() {
+    block_on(async {
+        let (client, mut session) = test_helpers::authenticated_client("serdeleaderboard").await;
+        let card = Card {
+            name: "Dragon".to_owned(),
+            power: 9,
+        };
+        let record = client
+            .write_leaderboard_record(
+                &mut session,
+                "serde",
+                1,
+                None,
+                ApiOverrideOperator::NO_OVERRIDE,
+                Some(&Serde(card)),
+            )
+            .await
+            .unwrap();
+        let metadata = Serde::<Card>::deserialize_json(&record.metadata).unwrap();
+        assert_eq!(metadata.0.name, "Dragon");
+    });
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Item {
+    id: u64,
+    tags: Vec<String>,
+}
+
+#[test]
+fn test_serde_wrapper_edge_cases() {
+    let json = r#"{"id":18446744073709551615,"tags":["a","[b]"]}"#;
+    let item = Serde::<Item>::deserialize_json(json).unwrap().0;
+    assert_eq!(item.id, u64::MAX);
+    assert_eq!(item.tags, vec!["a".to_owned(), "[b]".to_owned()]);
+
+    // Maps with keys that are not strings can't be serialized as JSON
+    let mut map = std::collections::BTreeMap::new();
+    map.insert(vec![1u8], 2);
+    assert_eq!(Serde(map).serialize_json(), "null");
+}
+
+#[test]
+fn test_serde_wrapper_depth_limit() {
+    let nested = |depth: usize| format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+    assert_eq!(
+        Serde::<serde_json::Value>::deserialize_json(&nested(100)).is_ok(),
+        true
+    );
+    assert_eq!(
+        Serde::<serde_json::Value>::deserialize_json(&nested(129)).is_err(),
+        true
+    );
+    // Fails without overflowing the stack
+    assert_eq!(
+        Serde::<serde_json::Value>::deserialize_json(&"[".repeat(1_000_000)).is_err(),
+        true
+    );
+}
+
+#[test]
+fn test_serde_rpc() {
+    block_on(async {
+        let (client, mut session) = test_helpers::authenticated_client("serderpcid").await;
+        let card = Card {
+            name: "Dragon".to_owned(),
+            power: 9,
+        };
+        let payload = serde_json::to_string(&card).unwrap();
+        let response = client
+            .rpc(&mut session, "echo", Some(&payload))
+            .await
+            .unwrap();
+        assert_eq!(response.payload_serde::<Card>().unwrap(), card);
+    });
+}
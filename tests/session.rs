@@ -1,11 +1,14 @@
 use futures::executor::block_on;
+use nakama_rs::api;
 use nakama_rs::client::Client;
-use nakama_rs::default_client::DefaultClient;
+use nakama_rs::http_adapter::ClientAdapter;
+use nakama_rs::session::Session;
+use nakama_rs::test_helpers;
 use std::collections::HashMap;
 
 #[test]
 fn test_session_variables() {
-    let client = DefaultClient::new_with_adapter();
+    let client = test_helpers::client();
 
     let result = block_on(async {
         let mut vars = HashMap::new();
@@ -21,3 +24,96 @@ fn test_session_variables() {
     // TODO: parse "vrs" from the token payload
     // let account = result.unwrap();
 }
+
+#[test]
+fn test_session_user() {
+    let client = test_helpers::client();
+
+    block_on(async {
+        let mut session = client
+            .authenticate_device(
+                "sessionuserdeviceid",
+                Some("sessionuser"),
+                true,
+                HashMap::new(),
+            )
+            .await
+            .unwrap();
+        let account = client.get_account(&mut session).await.unwrap();
+
+        assert_eq!(session.user_id(), Some(account.user.id));
+        assert_eq!(session.username(), Some("sessionuser".to_owned()));
+    });
+}
+
+#[test]
+fn test_session_missing_claims() {
+    let payload = base64::encode_config(r#"{"exp":1}"#, base64::URL_SAFE_NO_PAD);
+    let session = Session {
+        auth_token: format!("header.{}.signature", payload),
+        refresh_token: None,
+    };
+    assert_eq!(session.user_id(), None);
+    assert_eq!(session.username(), None);
+
+    let session = Session {
+        auth_token: "invalid".to_owned(),
+        refresh_token: None,
+    };
+    assert_eq!(session.user_id(), None);
+}
+
+#[test]
+fn test_healthcheck() {
+    block_on(async {
+        let (client, mut session) = test_helpers::authenticated_client("healthcheckdeviceid").await;
+
+        let result = client.healthcheck(&mut session).await;
+        assert_eq!(result.is_ok(), true);
+    });
+}
+
+#[test]
+fn test_rpc_http_key() {
+    let client = test_helpers::client();
+
+    block_on(async {
+        let rpc = client
+            .rpc_http_key("defaulthttpkey", "echo", Some("Hello"))
+            .await
+            .unwrap();
+        assert_eq!(rpc.payload, "Hello");
+
+        let result = client.rpc_http_key("wronghttpkey", "echo", None).await;
+        assert_eq!(result.is_err(), true);
+
+        // The server rejects an empty bearer token, even with a valid HTTP key
+        let adapter = test_helpers::TestHttpAdapter::new(
+            "http://127.0.0.1",
+            test_helpers::fake_server().port(),
+        );
+        let request = api::rpc_func("", "echo", "\"Hello\"", Some("defaulthttpkey"));
+        let result = adapter.send(request).await;
+        assert_eq!(result.is_err(), true);
+    });
+}
+
+#[test]
+fn test_session_logout_all() {
+    let client = test_helpers::client();
+
+    block_on(async {
+        let mut session1 = client
+            .authenticate_device("logoutalldeviceid", None, true, HashMap::new())
+            .await
+            .unwrap();
+        let mut session2 = client
+            .authenticate_device("logoutalldeviceid", None, true, HashMap::new())
+            .await
+            .unwrap();
+        client.session_logout_all(&mut session1).await.unwrap();
+
+        assert_eq!(client.get_account(&mut session1).await.is_err(), true);
+        assert_eq!(client.get_account(&mut session2).await.is_err(), true);
+    });
+}
@@ -1,20 +1,20 @@
 use futures::executor::block_on;
 use nakama_rs::client::Client;
-use nakama_rs::default_client::DefaultClient;
-use nakama_rs::error::NakamaError;
-use nakama_rs::helper::tick_socket;
+use nakama_rs::default_client::DefaultClientError;
+use nakama_rs::helper::SocketDriver;
 use nakama_rs::socket::Socket;
+use nakama_rs::test_helpers;
+use nakama_rs::test_helpers::{TestHttpAdapter, TestSocketAdapter};
 use nakama_rs::web_socket::WebSocket;
 
 use nakama_rs::session::Session;
-use nakama_rs::web_socket_adapter::WebSocketAdapter;
 use std::collections::HashMap;
 use std::sync::mpsc;
 
-async fn socket_with_user(id: &str) -> (Session, WebSocket<WebSocketAdapter>) {
-    let client = DefaultClient::new_with_adapter();
-    let socket = WebSocket::new_with_adapter();
-    tick_socket(&socket);
+async fn socket_with_user(id: &str) -> (Session, WebSocket<TestSocketAdapter>) {
+    let client = test_helpers::client();
+    let socket = test_helpers::socket();
+    SocketDriver::new(&socket).detach();
 
     let session = client
         .authenticate_device(id, Some("SocketTestUser"), true, HashMap::new())
@@ -26,11 +26,13 @@ async fn socket_with_user(id: &str) -> (Session, WebSocket<WebSocketAdapter>) {
 #[test]
 fn test_status_presence_received_after_connect() {
     let future = async {
-        let (mut session, mut socket) = socket_with_user("socket_test_user").await;
+        let (mut session, socket) = socket_with_user("socket_test_user").await;
 
         let (tx_presence, rx_presence) = mpsc::channel();
         socket.on_received_status_presence(move |presence| {
-            tx_presence.send(presence).expect("Failed to send status presence");
+            tx_presence
+                .send(presence)
+                .expect("Failed to send status presence");
         });
         socket.connect(&mut session, true, -1).await;
 
@@ -41,7 +43,7 @@ fn test_status_presence_received_after_connect() {
         Ok(())
     };
 
-    let result: Result<(), NakamaError> = block_on(future);
+    let result: Result<(), DefaultClientError<TestHttpAdapter>> = block_on(future);
     assert_eq!(result.is_ok(), true);
 }
 
@@ -50,7 +52,7 @@ fn test_on_connected_triggered() {
     let (tx, rx) = mpsc::channel::<()>();
 
     block_on(async {
-        let (mut session, mut socket) = socket_with_user("socket_test_user").await;
+        let (mut session, socket) = socket_with_user("socket_test_user").await;
 
         socket.on_connected(move || {
             tx.send(()).expect("Failed to send connected status");
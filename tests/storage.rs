@@ -2,11 +2,13 @@ use futures::executor::block_on;
 use nakama_rs::api::{ApiDeleteStorageObjectId, ApiReadStorageObjectId, ApiWriteStorageObject};
 use nakama_rs::client::Client;
 use nakama_rs::default_client::DefaultClient;
-use nakama_rs::http_adapter::RestHttpAdapter;
 use nakama_rs::session::Session;
+use nakama_rs::storage::{ReadPermission, StorageCollection, StorageError, WritePermission};
 use nakama_rs::test_helpers;
+use nakama_rs::test_helpers::TestHttpAdapter;
+use nanoserde::{DeJson, SerJson};
 
-async fn client_with_storage_object() -> (DefaultClient<RestHttpAdapter>, Session) {
+async fn client_with_storage_object() -> (DefaultClient<TestHttpAdapter>, Session) {
     let (client, mut session) = test_helpers::authenticated_client("storageclientid").await;
     client
         .write_storage_objects(
@@ -98,6 +100,29 @@ fn test_read_storage() {
     });
 }
 
+#[test]
+fn test_read_storage_value_json() {
+    block_on(async {
+        let (client, mut session) = client_with_storage_object().await;
+        let user_id = client.get_account(&mut session).await.unwrap().user.id;
+
+        let objects = client
+            .read_storage_objects(
+                &mut session,
+                &[ApiReadStorageObjectId {
+                    collection: "Cards".to_owned(),
+                    key: "card1".to_owned(),
+                    user_id,
+                }],
+            )
+            .await
+            .unwrap();
+        let value = objects.objects[0].value_json().unwrap();
+        assert_eq!(value["value"].as_str(), Some("A powerful card"));
+        assert_eq!(value["missing"].is_null(), true);
+    });
+}
+
 #[test]
 fn test_delete_storage() {
     block_on(async {
@@ -132,12 +157,12 @@ fn test_list_storage_objects() {
         let (client, mut session) = client_with_storage_object().await;
 
         let result1 = client
-            .list_storage_objects(&mut session, "Cards", Some(1), None)
+            .list_storage_objects(&mut session, "Cards", None, Some(1), None)
             .await
             .unwrap();
         assert_eq!(result1.cursor.len() > 0, true);
         let result2 = client
-            .list_storage_objects(&mut session, "Cards", None, Some(&result1.cursor))
+            .list_storage_objects(&mut session, "Cards", None, None, Some(&result1.cursor))
             .await;
 
         println!("{:?}", result2);
@@ -146,6 +171,27 @@ fn test_list_storage_objects() {
     });
 }
 
+#[test]
+fn test_list_storage_objects_of_user() {
+    block_on(async {
+        let (client, mut session) = client_with_storage_object().await;
+        let user_id = session.user_id().unwrap();
+
+        let result = client
+            .list_storage_objects(&mut session, "Cards", Some(&user_id), None, None)
+            .await
+            .unwrap();
+        assert_eq!(result.objects.len(), 2);
+        assert_eq!(
+            result
+                .objects
+                .iter()
+                .all(|object| object.user_id == user_id),
+            true
+        );
+    });
+}
+
 #[test]
 fn test_list_users_storage_objects() {
     block_on(async {
@@ -172,3 +218,224 @@ fn test_list_users_storage_objects() {
         assert_eq!(result2.unwrap().cursor, "".to_owned());
     });
 }
+
+#[derive(DeJson, SerJson, Debug, PartialEq)]
+struct Card {
+    value: String,
+}
+
+#[test]
+fn test_storage_collection() {
+    block_on(async {
+        let (client, mut session) = client_with_storage_object().await;
+        let mut cards = StorageCollection::<Card>::new("TypedCards");
+        cards.set_permissions(ReadPermission::PublicRead, WritePermission::OwnerWrite);
+
+        let card = Card {
+            value: "A typed card".to_owned(),
+        };
+        let version = cards
+            .put(&client, &mut session, "card1", &card)
+            .await
+            .unwrap();
+
+        let object = cards
+            .get(&client, &mut session, "card1")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(object.value, card);
+        assert_eq!(object.version, version);
+        assert_eq!(object.permission_read, ReadPermission::PublicRead);
+        assert_eq!(object.permission_write, WritePermission::OwnerWrite);
+
+        let objects = cards.list(&client, &mut session).await.unwrap();
+        assert_eq!(objects.len(), 1);
+
+        cards.delete(&client, &mut session, "card1").await.unwrap();
+        let object = cards.get(&client, &mut session, "card1").await.unwrap();
+        assert_eq!(object.is_none(), true);
+    });
+}
+
+#[test]
+fn test_storage_collection_invalid_value() {
+    block_on(async {
+        let (client, mut session) = client_with_storage_object().await;
+        let cards = StorageCollection::<Vec<u32>>::new("Cards");
+
+        let result = cards.get(&client, &mut session, "card1").await;
+        assert_eq!(matches!(result, Err(StorageError::JsonError(_))), true);
+    });
+}
+
+#[test]
+fn test_storage_collection_invalid_session() {
+    block_on(async {
+        let (client, _) = client_with_storage_object().await;
+        let mut session = Session {
+            auth_token: "not a token".to_owned(),
+            refresh_token: None,
+        };
+        let cards = StorageCollection::<Card>::new("Cards");
+
+        let result = cards.get(&client, &mut session, "card1").await;
+        assert_eq!(matches!(result, Err(StorageError::InvalidSession)), true);
+        let result = cards.list(&client, &mut session).await;
+        assert_eq!(matches!(result, Err(StorageError::InvalidSession)), true);
+    });
+}
+
+#[derive(DeJson, SerJson, Debug, PartialEq)]
+struct Counter {
+    count: i32,
+}
+
+#[test]
+fn test_storage_collection_create() {
+    block_on(async {
+        let (client, mut session) = test_helpers::authenticated_client("storagecreateid").await;
+        let counters = StorageCollection::<Counter>::new("Counters");
+        counters
+            .delete(&client, &mut session, "created")
+            .await
+            .unwrap();
+
+        let result = counters
+            .create(&client, &mut session, "created", &Counter { count: 1 })
+            .await;
+        assert_eq!(result.is_ok(), true);
+        let result = counters
+            .create(&client, &mut session, "created", &Counter { count: 2 })
+            .await;
+        assert_eq!(result.is_err(), true);
+    });
+}
+
+#[test]
+fn test_storage_collection_update() {
+    block_on(async {
+        let (client, mut session) = test_helpers::authenticated_client("storageupdateid").await;
+        let counters = StorageCollection::<Counter>::new("Counters");
+        counters
+            .delete(&client, &mut session, "updated")
+            .await
+            .unwrap();
+
+        for _ in 0..2 {
+            counters
+                .update(&client, &mut session, "updated", |counter| Counter {
+                    count: counter.map_or(0, |counter| counter.count) + 1,
+                })
+                .await
+                .unwrap();
+        }
+
+        let (counter, version) = counters
+            .update(&client, &mut session, "updated", |counter| Counter {
+                count: counter.unwrap().count * 10,
+            })
+            .await
+            .unwrap();
+        assert_eq!(counter, Counter { count: 20 });
+        let object = counters
+            .get(&client, &mut session, "updated")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(object.value, counter);
+        assert_eq!(object.version, version);
+    });
+}
+
+// Writes the counter from another device while `update` runs, the first `conflicts` times
+fn concurrent_update(conflicts: usize) -> impl FnMut(Option<Counter>) -> Counter {
+    let mut calls = 0;
+    move |counter| {
+        calls += 1;
+        if calls <= conflicts {
+            std::thread::spawn(|| {
+                block_on(async {
+                    let (client, mut session) =
+                        test_helpers::authenticated_client("storageconflictid").await;
+                    let counters = StorageCollection::<Counter>::new("Counters");
+                    counters
+                        .update(&client, &mut session, "conflicted", |counter| Counter {
+                            count: counter.map_or(0, |counter| counter.count) + 100,
+                        })
+                        .await
+                        .unwrap();
+                })
+            })
+            .join()
+            .unwrap();
+        }
+        Counter {
+            count: counter.map_or(0, |counter| counter.count) + 1,
+        }
+    }
+}
+
+#[test]
+fn test_storage_collection_update_conflict() {
+    block_on(async {
+        let (client, mut session) = test_helpers::authenticated_client("storageconflictid").await;
+        let mut counters = StorageCollection::<Counter>::new("Counters");
+        counters
+            .delete(&client, &mut session, "conflicted")
+            .await
+            .unwrap();
+
+        let (counter, _) = counters
+            .update(&client, &mut session, "conflicted", concurrent_update(2))
+            .await
+            .unwrap();
+        assert_eq!(counter, Counter { count: 201 });
+
+        counters.set_max_retries(1);
+        let result = counters
+            .update(&client, &mut session, "conflicted", concurrent_update(2))
+            .await;
+        assert_eq!(matches!(result, Err(StorageError::VersionConflict)), true);
+    });
+}
+
+#[test]
+fn test_bulk_storage_objects() {
+    block_on(async {
+        let (mut client, session) = test_helpers::authenticated_client("storagebulkid").await;
+        client.set_bulk_chunk_size(3);
+        let objects: Vec<ApiWriteStorageObject> = (0..7)
+            .map(|i| ApiWriteStorageObject {
+                collection: "Bulk".to_owned(),
+                key: format!("object{}", i),
+                permission_read: 1,
+                permission_write: 1,
+                value: format!(r#"{{"index":{}}}"#, i),
+                version: "".to_owned(),
+            })
+            .collect();
+
+        let result = client.bulk_write_storage_objects(&session, &objects).await;
+        assert_eq!(result.is_ok(), true);
+        assert_eq!(result.value.acks.len(), 7);
+
+        let ids: Vec<ApiReadStorageObjectId> = result
+            .value
+            .acks
+            .iter()
+            .map(|ack| ApiReadStorageObjectId {
+                collection: ack.collection.clone(),
+                key: ack.key.clone(),
+                user_id: ack.user_id.clone(),
+            })
+            .collect();
+        let objects = client
+            .bulk_read_storage_objects(&session, &ids)
+            .await
+            .into_result()
+            .unwrap();
+        assert_eq!(objects.objects.len(), 7);
+        assert_eq!(objects.objects[6].value, r#"{"index":6}"#);
+    });
+}
@@ -1,6 +1,7 @@
 use futures::executor::block_on;
 use nakama_rs::client::Client;
 use nakama_rs::test_helpers;
+use nakama_rs::timestamp::Timestamp;
 
 #[test]
 fn test_join_tournament() {
@@ -33,6 +34,26 @@ fn test_list_tournaments() {
     });
 }
 
+#[test]
+fn test_tournament_times() {
+    block_on(async {
+        let (client, mut session) = test_helpers::authenticated_client("tournamentclient1").await;
+        let tournaments = client
+            .list_tournaments(&mut session, None, None, None, None, None, None)
+            .await
+            .unwrap();
+
+        let now = Timestamp::now();
+        for tournament in tournaments.tournaments {
+            let start_time = tournament.start_time.unwrap();
+            assert_eq!(start_time <= now, true);
+            assert_eq!(start_time.unix_seconds(), tournament.start_active as i64);
+            // The tournaments of the test server never end
+            assert_eq!(tournament.end_time, None);
+        }
+    });
+}
+
 #[test]
 fn test_write_tournament_record() {
     // TODO: Why is the tournament not active?